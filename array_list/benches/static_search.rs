@@ -0,0 +1,63 @@
+//! Compare successor search over static sorted sets stored in different memory layouts.
+//! Run with `cargo bench -p array_list --bench static_search`.
+
+use array_list::eytzinger::EytzingerSet;
+use array_list::veb_layout::VebLayoutSet;
+use interface::set::SSet;
+use std::collections::BTreeSet;
+use std::hint::black_box;
+use std::time::Instant;
+
+const QUERIES: usize = 1 << 20;
+
+// Generate pseudo random queries by xorshift.
+fn queries(max: u64) -> Vec<u64> {
+    let mut s = 0x2545_f491_4f6c_dd1d_u64;
+    (0..QUERIES)
+        .map(|_| {
+            s ^= s << 13;
+            s ^= s >> 7;
+            s ^= s << 17;
+            s % max
+        })
+        .collect()
+}
+
+fn measure<F>(name: &str, n: usize, qs: &[u64], f: F)
+where
+    F: Fn(u64) -> Option<u64>,
+{
+    let start = Instant::now();
+    let mut hits = 0;
+    for &q in qs {
+        hits += black_box(f(q)).is_some() as usize;
+    }
+    let ns = start.elapsed().as_nanos() as f64 / qs.len() as f64;
+    println!(
+        "{:>10} n = {:>9}: {:>7.1} ns/query ({} hits)",
+        name, n, ns, hits
+    );
+}
+
+fn main() {
+    for shift in [10, 16, 20, 23] {
+        // perfect trees, so that no layout pays for a sparse last level
+        let n = (1 << shift) - 1;
+        let values = (0..n as u64).map(|x| 2 * x).collect::<Vec<_>>();
+        let qs = queries(2 * n as u64);
+
+        let sorted = values.clone();
+        let eytzinger = EytzingerSet::from_sorted(values.clone());
+        let veb = VebLayoutSet::from_sorted(values.clone());
+        let btree = values.into_iter().collect::<BTreeSet<_>>();
+
+        measure("sorted", n, &qs, |q| {
+            let i = sorted.partition_point(|&y| y < q);
+            sorted.get(i).copied()
+        });
+        measure("eytzinger", n, &qs, |q| eytzinger.find(q));
+        measure("veb", n, &qs, |q| veb.find(q));
+        measure("btree", n, &qs, |q| btree.range(q..).next().copied());
+        println!();
+    }
+}
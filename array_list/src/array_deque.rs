@@ -1,19 +1,36 @@
+use crate::raw::RawArray;
+use interface::fmt::{debug_entries, format_with};
+use interface::heap_size::HeapSize;
 use interface::list::List;
 use interface::queue::Deque;
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display, Formatter};
 
 /// List implementation with backing array realized by boxed slice.
 /// It is optimized for implementing deque interface.
-/// O(1): get(i), set(i, x)
+/// Elements are stored without Option, and only n slots from the head j are initialized, wrapping around.
+/// O(1): get(i), set(i, x), swap(i, j)
 /// O(1 + min{i, n - i}): add(i, x), remove(i)
-#[derive(Debug)]
+/// O(1 + k + min{i, n - i}): add_all(i, iter) for k values
+/// O(1 + min{k, n - k}): rotate_left(k), rotate_right(k), and O(1) if the backing array is full
 pub struct ArrayDeque<T>
 where
     T: Clone + Debug,
 {
-    a: Box<[Option<T>]>,
+    a: RawArray<T>,
     j: usize,
     n: usize,
+    // if true, removal never shrinks the backing array
+    retain: bool,
+}
+
+impl<T> Drop for ArrayDeque<T>
+where
+    T: Clone + Debug,
+{
+    fn drop(&mut self) {
+        let n = std::mem::replace(&mut self.n, 0);
+        unsafe { self.a.drop_wrapping(self.j, n) }
+    }
 }
 
 impl<T> ArrayDeque<T>
@@ -23,29 +40,119 @@ where
     /// Generate empty ArrayDeque
     pub fn new() -> Self {
         ArrayDeque {
-            a: allocate_with(0).into_boxed_slice(),
+            a: RawArray::new(0),
             j: 0,
             n: 0,
+            retain: false,
+        }
+    }
+
+    /// Return an iterator from the first element to the last one, which can be reversed.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            deque: self,
+            front: 0,
+            back: self.n,
+        }
+    }
+
+    /// Return an iterator over slices of size elements in order, where the last one may be shorter.
+    /// Elements are made contiguous first, so that no slice is split where the backing array wraps around.
+    /// Use as_slices to iterate both parts separately without moving elements.
+    /// Panic if size is 0.
+    pub fn chunks(&mut self, size: usize) -> std::slice::Chunks<'_, T> {
+        let values: &[T] = self.make_contiguous();
+        values.chunks(size)
+    }
+
+    /// Return an iterator over all slices of size consecutive elements in order, which overlap each other.
+    /// Elements are made contiguous first like chunks.
+    /// Panic if size is 0.
+    pub fn windows(&mut self, size: usize) -> std::slice::Windows<'_, T> {
+        let values: &[T] = self.make_contiguous();
+        values.windows(size)
+    }
+
+    /// Drop elements after the first len ones, keeping the backing array for reuse.
+    /// It does nothing if len is not less than the size.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.n {
+            return;
+        }
+        // the size is reduced first, so that a panicking drop leaks the rest instead of dropping them twice
+        let n = std::mem::replace(&mut self.n, len);
+        let k = (self.j + len) % self.capacity();
+        unsafe { self.a.drop_wrapping(k, n - len) }
+    }
+
+    /// Drop all elements, keeping the backing array for reuse.
+    /// Unlike removing elements one by one, it never shrinks the backing array.
+    pub fn clear_retaining_capacity(&mut self) {
+        self.truncate(0);
+        self.j = 0;
+    }
+
+    /// Choose whether removal keeps the backing array, instead of shrinking it once it's a third full.
+    /// Shrinking is the default, and a retained backing array is released only by drop.
+    pub fn retain_capacity(&mut self, retain: bool) {
+        self.retain = retain;
+    }
+
+    /// Resize to len elements, by generating new ones with f or dropping the ones after len.
+    pub fn resize_with<F>(&mut self, len: usize, mut f: F)
+    where
+        F: FnMut() -> T,
+    {
+        if len <= self.n {
+            self.truncate(len);
+            return;
+        }
+        if len > self.capacity() {
+            self.resize_to(len * 2);
+        }
+        // the size grows one by one, so that elements generated so far are kept even if f panics
+        for k in self.n..len {
+            let x = f();
+            self.a.write((self.j + k) % self.capacity(), x);
+            self.n = k + 1;
         }
     }
 
-    fn capacity(&self) -> usize {
+    /// Return the number of elements which the backing array can hold without reallocation.
+    pub fn capacity(&self) -> usize {
         self.a.len()
     }
 
+    /// Return elements in order as two slices, where the second one holds the elements which wrapped around
+    /// to the beginning of the backing array. The second one is empty if they don't wrap around.
+    /// O(1)
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        unsafe { self.a.slices_wrapping(self.j, self.n) }
+    }
+
+    /// Rearrange elements so that they are stored in order in one slice, and return it.
+    /// Elements are moved only if they wrap around, and then the head is placed at the beginning.
+    /// O(1) if they don't wrap around, otherwise O(capacity)
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.j + self.n > self.capacity() {
+            self.a.rotate_left(self.j);
+            self.j = 0;
+        }
+        unsafe { self.a.slice_mut(self.j..self.j + self.n) }
+    }
+
     fn within_bound(&self, i: usize) -> bool {
         i < self.n
     }
 
-    #[allow(clippy::needless_range_loop)]
     fn resize(&mut self) {
-        let len = std::cmp::max(self.n * 2, 1);
-        let mut new_array = allocate_with(len);
+        self.resize_to(std::cmp::max(self.n * 2, 1));
+    }
 
-        for k in 0..self.n {
-            new_array[k] = self.a[(self.j + k) % self.capacity()].take();
-        }
-        self.a = new_array.into_boxed_slice();
+    fn resize_to(&mut self, len: usize) {
+        let mut new_array = RawArray::new(len);
+        self.a.copy_wrapping_to(self.j, self.n, &mut new_array);
+        self.a = new_array;
         self.j = 0;
     }
 }
@@ -59,12 +166,23 @@ where
     }
 }
 
-fn allocate_with<T>(n: usize) -> Vec<Option<T>> {
-    let mut array = Vec::with_capacity(n);
-    unsafe {
-        array.set_len(n);
+impl<T> std::iter::FromIterator<T> for ArrayDeque<T>
+where
+    T: Clone + Debug,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.add_all(0, iter);
+        list
     }
-    array
+}
+
+/// Generate ArrayDeque holding given values in order, like `vec![]`.
+#[macro_export]
+macro_rules! array_deque {
+    ($($x:expr),* $(,)?) => {
+        <$crate::array_deque::ArrayDeque<_> as ::std::iter::FromIterator<_>>::from_iter([$($x),*])
+    };
 }
 
 impl<T> List<T> for ArrayDeque<T>
@@ -79,7 +197,7 @@ where
         if !self.within_bound(i) {
             None
         } else {
-            self.a[(self.j + i) % self.capacity()].as_ref()
+            Some(unsafe { self.a.get((self.j + i) % self.capacity()) })
         }
     }
 
@@ -91,10 +209,17 @@ where
                 self.size()
             )
         }
-        self.a[(self.j + i) % self.capacity()].replace(x)
+        let k = (self.j + i) % self.capacity();
+        Some(std::mem::replace(unsafe { self.a.get_mut(k) }, x))
     }
 
     fn add(&mut self, i: usize, x: T) {
+        if i > self.n {
+            panic!(
+                "index must be positive and not greater than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
         if self.size() + 1 > self.capacity() {
             self.resize();
         }
@@ -106,17 +231,23 @@ where
                 self.j - 1
             };
             for k in 0..i {
-                self.a[(self.j + k) % self.capacity()] =
-                    self.a[(self.j + k + 1) % self.capacity()].take();
+                self.a.copy(
+                    (self.j + k + 1) % self.capacity(),
+                    (self.j + k) % self.capacity(),
+                    1,
+                );
             }
         } else {
             // swap to right for a[i]..=a[n-1]
             for k in ((i + 1)..=self.size()).rev() {
-                self.a[(self.j + k) % self.capacity()] =
-                    self.a[(self.j + k - 1) % self.capacity()].take();
+                self.a.copy(
+                    (self.j + k - 1) % self.capacity(),
+                    (self.j + k) % self.capacity(),
+                    1,
+                );
             }
         }
-        self.a[(self.j + i) % self.capacity()].replace(x);
+        self.a.write((self.j + i) % self.capacity(), x);
         self.n += 1;
     }
 
@@ -124,27 +255,107 @@ where
         if !self.within_bound(i) {
             return None;
         }
-        let x = self.a.get_mut((self.j + i) % self.capacity())?.take();
+        let x = unsafe { self.a.read((self.j + i) % self.capacity()) };
 
         if i < self.size() / 2 {
             // swap to right for a[0]..=a[i-1]
             for k in (1..=i).rev() {
-                self.a[(self.j + k) % self.capacity()] =
-                    self.a[(self.j + k - 1) % self.capacity()].take();
+                self.a.copy(
+                    (self.j + k - 1) % self.capacity(),
+                    (self.j + k) % self.capacity(),
+                    1,
+                );
             }
             self.j = (self.j + 1) % self.capacity();
         } else {
             // swap to left for a[i+1]..=a[n-1]
             for k in i..(self.size() - 1) {
-                self.a[(self.j + k) % self.capacity()] =
-                    self.a[(self.j + k + 1) % self.capacity()].take();
+                self.a.copy(
+                    (self.j + k + 1) % self.capacity(),
+                    (self.j + k) % self.capacity(),
+                    1,
+                );
             }
         }
         self.n -= 1;
-        if 3 * self.size() < self.capacity() {
+        if !self.retain && 3 * self.size() < self.capacity() {
             self.resize();
         }
-        x
+        Some(x)
+    }
+
+    fn add_all<I>(&mut self, i: usize, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        if i > self.n {
+            panic!(
+                "index must be positive and not greater than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
+        let values: Vec<T> = iter.into_iter().collect();
+        let (k, n) = (values.len(), self.n);
+        if n + k > self.capacity() {
+            self.resize_to((n + k) * 2);
+        }
+        let capacity = self.capacity();
+        if i < n / 2 {
+            // shift a[0]..=a[i-1] to left by k
+            self.j = (self.j + capacity - k) % capacity;
+            for t in 0..i {
+                self.a
+                    .copy((self.j + t + k) % capacity, (self.j + t) % capacity, 1);
+            }
+        } else {
+            // shift a[i]..=a[n-1] to right by k
+            for t in (i..n).rev() {
+                self.a
+                    .copy((self.j + t) % capacity, (self.j + t + k) % capacity, 1);
+            }
+        }
+        for (t, x) in values.into_iter().enumerate() {
+            self.a.write((self.j + i + t) % capacity, x);
+        }
+        self.n += k;
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        if !self.within_bound(i) || !self.within_bound(j) {
+            panic!(
+                "index must be positive and less than the size of list. i: {}, j: {}, n: {}",
+                i,
+                j,
+                self.size()
+            )
+        }
+        let capacity = self.capacity();
+        self.a
+            .swap((self.j + i) % capacity, (self.j + j) % capacity);
+    }
+
+    fn rotate_left(&mut self, k: usize) {
+        if self.n == 0 {
+            return;
+        }
+        let (n, capacity) = (self.n, self.capacity());
+        let k = k % n;
+        if n == capacity {
+            // no gap in the backing array, so only the head moves
+            self.j = (self.j + k) % capacity;
+        } else if k <= n - k {
+            // move a[0]..=a[k-1] to the gap after the tail
+            for _ in 0..k {
+                self.a.copy(self.j, (self.j + n) % capacity, 1);
+                self.j = (self.j + 1) % capacity;
+            }
+        } else {
+            // move a[k]..=a[n-1] to the gap before the head
+            for _ in k..n {
+                self.j = (self.j + capacity - 1) % capacity;
+                self.a.copy((self.j + n) % capacity, self.j, 1);
+            }
+        }
     }
 }
 
@@ -167,10 +378,222 @@ where
     }
 }
 
+impl<T> HeapSize for ArrayDeque<T>
+where
+    T: Clone + Debug,
+{
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * std::mem::size_of::<T>()
+    }
+}
+
+impl<T> Debug for ArrayDeque<T>
+where
+    T: Clone + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArrayDeque")
+            .field("elements", &debug_entries(self.iter()))
+            .field("size", &self.n)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl<T> Display for ArrayDeque<T>
+where
+    T: Clone + Debug + Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        Display::fmt(&format_with(self.iter(), ", "), f)?;
+        f.write_str("]")
+    }
+}
+
+/// Iterator over elements of ArrayDeque.
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T>
+where
+    T: Clone + Debug,
+{
+    deque: &'a ArrayDeque<T>,
+    // positions of remaining elements are within [front, back)
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T>
+where
+    T: Clone + Debug,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        self.deque.get(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.back - self.front, Some(self.back - self.front))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T>
+where
+    T: Clone + Debug,
+{
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        self.deque.get(self.back)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> where T: Clone + Debug {}
+
+#[cfg(feature = "rayon")]
+mod par {
+    use super::ArrayDeque;
+    use crate::raw::RawArray;
+    use rayon::prelude::*;
+    use std::fmt::Debug;
+
+    impl<T> ArrayDeque<T>
+    where
+        T: Clone + Debug + Send,
+    {
+        /// Remove all elements in parallel, and leave the list empty.
+        /// Elements are moved out of the backing array in place, so the list keeps its capacity.
+        /// Elements which aren't consumed are dropped with the iterator.
+        pub fn par_drain(&mut self) -> impl IndexedParallelIterator<Item = T> + '_ {
+            // the size is reduced first, so that a leaked iterator leaks the elements instead of dropping them twice
+            let n = std::mem::replace(&mut self.n, 0);
+            let j = std::mem::replace(&mut self.j, 0);
+            unsafe { self.a.par_drain_wrapping(j, n) }
+        }
+
+        // Convert into Vec of elements in order, rotating the backing array so that the head comes first.
+        fn into_vec(self) -> Vec<T> {
+            let mut deque = std::mem::ManuallyDrop::new(self);
+            let mut a = std::mem::replace(&mut deque.a, RawArray::new(0));
+            a.rotate_left(deque.j);
+            unsafe { a.into_vec(deque.n) }
+        }
+    }
+
+    impl<'a, T> IntoParallelIterator for &'a ArrayDeque<T>
+    where
+        T: Clone + Debug + Sync,
+    {
+        type Item = &'a T;
+        type Iter = rayon::iter::Chain<rayon::slice::Iter<'a, T>, rayon::slice::Iter<'a, T>>;
+
+        fn into_par_iter(self) -> Self::Iter {
+            let (rest, wrapped) = self.as_slices();
+            rest.par_iter().chain(wrapped.par_iter())
+        }
+    }
+
+    impl<T> IntoParallelIterator for ArrayDeque<T>
+    where
+        T: Clone + Debug + Send,
+    {
+        type Item = T;
+        type Iter = rayon::vec::IntoIter<T>;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.into_vec().into_par_iter()
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::ArrayDeque;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::queue::Deque;
+    use std::fmt::Debug;
+
+    // Decode operations, where op % 4 chooses adding first, adding last,
+    // or adding last and removing first.
+    pub(super) fn ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Values are added to either end and sometimes removed, so that the backing array can wrap around.
+    impl<'a, T> Arbitrary<'a> for ArrayDeque<T>
+    where
+        T: Clone + Debug + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut deque = ArrayDeque::new();
+            for (op, x) in ops(u)? {
+                match op % 4 {
+                    0 => deque.add_first(x),
+                    1 | 2 => deque.add_last(x),
+                    _ => {
+                        deque.add_last(x);
+                        deque.remove_first();
+                    }
+                }
+            }
+            Ok(deque)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ArrayDeque;
 
+    #[test]
+    fn list_conformance_test() {
+        let mut list: ArrayDeque<i32> = ArrayDeque::new();
+        test_support::conformance::check_list(&mut list);
+    }
+
+    #[test]
+    fn clear_test() {
+        use interface::list::List;
+
+        let mut list: ArrayDeque<i32> = ArrayDeque::new();
+        for x in 0..100 {
+            list.push_back(x);
+        }
+        let capacity = list.capacity();
+        list.clear_retaining_capacity();
+        assert_eq!(list.size(), 0);
+        assert_eq!(list.get(0), None);
+        assert_eq!(list.capacity(), capacity);
+
+        // refilling within the capacity doesn't reallocate
+        for x in 0..100 {
+            list.push_back(x);
+        }
+        assert_eq!(list.capacity(), capacity);
+        assert_eq!(list.get(99), Some(&99));
+
+        // removal shrinks the backing array unless it's retained
+        list.retain_capacity(true);
+        while list.remove(0).is_some() {}
+        assert_eq!(list.capacity(), capacity);
+        list.retain_capacity(false);
+        list.push_back(0);
+        list.remove(0);
+        assert!(list.capacity() < capacity);
+    }
+
     #[test]
     fn list_test() {
         use interface::list::List;
@@ -217,4 +640,265 @@ mod tests {
         assert_eq!(deque.remove_first(), None);
         assert_eq!(deque.remove_last(), None);
     }
+
+    #[test]
+    fn add_all_test() {
+        use interface::list::List;
+
+        for i in [0, 1, 5, 9, 10] {
+            let mut list: ArrayDeque<usize> = ArrayDeque::new();
+            let mut expected: Vec<usize> = (0..10).collect();
+            list.add_all(0, 0..10);
+            // make the list wrap around the backing array
+            list.remove(0);
+            list.add(0, 0);
+            list.add_all(i, 100..105);
+            expected.splice(i..i, 100..105);
+            assert!((0..list.size())
+                .map(|i| list.get(i))
+                .eq(expected.iter().map(Some)));
+            list.add_all(3, std::iter::empty());
+            assert_eq!(list.size(), 15);
+        }
+    }
+
+    #[test]
+    fn resize_test() {
+        use interface::list::List;
+
+        let mut list: ArrayDeque<usize> = ArrayDeque::new();
+        let mut x = 0;
+        list.resize_with(6, || {
+            x += 1;
+            x
+        });
+        // make the list wrap around the backing array
+        list.rotate_left(10);
+        list.remove(0);
+        list.add(0, 5);
+        let capacity = list.capacity();
+        list.truncate(3);
+        assert_eq!(list.size(), 3);
+        assert!((0..3).map(|i| *list.get(i).unwrap()).eq([5, 6, 1]));
+        list.resize_with(8, || 0);
+        assert!((0..8)
+            .map(|i| *list.get(i).unwrap())
+            .eq([5, 6, 1, 0, 0, 0, 0, 0]));
+        assert_eq!(list.capacity(), capacity);
+        list.truncate(0);
+        assert_eq!(list.size(), 0);
+        assert_eq!(list.get(0), None);
+    }
+
+    #[test]
+    fn shift_test() {
+        use crate::raw::moves;
+        use interface::list::List;
+
+        // add(i) and remove(i) shift min(i, n - i) elements without resize
+        let n = 1000;
+        let mut list: ArrayDeque<usize> = ArrayDeque::new();
+        list.resize_with(n, Default::default);
+        list.retain_capacity(true);
+        for i in [
+            0,
+            1,
+            2,
+            100,
+            n / 2 - 1,
+            n / 2,
+            n / 2 + 1,
+            n - 100,
+            n - 2,
+            n - 1,
+            n,
+        ] {
+            let start = moves();
+            list.add(i, i);
+            let k = moves() - start;
+            assert!(k <= std::cmp::min(i, n - i) + 1, "add: {}, moves: {}", i, k);
+            assert_eq!(list.get(i), Some(&i));
+
+            let start = moves();
+            assert_eq!(list.remove(i), Some(i));
+            let k = moves() - start;
+            assert!(
+                k <= std::cmp::min(i, n - i) + 1,
+                "remove: {}, moves: {}",
+                i,
+                k
+            );
+            assert_eq!(list.size(), n);
+        }
+    }
+
+    #[test]
+    fn slices_test() {
+        use interface::queue::Deque;
+
+        let mut deque: ArrayDeque<i32> = ArrayDeque::new();
+        assert_eq!(deque.as_slices(), (&[][..], &[][..]));
+        assert!(deque.make_contiguous().is_empty());
+        for x in 0..4 {
+            deque.add_last(x);
+        }
+        assert_eq!(deque.as_slices(), (&[0, 1, 2, 3][..], &[][..]));
+        // elements added to the front wrap around to the end of the backing array
+        deque.add_first(-1);
+        deque.add_first(-2);
+        let (front, back) = deque.as_slices();
+        assert!(!front.is_empty() && !back.is_empty());
+        assert!(front.iter().chain(back).eq([-2, -1, 0, 1, 2, 3].iter()));
+
+        let capacity = deque.capacity();
+        let contiguous = deque.make_contiguous();
+        assert_eq!(contiguous, &[-2, -1, 0, 1, 2, 3]);
+        contiguous[0] = -3;
+        assert_eq!(deque.as_slices(), (&[-3, -1, 0, 1, 2, 3][..], &[][..]));
+        assert_eq!(deque.capacity(), capacity);
+        // the deque keeps working from the new head
+        deque.add_first(-4);
+        deque.add_last(4);
+        assert!(deque.iter().eq([-4, -3, -1, 0, 1, 2, 3, 4].iter()));
+        assert_eq!(deque.remove_first(), Some(-4));
+        assert_eq!(deque.make_contiguous(), &[-3, -1, 0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn chunks_test() {
+        use interface::queue::Deque;
+
+        // elements wrap around the backing array
+        let mut deque: ArrayDeque<i32> = (2..6).collect();
+        deque.add_first(1);
+        deque.add_first(0);
+        assert!(!deque.as_slices().1.is_empty());
+        assert_eq!(
+            deque.chunks(4).collect::<Vec<_>>(),
+            vec![&[0, 1, 2, 3][..], &[4, 5]]
+        );
+        assert_eq!(
+            deque.windows(5).collect::<Vec<_>>(),
+            vec![&[0, 1, 2, 3, 4][..], &[1, 2, 3, 4, 5]]
+        );
+        assert_eq!(ArrayDeque::<i32>::new().windows(1).next(), None);
+    }
+
+    #[test]
+    fn iter_test() {
+        use interface::queue::Deque;
+
+        let mut deque: ArrayDeque<i32> = ArrayDeque::new();
+        assert_eq!(deque.iter().next_back(), None);
+        for x in 0..4 {
+            deque.add_last(x);
+            deque.add_first(-x - 1);
+        }
+        let expected = [-4, -3, -2, -1, 0, 1, 2, 3];
+        assert!(deque.iter().eq(expected.iter()));
+        assert_eq!(deque.to_string(), "[-4, -3, -2, -1, 0, 1, 2, 3]");
+        assert_eq!(
+            format!("{:?}", deque),
+            format!(
+                "ArrayDeque {{ elements: {:?}, size: 8, capacity: {} }}",
+                expected,
+                deque.capacity()
+            )
+        );
+        assert!(deque.iter().rev().eq(expected.iter().rev()));
+        let mut iter = deque.iter();
+        assert_eq!(iter.next_back(), Some(&3));
+        assert_eq!(iter.next(), Some(&-4));
+        assert_eq!(iter.len(), 6);
+        assert!(iter.rev().eq([2, 1, 0, -1, -2, -3].iter()));
+    }
+
+    #[test]
+    fn rotate_test() {
+        use interface::list::List;
+        use std::collections::VecDeque;
+
+        // sizes 4 and 8 fill the backing array, and the others leave a gap
+        for n in [0, 3, 4, 5, 8] {
+            for k in 0..=2 * n {
+                let mut list: ArrayDeque<usize> = ArrayDeque::new();
+                let mut expected: VecDeque<usize> = (0..n).collect();
+                for x in 0..n {
+                    list.add(x, x);
+                }
+                list.rotate_left(k);
+                expected.rotate_left(if n == 0 { 0 } else { k % n });
+                assert!((0..n).map(|i| list.get(i)).eq(expected.iter().map(Some)));
+                list.rotate_right(k);
+                assert!((0..n).all(|i| list.get(i) == Some(&i)));
+            }
+        }
+
+        let mut list: ArrayDeque<i32> = ArrayDeque::new();
+        for x in 0..5 {
+            list.add(0, x);
+        }
+        list.swap(0, 4);
+        list.swap(2, 2);
+        assert!((0..5).map(|i| *list.get(i).unwrap()).eq([0, 3, 2, 1, 4]));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_iter_test() {
+        use interface::queue::Deque;
+        use rayon::prelude::*;
+
+        // [Some(3), Some(4), .., Some(0), Some(1), Some(2)] wrapped around the backing array
+        let mut deque: ArrayDeque<u64> = ArrayDeque::new();
+        for x in 3..1000 {
+            deque.add_last(x);
+        }
+        for x in (0..3).rev() {
+            deque.add_first(x);
+        }
+        assert_eq!(
+            (&deque).into_par_iter().copied().collect::<Vec<_>>(),
+            (0..1000).collect::<Vec<_>>()
+        );
+        assert_eq!(deque.par_iter().sum::<u64>(), 499500);
+
+        let capacity = deque.capacity();
+        let drained = deque.par_drain().collect::<Vec<_>>();
+        assert_eq!(drained, (0..1000).collect::<Vec<_>>());
+        assert_eq!(deque.remove_first(), None);
+        assert_eq!(deque.capacity(), capacity);
+
+        deque.add_last(2);
+        deque.add_first(1);
+        assert_eq!(deque.into_par_iter().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use interface::list::List;
+
+        let mut wrapped = false;
+        test_support::replay::check_arbitrary(|deque: ArrayDeque<u16>, u| {
+            // replay the same operations on VecDeque
+            let mut expected = std::collections::VecDeque::new();
+            for (op, x) in super::arb::ops::<u16>(u).unwrap() {
+                match op % 4 {
+                    0 => expected.push_front(x),
+                    1 | 2 => expected.push_back(x),
+                    _ => {
+                        expected.push_back(x);
+                        expected.remove(0);
+                    }
+                }
+            }
+            assert!(deque.size() <= deque.capacity());
+            assert!(deque.j < std::cmp::max(deque.capacity(), 1));
+            wrapped |= deque.j + deque.size() > deque.capacity();
+            assert!(deque.iter().eq(expected.iter()));
+            assert!(deque.iter().rev().eq(expected.iter().rev()));
+        });
+        assert!(wrapped);
+    }
 }
@@ -1,17 +1,32 @@
+use crate::raw::RawArray;
+use interface::fmt::{debug_entries, format_with};
+use interface::heap_size::HeapSize;
 use interface::list::List;
 use interface::queue::Queue;
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display, Formatter};
 
 /// List implementation with backing array realized by boxed slice.
 /// It is optimized for implementing fifo queue interface.
+/// Elements are stored without Option, and only n slots from the head j are initialized, wrapping around.
 /// O(1): add(x), remove()
-#[derive(Debug)]
+/// O(k): add_all(iter), remove_n(k) for k values, which visit at most two contiguous regions
+/// O(1 + n - i): add(i, x), remove(i) as List
 pub struct ArrayQueue<T: Clone + Debug> {
-    a: Box<[Option<T>]>,
+    a: RawArray<T>,
     j: usize,
     n: usize,
 }
 
+impl<T> Drop for ArrayQueue<T>
+where
+    T: Clone + Debug,
+{
+    fn drop(&mut self) {
+        let n = std::mem::replace(&mut self.n, 0);
+        unsafe { self.a.drop_wrapping(self.j, n) }
+    }
+}
+
 impl<T> Default for ArrayQueue<T>
 where
     T: Clone + Debug,
@@ -21,6 +36,25 @@ where
     }
 }
 
+impl<T> std::iter::FromIterator<T> for ArrayQueue<T>
+where
+    T: Clone + Debug,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut queue = Self::new();
+        Queue::add_all(&mut queue, iter);
+        queue
+    }
+}
+
+/// Generate ArrayQueue holding given values in the order they are removed, like `vec![]`.
+#[macro_export]
+macro_rules! array_queue {
+    ($($x:expr),* $(,)?) => {
+        <$crate::array_queue::ArrayQueue<_> as ::std::iter::FromIterator<_>>::from_iter([$($x),*])
+    };
+}
+
 impl<T> ArrayQueue<T>
 where
     T: Clone + Debug,
@@ -32,25 +66,57 @@ where
 
     fn with_capacity(i: usize) -> Self {
         ArrayQueue {
-            a: allocate_with(i).into_boxed_slice(),
+            a: RawArray::new(i),
             j: 0,
             n: 0,
         }
     }
 
-    fn capacity(&self) -> usize {
+    /// Return the number of elements which the backing array can hold without reallocation.
+    pub fn capacity(&self) -> usize {
         self.a.len()
     }
 
-    #[allow(clippy::needless_range_loop)]
-    fn resize(&mut self) {
-        let len = std::cmp::max(self.n * 2, 1);
-        let mut new_array = allocate_with(len);
+    /// Return elements in order as two slices, where the second one holds the elements which wrapped around
+    /// to the beginning of the backing array. The second one is empty if they don't wrap around.
+    /// O(1)
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        unsafe { self.a.slices_wrapping(self.j, self.n) }
+    }
 
-        for k in 0..self.n {
-            new_array[k] = self.a[(self.j + k) % self.capacity()].take();
+    /// Rearrange elements so that they are stored in order in one slice, and return it.
+    /// Elements are moved only if they wrap around, and then the head is placed at the beginning.
+    /// O(1) if they don't wrap around, otherwise O(capacity)
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.j + self.n > self.capacity() {
+            self.a.rotate_left(self.j);
+            self.j = 0;
         }
-        self.a = new_array.into_boxed_slice();
+        unsafe { self.a.slice_mut(self.j..self.j + self.n) }
+    }
+
+    fn resize(&mut self) {
+        self.resize_to(std::cmp::max(self.n * 2, 1));
+    }
+
+    fn resize_to(&mut self, len: usize) {
+        let mut new_array = RawArray::new(len);
+        self.a.copy_wrapping_to(self.j, self.n, &mut new_array);
+        self.a = new_array;
+        self.j = 0;
+    }
+
+    /// Return an iterator which removes elements in FIFO order.
+    /// Elements which aren't consumed are dropped with the iterator, so the queue is left empty.
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain { queue: self }
+    }
+
+    /// Drop all elements, keeping the backing array for reuse.
+    /// Unlike removing elements one by one, it never shrinks the backing array.
+    pub fn clear_retaining_capacity(&mut self) {
+        let n = std::mem::replace(&mut self.n, 0);
+        unsafe { self.a.drop_wrapping(self.j, n) }
         self.j = 0;
     }
 
@@ -64,14 +130,6 @@ where
     }
 }
 
-fn allocate_with<T>(n: usize) -> Vec<Option<T>> {
-    let mut array = Vec::with_capacity(n);
-    unsafe {
-        array.set_len(n);
-    }
-    array
-}
-
 impl<T> Queue<T> for ArrayQueue<T>
 where
     T: Clone + Debug,
@@ -81,7 +139,7 @@ where
             self.resize();
         }
         let pos = (self.j + self.size()) % self.capacity();
-        self.a[pos] = Some(x);
+        self.a.write(pos, x);
         self.n += 1;
         true
     }
@@ -90,10 +148,43 @@ where
         if self.n == 0 {
             return None;
         }
-        let x = self.a[self.j].take();
+        let x = unsafe { self.a.read(self.j) };
         self.j = (self.j + 1) % self.capacity();
         self.n -= 1;
-        x
+        Some(x)
+    }
+
+    fn add_all<I>(&mut self, iter: I) -> usize
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let values: Vec<T> = iter.into_iter().collect();
+        let k = values.len();
+        if k == 0 {
+            return 0;
+        }
+        if self.n + k > self.capacity() {
+            self.resize_to((self.n + k) * 2);
+        }
+        // free slots are a[tail..] followed by a[..j]
+        let tail = (self.j + self.n) % self.capacity();
+        self.a.write_wrapping(tail, values);
+        self.n += k;
+        k
+    }
+
+    fn remove_n(&mut self, k: usize) -> Vec<T> {
+        let k = std::cmp::min(k, self.n);
+        if k == 0 {
+            return Vec::new();
+        }
+        let (j, capacity) = (self.j, self.capacity());
+        // values are a[j..] followed by a[..j] which wrapped around
+        let mut values = RawArray::new(k);
+        self.a.copy_wrapping_to(j, k, &mut values);
+        self.j = (j + k) % capacity;
+        self.n -= k;
+        unsafe { values.into_vec(k) }
     }
 }
 
@@ -108,7 +199,7 @@ where
         if !self.within_bound(i) {
             None
         } else {
-            self.a[(self.j + i) % self.capacity()].as_ref()
+            Some(unsafe { self.a.get((self.j + i) % self.capacity()) })
         }
     }
 
@@ -119,16 +210,150 @@ where
                 i, self.n
             )
         } else {
-            self.a[(self.j + i) % self.capacity()].replace(x)
+            let k = (self.j + i) % self.capacity();
+            Some(std::mem::replace(unsafe { self.a.get_mut(k) }, x))
+        }
+    }
+
+    // Elements after i are shifted toward the tail, so add(n, x) is the same as the Queue one.
+    fn add(&mut self, i: usize, x: T) {
+        if i > self.n {
+            panic!(
+                "index must be positive and not greater than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
+        if self.size() + 1 > self.capacity() {
+            self.resize();
+        }
+        let capacity = self.capacity();
+        for k in (i..self.n).rev() {
+            self.a
+                .copy((self.j + k) % capacity, (self.j + k + 1) % capacity, 1);
+        }
+        self.a.write((self.j + i) % capacity, x);
+        self.n += 1;
+    }
+
+    // Elements after i are shifted toward the head, so remove(0) is the same as the Queue one.
+    fn remove(&mut self, i: usize) -> Option<T> {
+        if !self.within_bound(i) {
+            return None;
+        }
+        let capacity = self.capacity();
+        let x = unsafe { self.a.read((self.j + i) % capacity) };
+        for k in i + 1..self.n {
+            self.a
+                .copy((self.j + k) % capacity, (self.j + k - 1) % capacity, 1);
         }
+        self.n -= 1;
+        Some(x)
     }
+}
 
-    fn add(&mut self, _i: usize, _x: T) {
-        unimplemented!();
+impl<T> HeapSize for ArrayQueue<T>
+where
+    T: Clone + Debug,
+{
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * std::mem::size_of::<T>()
     }
+}
 
-    fn remove(&mut self, _i: usize) -> Option<T> {
-        unimplemented!();
+impl<T> Debug for ArrayQueue<T>
+where
+    T: Clone + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let values = (0..self.n).filter_map(move |i| List::get(self, i));
+        f.debug_struct("ArrayQueue")
+            .field("elements", &debug_entries(values))
+            .field("size", &self.n)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl<T> Display for ArrayQueue<T>
+where
+    T: Clone + Debug + Display,
+{
+    // Elements are written from the head to the tail.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let values = (0..self.n).filter_map(move |i| List::get(self, i));
+        f.write_str("[")?;
+        Display::fmt(&format_with(values, ", "), f)?;
+        f.write_str("]")
+    }
+}
+
+/// Iterator which removes elements from ArrayQueue.
+#[derive(Debug)]
+pub struct Drain<'a, T>
+where
+    T: Clone + Debug,
+{
+    queue: &'a mut ArrayQueue<T>,
+}
+
+impl<'a, T> Iterator for Drain<'a, T>
+where
+    T: Clone + Debug,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        Queue::remove(self.queue)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.queue.n, Some(self.queue.n))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> where T: Clone + Debug {}
+
+impl<'a, T> Drop for Drain<'a, T>
+where
+    T: Clone + Debug,
+{
+    fn drop(&mut self) {
+        self.queue.clear_retaining_capacity();
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::ArrayQueue;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::queue::Queue;
+    use std::fmt::Debug;
+
+    // Decode operations, each of which adds a value and removes one if op % 3 == 0.
+    pub(super) fn ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Values are added and sometimes removed, so that the backing array can wrap around.
+    impl<'a, T> Arbitrary<'a> for ArrayQueue<T>
+    where
+        T: Clone + Debug + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut queue = ArrayQueue::new();
+            for (op, x) in ops(u)? {
+                queue.add(x);
+                if op % 3 == 0 {
+                    queue.remove();
+                }
+            }
+            Ok(queue)
+        }
     }
 }
 
@@ -137,27 +362,55 @@ mod tests {
     use super::ArrayQueue;
     use interface::queue::Queue;
 
+    #[test]
+    fn list_conformance_test() {
+        let mut list: ArrayQueue<i32> = ArrayQueue::new();
+        test_support::conformance::check_list(&mut list);
+    }
+
+    #[test]
+    fn clear_test() {
+        use interface::list::List;
+
+        let mut list: ArrayQueue<i32> = ArrayQueue::new();
+        for x in 0..100 {
+            Queue::add(&mut list, x);
+        }
+        let capacity = list.capacity();
+        list.clear_retaining_capacity();
+        assert_eq!(list.size(), 0);
+        assert_eq!(list.get(0), None);
+        assert_eq!(list.capacity(), capacity);
+
+        // refilling within the capacity doesn't reallocate
+        for x in 0..100 {
+            Queue::add(&mut list, x);
+        }
+        assert_eq!(list.capacity(), capacity);
+        assert_eq!(list.get(99), Some(&99));
+    }
+
     #[test]
     fn capacity_test() {
         // [None, None, Some(a), Some(b), Some(c), None]
         let mut queue: ArrayQueue<char> = ArrayQueue::with_capacity(6);
-        assert_eq!(queue.add('a'), true);
-        assert_eq!(queue.add('a'), true);
-        assert_eq!(queue.add('a'), true);
-        assert_eq!(queue.add('b'), true);
-        assert_eq!(queue.add('c'), true);
+        assert!(queue.add('a'));
+        assert!(queue.add('a'));
+        assert!(queue.add('a'));
+        assert!(queue.add('b'));
+        assert!(queue.add('c'));
         assert_eq!(queue.remove(), Some('a'));
         assert_eq!(queue.remove(), Some('a'));
         assert_eq!(queue.capacity(), 6);
         assert_eq!(queue.j, 2);
         assert_eq!(queue.size(), 3);
 
-        assert_eq!(queue.add('d'), true);
+        assert!(queue.add('d'));
         assert_eq!(queue.capacity(), 6);
         assert_eq!(queue.j, 2);
         assert_eq!(queue.size(), 4);
 
-        assert_eq!(queue.add('e'), true);
+        assert!(queue.add('e'));
         assert_eq!(queue.j, 2);
         assert_eq!(queue.capacity(), 6);
         assert_eq!(queue.size(), 5);
@@ -167,17 +420,17 @@ mod tests {
         assert_eq!(queue.capacity(), 6);
         assert_eq!(queue.size(), 4);
 
-        assert_eq!(queue.add('f'), true);
+        assert!(queue.add('f'));
         assert_eq!(queue.j, 3);
         assert_eq!(queue.capacity(), 6);
         assert_eq!(queue.size(), 5);
 
-        assert_eq!(queue.add('g'), true);
+        assert!(queue.add('g'));
         assert_eq!(queue.j, 3);
         assert_eq!(queue.capacity(), 6);
         assert_eq!(queue.size(), 6);
 
-        assert_eq!(queue.add('h'), true);
+        assert!(queue.add('h'));
         assert_eq!(queue.j, 0);
         assert_eq!(queue.capacity(), 12);
         assert_eq!(queue.size(), 7);
@@ -191,12 +444,133 @@ mod tests {
     #[test]
     fn queue_test() {
         let mut queue: ArrayQueue<i32> = ArrayQueue::new();
-        assert_eq!(queue.add(1), true);
-        assert_eq!(queue.add(2), true);
-        assert_eq!(queue.add(3), true);
+        assert!(queue.add(1));
+        assert!(queue.add(2));
+        assert!(queue.add(3));
         assert_eq!(queue.remove(), Some(1));
         assert_eq!(queue.remove(), Some(2));
         assert_eq!(queue.remove(), Some(3));
         assert_eq!(queue.remove(), None);
     }
+
+    #[test]
+    fn slices_test() {
+        let mut queue: ArrayQueue<i32> = (0..8).collect();
+        assert_eq!(
+            queue.as_slices(),
+            (&(0..8).collect::<Vec<_>>()[..], &[][..])
+        );
+        queue.remove_n(6);
+        let capacity = queue.capacity();
+        for x in 8..capacity as i32 + 4 {
+            queue.add(x);
+        }
+        assert_eq!(queue.capacity(), capacity);
+        // the tail wraps around to the beginning of the backing array
+        let expected = (6..capacity as i32 + 4).collect::<Vec<_>>();
+        let (front, back) = queue.as_slices();
+        assert!(!back.is_empty());
+        assert!(front.iter().chain(back).eq(expected.iter()));
+
+        assert_eq!(queue.make_contiguous(), &expected[..]);
+        assert_eq!(queue.as_slices(), (&expected[..], &[][..]));
+        assert_eq!(queue.capacity(), capacity);
+        assert_eq!(queue.remove_n(expected.len()), expected);
+        assert_eq!(queue.as_slices(), (&[][..], &[][..]));
+        assert!(queue.make_contiguous().is_empty());
+    }
+
+    #[test]
+    fn bulk_test() {
+        let mut queue: ArrayQueue<usize> = ArrayQueue::new();
+        let mut expected = std::collections::VecDeque::new();
+        assert!(queue.remove_n(3).is_empty());
+        for x in 0..50 {
+            // batches wrap around the backing array
+            let batch = x * 10..x * 10 + x % 7;
+            assert_eq!(queue.add_all(batch.clone()), batch.len());
+            expected.extend(batch);
+            let k = x % 5;
+            let removed: Vec<usize> = (0..k).filter_map(|_| expected.pop_front()).collect();
+            assert_eq!(queue.remove_n(k), removed);
+            assert_eq!(queue.size(), expected.len());
+        }
+        let n = expected.len();
+        assert_eq!(queue.remove_n(n + 1), Vec::from(expected));
+        assert_eq!(queue.remove(), None);
+
+        // a batch which wraps around is moved into the free slots without resize
+        let mut queue: ArrayQueue<i32> = (0..8).collect();
+        queue.remove_n(6);
+        let capacity = queue.capacity();
+        let start = crate::raw::moves();
+        queue.add_all(8..20);
+        assert_eq!(crate::raw::moves() - start, 12);
+        assert_eq!(queue.capacity(), capacity);
+        assert_eq!(queue.remove_n(14), (6..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn macro_test() {
+        let mut queue = crate::array_queue![1, 2, 3];
+        assert!(queue.drain().eq(1..4));
+    }
+
+    #[test]
+    fn drain_test() {
+        let mut queue: ArrayQueue<String> = ArrayQueue::new();
+        for x in 0..5 {
+            queue.add(x.to_string());
+        }
+        queue.remove();
+        queue.add(5.to_string());
+        assert_eq!(queue.to_string(), "[1, 2, 3, 4, 5]");
+        assert_eq!(
+            format!("{:?}", queue),
+            format!(
+                "ArrayQueue {{ elements: [\"1\", \"2\", \"3\", \"4\", \"5\"], size: 5, capacity: {} }}",
+                queue.capacity()
+            )
+        );
+        let mut drained = Vec::new();
+        for x in queue.drain() {
+            drained.push(x);
+        }
+        assert_eq!(drained, ["1", "2", "3", "4", "5"]);
+        assert_eq!(queue.size(), 0);
+
+        // elements left in the iterator are dropped with it
+        for x in 0..5 {
+            queue.add(x.to_string());
+        }
+        let mut drain = queue.drain();
+        assert_eq!(drain.len(), 5);
+        assert_eq!(drain.next(), Some("0".to_string()));
+        drop(drain);
+        assert_eq!(queue.size(), 0);
+        assert_eq!(queue.remove(), None);
+        queue.add("6".to_string());
+        assert_eq!(queue.remove(), Some("6".to_string()));
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        let mut wrapped = false;
+        test_support::replay::check_arbitrary(|mut queue: ArrayQueue<u16>, u| {
+            // replay the same operations on VecDeque
+            let mut expected = std::collections::VecDeque::new();
+            for (op, x) in super::arb::ops::<u16>(u).unwrap() {
+                expected.push_back(x);
+                if op % 3 == 0 {
+                    expected.pop_front();
+                }
+            }
+            assert!(queue.size() <= queue.capacity());
+            assert!(queue.j < std::cmp::max(queue.capacity(), 1));
+            wrapped |= queue.j + queue.size() > queue.capacity();
+            assert!(queue.drain().eq(expected.into_iter()));
+        });
+        assert!(wrapped);
+    }
 }
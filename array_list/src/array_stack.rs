@@ -1,15 +1,32 @@
+use crate::raw::RawArray;
+use interface::fmt::{debug_entries, format_with};
+use interface::heap_size::HeapSize;
 use interface::list::List;
 use interface::queue::Stack;
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display, Formatter};
 
 /// List implementation with backing array realized by boxed slice.
 /// It is optimized for implementing stack interface.
-/// O(1): get(i), set(i, x)
+/// Elements are stored without Option, and only the first n slots of the backing array are initialized.
+/// O(1): get(i), set(i, x), swap(i, j)
 /// O(1 + n - i): add(i, x), remove(i)
-#[derive(Debug)]
+/// O(n + k): add_all(i, iter) for k values
+/// O(n): rotate_left(k), rotate_right(k)
 pub struct ArrayStack<T: Clone + Debug> {
-    a: Box<[Option<T>]>,
+    a: RawArray<T>,
     n: usize,
+    // if true, removal never shrinks the backing array
+    retain: bool,
+}
+
+impl<T> Drop for ArrayStack<T>
+where
+    T: Clone + Debug,
+{
+    fn drop(&mut self) {
+        let n = std::mem::replace(&mut self.n, 0);
+        unsafe { self.a.drop_range(0..n) }
+    }
 }
 
 impl<T> Default for ArrayStack<T>
@@ -21,6 +38,25 @@ where
     }
 }
 
+impl<T> std::iter::FromIterator<T> for ArrayStack<T>
+where
+    T: Clone + Debug,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.add_all(0, iter);
+        list
+    }
+}
+
+/// Generate ArrayStack holding given values in order, like `vec![]`.
+#[macro_export]
+macro_rules! array_stack {
+    ($($x:expr),* $(,)?) => {
+        <$crate::array_stack::ArrayStack<_> as ::std::iter::FromIterator<_>>::from_iter([$($x),*])
+    };
+}
+
 impl<T> ArrayStack<T>
 where
     T: Clone + Debug,
@@ -30,40 +66,130 @@ where
         ArrayStack::with_capacity(0)
     }
 
-    fn with_capacity(i: usize) -> Self {
+    /// Return an iterator from the first element to the last one, which can be reversed.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.as_slice().iter(),
+        }
+    }
+
+    /// Return an iterator over slices of size elements in order, where the last one may be shorter.
+    /// Slices borrow the backing array, so elements are not copied.
+    /// Panic if size is 0.
+    pub fn chunks(&self, size: usize) -> std::slice::Chunks<'_, T> {
+        self.as_slice().chunks(size)
+    }
+
+    /// Return an iterator over all slices of size consecutive elements in order, which overlap each other.
+    /// Panic if size is 0.
+    pub fn windows(&self, size: usize) -> std::slice::Windows<'_, T> {
+        self.as_slice().windows(size)
+    }
+
+    /// Drop elements after the first len ones, keeping the backing array for reuse.
+    /// It does nothing if len is not less than the size.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.n {
+            return;
+        }
+        // the size is reduced first, so that a panicking drop leaks the rest instead of dropping them twice
+        let n = std::mem::replace(&mut self.n, len);
+        unsafe { self.a.drop_range(len..n) }
+    }
+
+    /// Drop all elements, keeping the backing array for reuse.
+    /// Unlike removing elements one by one, it never shrinks the backing array.
+    pub fn clear_retaining_capacity(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Choose whether removal keeps the backing array, instead of shrinking it once it's a third full.
+    /// Shrinking is the default, and a retained backing array is released only by drop or conversion.
+    pub fn retain_capacity(&mut self, retain: bool) {
+        self.retain = retain;
+    }
+
+    /// Convert into Vec of elements in order, which takes over the backing array without moving elements.
+    /// O(1)
+    pub fn into_vec(self) -> Vec<T> {
+        let mut list = std::mem::ManuallyDrop::new(self);
+        let a = std::mem::replace(&mut list.a, RawArray::new(0));
+        unsafe { a.into_vec(list.n) }
+    }
+
+    /// Convert into a boxed slice of elements in order, without cloning them.
+    /// The spare capacity is released, which may reallocate the backing array.
+    /// O(n) if the spare capacity is released
+    pub fn into_boxed_slice(self) -> Box<[T]> {
+        self.into_vec().into_boxed_slice()
+    }
+
+    /// Resize to len elements, by generating new ones with f or dropping the ones after len.
+    pub fn resize_with<F>(&mut self, len: usize, mut f: F)
+    where
+        F: FnMut() -> T,
+    {
+        if len <= self.n {
+            self.truncate(len);
+            return;
+        }
+        self.reserve_for(len);
+        // the size grows one by one, so that elements generated so far are kept even if f panics
+        for k in self.n..len {
+            self.a.write(k, f());
+            self.n = k + 1;
+        }
+    }
+
+    // Return initialized elements of the backing array.
+    fn as_slice(&self) -> &[T] {
+        unsafe { self.a.slice(0..self.n) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { self.a.slice_mut(0..self.n) }
+    }
+
+    pub(crate) fn with_capacity(i: usize) -> Self {
         ArrayStack {
-            a: allocate_with(i).into_boxed_slice(),
+            a: RawArray::new(i),
             n: 0,
+            retain: false,
         }
     }
 
-    // Return internally allocated capacity of backing array.
-    fn capacity(&self) -> usize {
+    /// Return the number of elements which the backing array can hold without reallocation.
+    pub fn capacity(&self) -> usize {
         self.a.len()
     }
 
     // Validate whether the given index is within a range of the list.
     fn within_bound(&self, i: usize) -> bool {
-        i < self.a.len() && i < self.n
+        i < self.n
     }
 
     fn resize(&mut self) {
-        let len = std::cmp::max(self.n * 2, 1);
-        let mut new_array = allocate_with(len);
+        self.resize_to(std::cmp::max(self.n * 2, 1));
+    }
 
-        for (i, elem) in self.a.iter_mut().enumerate().take(self.n) {
-            new_array[i] = elem.clone();
-        }
-        self.a = new_array.into_boxed_slice();
+    // Move elements to a new backing array of len slots.
+    fn resize_to(&mut self, len: usize) {
+        let mut new_array = RawArray::new(len);
+        self.a.copy_to(0, &mut new_array, 0, self.n);
+        self.a = new_array;
     }
 }
 
-fn allocate_with<T>(n: usize) -> Vec<Option<T>> {
-    let mut array = Vec::with_capacity(n);
-    unsafe {
-        array.set_len(n);
+impl<T> ArrayStack<T>
+where
+    T: Clone + Debug,
+{
+    // Grow the backing array to hold m elements, keeping the same growth rate as resize.
+    fn reserve_for(&mut self, m: usize) {
+        if m > self.capacity() {
+            self.resize_to(m * 2);
+        }
     }
-    array
 }
 
 impl<T> List<T> for ArrayStack<T>
@@ -74,11 +200,7 @@ where
         self.n
     }
     fn get(&self, i: usize) -> Option<&T> {
-        if !self.within_bound(i) {
-            None
-        } else {
-            self.a[i].as_ref()
-        }
+        self.as_slice().get(i)
     }
 
     fn set(&mut self, i: usize, x: T) -> Option<T> {
@@ -88,36 +210,76 @@ where
                 i, self.n
             )
         } else {
-            self.a[i].replace(x)
+            Some(std::mem::replace(&mut self.as_mut_slice()[i], x))
         }
     }
 
     fn add(&mut self, i: usize, x: T) {
+        if i > self.n {
+            panic!(
+                "index must be positive and not greater than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
         if self.size() + 1 > self.capacity() {
             self.resize();
         }
 
-        if i >= self.n {
-            self.a[self.n] = Some(x);
-        } else {
-            self.a[i..self.n].rotate_right(1);
-            let end = self.a[i].replace(x);
-            self.a[self.n] = end;
-        }
+        self.a.copy(i, i + 1, self.n - i);
+        self.a.write(i, x);
         self.n += 1;
     }
 
     fn remove(&mut self, i: usize) -> Option<T> {
-        let x = self.a.get_mut(i)?.take();
-        if i < self.n {
-            self.a[i..self.n].rotate_left(1);
+        if !self.within_bound(i) {
+            return None;
         }
+        let x = unsafe { self.a.read(i) };
+        self.a.copy(i + 1, i, self.n - i - 1);
         self.n -= 1;
-        if self.capacity() >= 3 * self.size() {
+        if !self.retain && self.capacity() >= 3 * self.size() {
             self.resize();
         }
 
-        x
+        Some(x)
+    }
+
+    fn add_all<I>(&mut self, i: usize, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        if i > self.n {
+            panic!(
+                "index must be positive and not greater than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
+        let values: Vec<T> = iter.into_iter().collect();
+        let k = values.len();
+        self.reserve_for(self.n + k);
+        // a[i..n] moves behind the k slots which receive values
+        self.a.copy(i, i + k, self.n - i);
+        for (t, x) in values.into_iter().enumerate() {
+            self.a.write(i + t, x);
+        }
+        self.n += k;
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        if !self.within_bound(i) || !self.within_bound(j) {
+            panic!(
+                "index must be positive and less than the size of list. i: {}, j: {}, n: {}",
+                i, j, self.n
+            )
+        }
+        self.as_mut_slice().swap(i, j);
+    }
+
+    fn rotate_left(&mut self, k: usize) {
+        if self.n > 0 {
+            let n = self.n;
+            self.as_mut_slice().rotate_left(k % n);
+        }
     }
 }
 
@@ -129,7 +291,146 @@ where
         self.add(self.size(), x);
     }
     fn pop(&mut self) -> Option<T> {
-        self.remove(self.size() - 1)
+        self.pop_back()
+    }
+}
+
+impl<T> HeapSize for ArrayStack<T>
+where
+    T: Clone + Debug,
+{
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * std::mem::size_of::<T>()
+    }
+}
+
+impl<T> Debug for ArrayStack<T>
+where
+    T: Clone + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArrayStack")
+            .field("elements", &debug_entries(self.iter()))
+            .field("size", &self.n)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl<T> Display for ArrayStack<T>
+where
+    T: Clone + Debug + Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        Display::fmt(&format_with(self.iter(), ", "), f)?;
+        f.write_str("]")
+    }
+}
+
+/// Iterator over elements of ArrayStack.
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+    inner: std::slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+#[cfg(feature = "rayon")]
+mod par {
+    use super::ArrayStack;
+    use rayon::prelude::*;
+    use std::fmt::Debug;
+
+    impl<T> ArrayStack<T>
+    where
+        T: Clone + Debug + Send,
+    {
+        /// Remove all elements in parallel, and leave the list empty.
+        /// Elements are moved out of the backing array in place, so the list keeps its capacity.
+        /// Elements which aren't consumed are dropped with the iterator.
+        pub fn par_drain(&mut self) -> impl IndexedParallelIterator<Item = T> + '_ {
+            // the size is reduced first, so that a leaked iterator leaks the elements instead of dropping them twice
+            let n = std::mem::replace(&mut self.n, 0);
+            unsafe { self.a.par_drain(0..n) }
+        }
+    }
+
+    impl<'a, T> IntoParallelIterator for &'a ArrayStack<T>
+    where
+        T: Clone + Debug + Sync,
+    {
+        type Item = &'a T;
+        type Iter = rayon::slice::Iter<'a, T>;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.as_slice().par_iter()
+        }
+    }
+
+    impl<T> IntoParallelIterator for ArrayStack<T>
+    where
+        T: Clone + Debug + Send,
+    {
+        type Item = T;
+        type Iter = rayon::vec::IntoIter<T>;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.into_vec().into_par_iter()
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::ArrayStack;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::queue::Stack;
+    use std::fmt::Debug;
+
+    // Decode operations, each of which pushes a value and pops one if op % 4 == 0.
+    pub(super) fn ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Values are pushed and sometimes popped, so that the backing array can have vacant slots.
+    impl<'a, T> Arbitrary<'a> for ArrayStack<T>
+    where
+        T: Clone + Debug + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut list = ArrayStack::new();
+            for (op, x) in ops(u)? {
+                list.push(x);
+                if op % 4 == 0 {
+                    list.pop();
+                }
+            }
+            Ok(list)
+        }
     }
 }
 
@@ -139,6 +440,47 @@ mod tests {
     use interface::list::List;
     use interface::queue::Stack;
 
+    #[test]
+    fn list_conformance_test() {
+        let mut list: ArrayStack<i32> = ArrayStack::new();
+        test_support::conformance::check_list(&mut list);
+    }
+
+    #[test]
+    fn clear_test() {
+        let mut list: ArrayStack<i32> = ArrayStack::new();
+        for x in 0..100 {
+            list.push_back(x);
+        }
+        let capacity = list.capacity();
+        list.clear_retaining_capacity();
+        assert_eq!(list.size(), 0);
+        assert_eq!(list.get(0), None);
+        assert_eq!(list.capacity(), capacity);
+
+        // refilling within the capacity doesn't reallocate
+        for x in 0..100 {
+            list.push_back(x);
+        }
+        assert_eq!(list.capacity(), capacity);
+        assert_eq!(list.get(99), Some(&99));
+
+        // removal shrinks the backing array unless it's retained
+        list.retain_capacity(true);
+        while list.pop_back().is_some() {}
+        assert_eq!(list.capacity(), capacity);
+        list.retain_capacity(false);
+        list.push_back(0);
+        list.pop_back();
+        assert!(list.capacity() < capacity);
+    }
+
+    #[test]
+    fn stack_conformance_test() {
+        let mut stack: ArrayStack<i32> = ArrayStack::new();
+        test_support::conformance::check_stack(&mut stack);
+    }
+
     #[test]
     fn capacity_test() {
         // [Some(b), Some(r), Some(e), Some(d), None, None]
@@ -179,6 +521,18 @@ mod tests {
         assert_eq!(list.get(3), Some(&'e'));
     }
 
+    #[test]
+    fn heap_size_test() {
+        use interface::heap_size::HeapSize;
+
+        let mut list: ArrayStack<u32> = ArrayStack::with_capacity(6);
+        assert_eq!(list.heap_bytes(), 6 * std::mem::size_of::<u32>());
+        list.push(1);
+        assert_eq!(list.heap_bytes(), 6 * std::mem::size_of::<u32>());
+        list.pop();
+        assert_eq!(list.heap_bytes(), std::mem::size_of::<u32>());
+    }
+
     #[test]
     fn list_test() {
         let mut list: ArrayStack<i32> = ArrayStack::new();
@@ -228,5 +582,292 @@ mod tests {
         assert_eq!(stack.pop(), Some(2));
         assert_eq!(stack.size(), 0);
         assert_eq!(stack.get(0), None);
+
+        // popping or removing beyond the size leaves the stack intact
+        assert_eq!(stack.pop(), None);
+        stack.push(3);
+        assert_eq!(stack.remove(1), None);
+        assert_eq!(stack.size(), 1);
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), None);
+        assert_eq!(stack.size(), 0);
+    }
+
+    #[test]
+    fn amortized_test() {
+        use crate::raw::moves;
+
+        let mut stack: ArrayStack<i32> = ArrayStack::new();
+        // m pushes move at most 3m elements
+        let m = 1000;
+        let start = moves();
+        for x in 0..m {
+            stack.push(x as i32);
+        }
+        let k = moves() - start;
+        assert!(k <= 3 * m, "moves: {}, m: {}", k, m);
+
+        // pushes and pops around the capacity don't resize every time
+        stack.truncate(1024);
+        while stack.size() < 1024 {
+            stack.push(0);
+        }
+        let start = moves();
+        for _ in 0..m {
+            stack.push(1);
+            stack.pop();
+            stack.pop();
+            stack.push(2);
+        }
+        let k = moves() - start;
+        assert!(k <= 3 * 4 * m, "moves: {}, m: {}", k, m);
+
+        // m pops move at most 3m elements as well
+        let n = stack.size();
+        let start = moves();
+        while stack.size() > 0 {
+            stack.pop();
+        }
+        let k = moves() - start;
+        assert!(k <= 3 * n, "moves: {}, n: {}", k, n);
+    }
+
+    #[test]
+    fn into_vec_test() {
+        let rc = std::rc::Rc::new(());
+        let stack: ArrayStack<_> = (0..100).map(|_| rc.clone()).collect();
+        // the backing array is taken over without moving or cloning elements
+        let start = crate::raw::moves();
+        let v = stack.into_vec();
+        assert_eq!(crate::raw::moves(), start);
+        assert_eq!(v.len(), 100);
+        assert_eq!(std::rc::Rc::strong_count(&rc), 101);
+
+        let stack: ArrayStack<i32> = (0..5).collect();
+        assert_eq!(&*stack.into_boxed_slice(), &[0, 1, 2, 3, 4]);
+        assert!(ArrayStack::<i32>::new().into_vec().is_empty());
+    }
+
+    #[test]
+    fn default_methods_test() {
+        let mut list: ArrayStack<i32> = ArrayStack::new();
+        assert_eq!(list.first(), None);
+        assert_eq!(list.last(), None);
+        assert_eq!(list.pop_back(), None);
+        for x in 1..4 {
+            list.push_back(x);
+        }
+        assert_eq!(list.first(), Some(&1));
+        assert_eq!(list.last(), Some(&3));
+        assert!(list.contains(&2));
+        assert!(!list.contains(&4));
+        list.push_back(1);
+        assert_eq!(list.index_of(&1), Some(0));
+        assert_eq!(list.rposition(&1), Some(3));
+        assert_eq!(list.index_of(&4), None);
+        assert_eq!(list.rposition(&4), None);
+        let i = list.index_of(&2).unwrap();
+        assert_eq!(list.remove(i), Some(2));
+        assert_eq!(list.pop_back(), Some(1));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.size(), 1);
+
+        list.push_back(5);
+        assert_eq!(list.get_from_end(0), Some(&5));
+        assert_eq!(list.get_from_end(1), Some(&1));
+        assert_eq!(list.get_from_end(2), None);
+        assert_eq!(list.set_from_end(1, 4), Some(1));
+        assert_eq!(list.first(), Some(&4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_from_end_test() {
+        let mut list: ArrayStack<i32> = ArrayStack::new();
+        list.set_from_end(0, 1);
+    }
+
+    #[test]
+    fn add_all_test() {
+        let mut list: ArrayStack<usize> = ArrayStack::new();
+        list.add_all(0, 0..4);
+        list.add_all(2, vec![10, 11, 12]);
+        list.add_all(7, vec![20]);
+        assert!((0..list.size())
+            .map(|i| *list.get(i).unwrap())
+            .eq([0, 1, 10, 11, 12, 2, 3, 20]));
+
+        let mut list: ArrayStack<usize> = ArrayStack::new();
+        let mut expected = Vec::new();
+        for x in 0..100 {
+            let i = x % (list.size() + 1);
+            list.add_all(i, x * 10..x * 10 + x % 4);
+            expected.splice(i..i, x * 10..x * 10 + x % 4);
+        }
+        assert!((0..list.size())
+            .map(|i| list.get(i))
+            .eq(expected.iter().map(Some)));
+    }
+
+    #[test]
+    fn resize_test() {
+        let mut list: ArrayStack<Vec<u8>> = ArrayStack::new();
+        list.resize_with(5, Vec::new);
+        assert_eq!(list.size(), 5);
+        list.set(4, vec![4]);
+        let capacity = list.capacity();
+        list.truncate(8);
+        assert_eq!(list.size(), 5);
+        list.truncate(2);
+        assert_eq!(list.size(), 2);
+        assert_eq!(list.get(2), None);
+        assert_eq!(list.capacity(), capacity);
+
+        let mut x = 0;
+        list.resize_with(4, || {
+            x += 1;
+            vec![x]
+        });
+        assert_eq!(list.get(1), Some(&vec![]));
+        assert_eq!(list.get(3), Some(&vec![2]));
+        assert_eq!(list.capacity(), capacity);
+        list.resize_with(0, Vec::new);
+        assert_eq!(list.size(), 0);
+    }
+
+    #[test]
+    fn macro_test() {
+        let list: ArrayStack<i32> = crate::array_stack![1, 2, 3];
+        assert!(list.iter().eq([1, 2, 3].iter()));
+        let list: ArrayStack<String> = crate::array_stack![];
+        assert_eq!(list.size(), 0);
+        let deque = crate::array_deque!['a', 'b',];
+        assert_eq!(deque.to_string(), "[a, b]");
+    }
+
+    #[test]
+    fn iter_test() {
+        let mut list: ArrayStack<i32> = ArrayStack::new();
+        assert_eq!(list.iter().next(), None);
+        for x in 0..5 {
+            list.push(x);
+        }
+        assert!(list.iter().eq([0, 1, 2, 3, 4].iter()));
+        assert_eq!(list.to_string(), "[0, 1, 2, 3, 4]");
+        assert_eq!(ArrayStack::<i32>::new().to_string(), "[]");
+        list.pop();
+        // vacant slots of the backing array aren't shown
+        assert_eq!(
+            format!("{:?}", list),
+            format!(
+                "ArrayStack {{ elements: [0, 1, 2, 3], size: 4, capacity: {} }}",
+                list.capacity()
+            )
+        );
+        list.push(4);
+        assert!(list.iter().rev().eq([4, 3, 2, 1, 0].iter()));
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back(), Some(&3));
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn chunks_test() {
+        let list: ArrayStack<i32> = (0..7).collect();
+        assert_eq!(
+            list.chunks(3).collect::<Vec<_>>(),
+            vec![&[0, 1, 2][..], &[3, 4, 5], &[6]]
+        );
+        assert_eq!(
+            list.windows(5).collect::<Vec<_>>(),
+            vec![&[0, 1, 2, 3, 4][..], &[1, 2, 3, 4, 5], &[2, 3, 4, 5, 6]]
+        );
+        assert_eq!(list.windows(8).next(), None);
+        assert_eq!(ArrayStack::<i32>::new().chunks(2).next(), None);
+    }
+
+    #[test]
+    fn rotate_test() {
+        let mut list: ArrayStack<i32> = ArrayStack::new();
+        list.rotate_left(3);
+        for x in 0..5 {
+            list.push(x);
+        }
+        list.rotate_left(7);
+        assert!((0..5).map(|i| *list.get(i).unwrap()).eq([2, 3, 4, 0, 1]));
+        list.rotate_right(2);
+        list.swap(4, 0);
+        assert!((0..5).map(|i| *list.get(i).unwrap()).eq([4, 1, 2, 3, 0]));
+    }
+
+    #[test]
+    fn dyn_list_test() {
+        // a structure can be chosen at runtime behind the same List
+        let mut lists: Vec<Box<dyn List<i32>>> = vec![
+            Box::new(ArrayStack::new()),
+            Box::new(crate::array_deque::ArrayDeque::new()),
+        ];
+        for list in lists.iter_mut() {
+            for x in 0..10 {
+                list.add(0, x);
+            }
+            assert_eq!(list.remove(9), Some(0));
+            assert_eq!(list.size(), 9);
+            assert_eq!(list.get(0), Some(&9));
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_iter_test() {
+        use rayon::prelude::*;
+
+        let mut list: ArrayStack<u64> = ArrayStack::new();
+        for x in 0..1000 {
+            list.push(x);
+        }
+        assert_eq!((&list).into_par_iter().sum::<u64>(), 499500);
+        assert_eq!(list.par_iter().map(|x| x * 2).max(), Some(1998));
+
+        let capacity = list.capacity();
+        let drained = list.par_drain().collect::<Vec<_>>();
+        assert_eq!(list.size(), 0);
+        assert_eq!(list.capacity(), capacity);
+        assert_eq!(drained, (0..1000).collect::<Vec<_>>());
+
+        // elements which are not consumed are dropped with the iterator
+        let rc = std::sync::Arc::new(());
+        let mut arcs: ArrayStack<_> = (0..100).map(|_| rc.clone()).collect();
+        assert!(arcs.par_drain().find_any(|_| true).is_some());
+        assert_eq!(std::sync::Arc::strong_count(&rc), 1);
+        assert_eq!(arcs.size(), 0);
+
+        list.push(1);
+        list.push(2);
+        assert_eq!(list.into_par_iter().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        test_support::replay::check_arbitrary(|list: ArrayStack<u16>, u| {
+            // replay the same operations on Vec
+            let mut expected = Vec::new();
+            for (op, x) in super::arb::ops::<u16>(u).unwrap() {
+                expected.push(x);
+                if op % 4 == 0 {
+                    expected.pop();
+                }
+            }
+            assert!(list.iter().eq(expected.iter()));
+            // the backing array has grown by doubling and shrunk on removal
+            assert!(list.size() <= list.capacity());
+            assert!(list.capacity() <= std::cmp::max(3 * list.size(), 1));
+        });
     }
 }
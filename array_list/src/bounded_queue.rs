@@ -0,0 +1,276 @@
+use crate::raw::RawArray;
+use interface::fmt::debug_entries;
+use interface::heap_size::HeapSize;
+use interface::queue::{BoundedQueue, Queue};
+use std::fmt::{self, Debug, Formatter};
+
+/// Ring buffer implementation with fixed capacity realized by boxed slice.
+/// When it's full, add(x) either rejects a value or overwrites the oldest one,
+/// depending on the mode chosen at construction.
+/// Elements are stored without Option, and only n slots from the head j are initialized, wrapping around.
+/// O(1): add(x), try_add(x), remove()
+pub struct BoundedArrayQueue<T: Debug> {
+    a: RawArray<T>,
+    j: usize,
+    n: usize,
+    overwrite: bool,
+}
+
+impl<T> Drop for BoundedArrayQueue<T>
+where
+    T: Debug,
+{
+    fn drop(&mut self) {
+        let n = std::mem::replace(&mut self.n, 0);
+        unsafe { self.a.drop_wrapping(self.j, n) }
+    }
+}
+
+impl<T> BoundedArrayQueue<T>
+where
+    T: Debug,
+{
+    /// Generate empty BoundedArrayQueue which rejects a value when it's full.
+    pub fn new(capacity: usize) -> Self {
+        BoundedArrayQueue {
+            a: RawArray::new(capacity),
+            j: 0,
+            n: 0,
+            overwrite: false,
+        }
+    }
+
+    /// Generate empty BoundedArrayQueue which overwrites the oldest value when it's full.
+    pub fn with_overwrite(capacity: usize) -> Self {
+        BoundedArrayQueue {
+            a: RawArray::new(capacity),
+            j: 0,
+            n: 0,
+            overwrite: true,
+        }
+    }
+
+    /// Add a value to a queue, removing the oldest value if it's full.
+    /// Return the removed value if exists.
+    pub fn force_add(&mut self, x: T) -> Option<T> {
+        if self.capacity() == 0 {
+            return Some(x);
+        }
+        let oldest = if self.is_full() { self.remove() } else { None };
+        self.push(x);
+        oldest
+    }
+
+    fn push(&mut self, x: T) {
+        let pos = (self.j + self.n) % self.capacity();
+        self.a.write(pos, x);
+        self.n += 1;
+    }
+}
+
+impl<T> Queue<T> for BoundedArrayQueue<T>
+where
+    T: Debug,
+{
+    fn add(&mut self, x: T) -> bool {
+        if self.overwrite {
+            self.force_add(x);
+            self.capacity() > 0
+        } else {
+            self.try_add(x).is_ok()
+        }
+    }
+
+    fn remove(&mut self) -> Option<T> {
+        if self.n == 0 {
+            return None;
+        }
+        let x = unsafe { self.a.read(self.j) };
+        self.j = (self.j + 1) % self.capacity();
+        self.n -= 1;
+        Some(x)
+    }
+}
+
+impl<T> BoundedQueue<T> for BoundedArrayQueue<T>
+where
+    T: Debug,
+{
+    fn size(&self) -> usize {
+        self.n
+    }
+
+    fn capacity(&self) -> usize {
+        self.a.len()
+    }
+
+    fn try_add(&mut self, x: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(x);
+        }
+        self.push(x);
+        Ok(())
+    }
+}
+
+impl<T> HeapSize for BoundedArrayQueue<T>
+where
+    T: Debug,
+{
+    fn heap_bytes(&self) -> usize {
+        self.a.len() * std::mem::size_of::<T>()
+    }
+}
+
+impl<T> Debug for BoundedArrayQueue<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let capacity = self.a.len();
+        let values = (0..self.n).map(move |k| unsafe { self.a.get((self.j + k) % capacity) });
+        f.debug_struct("BoundedArrayQueue")
+            .field("elements", &debug_entries(values))
+            .field("size", &self.n)
+            .field("capacity", &capacity)
+            .field("overwrite", &self.overwrite)
+            .finish()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::BoundedArrayQueue;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::queue::Queue;
+    use std::fmt::Debug;
+
+    // Decode the capacity, whether to overwrite, and operations, each of which adds a value
+    // and removes one if op % 3 == 0.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn ops<'a, T>(u: &mut Unstructured<'a>) -> Result<(usize, bool, Vec<(u8, T)>)>
+    where
+        T: Arbitrary<'a>,
+    {
+        let capacity = u.int_in_range(0..=64)?;
+        let overwrite = bool::arbitrary(u)?;
+        let ops = (0..u.arbitrary_len::<(u8, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect::<Result<_>>()?;
+        Ok((capacity, overwrite, ops))
+    }
+
+    // Either mode is chosen with a small capacity, and values are added beyond it and sometimes removed,
+    // so that the ring buffer can be full and wrapped around.
+    impl<'a, T> Arbitrary<'a> for BoundedArrayQueue<T>
+    where
+        T: Debug + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let (capacity, overwrite, ops) = ops::<T>(u)?;
+            let mut queue = if overwrite {
+                BoundedArrayQueue::with_overwrite(capacity)
+            } else {
+                BoundedArrayQueue::new(capacity)
+            };
+            for (op, x) in ops {
+                queue.add(x);
+                if op % 3 == 0 {
+                    queue.remove();
+                }
+            }
+            Ok(queue)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoundedArrayQueue;
+    use interface::queue::{BoundedQueue, Queue};
+
+    #[test]
+    fn bounded_test() {
+        let mut queue: BoundedArrayQueue<i32> = BoundedArrayQueue::new(3);
+        assert_eq!(queue.capacity(), 3);
+        assert_eq!(queue.try_add(1), Ok(()));
+        assert!(queue.add(2));
+        assert!(queue.add(3));
+        assert!(queue.is_full());
+        assert!(!queue.add(4));
+        assert_eq!(queue.try_add(4), Err(4));
+        assert_eq!(queue.size(), 3);
+
+        assert_eq!(queue.remove(), Some(1));
+        assert!(!queue.is_full());
+        assert!(queue.add(4));
+        assert_eq!(queue.remove(), Some(2));
+        assert_eq!(queue.remove(), Some(3));
+        assert_eq!(queue.remove(), Some(4));
+        assert_eq!(queue.remove(), None);
+        assert_eq!(queue.size(), 0);
+    }
+
+    #[test]
+    fn bulk_test() {
+        let mut queue: BoundedArrayQueue<i32> = BoundedArrayQueue::new(3);
+        assert_eq!(queue.add_all(0..5), 3);
+        assert_eq!(queue.remove_n(2), vec![0, 1]);
+        assert_eq!(queue.remove_n(2), vec![2]);
+        assert!(queue.remove_n(2).is_empty());
+    }
+
+    #[test]
+    fn overwrite_test() {
+        let mut queue: BoundedArrayQueue<char> = BoundedArrayQueue::with_overwrite(3);
+        assert!(queue.add('a'));
+        assert!(queue.add('b'));
+        assert!(queue.add('c'));
+        assert!(queue.add('d'));
+        assert_eq!(queue.size(), 3);
+        assert_eq!(queue.try_add('e'), Err('e'));
+        assert_eq!(queue.force_add('e'), Some('b'));
+
+        assert_eq!(queue.remove(), Some('c'));
+        assert_eq!(queue.remove(), Some('d'));
+        assert_eq!(queue.remove(), Some('e'));
+        assert_eq!(queue.remove(), None);
+    }
+
+    #[test]
+    fn zero_capacity_test() {
+        let mut queue: BoundedArrayQueue<i32> = BoundedArrayQueue::with_overwrite(0);
+        assert!(queue.is_full());
+        assert!(!queue.add(1));
+        assert_eq!(queue.force_add(1), Some(1));
+        assert_eq!(queue.remove(), None);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use std::collections::VecDeque;
+
+        test_support::replay::check_arbitrary(|mut queue: BoundedArrayQueue<u16>, u| {
+            // replay the same operations on VecDeque
+            let (capacity, overwrite, ops) = super::arb::ops::<u16>(u).unwrap();
+            let mut expected = VecDeque::new();
+            for (op, x) in ops {
+                if overwrite && capacity > 0 && expected.len() == capacity {
+                    expected.pop_front();
+                }
+                if expected.len() < capacity {
+                    expected.push_back(x);
+                }
+                if op % 3 == 0 {
+                    expected.pop_front();
+                }
+            }
+            assert_eq!(queue.capacity(), capacity);
+            assert_eq!(queue.overwrite, overwrite);
+            assert_eq!(queue.size(), expected.len());
+            assert!(queue.j < std::cmp::max(capacity, 1));
+            assert!(std::iter::from_fn(|| queue.remove()).eq(expected));
+        });
+    }
+}
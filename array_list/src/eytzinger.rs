@@ -0,0 +1,381 @@
+use crate::sorted_layout::{check_sorted, in_order, SortedLayout};
+use interface::heap_size::HeapSize;
+use interface::set::{SSet, SSetExt};
+use std::borrow::Borrow;
+
+/// Static SSet implementation with backing array in Eytzinger (BFS) order of an implicit complete binary search tree.
+/// The node k has children at 2k and 2k + 1 (1-indexed), so the search touches one cache line per few levels
+/// and descends without branches: k = 2k + (a[k] < x).
+/// O(log n): find(x)
+/// O(n): add(x), remove(x) since the layout is rebuilt
+#[derive(Debug)]
+pub struct EytzingerSet<T>
+where
+    T: Ord,
+{
+    // a[k - 1] holds the node k
+    a: Box<[T]>,
+}
+
+impl<T> EytzingerSet<T>
+where
+    T: Ord,
+{
+    /// Generate empty EytzingerSet
+    pub fn new() -> Self {
+        EytzingerSet {
+            a: Vec::new().into_boxed_slice(),
+        }
+    }
+
+    /// Generate EytzingerSet from values sorted in strictly ascending order.
+    pub fn from_sorted(values: Vec<T>) -> Self {
+        SortedLayout::from_sorted(values)
+    }
+
+    /// Return an iterator which visits elements in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        in_order(self.a.len()).map(move |k| &self.a[k - 1])
+    }
+
+    // Return the index of the smallest value y which satisfies y >= x.
+    fn lower_bound<Q>(&self, x: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.partition(|y| y.borrow() < x).1
+    }
+
+    // Return the indices of the largest value which satisfies below and the smallest one which doesn't,
+    // where below holds for a prefix of values in ascending order.
+    fn partition<F>(&self, below: F) -> (Option<usize>, Option<usize>)
+    where
+        F: Fn(&T) -> bool,
+    {
+        let n = self.a.len();
+        let mut k = 1;
+        while k <= n {
+            prefetch(&self.a, 16 * k);
+            k = 2 * k + below(&self.a[k - 1]) as usize;
+        }
+        // cancel turns taken after the last left (or right) turn
+        let last = k >> (k.trailing_zeros() + 1);
+        let first = k >> (k.trailing_ones() + 1);
+        (last.checked_sub(1), first.checked_sub(1))
+    }
+}
+
+impl<T> SortedLayout<T> for EytzingerSet<T>
+where
+    T: Ord,
+{
+    fn from_sorted(values: Vec<T>) -> Self {
+        check_sorted(&values);
+        let mut b = std::iter::repeat_with(|| None)
+            .take(values.len())
+            .collect::<Vec<_>>();
+        for (k, x) in in_order(b.len()).zip(values) {
+            b[k - 1] = Some(x);
+        }
+        EytzingerSet {
+            a: b.into_iter()
+                .map(|x| x.expect("every node must be filled"))
+                .collect(),
+        }
+    }
+
+    fn into_sorted_vec(self) -> Vec<T> {
+        let n = self.a.len();
+        let mut a = self.a.into_vec().into_iter().map(Some).collect::<Vec<_>>();
+        in_order(n)
+            .map(|k| a[k - 1].take().expect("every node must be visited once"))
+            .collect()
+    }
+}
+
+// Hint the cache to load the node k, which is 4 levels below the current node.
+#[inline(always)]
+fn prefetch<T>(a: &[T], k: usize) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if k <= a.len() {
+            use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+            unsafe { _mm_prefetch(a.as_ptr().add(k - 1) as *const i8, _MM_HINT_T0) };
+        }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = (a, k);
+}
+
+impl<T> Default for EytzingerSet<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::iter::FromIterator<T> for EytzingerSet<T>
+where
+    T: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_unsorted(iter)
+    }
+}
+
+impl<T> SSet<T> for EytzingerSet<T>
+where
+    T: Ord + Clone,
+{
+    fn size(&self) -> usize {
+        self.a.len()
+    }
+
+    fn add(&mut self, x: T) -> bool {
+        self.add_sorted(x)
+    }
+
+    fn remove(&mut self, x: T) -> Option<T> {
+        self.remove_sorted(x)
+    }
+
+    fn find(&self, x: T) -> Option<T> {
+        self.find_by(&x).cloned()
+    }
+
+    fn find_ref(&self, x: &T) -> Option<&T> {
+        self.find_by(x)
+    }
+
+    fn successor(&self, x: &T) -> Option<&T> {
+        self.partition(|y| y <= x).1.map(|k| &self.a[k])
+    }
+
+    fn predecessor(&self, x: &T) -> Option<&T> {
+        self.partition(|y| y < x).0.map(|k| &self.a[k])
+    }
+}
+
+impl<T> SSetExt<T> for EytzingerSet<T>
+where
+    T: Ord + Clone,
+{
+    fn find_by<Q>(&self, x: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.lower_bound(x).map(|k| &self.a[k])
+    }
+}
+
+impl<T> EytzingerSet<T>
+where
+    T: Ord,
+{
+    /// Remove all elements and return them in ascending order, leaving an empty set.
+    pub fn drain(&mut self) -> std::vec::IntoIter<T> {
+        std::mem::take(self).into_iter()
+    }
+
+    /// Return an element chosen uniformly at random, where rng generates uniformly random u64 values.
+    /// Every slot of the backing array holds an element, so a random index is enough.
+    /// O(1)
+    pub fn choose<R>(&self, rng: &mut R) -> Option<&T>
+    where
+        R: FnMut() -> u64,
+    {
+        // the high half of the product maps u64 into [0, n) without the bias of modulo
+        let i = (rng() as u128 * self.a.len() as u128) >> 64;
+        self.a.get(i as usize)
+    }
+
+    /// Merge two sets into one, keeping elements of self for values contained in both.
+    /// Sorted elements of both are merged and the layout is built once, instead of adding them one by one.
+    /// O(n + m)
+    pub fn merge(self, other: Self) -> Self {
+        self.merge_sorted(other)
+    }
+}
+
+impl<T> IntoIterator for EytzingerSet<T>
+where
+    T: Ord,
+{
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    // Elements are moved out in ascending order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_sorted_vec().into_iter()
+    }
+}
+
+impl<T> HeapSize for EytzingerSet<T>
+where
+    T: Ord,
+{
+    fn heap_bytes(&self) -> usize {
+        self.a.len() * std::mem::size_of::<T>()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::EytzingerSet;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // The layout is determined by the set of values, so they're simply collected.
+    impl<'a, T> Arbitrary<'a> for EytzingerSet<T>
+    where
+        T: Ord + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_iter::<T>()?.collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EytzingerSet;
+    use interface::set::{SSet, SSetExt};
+
+    #[test]
+    fn sset_conformance_test() {
+        let mut set: EytzingerSet<usize> = EytzingerSet::new();
+        test_support::conformance::check_sset(&mut set);
+    }
+
+    #[test]
+    fn sset_test() {
+        let mut set: EytzingerSet<i32> = EytzingerSet::new();
+        assert_eq!(set.size(), 0);
+        assert_eq!(set.find(0), None);
+
+        set = vec![9, 1, 5, 3, 7, 5].into_iter().collect();
+        assert_eq!(set.size(), 5);
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![1, 3, 5, 7, 9]);
+        assert_eq!(set.find(0), Some(1));
+        assert_eq!(set.find(4), Some(5));
+        assert_eq!(set.find(7), Some(7));
+        assert_eq!(set.find(10), None);
+
+        assert!(set.add(4));
+        assert!(!set.add(4));
+        assert_eq!(set.remove(1), Some(1));
+        assert_eq!(set.remove(1), None);
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![3, 4, 5, 7, 9]);
+        assert_eq!(set.find(4), Some(4));
+    }
+
+    #[test]
+    fn find_test() {
+        for n in 0..70 {
+            let set = EytzingerSet::from_sorted((0..n).map(|x| 2 * x).collect());
+            assert!(set.iter().copied().eq((0..n).map(|x| 2 * x)));
+            for x in 0..2 * n + 2 {
+                let expected = if x + 1 < 2 * n { Some(x + x % 2) } else { None };
+                assert_eq!(set.find(x), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn find_by_test() {
+        let set: EytzingerSet<String> = ["pear", "apple", "fig"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(set.find_by("b").map(String::as_str), Some("fig"));
+        assert_eq!(set.find_by("q"), None);
+        assert_eq!(set.find_ref(&"b".to_string()), Some(&"fig".to_string()));
+    }
+
+    #[test]
+    fn choose_test() {
+        let mut x: u64 = 1;
+        let mut rng = || {
+            x = x
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            x
+        };
+        let empty: EytzingerSet<i32> = EytzingerSet::new();
+        assert_eq!(empty.choose(&mut rng), None);
+
+        // every element is chosen about equally often
+        let set = EytzingerSet::from_sorted((0..10).collect());
+        let mut count = [0; 10];
+        for _ in 0..10000 {
+            count[*set.choose(&mut rng).unwrap()] += 1;
+        }
+        assert!(count.iter().all(|&c| 800 < c && c < 1200), "{:?}", count);
+    }
+
+    #[test]
+    fn drain_test() {
+        let mut set: EytzingerSet<String> = ["b", "c", "a"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(set.drain().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(set.size(), 0);
+        assert_eq!(set.drain().next(), None);
+
+        set.add("x".to_string());
+        set.add("w".to_string());
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec!["w", "x"]);
+    }
+
+    #[test]
+    fn set_ops_test() {
+        use interface::set_ops::sorted;
+
+        let a: EytzingerSet<i32> = (0..20).filter(|x| x % 2 == 0).collect();
+        let b: EytzingerSet<i32> = (0..20).filter(|x| x % 3 == 0).collect();
+        let union: EytzingerSet<i32> = sorted::union(a.iter(), b.iter()).copied().collect();
+        assert_eq!(union.size(), 13);
+        assert!(sorted::intersection(a.iter(), b.iter()).eq([0, 6, 12, 18].iter()));
+        assert!(sorted::difference(b.iter(), a.iter()).eq([3, 9, 15].iter()));
+        assert!(sorted::is_subset(a.iter(), union.iter()));
+    }
+
+    #[test]
+    fn merge_test() {
+        for n in [0, 1, 30, 100].iter().copied() {
+            let a: EytzingerSet<i32> = (0..n).filter(|x| x % 2 == 0).collect();
+            let b: EytzingerSet<i32> = (0..n).filter(|x| x % 3 == 0).collect();
+            let merged = a.merge(b);
+            assert!(merged
+                .iter()
+                .copied()
+                .eq((0..n).filter(|x| x % 2 == 0 || x % 3 == 0)));
+            assert_eq!(merged.find(7), (8..n).find(|x| x % 2 == 0 || x % 3 == 0));
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use std::collections::BTreeSet;
+
+        test_support::replay::check_arbitrary(|set: EytzingerSet<u8>, u| {
+            let expected = u
+                .arbitrary_iter::<u8>()
+                .unwrap()
+                .collect::<Result<BTreeSet<_>, _>>()
+                .unwrap();
+            assert!(set.iter().eq(expected.iter()));
+            // the node k is larger than its left child 2k and smaller than its right child 2k + 1
+            let n = set.a.len();
+            for k in 1..=n {
+                assert!(2 * k > n || set.a[2 * k - 1] < set.a[k - 1]);
+                assert!(2 * k + 1 > n || set.a[k - 1] < set.a[2 * k]);
+            }
+            for x in 0..=u8::MAX {
+                assert_eq!(set.find(x), expected.range(x..).next().copied());
+            }
+        });
+    }
+}
@@ -0,0 +1,339 @@
+use interface::fmt::debug_entries;
+use interface::heap_size::HeapSize;
+use interface::list::List;
+use interface::queue::Stack;
+use std::fmt::{self, Debug, Formatter};
+use std::mem::MaybeUninit;
+
+/// List implementation with fixed capacity N, whose elements are stored inline without heap allocation.
+/// It is optimized for implementing stack interface.
+/// Adding a value to a full list panics. Use try_push(x) to handle overflow.
+/// Elements are stored without Option, and only the first n slots are initialized.
+/// O(1): get(i), set(i, x)
+/// O(1 + n - i): add(i, x), remove(i)
+pub struct FixedArrayStack<T: Debug, const N: usize> {
+    a: [MaybeUninit<T>; N],
+    n: usize,
+}
+
+impl<T, const N: usize> Drop for FixedArrayStack<T, N>
+where
+    T: Debug,
+{
+    fn drop(&mut self) {
+        let n = std::mem::replace(&mut self.n, 0);
+        for x in &mut self.a[..n] {
+            unsafe { x.assume_init_drop() }
+        }
+    }
+}
+
+impl<T, const N: usize> Default for FixedArrayStack<T, N>
+where
+    T: Debug,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> FixedArrayStack<T, N>
+where
+    T: Debug,
+{
+    /// Generate empty FixedArrayStack.
+    pub fn new() -> Self {
+        FixedArrayStack {
+            a: std::array::from_fn(|_| MaybeUninit::uninit()),
+            n: 0,
+        }
+    }
+
+    /// Return the maximum number of elements.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Return true if no more value can be added.
+    pub fn is_full(&self) -> bool {
+        self.n == N
+    }
+
+    /// Add a value to the tail if it's not full.
+    /// Return the given value as an error if it's full.
+    pub fn try_push(&mut self, x: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(x);
+        }
+        self.a[self.n].write(x);
+        self.n += 1;
+        Ok(())
+    }
+
+    // Take all elements out in order, and leave the list empty.
+    // Elements which are not consumed by the iterator are dropped with it.
+    pub(crate) fn drain(&mut self) -> Drain<'_, T> {
+        let n = std::mem::replace(&mut self.n, 0);
+        Drain {
+            rest: self.a[..n].iter_mut(),
+        }
+    }
+
+    // Validate whether the given index is within a range of the list.
+    fn within_bound(&self, i: usize) -> bool {
+        i < self.n
+    }
+}
+
+impl<T, const N: usize> List<T> for FixedArrayStack<T, N>
+where
+    T: Debug,
+{
+    fn size(&self) -> usize {
+        self.n
+    }
+
+    fn get(&self, i: usize) -> Option<&T> {
+        if !self.within_bound(i) {
+            None
+        } else {
+            Some(unsafe { self.a[i].assume_init_ref() })
+        }
+    }
+
+    fn set(&mut self, i: usize, x: T) -> Option<T> {
+        if !self.within_bound(i) {
+            panic!(
+                "index must be positive and less than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        } else {
+            Some(std::mem::replace(unsafe { self.a[i].assume_init_mut() }, x))
+        }
+    }
+
+    fn add(&mut self, i: usize, x: T) {
+        if i > self.n {
+            panic!(
+                "index must be positive and not greater than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
+        if self.is_full() {
+            panic!("list is full. capacity: {}", N)
+        }
+        self.a[self.n].write(x);
+        self.a[i..=self.n].rotate_right(1);
+        self.n += 1;
+    }
+
+    fn remove(&mut self, i: usize) -> Option<T> {
+        if !self.within_bound(i) {
+            return None;
+        }
+        let x = unsafe { self.a[i].assume_init_read() };
+        self.a[i..self.n].rotate_left(1);
+        self.n -= 1;
+        Some(x)
+    }
+}
+
+impl<T, const N: usize> Stack<T> for FixedArrayStack<T, N>
+where
+    T: Debug,
+{
+    fn push(&mut self, x: T) {
+        self.add(self.size(), x);
+    }
+    fn pop(&mut self) -> Option<T> {
+        if self.n == 0 {
+            return None;
+        }
+        self.remove(self.n - 1)
+    }
+}
+
+// Iterator which takes elements out of FixedArrayStack in order.
+pub(crate) struct Drain<'a, T> {
+    // initialized slots which are not taken yet
+    rest: std::slice::IterMut<'a, MaybeUninit<T>>,
+}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rest.next().map(|x| unsafe { x.assume_init_read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rest.size_hint()
+    }
+}
+
+impl<'a, T> Drop for Drain<'a, T> {
+    fn drop(&mut self) {
+        for x in &mut self.rest {
+            unsafe { x.assume_init_drop() }
+        }
+    }
+}
+
+impl<T, const N: usize> HeapSize for FixedArrayStack<T, N>
+where
+    T: Debug,
+{
+    fn heap_bytes(&self) -> usize {
+        0
+    }
+}
+
+impl<T, const N: usize> Debug for FixedArrayStack<T, N>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let values = (0..self.n).filter_map(move |i| self.get(i));
+        f.debug_struct("FixedArrayStack")
+            .field("elements", &debug_entries(values))
+            .field("size", &self.n)
+            .field("capacity", &N)
+            .finish()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::FixedArrayStack;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::queue::Stack;
+    use std::fmt::Debug;
+
+    // Decode operations, each of which pushes a value if it has room and pops one if op % 4 == 0.
+    pub(super) fn ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Values are pushed until it's full and sometimes popped.
+    impl<'a, T, const N: usize> Arbitrary<'a> for FixedArrayStack<T, N>
+    where
+        T: Debug + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut list = FixedArrayStack::new();
+            for (op, x) in ops(u)? {
+                let _ = list.try_push(x);
+                if op % 4 == 0 {
+                    list.pop();
+                }
+            }
+            Ok(list)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FixedArrayStack;
+    use interface::list::List;
+    use interface::queue::Stack;
+
+    #[test]
+    fn list_conformance_test() {
+        let mut list: FixedArrayStack<i32, 64> = FixedArrayStack::new();
+        test_support::conformance::check_list(&mut list);
+    }
+
+    #[test]
+    fn stack_conformance_test() {
+        let mut stack: FixedArrayStack<i32, 64> = FixedArrayStack::new();
+        test_support::conformance::check_stack(&mut stack);
+    }
+
+    #[test]
+    fn list_test() {
+        let mut list: FixedArrayStack<char, 4> = FixedArrayStack::new();
+        assert_eq!(list.capacity(), 4);
+        assert_eq!(list.size(), 0);
+        assert_eq!(list.get(0), None);
+
+        list.add(0, 'b');
+        list.add(1, 'e');
+        list.add(1, 'r');
+        list.add(3, 'd');
+        assert!(list.is_full());
+        assert_eq!(list.get(0), Some(&'b'));
+        assert_eq!(list.get(1), Some(&'r'));
+        assert_eq!(list.get(2), Some(&'e'));
+        assert_eq!(list.get(3), Some(&'d'));
+
+        assert_eq!(list.remove(1), Some('r'));
+        assert_eq!(list.remove(3), None);
+        assert_eq!(list.size(), 3);
+        assert_eq!(list.set(2, 'a'), Some('d'));
+        assert_eq!(list.get(0), Some(&'b'));
+        assert_eq!(list.get(1), Some(&'e'));
+        assert_eq!(list.get(2), Some(&'a'));
+    }
+
+    #[test]
+    fn stack_test() {
+        let mut stack: FixedArrayStack<i32, 2> = FixedArrayStack::new();
+        assert_eq!(stack.pop(), None);
+        stack.push(1);
+        assert_eq!(stack.try_push(2), Ok(()));
+        assert_eq!(stack.try_push(3), Err(3));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn drain_test() {
+        // elements which are not taken out are dropped with the iterator
+        let rc = std::rc::Rc::new(());
+        let mut stack: FixedArrayStack<_, 4> = FixedArrayStack::new();
+        for _ in 0..4 {
+            stack.push(rc.clone());
+        }
+        let mut drain = stack.drain();
+        assert!(drain.next().is_some());
+        drop(drain);
+        assert_eq!(stack.size(), 0);
+        assert_eq!(std::rc::Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn overflow_test() {
+        let mut stack: FixedArrayStack<i32, 1> = FixedArrayStack::new();
+        stack.push(1);
+        stack.push(2);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        test_support::replay::check_arbitrary(|list: FixedArrayStack<u16, 8>, u| {
+            // replay the same operations on Vec
+            let mut expected = Vec::new();
+            for (op, x) in super::arb::ops::<u16>(u).unwrap() {
+                if expected.len() < 8 {
+                    expected.push(x);
+                }
+                if op % 4 == 0 {
+                    expected.pop();
+                }
+            }
+            assert!((0..list.size())
+                .map(|i| list.get(i))
+                .eq(expected.iter().map(Some)));
+            assert!(list.size() <= list.capacity());
+        });
+    }
+}
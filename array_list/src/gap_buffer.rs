@@ -0,0 +1,356 @@
+use crate::raw::RawArray;
+use interface::fmt::debug_entries;
+use interface::heap_size::HeapSize;
+use interface::list::List;
+use std::fmt::{self, Debug, Formatter};
+
+/// List implementation with backing array realized by boxed slice which has a movable gap.
+/// Elements before the gap are stored at the head of the array, and the rest at its tail.
+/// It is optimized for localized addition/removal around the gap (cursor).
+/// Elements are stored without Option, and slots within the gap are uninitialized.
+/// O(1): get(i), set(i, x)
+/// O(1 + d): add(i, x), remove(i), move_gap(i) where d is the distance between i and the gap
+pub struct GapBuffer<T>
+where
+    T: Clone + Debug,
+{
+    a: RawArray<T>,
+    // the gap is a[gap_start..gap_end]
+    gap_start: usize,
+    gap_end: usize,
+}
+
+impl<T> Drop for GapBuffer<T>
+where
+    T: Clone + Debug,
+{
+    fn drop(&mut self) {
+        let (head, tail) = (self.gap_start, self.gap_end..self.capacity());
+        // the whole buffer becomes a gap first, so that a panicking drop leaks the rest
+        self.gap_start = 0;
+        self.gap_end = self.capacity();
+        unsafe {
+            self.a.drop_range(0..head);
+            self.a.drop_range(tail);
+        }
+    }
+}
+
+impl<T> GapBuffer<T>
+where
+    T: Clone + Debug,
+{
+    /// Generate empty GapBuffer
+    pub fn new() -> Self {
+        GapBuffer {
+            a: RawArray::new(0),
+            gap_start: 0,
+            gap_end: 0,
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.a.len()
+    }
+
+    fn gap_len(&self) -> usize {
+        self.gap_end - self.gap_start
+    }
+
+    fn within_bound(&self, i: usize) -> bool {
+        i < self.size()
+    }
+
+    // Return the index of the backing array for the position i.
+    fn index(&self, i: usize) -> usize {
+        if i < self.gap_start {
+            i
+        } else {
+            i + self.gap_len()
+        }
+    }
+
+    /// Return the position of the gap, which is the number of elements before it.
+    pub fn cursor(&self) -> usize {
+        self.gap_start
+    }
+
+    /// Move the gap so that it is placed just before the position i.
+    /// i larger than the size of list is clamped to it.
+    pub fn move_gap(&mut self, i: usize) {
+        let i = std::cmp::min(i, self.size());
+        if self.gap_start > i {
+            // move a[i..gap_start] to the tail side
+            let d = self.gap_start - i;
+            self.a.copy(i, self.gap_end - d, d);
+            self.gap_start -= d;
+            self.gap_end -= d;
+        } else {
+            // move a[gap_end..] to the head side as many as the distance
+            let d = i - self.gap_start;
+            self.a.copy(self.gap_end, self.gap_start, d);
+            self.gap_start += d;
+            self.gap_end += d;
+        }
+    }
+
+    fn resize(&mut self) {
+        let n = self.size();
+        let len = std::cmp::max(n * 2, 1);
+        let mut new_array = RawArray::new(len);
+        let tail = self.capacity() - self.gap_end;
+
+        self.a.copy_to(0, &mut new_array, 0, self.gap_start);
+        self.a
+            .copy_to(self.gap_end, &mut new_array, len - tail, tail);
+        self.a = new_array;
+        self.gap_end = len - tail;
+    }
+}
+
+impl<T> Default for GapBuffer<T>
+where
+    T: Clone + Debug,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> List<T> for GapBuffer<T>
+where
+    T: Clone + Debug,
+{
+    fn size(&self) -> usize {
+        self.capacity() - self.gap_len()
+    }
+
+    fn get(&self, i: usize) -> Option<&T> {
+        if !self.within_bound(i) {
+            None
+        } else {
+            Some(unsafe { self.a.get(self.index(i)) })
+        }
+    }
+
+    fn set(&mut self, i: usize, x: T) -> Option<T> {
+        if !self.within_bound(i) {
+            panic!(
+                "index must be positive and less than the size of list. i: {}, n: {}",
+                i,
+                self.size()
+            )
+        }
+        let k = self.index(i);
+        Some(std::mem::replace(unsafe { self.a.get_mut(k) }, x))
+    }
+
+    fn add(&mut self, i: usize, x: T) {
+        if i > self.size() {
+            panic!(
+                "index must be positive and not greater than the size of list. i: {}, n: {}",
+                i,
+                self.size()
+            )
+        }
+        if self.gap_len() == 0 {
+            self.resize();
+        }
+        self.move_gap(i);
+        self.a.write(self.gap_start, x);
+        self.gap_start += 1;
+    }
+
+    fn remove(&mut self, i: usize) -> Option<T> {
+        if !self.within_bound(i) {
+            return None;
+        }
+        self.move_gap(i);
+        let x = unsafe { self.a.read(self.gap_end) };
+        self.gap_end += 1;
+        if 3 * self.size() < self.capacity() {
+            self.resize();
+        }
+        Some(x)
+    }
+}
+
+impl<T> HeapSize for GapBuffer<T>
+where
+    T: Clone + Debug,
+{
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * std::mem::size_of::<T>()
+    }
+}
+
+impl<T> Debug for GapBuffer<T>
+where
+    T: Clone + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let values = (0..self.size()).filter_map(move |i| self.get(i));
+        f.debug_struct("GapBuffer")
+            .field("elements", &debug_entries(values))
+            .field("size", &self.size())
+            .field("cursor", &self.cursor())
+            .finish()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::GapBuffer;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::list::List;
+    use std::fmt::Debug;
+
+    // Decode operations, each of which adds a value at i and removes one at op / 4 if op % 4 == 0.
+    pub(super) fn ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, usize, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, usize, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Values are added at arbitrary positions and sometimes removed, so that the gap can be anywhere.
+    impl<'a, T> Arbitrary<'a> for GapBuffer<T>
+    where
+        T: Clone + Debug + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut list = GapBuffer::new();
+            for (op, i, x) in ops(u)? {
+                list.add(i % (list.size() + 1), x);
+                if op % 4 == 0 {
+                    list.remove(op as usize / 4 % list.size());
+                }
+            }
+            Ok(list)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GapBuffer;
+    use interface::list::List;
+
+    #[test]
+    fn list_conformance_test() {
+        let mut list: GapBuffer<i32> = GapBuffer::new();
+        test_support::conformance::check_list(&mut list);
+    }
+
+    #[test]
+    fn list_test() {
+        let mut list: GapBuffer<char> = GapBuffer::new();
+        assert_eq!(list.size(), 0);
+        assert_eq!(list.get(0), None);
+        assert_eq!(list.remove(0), None);
+
+        list.add(0, 'b');
+        list.add(1, 'd');
+        list.add(1, 'r');
+        list.add(2, 'e');
+        list.add(0, 'a');
+        assert_eq!(list.size(), 5);
+        assert_eq!(list.cursor(), 1);
+        let expected = ['a', 'b', 'r', 'e', 'd'];
+        for (i, x) in expected.iter().enumerate() {
+            assert_eq!(list.get(i), Some(x));
+        }
+
+        assert_eq!(list.remove(4), Some('d'));
+        assert_eq!(list.remove(0), Some('a'));
+        assert_eq!(list.set(1, 'i'), Some('r'));
+        assert_eq!(list.get(0), Some(&'b'));
+        assert_eq!(list.get(1), Some(&'i'));
+        assert_eq!(list.get(2), Some(&'e'));
+        assert_eq!(list.get(3), None);
+        assert_eq!(list.size(), 3);
+    }
+
+    #[test]
+    fn rotate_test() {
+        // GapBuffer relies on the default swap and rotation of List
+        let mut list: GapBuffer<usize> = GapBuffer::new();
+        let mut expected: Vec<usize> = (0..10).collect();
+        for x in 0..10 {
+            list.push_back(x);
+        }
+        for k in [3, 8, 10, 17] {
+            list.rotate_left(k);
+            expected.rotate_left(k % 10);
+            assert!((0..10).map(|i| list.get(i)).eq(expected.iter().map(Some)));
+        }
+        list.rotate_right(4);
+        expected.rotate_right(4);
+        list.swap(7, 2);
+        expected.swap(7, 2);
+        list.add_all(5, vec![20, 21]);
+        expected.splice(5..5, vec![20, 21]);
+        assert!((0..12).map(|i| list.get(i)).eq(expected.iter().map(Some)));
+    }
+
+    #[test]
+    fn cursor_test() {
+        let mut list: GapBuffer<usize> = GapBuffer::new();
+        let mut expected = Vec::new();
+        for x in 0..100 {
+            // type around a cursor which sometimes jumps
+            let i = if x % 10 == 0 { x / 2 } else { list.cursor() };
+            list.add(i, x);
+            expected.insert(i, x);
+            if x % 7 == 0 {
+                let i = list.cursor() - 1;
+                assert_eq!(list.remove(i), Some(expected.remove(i)));
+            }
+        }
+        list.move_gap(1000);
+        assert_eq!(list.cursor(), list.size());
+        list.move_gap(0);
+        assert_eq!(list.cursor(), 0);
+        assert_eq!(list.size(), expected.len());
+        for (i, x) in expected.iter().enumerate() {
+            assert_eq!(list.get(i), Some(x));
+        }
+    }
+
+    #[test]
+    fn drop_test() {
+        // values on both sides of the gap are dropped exactly once
+        let rc = std::rc::Rc::new(());
+        let mut list = GapBuffer::new();
+        for i in 0..10 {
+            list.add(i / 2, rc.clone());
+        }
+        drop(list.remove(3));
+        list.set(0, rc.clone());
+        assert_eq!(std::rc::Rc::strong_count(&rc), 10);
+        drop(list);
+        assert_eq!(std::rc::Rc::strong_count(&rc), 1);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        test_support::replay::check_arbitrary(|list: GapBuffer<u16>, u| {
+            // replay the same operations on Vec
+            let mut expected = Vec::new();
+            for (op, i, x) in super::arb::ops::<u16>(u).unwrap() {
+                expected.insert(i % (expected.len() + 1), x);
+                if op % 4 == 0 {
+                    expected.remove(op as usize / 4 % expected.len());
+                }
+            }
+            assert!((0..list.size())
+                .map(|i| list.get(i))
+                .eq(expected.iter().map(Some)));
+            assert!(list.gap_start <= list.gap_end && list.gap_end <= list.capacity());
+            assert!(list.capacity() <= std::cmp::max(3 * list.size(), 1));
+        });
+    }
+}
@@ -7,3 +7,30 @@ pub mod array_deque;
 pub mod array_queue;
 /// Implementation for List optimized to realize double-ended queue.
 pub mod array_stack;
+/// Implementation for Queue with fixed capacity realized as ring buffer.
+pub mod bounded_queue;
+/// Implementation for static SSet stored in Eytzinger (BFS) order.
+pub mod eytzinger;
+/// Implementation for List with fixed capacity stored inline without heap allocation.
+pub mod fixed;
+/// Implementation for List with a movable gap optimized for localized edits.
+pub mod gap_buffer;
+/// Implementation for stack and queue which track the minimum value.
+pub mod min_stack;
+/// Implementation for List backed by memory-mapped array, which can be larger than memory and persisted to a file.
+#[cfg(feature = "mmap")]
+pub mod mmap_stack;
+/// Implementation for dynamic SSet kept sorted in an array with gaps.
+pub mod pma;
+/// Implementation for pool which recycles emptied buffers without reallocation.
+pub mod pool;
+mod raw;
+/// Implementation for FIFO window answering associative aggregates.
+pub mod sliding_window;
+/// Implementation for List which stores small number of elements inline before spilling to heap.
+pub mod small;
+mod sorted_layout;
+/// Implementation for FIFO queue built from two stacks.
+pub mod two_stack_queue;
+/// Implementation for static SSet stored in cache-oblivious van Emde Boas layout.
+pub mod veb_layout;
@@ -0,0 +1,425 @@
+use crate::array_stack::ArrayStack;
+use interface::fmt::{debug_entries, format_with};
+use interface::heap_size::HeapSize;
+use interface::list::List;
+use interface::queue::{Queue, Stack};
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// Stack implementation which tracks the minimum value, built on ArrayStack.
+/// Another stack keeps positions of values which were the minimum when they were pushed.
+/// O(1): push(x), pop(), min()
+pub struct MinStack<T>
+where
+    T: Clone + Debug + Ord,
+{
+    a: ArrayStack<T>,
+    // positions of prefix minimums, whose top is the position of the current minimum
+    mins: ArrayStack<usize>,
+}
+
+impl<T> Default for MinStack<T>
+where
+    T: Clone + Debug + Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MinStack<T>
+where
+    T: Clone + Debug + Ord,
+{
+    /// Generate empty MinStack.
+    pub fn new() -> Self {
+        MinStack {
+            a: ArrayStack::new(),
+            mins: ArrayStack::new(),
+        }
+    }
+
+    /// Return the number of elements.
+    pub fn size(&self) -> usize {
+        self.a.size()
+    }
+
+    /// Return the minimum value, otherwise return null(Empty).
+    pub fn min(&self) -> Option<&T> {
+        let i = *self.mins.get(self.mins.size().checked_sub(1)?)?;
+        self.a.get(i)
+    }
+}
+
+impl<T> Stack<T> for MinStack<T>
+where
+    T: Clone + Debug + Ord,
+{
+    fn push(&mut self, x: T) {
+        if self.min().is_none_or(|y| x < *y) {
+            self.mins.push(self.a.size());
+        }
+        self.a.push(x);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let n = self.a.size();
+        if n == 0 {
+            return None;
+        }
+        if self.mins.get(self.mins.size() - 1) == Some(&(n - 1)) {
+            self.mins.pop();
+        }
+        self.a.pop()
+    }
+}
+
+impl<T> HeapSize for MinStack<T>
+where
+    T: Clone + Debug + Ord,
+{
+    fn heap_bytes(&self) -> usize {
+        self.a.heap_bytes() + self.mins.heap_bytes()
+    }
+}
+
+impl<T> Debug for MinStack<T>
+where
+    T: Clone + Debug + Ord,
+{
+    // The stack of minimum positions is summarized by the current minimum.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MinStack")
+            .field("elements", &debug_entries(self.a.iter()))
+            .field("size", &self.size())
+            .field("capacity", &self.a.capacity())
+            .field("min", &self.min())
+            .finish()
+    }
+}
+
+impl<T> Display for MinStack<T>
+where
+    T: Clone + Debug + Display + Ord,
+{
+    // Elements are written from the bottom to the top.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        Display::fmt(&format_with(self.a.iter(), ", "), f)?;
+        f.write_str("]")
+    }
+}
+
+/// FIFO queue implementation which tracks the minimum value, built from two MinStacks.
+/// Values are added to the back stack, and moved to the front stack in reverse order when it runs out.
+/// O(1): add(x), min()
+/// O(1) amortized: remove()
+pub struct MinQueue<T>
+where
+    T: Clone + Debug + Ord,
+{
+    front: MinStack<T>,
+    back: MinStack<T>,
+}
+
+impl<T> Default for MinQueue<T>
+where
+    T: Clone + Debug + Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MinQueue<T>
+where
+    T: Clone + Debug + Ord,
+{
+    /// Generate empty MinQueue.
+    pub fn new() -> Self {
+        MinQueue {
+            front: MinStack::new(),
+            back: MinStack::new(),
+        }
+    }
+
+    /// Return the number of elements.
+    pub fn size(&self) -> usize {
+        self.front.size() + self.back.size()
+    }
+
+    /// Return the minimum value, otherwise return null(Empty).
+    pub fn min(&self) -> Option<&T> {
+        match (self.front.min(), self.back.min()) {
+            (Some(x), Some(y)) => Some(std::cmp::min(x, y)),
+            (x, y) => x.or(y),
+        }
+    }
+}
+
+impl<T> Queue<T> for MinQueue<T>
+where
+    T: Clone + Debug + Ord,
+{
+    fn add(&mut self, x: T) -> bool {
+        self.back.push(x);
+        true
+    }
+
+    fn remove(&mut self) -> Option<T> {
+        if self.front.size() == 0 {
+            while let Some(x) = self.back.pop() {
+                self.front.push(x);
+            }
+        }
+        self.front.pop()
+    }
+}
+
+impl<T> HeapSize for MinQueue<T>
+where
+    T: Clone + Debug + Ord,
+{
+    fn heap_bytes(&self) -> usize {
+        self.front.heap_bytes() + self.back.heap_bytes()
+    }
+}
+
+impl<T> MinQueue<T>
+where
+    T: Clone + Debug + Ord,
+{
+    // Return the elements from the head to the tail, where the front stack holds the head at its top.
+    fn values(&self) -> impl Iterator<Item = &T> + Clone {
+        self.front.a.iter().rev().chain(self.back.a.iter())
+    }
+}
+
+impl<T> Debug for MinQueue<T>
+where
+    T: Clone + Debug + Ord,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MinQueue")
+            .field("elements", &debug_entries(self.values()))
+            .field("size", &self.size())
+            .field(
+                "capacity",
+                &(self.front.a.capacity() + self.back.a.capacity()),
+            )
+            .field("min", &self.min())
+            .finish()
+    }
+}
+
+impl<T> Display for MinQueue<T>
+where
+    T: Clone + Debug + Display + Ord,
+{
+    // Elements are written from the head to the tail.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        Display::fmt(&format_with(self.values(), ", "), f)?;
+        f.write_str("]")
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::{MinQueue, MinStack};
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::queue::{Queue, Stack};
+    use std::fmt::Debug;
+
+    // Decode operations, each of which pushes a value and pops one if op % 4 == 0.
+    pub(super) fn stack_ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Decode operations, each of which adds a value and removes one if op % 3 == 0.
+    pub(super) fn queue_ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Values are pushed and sometimes popped, so that the stack of minimums shrinks as well.
+    impl<'a, T> Arbitrary<'a> for MinStack<T>
+    where
+        T: Clone + Debug + Ord + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut stack = MinStack::new();
+            for (op, x) in stack_ops(u)? {
+                stack.push(x);
+                if op % 4 == 0 {
+                    stack.pop();
+                }
+            }
+            Ok(stack)
+        }
+    }
+
+    // Values are added and sometimes removed, so that both inner stacks can hold values.
+    impl<'a, T> Arbitrary<'a> for MinQueue<T>
+    where
+        T: Clone + Debug + Ord + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut queue = MinQueue::new();
+            for (op, x) in queue_ops(u)? {
+                queue.add(x);
+                if op % 3 == 0 {
+                    queue.remove();
+                }
+            }
+            Ok(queue)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MinQueue, MinStack};
+
+    #[test]
+    fn stack_conformance_test() {
+        let mut stack: MinStack<i32> = MinStack::new();
+        test_support::conformance::check_stack(&mut stack);
+    }
+
+    #[test]
+    fn min_stack_test() {
+        use interface::queue::Stack;
+
+        let mut stack: MinStack<i32> = MinStack::new();
+        assert_eq!(stack.min(), None);
+        assert_eq!(stack.pop(), None);
+
+        for x in [5, 3, 7, 3, 1, 8] {
+            stack.push(x);
+        }
+        assert_eq!(stack.size(), 6);
+        let mut mins = Vec::new();
+        while let Some(min) = stack.min().copied() {
+            mins.push(min);
+            stack.pop();
+        }
+        assert_eq!(mins, vec![1, 1, 3, 3, 3, 5]);
+        assert_eq!(stack.size(), 0);
+    }
+
+    #[test]
+    fn min_queue_test() {
+        use interface::queue::Queue;
+
+        let mut queue: MinQueue<u64> = MinQueue::new();
+        assert_eq!(queue.min(), None);
+        assert_eq!(queue.remove(), None);
+
+        // sliding window minimum of width 4
+        let xs = (0..200u64)
+            .map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 56)
+            .collect::<Vec<_>>();
+        for (i, &x) in xs.iter().enumerate() {
+            assert!(queue.add(x));
+            if i >= 4 {
+                assert_eq!(queue.remove(), Some(xs[i - 4]));
+            }
+            let lo = i.saturating_sub(3);
+            assert_eq!(queue.min(), xs[lo..=i].iter().min());
+        }
+        assert_eq!(queue.size(), 4);
+    }
+
+    #[test]
+    fn fmt_test() {
+        use interface::queue::{Queue, Stack};
+
+        let mut stack: MinStack<i32> = MinStack::new();
+        stack.push(3);
+        stack.push(1);
+        stack.push(2);
+        assert_eq!(stack.to_string(), "[3, 1, 2]");
+        assert_eq!(
+            format!("{:?}", stack),
+            format!(
+                "MinStack {{ elements: [3, 1, 2], size: 3, capacity: {}, min: Some(1) }}",
+                stack.a.capacity()
+            )
+        );
+
+        // the front stack holds the head reversed, which isn't exposed
+        let mut queue: MinQueue<i32> = MinQueue::new();
+        for x in [4, 2, 5] {
+            queue.add(x);
+        }
+        assert_eq!(queue.remove(), Some(4));
+        queue.add(1);
+        assert_eq!(queue.to_string(), "[2, 5, 1]");
+        let capacity = queue.front.a.capacity() + queue.back.a.capacity();
+        assert_eq!(
+            format!("{:?}", queue),
+            format!(
+                "MinQueue {{ elements: [2, 5, 1], size: 3, capacity: {}, min: Some(1) }}",
+                capacity
+            )
+        );
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use interface::list::List;
+        use interface::queue::{Queue, Stack};
+        use std::collections::VecDeque;
+
+        test_support::replay::check_arbitrary(|mut stack: MinStack<u16>, u| {
+            // replay the same operations on Vec
+            let mut expected = Vec::new();
+            for (op, x) in super::arb::stack_ops::<u16>(u).unwrap() {
+                expected.push(x);
+                if op % 4 == 0 {
+                    expected.pop();
+                }
+            }
+            // mins holds exactly the positions of strict prefix minimums
+            let mins = (0..stack.mins.size())
+                .filter_map(|i| stack.mins.get(i).copied())
+                .collect::<Vec<_>>();
+            let prefix_mins = (0..expected.len())
+                .filter(|&i| expected[..i].iter().all(|y| expected[i] < *y))
+                .collect::<Vec<_>>();
+            assert_eq!(mins, prefix_mins);
+            while !expected.is_empty() {
+                assert_eq!(stack.min(), expected.iter().min());
+                assert_eq!(stack.pop(), expected.pop());
+            }
+            assert_eq!(stack.pop(), None);
+        });
+
+        test_support::replay::check_arbitrary(|mut queue: MinQueue<u16>, u| {
+            // replay the same operations on VecDeque
+            let mut expected = VecDeque::new();
+            for (op, x) in super::arb::queue_ops::<u16>(u).unwrap() {
+                expected.push_back(x);
+                if op % 3 == 0 {
+                    expected.pop_front();
+                }
+            }
+            assert_eq!(queue.size(), expected.len());
+            while !expected.is_empty() {
+                assert_eq!(queue.min(), expected.iter().min());
+                assert_eq!(queue.remove(), expected.pop_front());
+            }
+            assert_eq!(queue.remove(), None);
+        });
+    }
+}
@@ -0,0 +1,308 @@
+use bytemuck::Pod;
+use interface::heap_size::HeapSize;
+use interface::list::List;
+use interface::queue::Stack;
+use memmap2::{MmapMut, MmapOptions};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::marker::PhantomData;
+use std::path::Path;
+
+// Bytes before elements, which hold the number of elements and keep elements aligned up to 64 bytes.
+const HEADER: usize = 64;
+
+/// List implementation whose backing array lives in a memory-mapped region instead of heap.
+/// The region is either anonymous, or file-backed so that the list is persisted to the file and restored by open.
+/// The OS pages elements in and out, which lets the list grow beyond main memory.
+/// It is optimized for implementing stack interface.
+/// O(1): get(i), set(i, x)
+/// O(1 + n - i): add(i, x), remove(i)
+#[derive(Debug)]
+pub struct MmapArrayStack<T: Pod> {
+    map: MmapMut,
+    // the backing file, or None for anonymous region
+    file: Option<File>,
+    n: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> MmapArrayStack<T>
+where
+    T: Pod,
+{
+    /// Generate empty MmapArrayStack backed by an anonymous region.
+    pub fn new() -> io::Result<Self> {
+        Self::validate_type();
+        Ok(MmapArrayStack {
+            map: Self::map_anon(1)?,
+            file: None,
+            n: 0,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Open MmapArrayStack backed by a file, restoring elements stored in it.
+    /// The file is created if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::validate_type();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let len = file.metadata()?.len() as usize;
+        if len < HEADER + std::mem::size_of::<T>() {
+            file.set_len(Self::bytes_for(1) as u64)?;
+        }
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        let mut header = [0; 8];
+        header.copy_from_slice(&map[..8]);
+        let n = u64::from_le_bytes(header) as usize;
+        let capacity = (map.len() - HEADER) / std::mem::size_of::<T>();
+        if n > capacity {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "file holds fewer elements than its header says. n: {}, capacity: {}",
+                    n, capacity
+                ),
+            ));
+        }
+        Ok(MmapArrayStack {
+            map,
+            file: Some(file),
+            n,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Write modified elements back to the backing file. It does nothing for anonymous region.
+    pub fn flush(&self) -> io::Result<()> {
+        if self.file.is_some() {
+            self.map.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Return the number of elements which fit in the mapped region.
+    pub fn capacity(&self) -> usize {
+        (self.map.len() - HEADER) / std::mem::size_of::<T>()
+    }
+
+    fn validate_type() {
+        if std::mem::size_of::<T>() == 0 || std::mem::align_of::<T>() > HEADER {
+            panic!(
+                "element must be sized and aligned within {} bytes. size: {}, align: {}",
+                HEADER,
+                std::mem::size_of::<T>(),
+                std::mem::align_of::<T>()
+            )
+        }
+    }
+
+    fn bytes_for(capacity: usize) -> usize {
+        HEADER + capacity * std::mem::size_of::<T>()
+    }
+
+    fn map_anon(capacity: usize) -> io::Result<MmapMut> {
+        MmapOptions::new().len(Self::bytes_for(capacity)).map_anon()
+    }
+
+    fn elements(&self) -> &[T] {
+        bytemuck::cast_slice(&self.map[HEADER..Self::bytes_for(self.n)])
+    }
+
+    fn elements_mut(&mut self) -> &mut [T] {
+        let end = Self::bytes_for(self.capacity());
+        bytemuck::cast_slice_mut(&mut self.map[HEADER..end])
+    }
+
+    // Store the number of elements to the header, so that the file can be restored.
+    fn set_size(&mut self, n: usize) {
+        self.n = n;
+        self.map[..8].copy_from_slice(&(n as u64).to_le_bytes());
+    }
+
+    // Map a region twice as large, since the mapping can't be extended in place.
+    fn resize(&mut self) -> io::Result<()> {
+        let capacity = std::cmp::max(self.capacity() * 2, 1);
+        match self.file.as_ref() {
+            Some(file) => {
+                self.map.flush()?;
+                file.set_len(Self::bytes_for(capacity) as u64)?;
+                self.map = unsafe { MmapMut::map_mut(file)? };
+            }
+            None => {
+                let mut map = Self::map_anon(capacity)?;
+                let used = Self::bytes_for(self.n);
+                map[..used].copy_from_slice(&self.map[..used]);
+                self.map = map;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T> List<T> for MmapArrayStack<T>
+where
+    T: Pod,
+{
+    fn size(&self) -> usize {
+        self.n
+    }
+
+    fn get(&self, i: usize) -> Option<&T> {
+        self.elements().get(i)
+    }
+
+    fn set(&mut self, i: usize, x: T) -> Option<T> {
+        if i >= self.n {
+            panic!(
+                "index must be positive and less than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
+        Some(std::mem::replace(&mut self.elements_mut()[i], x))
+    }
+
+    fn add(&mut self, i: usize, x: T) {
+        if i > self.n {
+            panic!(
+                "index must be positive and not greater than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
+        if self.n + 1 > self.capacity() {
+            if let Err(e) = self.resize() {
+                panic!("failed to grow mapped region. n: {}, error: {}", self.n, e)
+            }
+        }
+        let n = self.n;
+        let a = self.elements_mut();
+        a.copy_within(i..n, i + 1);
+        a[i] = x;
+        self.set_size(n + 1);
+    }
+
+    fn remove(&mut self, i: usize) -> Option<T> {
+        if i >= self.n {
+            return None;
+        }
+        let n = self.n;
+        let a = self.elements_mut();
+        let x = a[i];
+        a.copy_within(i + 1..n, i);
+        self.set_size(n - 1);
+        Some(x)
+    }
+}
+
+impl<T> Stack<T> for MmapArrayStack<T>
+where
+    T: Pod,
+{
+    fn push(&mut self, x: T) {
+        self.add(self.n, x);
+    }
+    fn pop(&mut self) -> Option<T> {
+        if self.n == 0 {
+            return None;
+        }
+        self.remove(self.n - 1)
+    }
+}
+
+impl<T> HeapSize for MmapArrayStack<T>
+where
+    T: Pod,
+{
+    // An anonymous region takes memory like heap, while a file-backed one is paged to the file.
+    fn heap_bytes(&self) -> usize {
+        if self.file.is_some() {
+            0
+        } else {
+            self.map.len()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MmapArrayStack;
+    use interface::list::List;
+    use interface::queue::Stack;
+
+    #[test]
+    // Miri can't map files
+    #[cfg_attr(miri, ignore)]
+    fn list_conformance_test() {
+        let mut list: MmapArrayStack<i32> = MmapArrayStack::new().unwrap();
+        test_support::conformance::check_list(&mut list);
+    }
+
+    #[test]
+    // Miri can't map files
+    #[cfg_attr(miri, ignore)]
+    fn stack_conformance_test() {
+        let mut stack: MmapArrayStack<i32> = MmapArrayStack::new().unwrap();
+        test_support::conformance::check_stack(&mut stack);
+    }
+
+    #[test]
+    // Miri can't map files
+    #[cfg_attr(miri, ignore)]
+    fn list_test() {
+        let mut list: MmapArrayStack<u32> = MmapArrayStack::new().unwrap();
+        assert_eq!(list.size(), 0);
+        assert_eq!(list.get(0), None);
+        assert_eq!(list.pop(), None);
+
+        list.add(0, 2);
+        list.add(0, 1);
+        list.add(2, 4);
+        list.add(2, 3);
+        assert_eq!(list.size(), 4);
+        for (i, x) in [1, 2, 3, 4].iter().enumerate() {
+            assert_eq!(list.get(i), Some(x));
+        }
+        assert_eq!(list.set(1, 5), Some(2));
+        assert_eq!(list.remove(0), Some(1));
+        assert_eq!(list.remove(3), None);
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.size(), 0);
+
+        for x in 0..10000 {
+            list.push(x);
+        }
+        assert!(list.capacity() >= 10000);
+        assert!((0..10000).all(|i| list.get(i as usize) == Some(&i)));
+    }
+
+    #[test]
+    // Miri can't map files
+    #[cfg_attr(miri, ignore)]
+    fn persist_test() {
+        let path = std::env::temp_dir().join(format!("mmap_stack_test-{}", std::process::id()));
+        {
+            let mut list: MmapArrayStack<u64> = MmapArrayStack::open(&path).unwrap();
+            for x in 0..5000 {
+                list.push(x * x);
+            }
+            list.pop();
+            list.flush().unwrap();
+        }
+        {
+            let mut list: MmapArrayStack<u64> = MmapArrayStack::open(&path).unwrap();
+            assert_eq!(list.size(), 4999);
+            assert_eq!(list.get(4998), Some(&(4998 * 4998)));
+            list.set(0, 7);
+        }
+        let list: MmapArrayStack<u64> = MmapArrayStack::open(&path).unwrap();
+        assert_eq!(list.get(0), Some(&7));
+        drop(list);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
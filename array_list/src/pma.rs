@@ -0,0 +1,597 @@
+use interface::heap_size::HeapSize;
+use interface::set::{SSet, SSetExt};
+use interface::set_ops::sorted;
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::ops::{Bound, RangeBounds};
+
+// The minimum capacity, which is also the minimum segment size.
+const MIN_CAPACITY: usize = 8;
+
+/// SSet implementation with backing array which keeps sorted elements with gaps (packed-memory array).
+/// The array is divided into segments of O(log n) slots, whose elements are packed to the left.
+/// When a segment overflows or becomes empty, the smallest enclosing window of segments whose density is within
+/// the thresholds of its level is rebalanced by spreading its elements evenly.
+/// O(log n): find(x)
+/// O(log^2 n) amortized: add(x), remove(x)
+/// O(log n + k): range(r) for k elements in range
+#[derive(Debug)]
+pub struct PackedMemoryArray<T>
+where
+    T: Ord + Clone + Debug,
+{
+    a: Box<[Option<T>]>,
+    // the number of elements in each segment
+    cnt: Box<[usize]>,
+    seg: usize,
+    n: usize,
+}
+
+impl<T> PackedMemoryArray<T>
+where
+    T: Ord + Clone + Debug,
+{
+    /// Generate empty PackedMemoryArray
+    pub fn new() -> Self {
+        PackedMemoryArray {
+            a: allocate_with(MIN_CAPACITY).into_boxed_slice(),
+            cnt: vec![0; 1].into_boxed_slice(),
+            seg: MIN_CAPACITY,
+            n: 0,
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.a.len()
+    }
+
+    fn segments(&self) -> usize {
+        self.cnt.len()
+    }
+
+    // Return the height of the implicit tree over segments.
+    fn height(&self) -> usize {
+        self.segments().trailing_zeros() as usize
+    }
+
+    // Thresholds of density for windows at the level l, which get tighter toward the root.
+    fn upper(&self, l: usize) -> f64 {
+        1.0 - 0.25 * l as f64 / self.height().max(1) as f64
+    }
+
+    fn lower(&self, l: usize) -> f64 {
+        0.125 + 0.125 * l as f64 / self.height().max(1) as f64
+    }
+
+    // Every segment is non-empty while the array is non-empty, so that it starts with an element.
+    fn first(&self, s: usize) -> &T {
+        self.a[s * self.seg]
+            .as_ref()
+            .expect("segment must not be empty")
+    }
+
+    // Return the segment and the offset in it for the first element y which satisfies y >= x.
+    // The offset may be equal to the number of elements in the segment.
+    fn locate<Q>(&self, x: &Q) -> (usize, usize)
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        if self.n == 0 {
+            return (0, 0);
+        }
+        // the last segment whose first element is not greater than x, or the first segment
+        let (mut lo, mut hi) = (0, self.segments());
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if self.first(mid).borrow() <= x {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let s = lo;
+        let start = s * self.seg;
+        let i = self.a[start..start + self.cnt[s]].partition_point(|y| {
+            y.as_ref()
+                .expect("element within count must exist")
+                .borrow()
+                < x
+        });
+        (s, i)
+    }
+
+    // Return the element at the offset i in the segment s,
+    // where the offset equal to the number of elements refers to the first element of the next segment.
+    fn element(&self, s: usize, i: usize) -> Option<&T> {
+        if i < self.cnt[s] {
+            self.a[s * self.seg + i].as_ref()
+        } else if s + 1 < self.segments() && self.n > 0 {
+            Some(self.first(s + 1))
+        } else {
+            None
+        }
+    }
+
+    // Take all elements in the window of k segments from the segment s.
+    fn take_window(&mut self, s: usize, k: usize) -> Vec<T> {
+        let mut values = Vec::new();
+        for t in s..s + k {
+            let start = t * self.seg;
+            for y in self.a[start..start + self.cnt[t]].iter_mut() {
+                values.push(y.take().expect("element within count must exist"));
+            }
+            self.cnt[t] = 0;
+        }
+        values
+    }
+
+    // Spread values evenly over the window of k segments from the segment s.
+    fn spread(&mut self, s: usize, k: usize, values: Vec<T>) {
+        let m = values.len();
+        let mut values = values.into_iter();
+        for j in 0..k {
+            let c = m * (j + 1) / k - m * j / k;
+            let start = (s + j) * self.seg;
+            for y in self.a[start..start + c].iter_mut() {
+                *y = values.next();
+            }
+            self.cnt[s + j] = c;
+        }
+    }
+
+    // Rebuild the whole array with capacity about twice the number of elements.
+    fn resize(&mut self, values: Vec<T>) {
+        let m = values.len();
+        let cap = std::cmp::max(MIN_CAPACITY, (2 * m).next_power_of_two());
+        let log = cap.trailing_zeros() as usize;
+        self.seg = std::cmp::max(MIN_CAPACITY, log.next_power_of_two());
+        self.a = allocate_with(cap).into_boxed_slice();
+        self.cnt = vec![0; cap / self.seg].into_boxed_slice();
+        self.spread(0, self.segments(), values);
+    }
+
+    // Find the smallest window containing the segment s whose density satisfies ok,
+    // and rebalance it with f applied to its values. Resize if no window satisfies.
+    fn rebalance<F, P>(&mut self, s: usize, ok: P, f: F)
+    where
+        P: Fn(&Self, usize, usize, usize) -> bool,
+        F: FnOnce(&mut Vec<T>),
+    {
+        for l in 1..=self.height() {
+            let k = 1 << l;
+            let w = (s >> l) << l;
+            let count = self.cnt[w..w + k].iter().sum();
+            if ok(self, l, k, count) {
+                let mut values = self.take_window(w, k);
+                f(&mut values);
+                self.spread(w, k, values);
+                return;
+            }
+        }
+        let mut values = self.take_window(0, self.segments());
+        f(&mut values);
+        self.resize(values);
+    }
+
+    fn into_sorted_vec(mut self) -> Vec<T> {
+        self.take_window(0, self.segments())
+    }
+
+    /// Remove all elements within r, and return the number of removed elements.
+    /// Remaining elements are spread over a new array at once, instead of rebalancing for each removal.
+    /// O(n)
+    pub fn remove_range<R>(&mut self, r: R) -> usize
+    where
+        R: RangeBounds<T>,
+    {
+        let m = self.n;
+        let mut values = self.take_window(0, self.segments());
+        values.retain(|y| !r.contains(y));
+        self.n = values.len();
+        self.resize(values);
+        m - self.n
+    }
+
+    /// Return an iterator which visits elements in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.range(..)
+    }
+
+    /// Return an iterator which visits elements within r in ascending order.
+    /// Elements are scanned from contiguous memory, skipping gaps at the end of segments.
+    pub fn range<'a, R>(&'a self, r: R) -> impl Iterator<Item = &'a T> + 'a
+    where
+        R: RangeBounds<T> + 'a,
+    {
+        let (mut s, mut i) = match r.start_bound() {
+            Bound::Included(x) | Bound::Excluded(x) => self.locate(x),
+            Bound::Unbounded => (0, 0),
+        };
+        if let Bound::Excluded(x) = r.start_bound() {
+            if self.n > 0 && i < self.cnt[s] && self.a[s * self.seg + i].as_ref() == Some(x) {
+                i += 1;
+            }
+        }
+        std::iter::from_fn(move || {
+            while s < self.segments() && i >= self.cnt[s] {
+                s += 1;
+                i = 0;
+            }
+            let y = self.a.get(s * self.seg + i)?.as_ref()?;
+            i += 1;
+            Some(y)
+        })
+        .take_while(move |y| match r.end_bound() {
+            Bound::Included(x) => *y <= x,
+            Bound::Excluded(x) => *y < x,
+            Bound::Unbounded => true,
+        })
+    }
+}
+
+impl<T> Default for PackedMemoryArray<T>
+where
+    T: Ord + Clone + Debug,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn allocate_with<T>(n: usize) -> Vec<Option<T>> {
+    std::iter::repeat_with(|| None).take(n).collect()
+}
+
+impl<T> SSet<T> for PackedMemoryArray<T>
+where
+    T: Ord + Clone + Debug,
+{
+    fn size(&self) -> usize {
+        self.n
+    }
+
+    fn add(&mut self, x: T) -> bool {
+        let (s, i) = self.locate(&x);
+        let start = s * self.seg;
+        if i < self.cnt[s] && self.a[start + i].as_ref() == Some(&x) {
+            return false;
+        }
+        self.n += 1;
+        if self.cnt[s] < self.seg {
+            // shift to right for the rest of the segment
+            for k in (i..self.cnt[s]).rev() {
+                self.a[start + k + 1] = self.a[start + k].take();
+            }
+            self.a[start + i] = Some(x);
+            self.cnt[s] += 1;
+            return true;
+        }
+        let seg = self.seg;
+        self.rebalance(
+            s,
+            |pma, l, k, count| (count + 1) as f64 <= pma.upper(l) * (k * seg) as f64,
+            |values| {
+                let j = values.partition_point(|y| *y < x);
+                values.insert(j, x);
+            },
+        );
+        true
+    }
+
+    fn remove(&mut self, x: T) -> Option<T> {
+        if self.n == 0 {
+            return None;
+        }
+        let (s, i) = self.locate(&x);
+        let start = s * self.seg;
+        if i >= self.cnt[s] || self.a[start + i].as_ref() != Some(&x) {
+            return None;
+        }
+        let y = self.a[start + i].take();
+        // shift to left for the rest of the segment
+        for k in i + 1..self.cnt[s] {
+            self.a[start + k - 1] = self.a[start + k].take();
+        }
+        self.cnt[s] -= 1;
+        self.n -= 1;
+        if self.capacity() > MIN_CAPACITY && 4 * self.n < self.capacity() {
+            let values = self.take_window(0, self.segments());
+            self.resize(values);
+        } else if self.n > 0 && self.cnt[s] == 0 {
+            let seg = self.seg;
+            self.rebalance(
+                s,
+                |pma, l, k, count| count >= k && count as f64 >= pma.lower(l) * (k * seg) as f64,
+                |_| {},
+            );
+        }
+        y
+    }
+
+    fn find(&self, x: T) -> Option<T> {
+        self.find_by(&x).cloned()
+    }
+
+    fn find_ref(&self, x: &T) -> Option<&T> {
+        self.find_by(x)
+    }
+
+    fn successor(&self, x: &T) -> Option<&T> {
+        let (s, i) = self.locate(x);
+        match self.element(s, i) {
+            Some(y) if y == x => self.element(s, i + 1),
+            y => y,
+        }
+    }
+
+    fn predecessor(&self, x: &T) -> Option<&T> {
+        // an element before the segment is the last one of the previous segment, which is never empty
+        let (s, i) = self.locate(x);
+        if i > 0 {
+            self.a[s * self.seg + i - 1].as_ref()
+        } else if s > 0 {
+            self.a[(s - 1) * self.seg + self.cnt[s - 1] - 1].as_ref()
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> SSetExt<T> for PackedMemoryArray<T>
+where
+    T: Ord + Clone + Debug,
+{
+    fn find_by<Q>(&self, x: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let (s, i) = self.locate(x);
+        self.element(s, i)
+    }
+}
+
+impl<T> PackedMemoryArray<T>
+where
+    T: Ord + Clone + Debug,
+{
+    /// Remove all elements and return them in ascending order, leaving an empty set.
+    pub fn drain(&mut self) -> std::vec::IntoIter<T> {
+        std::mem::take(self).into_iter()
+    }
+
+    /// Merge two sets into one, keeping elements of self for values contained in both.
+    /// Sorted elements of both are merged and spread over a new array at once, instead of adding them one by one.
+    /// O(n + m)
+    pub fn merge(self, other: Self) -> Self {
+        let values =
+            sorted::union(self.into_sorted_vec(), other.into_sorted_vec()).collect::<Vec<_>>();
+        let mut merged = Self::new();
+        merged.n = values.len();
+        merged.resize(values);
+        merged
+    }
+}
+
+impl<T> IntoIterator for PackedMemoryArray<T>
+where
+    T: Ord + Clone + Debug,
+{
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    // Elements are moved out in ascending order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_sorted_vec().into_iter()
+    }
+}
+
+impl<T> HeapSize for PackedMemoryArray<T>
+where
+    T: Ord + Clone + Debug,
+{
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * std::mem::size_of::<Option<T>>()
+            + self.segments() * std::mem::size_of::<usize>()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::PackedMemoryArray;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::set::SSet;
+    use std::fmt::Debug;
+
+    // Decode operations, each of which adds a value or removes it.
+    pub(super) fn ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(bool, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(bool, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Values are added and removed, so that segments are rebalanced and the array is resized both ways.
+    impl<'a, T> Arbitrary<'a> for PackedMemoryArray<T>
+    where
+        T: Ord + Clone + Debug + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut set = PackedMemoryArray::new();
+            for (add, x) in ops(u)? {
+                if add {
+                    set.add(x);
+                } else {
+                    set.remove(x);
+                }
+            }
+            Ok(set)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PackedMemoryArray;
+    use interface::set::SSet;
+
+    #[test]
+    fn sset_conformance_test() {
+        let mut set: PackedMemoryArray<usize> = PackedMemoryArray::new();
+        test_support::conformance::check_sset(&mut set);
+    }
+
+    #[test]
+    fn sset_test() {
+        let mut set: PackedMemoryArray<i32> = PackedMemoryArray::new();
+        assert_eq!(set.size(), 0);
+        assert_eq!(set.find(0), None);
+        assert_eq!(set.remove(0), None);
+
+        for x in [5, 1, 9, 3, 7] {
+            assert!(set.add(x));
+        }
+        assert!(!set.add(3));
+        assert_eq!(set.size(), 5);
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![1, 3, 5, 7, 9]);
+        assert_eq!(set.find(0), Some(1));
+        assert_eq!(set.find(4), Some(5));
+        assert_eq!(set.find(9), Some(9));
+        assert_eq!(set.find(10), None);
+
+        assert_eq!(set.remove(5), Some(5));
+        assert_eq!(set.remove(5), None);
+        assert_eq!(set.find(4), Some(7));
+        assert_eq!(set.size(), 4);
+    }
+
+    #[test]
+    fn range_test() {
+        let set = (0..1000)
+            .map(|x| x * 3)
+            .fold(PackedMemoryArray::new(), |mut set, x| {
+                set.add(x);
+                set
+            });
+        assert!(set.range(10..20).copied().eq([12, 15, 18]));
+        assert!(set.range(12..=18).copied().eq([12, 15, 18]));
+        assert!(set
+            .range((std::ops::Bound::Excluded(12), std::ops::Bound::Unbounded))
+            .take(2)
+            .copied()
+            .eq([15, 18]));
+        assert_eq!(set.range(2990..).count(), 3);
+        assert_eq!(set.range(..).count(), 1000);
+    }
+
+    #[test]
+    fn random_test() {
+        let mut set = PackedMemoryArray::new();
+        let mut expected = std::collections::BTreeSet::new();
+        let n: u64 = if cfg!(miri) { 1000 } else { 5000 };
+        for i in 0..n {
+            let x = i.wrapping_mul(0x9e37_79b9_7f4a_7c15) % 2000;
+            if i % 3 == 2 || (i > n * 3 / 5 && i % 3 == 1) {
+                assert_eq!(set.remove(x), expected.take(&x));
+            } else {
+                assert_eq!(set.add(x), expected.insert(x));
+            }
+            assert_eq!(set.size(), expected.len());
+            let y = (x * 7) % 2000;
+            assert_eq!(set.find(y), expected.range(y..).next().copied());
+        }
+        assert!(set.iter().eq(expected.iter()));
+        for x in expected.clone() {
+            assert_eq!(set.remove(x), Some(x));
+        }
+        assert_eq!(set.size(), 0);
+        assert_eq!(set.iter().next(), None);
+    }
+
+    #[test]
+    fn drain_test() {
+        let mut set: PackedMemoryArray<String> = PackedMemoryArray::new();
+        for s in ["b", "c", "a"].iter() {
+            set.add(s.to_string());
+        }
+        assert_eq!(set.drain().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(set.size(), 0);
+        assert_eq!(set.drain().next(), None);
+
+        set.add("x".to_string());
+        set.add("w".to_string());
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec!["w", "x"]);
+    }
+
+    #[test]
+    fn merge_test() {
+        for n in [0, 1, 30, 1000].iter().copied() {
+            let (mut a, mut b) = (PackedMemoryArray::new(), PackedMemoryArray::new());
+            for x in (0..n).filter(|x| x % 2 == 0) {
+                a.add(x);
+            }
+            for x in (0..n).filter(|x| x % 3 == 0) {
+                b.add(x);
+            }
+            let mut merged = a.merge(b);
+            let expected = (0..n).filter(|x| x % 2 == 0 || x % 3 == 0);
+            assert_eq!(merged.size(), expected.clone().count());
+            assert!(merged.iter().copied().eq(expected));
+            // the merged array keeps working as a set
+            assert!(merged.add(n + 1));
+            assert_eq!(merged.remove(0), if n > 0 { Some(0) } else { None });
+            assert_eq!(merged.find(n), Some(n + 1));
+        }
+    }
+
+    #[test]
+    fn remove_range_test() {
+        let mut set = PackedMemoryArray::new();
+        for x in 0..1000 {
+            set.add(x);
+        }
+        assert_eq!(set.remove_range(100..900), 800);
+        assert_eq!(set.size(), 200);
+        assert!(set.iter().copied().eq((0..100).chain(900..1000)));
+        assert_eq!(set.find(100), Some(900));
+        assert_eq!(set.remove_range(..50), 50);
+        assert_eq!(set.remove_range(2000..), 0);
+        assert_eq!(set.remove_range(..), 150);
+        assert_eq!(set.find(0), None);
+        assert!(set.add(5));
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![5]);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use std::collections::BTreeSet;
+
+        test_support::replay::check_arbitrary(|set: PackedMemoryArray<u8>, u| {
+            // replay the same operations on BTreeSet
+            let mut expected = BTreeSet::new();
+            for (add, x) in super::arb::ops::<u8>(u).unwrap() {
+                if add {
+                    expected.insert(x);
+                } else {
+                    expected.remove(&x);
+                }
+            }
+            assert!(set.iter().eq(expected.iter()));
+            assert_eq!(set.size(), expected.len());
+
+            // each segment is packed to the left, and non-empty unless the whole array is empty
+            assert_eq!(set.segments() * set.seg, set.capacity());
+            assert_eq!(set.cnt.iter().sum::<usize>(), set.size());
+            for (s, &cnt) in set.cnt.iter().enumerate() {
+                let segment = &set.a[s * set.seg..(s + 1) * set.seg];
+                assert!(segment[..cnt].iter().all(Option::is_some));
+                assert!(segment[cnt..].iter().all(Option::is_none));
+                assert!(set.size() == 0 || cnt > 0);
+            }
+        });
+    }
+}
@@ -0,0 +1,157 @@
+use crate::array_deque::ArrayDeque;
+use crate::array_queue::ArrayQueue;
+use crate::array_stack::ArrayStack;
+use std::fmt::Debug;
+
+/// Recycle represents a buffer which can be emptied while keeping its allocation.
+pub trait Recycle {
+    /// Drop all elements, keeping the backing storage for reuse.
+    /// Removal never shrinks the backing storage afterward, so it survives until the buffer comes back.
+    fn recycle(&mut self);
+}
+
+impl<T> Recycle for ArrayStack<T>
+where
+    T: Clone + Debug,
+{
+    fn recycle(&mut self) {
+        self.retain_capacity(true);
+        self.clear_retaining_capacity();
+    }
+}
+
+impl<T> Recycle for ArrayQueue<T>
+where
+    T: Clone + Debug,
+{
+    fn recycle(&mut self) {
+        self.clear_retaining_capacity();
+    }
+}
+
+impl<T> Recycle for ArrayDeque<T>
+where
+    T: Clone + Debug,
+{
+    fn recycle(&mut self) {
+        self.retain_capacity(true);
+        self.clear_retaining_capacity();
+    }
+}
+
+/// Pool of emptied buffers, so that a hot loop can reuse their backing arrays instead of allocating new ones.
+/// Buffers are handed out in LIFO order, which reuses the most recently returned and likely largest one.
+/// It keeps at most limit buffers, and the rest are dropped when returned.
+/// O(1): take(), and put(b) in addition to emptying b
+#[derive(Debug)]
+pub struct Pool<B>
+where
+    B: Recycle + Default,
+{
+    buffers: Vec<B>,
+    limit: usize,
+}
+
+impl<B> Default for Pool<B>
+where
+    B: Recycle + Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> Pool<B>
+where
+    B: Recycle + Default,
+{
+    /// Generate empty Pool without limit of buffers.
+    pub fn new() -> Self {
+        Self::with_limit(usize::MAX)
+    }
+
+    /// Generate empty Pool which keeps at most limit buffers.
+    pub fn with_limit(limit: usize) -> Self {
+        Pool {
+            buffers: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Return the number of buffers kept for reuse.
+    pub fn size(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Return an empty buffer, which is a returned one if any, otherwise a new one.
+    pub fn take(&mut self) -> B {
+        self.buffers.pop().unwrap_or_default()
+    }
+
+    /// Return a buffer to the pool after emptying it.
+    /// It's dropped if the pool already keeps limit buffers.
+    pub fn put(&mut self, mut b: B) {
+        if self.buffers.len() < self.limit {
+            b.recycle();
+            self.buffers.push(b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pool;
+    use crate::array_deque::ArrayDeque;
+    use crate::array_stack::ArrayStack;
+    use interface::list::List;
+    use interface::queue::Stack;
+
+    #[test]
+    fn pool_test() {
+        let mut pool: Pool<ArrayStack<i32>> = Pool::with_limit(1);
+        assert_eq!(pool.size(), 0);
+        let mut a = pool.take();
+        for x in 0..100 {
+            a.push(x);
+        }
+        let capacity = a.capacity();
+        pool.put(a);
+        pool.put(ArrayStack::new());
+        assert_eq!(pool.size(), 1);
+
+        // the returned buffer comes back empty with its backing array
+        let b = pool.take();
+        assert_eq!(b.size(), 0);
+        assert_eq!(b.capacity(), capacity);
+        assert_eq!(pool.size(), 0);
+        assert_eq!(pool.take().capacity(), 0);
+    }
+
+    #[test]
+    fn retain_test() {
+        // a buffer from the pool keeps its backing array while it's filled and emptied one by one
+        let mut pool: Pool<ArrayStack<i32>> = Pool::new();
+        let mut a = pool.take();
+        a.resize_with(100, Default::default);
+        let capacity = a.capacity();
+        pool.put(a);
+        let mut b = pool.take();
+        for x in 0..10 {
+            b.push(x);
+        }
+        while b.pop().is_some() {}
+        assert_eq!(b.capacity(), capacity);
+
+        let mut pool: Pool<ArrayDeque<i32>> = Pool::new();
+        let mut a = pool.take();
+        a.resize_with(100, Default::default);
+        let capacity = a.capacity();
+        pool.put(a);
+        let mut b = pool.take();
+        for x in 0..10 {
+            b.push_back(x);
+        }
+        while b.remove(0).is_some() {}
+        assert_eq!(b.capacity(), capacity);
+    }
+}
@@ -0,0 +1,291 @@
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ops::Range;
+use std::ptr;
+
+// Backing array of possibly uninitialized slots, which stores T without the tag of Option<T>.
+// It never drops values by itself, and the owner tracks which slots are initialized like Vec does with len.
+// Moving slots is a bitwise copy, so the owner must treat the source slots as uninitialized afterward.
+pub(crate) struct RawArray<T> {
+    a: Box<[MaybeUninit<T>]>,
+}
+
+#[cfg(test)]
+thread_local! {
+    // Number of slots moved by copy and copy_to on this thread, which tests read to check bounds of moves.
+    static MOVES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+// Return the number of slots moved so far on this thread.
+#[cfg(test)]
+pub(crate) fn moves() -> usize {
+    MOVES.with(|m| m.get())
+}
+
+#[cfg(test)]
+fn count_moves(k: usize) {
+    MOVES.with(|m| m.set(m.get() + k));
+}
+
+#[cfg(not(test))]
+fn count_moves(_: usize) {}
+
+impl<T> RawArray<T> {
+    pub(crate) fn new(len: usize) -> Self {
+        RawArray {
+            a: std::iter::repeat_with(MaybeUninit::uninit)
+                .take(len)
+                .collect(),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.a.len()
+    }
+
+    // Safety: the slot i must be initialized.
+    pub(crate) unsafe fn get(&self, i: usize) -> &T {
+        self.a[i].assume_init_ref()
+    }
+
+    // Safety: the slot i must be initialized.
+    pub(crate) unsafe fn get_mut(&mut self, i: usize) -> &mut T {
+        self.a[i].assume_init_mut()
+    }
+
+    // Move the value out of the slot i.
+    // Safety: the slot i must be initialized, and it's regarded as uninitialized afterward.
+    pub(crate) unsafe fn read(&mut self, i: usize) -> T {
+        self.a[i].assume_init_read()
+    }
+
+    // Initialize the slot i. A value which is already there is leaked instead of dropped.
+    pub(crate) fn write(&mut self, i: usize, x: T) {
+        self.a[i].write(x);
+    }
+
+    // Safety: all slots within r must be initialized.
+    pub(crate) unsafe fn slice(&self, r: Range<usize>) -> &[T] {
+        let s = &self.a[r];
+        std::slice::from_raw_parts(s.as_ptr() as *const T, s.len())
+    }
+
+    // Safety: all slots within r must be initialized.
+    pub(crate) unsafe fn slice_mut(&mut self, r: Range<usize>) -> &mut [T] {
+        assume_init_mut(&mut self.a[r])
+    }
+
+    // Return n values from the slot j as two slices, where the second one wrapped around to the first slot.
+    // Safety: all those slots must be initialized.
+    pub(crate) unsafe fn slices_wrapping(&self, j: usize, n: usize) -> (&[T], &[T]) {
+        let front = std::cmp::min(n, self.len() - j);
+        (self.slice(j..j + front), self.slice(0..n - front))
+    }
+
+    // Drop values within r.
+    // Safety: all slots within r must be initialized, and they're regarded as uninitialized afterward.
+    pub(crate) unsafe fn drop_range(&mut self, r: Range<usize>) {
+        ptr::drop_in_place(self.slice_mut(r));
+    }
+
+    // Drop n values from the slot j, wrapping around to the first slot like a ring buffer.
+    // Safety: all those slots must be initialized, and they're regarded as uninitialized afterward.
+    pub(crate) unsafe fn drop_wrapping(&mut self, j: usize, n: usize) {
+        let front = std::cmp::min(n, self.len() - j);
+        self.drop_range(j..j + front);
+        self.drop_range(0..n - front);
+    }
+
+    // Move n slots from the slot j of self, wrapping around like a ring buffer, to the first slots of other.
+    pub(crate) fn copy_wrapping_to(&self, j: usize, n: usize, other: &mut RawArray<T>) {
+        let front = std::cmp::min(n, self.len() - j);
+        self.copy_to(j, other, 0, front);
+        self.copy_to(0, other, front, n - front);
+    }
+
+    // Move values to the slots from j, wrapping around to the first slot like a ring buffer.
+    // Values which are already there are leaked instead of dropped.
+    pub(crate) fn write_wrapping(&mut self, j: usize, mut values: Vec<T>) {
+        let n = values.len();
+        let front = std::cmp::min(n, self.len() - j);
+        let (head, rest) = self.a.split_at_mut(j);
+        let (back, wrapped) = (&mut rest[..front], &mut head[..n - front]);
+        count_moves(n);
+        unsafe {
+            let p = values.as_ptr() as *const MaybeUninit<T>;
+            ptr::copy_nonoverlapping(p, back.as_mut_ptr(), front);
+            ptr::copy_nonoverlapping(p.add(front), wrapped.as_mut_ptr(), n - front);
+            // values are moved out, so only the allocation is released
+            values.set_len(0);
+        }
+    }
+
+    pub(crate) fn swap(&mut self, i: usize, j: usize) {
+        self.a.swap(i, j);
+    }
+
+    // Rotate slots to the left by k, so that the slot k becomes the first.
+    pub(crate) fn rotate_left(&mut self, k: usize) {
+        self.a.rotate_left(k);
+    }
+
+    // Move k slots from src to dst, which may overlap.
+    pub(crate) fn copy(&mut self, src: usize, dst: usize, k: usize) {
+        assert!(src + k <= self.len() && dst + k <= self.len());
+        count_moves(k);
+        unsafe {
+            let p = self.a.as_mut_ptr();
+            ptr::copy(p.add(src), p.add(dst), k);
+        }
+    }
+
+    // Move k slots from src of self to dst of other.
+    pub(crate) fn copy_to(&self, src: usize, other: &mut RawArray<T>, dst: usize, k: usize) {
+        let (from, to) = (&self.a[src..src + k], &mut other.a[dst..dst + k]);
+        count_moves(k);
+        unsafe { ptr::copy_nonoverlapping(from.as_ptr(), to.as_mut_ptr(), k) }
+    }
+
+    // Return a parallel iterator which moves values out of the slots within r.
+    // Values which the iterator doesn't yield are dropped with it.
+    // Safety: all slots within r must be initialized, and they're regarded as uninitialized afterward.
+    #[cfg(feature = "rayon")]
+    pub(crate) unsafe fn par_drain(&mut self, r: Range<usize>) -> par::Drain<'_, T> {
+        par::Drain {
+            slice: self.slice_mut(r),
+        }
+    }
+
+    // Return a parallel iterator which moves n values out of the slots from j, wrapping around like a ring buffer.
+    // Safety: all those slots must be initialized, and they're regarded as uninitialized afterward.
+    #[cfg(feature = "rayon")]
+    pub(crate) unsafe fn par_drain_wrapping(
+        &mut self,
+        j: usize,
+        n: usize,
+    ) -> rayon::iter::Chain<par::Drain<'_, T>, par::Drain<'_, T>>
+    where
+        T: Send,
+    {
+        use rayon::iter::ParallelIterator;
+
+        let front = std::cmp::min(n, self.len() - j);
+        let (head, rest) = self.a.split_at_mut(j);
+        let back = par::Drain {
+            slice: assume_init_mut(&mut rest[..front]),
+        };
+        let wrapped = par::Drain {
+            slice: assume_init_mut(&mut head[..n - front]),
+        };
+        back.chain(wrapped)
+    }
+
+    // Convert into Vec which holds the first n slots, reusing the allocation.
+    // Safety: the first n slots must be initialized, and the rest are left uninitialized.
+    pub(crate) unsafe fn into_vec(self, n: usize) -> Vec<T> {
+        assert!(n <= self.len());
+        let mut a = ManuallyDrop::new(self.a.into_vec());
+        Vec::from_raw_parts(a.as_mut_ptr() as *mut T, n, a.capacity())
+    }
+}
+
+// Safety: all slots of s must be initialized.
+unsafe fn assume_init_mut<T>(s: &mut [MaybeUninit<T>]) -> &mut [T] {
+    std::slice::from_raw_parts_mut(s.as_mut_ptr() as *mut T, s.len())
+}
+
+#[cfg(feature = "rayon")]
+pub(crate) mod par {
+    use rayon::iter::plumbing::{bridge, Consumer, Producer, ProducerCallback, UnindexedConsumer};
+    use rayon::prelude::*;
+    use std::ptr;
+
+    // Parallel iterator which moves values out of a slice and drops the ones it doesn't yield.
+    // The slice is split into producers, each of which owns the values within its part.
+    pub(crate) struct Drain<'a, T> {
+        pub(super) slice: &'a mut [T],
+    }
+
+    impl<'a, T: Send> ParallelIterator for Drain<'a, T> {
+        type Item = T;
+
+        fn drive_unindexed<C>(self, consumer: C) -> C::Result
+        where
+            C: UnindexedConsumer<T>,
+        {
+            bridge(self, consumer)
+        }
+
+        fn opt_len(&self) -> Option<usize> {
+            Some(self.slice.len())
+        }
+    }
+
+    impl<'a, T: Send> IndexedParallelIterator for Drain<'a, T> {
+        fn drive<C: Consumer<T>>(self, consumer: C) -> C::Result {
+            bridge(self, consumer)
+        }
+
+        fn len(&self) -> usize {
+            self.slice.len()
+        }
+
+        fn with_producer<CB: ProducerCallback<T>>(mut self, callback: CB) -> CB::Output {
+            let slice = std::mem::take(&mut self.slice);
+            callback.callback(Drain { slice })
+        }
+    }
+
+    impl<'a, T: Send> Producer for Drain<'a, T> {
+        type Item = T;
+        type IntoIter = DrainIter<'a, T>;
+
+        fn into_iter(mut self) -> DrainIter<'a, T> {
+            DrainIter {
+                rest: std::mem::take(&mut self.slice).iter_mut(),
+            }
+        }
+
+        fn split_at(mut self, index: usize) -> (Self, Self) {
+            let (left, right) = std::mem::take(&mut self.slice).split_at_mut(index);
+            (Drain { slice: left }, Drain { slice: right })
+        }
+    }
+
+    impl<'a, T> Drop for Drain<'a, T> {
+        fn drop(&mut self) {
+            unsafe { ptr::drop_in_place(std::mem::take(&mut self.slice) as *mut [T]) }
+        }
+    }
+
+    // Sequential part of Drain, which moves values out one by one.
+    pub(crate) struct DrainIter<'a, T> {
+        rest: std::slice::IterMut<'a, T>,
+    }
+
+    impl<'a, T> Iterator for DrainIter<'a, T> {
+        type Item = T;
+
+        fn next(&mut self) -> Option<T> {
+            self.rest.next().map(|x| unsafe { ptr::read(x) })
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.rest.size_hint()
+        }
+    }
+
+    impl<'a, T> DoubleEndedIterator for DrainIter<'a, T> {
+        fn next_back(&mut self) -> Option<T> {
+            self.rest.next_back().map(|x| unsafe { ptr::read(x) })
+        }
+    }
+
+    impl<'a, T> ExactSizeIterator for DrainIter<'a, T> {}
+
+    impl<'a, T> Drop for DrainIter<'a, T> {
+        fn drop(&mut self) {
+            let rest = std::mem::take(&mut self.rest).into_slice();
+            unsafe { ptr::drop_in_place(rest as *mut [T]) }
+        }
+    }
+}
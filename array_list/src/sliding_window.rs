@@ -0,0 +1,166 @@
+use crate::array_deque::ArrayDeque;
+use crate::array_stack::ArrayStack;
+use interface::heap_size::HeapSize;
+use interface::list::List;
+use interface::queue::{Deque, Queue, Stack};
+use std::fmt::Debug;
+
+/// FIFO window which answers an associative aggregate (e.g. min, max, sum) of its elements, built on ArrayDeque.
+/// Elements are split into the front part and the back part (two-stack technique): the front part keeps aggregates
+/// of its suffixes in a stack, and the back part keeps the running aggregate. When the front part runs out,
+/// the back part is turned into the front part. The operation needs to be associative, but not commutative.
+/// O(1): push(x), query()
+/// O(1) amortized: evict()
+#[derive(Debug)]
+pub struct SlidingWindow<T, F>
+where
+    T: Clone + Debug,
+    F: Fn(&T, &T) -> T,
+{
+    values: ArrayDeque<T>,
+    // front.get(k) is the aggregate of values[f - 1 - k..f], where f is the size of the front part
+    front: ArrayStack<T>,
+    // the aggregate of values[f..n]
+    back: Option<T>,
+    op: F,
+}
+
+impl<T, F> SlidingWindow<T, F>
+where
+    T: Clone + Debug,
+    F: Fn(&T, &T) -> T,
+{
+    /// Generate empty SlidingWindow which aggregates elements by op.
+    pub fn new(op: F) -> Self {
+        SlidingWindow {
+            values: ArrayDeque::new(),
+            front: ArrayStack::new(),
+            back: None,
+            op,
+        }
+    }
+
+    /// Return the number of elements in the window.
+    pub fn size(&self) -> usize {
+        self.values.size()
+    }
+
+    /// Return the value at the position i from the oldest one.
+    pub fn get(&self, i: usize) -> Option<&T> {
+        self.values.get(i)
+    }
+
+    /// Add a value to the back of the window.
+    pub fn push(&mut self, x: T) {
+        self.back = Some(match self.back.take() {
+            None => x.clone(),
+            Some(a) => (self.op)(&a, &x),
+        });
+        self.values.add_last(x);
+    }
+
+    /// Remove the oldest value from the window and return it.
+    pub fn evict(&mut self) -> Option<T> {
+        if self.values.size() == 0 {
+            return None;
+        }
+        if self.front.size() == 0 {
+            // all elements move to the front part, whose suffix aggregates are built from the newest one
+            let mut agg: Option<T> = None;
+            for i in (0..self.values.size()).rev() {
+                let x = self.values.get(i).expect("element within size must exist");
+                let a = match agg {
+                    None => x.clone(),
+                    Some(a) => (self.op)(x, &a),
+                };
+                self.front.push(a.clone());
+                agg = Some(a);
+            }
+            self.back = None;
+        }
+        self.front.pop();
+        self.values.remove_first()
+    }
+
+    /// Return the aggregate of all values in the window, otherwise return null(Empty).
+    pub fn query(&self) -> Option<T> {
+        let front = self
+            .front
+            .size()
+            .checked_sub(1)
+            .and_then(|k| self.front.get(k));
+        match (front, self.back.as_ref()) {
+            (Some(a), Some(b)) => Some((self.op)(a, b)),
+            (a, b) => a.or(b).cloned(),
+        }
+    }
+}
+
+impl<T, F> Queue<T> for SlidingWindow<T, F>
+where
+    T: Clone + Debug,
+    F: Fn(&T, &T) -> T,
+{
+    fn add(&mut self, x: T) -> bool {
+        self.push(x);
+        true
+    }
+
+    fn remove(&mut self) -> Option<T> {
+        self.evict()
+    }
+}
+
+impl<T, F> HeapSize for SlidingWindow<T, F>
+where
+    T: Clone + Debug,
+    F: Fn(&T, &T) -> T,
+{
+    fn heap_bytes(&self) -> usize {
+        self.values.heap_bytes() + self.front.heap_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SlidingWindow;
+
+    #[test]
+    fn rolling_max_test() {
+        let mut window = SlidingWindow::new(|a: &u64, b: &u64| *std::cmp::max(a, b));
+        assert_eq!(window.query(), None);
+        assert_eq!(window.evict(), None);
+
+        let xs = (0..300u64)
+            .map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 54)
+            .collect::<Vec<_>>();
+        for (i, &x) in xs.iter().enumerate() {
+            window.push(x);
+            if i >= 10 {
+                assert_eq!(window.evict(), Some(xs[i - 10]));
+            }
+            let lo = i.saturating_sub(9);
+            assert_eq!(window.query().as_ref(), xs[lo..=i].iter().max());
+        }
+        assert_eq!(window.size(), 10);
+        assert_eq!(window.get(0), Some(&xs[290]));
+    }
+
+    #[test]
+    fn non_commutative_test() {
+        // concatenation is associative but not commutative
+        let mut window = SlidingWindow::new(|a: &String, b: &String| format!("{}{}", a, b));
+        for c in ["a", "b", "c"] {
+            window.push(c.to_string());
+        }
+        assert_eq!(window.query().as_deref(), Some("abc"));
+        assert_eq!(window.evict().as_deref(), Some("a"));
+        window.push("d".to_string());
+        assert_eq!(window.query().as_deref(), Some("bcd"));
+        assert_eq!(window.evict().as_deref(), Some("b"));
+        assert_eq!(window.evict().as_deref(), Some("c"));
+        assert_eq!(window.query().as_deref(), Some("d"));
+        assert_eq!(window.evict().as_deref(), Some("d"));
+        assert_eq!(window.query(), None);
+    }
+}
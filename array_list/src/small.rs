@@ -0,0 +1,331 @@
+use crate::array_stack::ArrayStack;
+use crate::fixed::FixedArrayStack;
+use interface::fmt::{debug_entries, format_with};
+use interface::heap_size::HeapSize;
+use interface::list::List;
+use interface::queue::Stack;
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// List implementation which stores up to N elements inline,
+/// and spills them into a heap-allocated ArrayStack when it grows further.
+/// It avoids heap allocation for lists which are small in most cases.
+/// O(1): get(i), set(i, x)
+/// O(1 + n - i): add(i, x), remove(i)
+pub struct SmallArrayStack<T: Clone + Debug, const N: usize> {
+    storage: Storage<T, N>,
+}
+
+enum Storage<T: Clone + Debug, const N: usize> {
+    Inline(FixedArrayStack<T, N>),
+    Heap(ArrayStack<T>),
+}
+
+impl<T, const N: usize> Default for SmallArrayStack<T, N>
+where
+    T: Clone + Debug,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> SmallArrayStack<T, N>
+where
+    T: Clone + Debug,
+{
+    /// Generate empty SmallArrayStack.
+    pub fn new() -> Self {
+        SmallArrayStack {
+            storage: Storage::Inline(FixedArrayStack::new()),
+        }
+    }
+
+    /// Return true if elements have been moved to heap.
+    pub fn spilled(&self) -> bool {
+        matches!(self.storage, Storage::Heap(_))
+    }
+
+    /// Return the number of elements which can be held without reallocation.
+    pub fn capacity(&self) -> usize {
+        match &self.storage {
+            Storage::Inline(_) => N,
+            Storage::Heap(a) => a.capacity(),
+        }
+    }
+
+    fn spill(&mut self) {
+        if let Storage::Inline(inline) = &mut self.storage {
+            // room for as many elements again, so that growing after the spill doesn't reallocate at once
+            let mut heap = ArrayStack::with_capacity(2 * N);
+            for x in inline.drain() {
+                heap.push(x);
+            }
+            self.storage = Storage::Heap(heap);
+        }
+    }
+}
+
+impl<T, const N: usize> List<T> for SmallArrayStack<T, N>
+where
+    T: Clone + Debug,
+{
+    fn size(&self) -> usize {
+        match &self.storage {
+            Storage::Inline(a) => a.size(),
+            Storage::Heap(a) => a.size(),
+        }
+    }
+
+    fn get(&self, i: usize) -> Option<&T> {
+        match &self.storage {
+            Storage::Inline(a) => a.get(i),
+            Storage::Heap(a) => a.get(i),
+        }
+    }
+
+    fn set(&mut self, i: usize, x: T) -> Option<T> {
+        match &mut self.storage {
+            Storage::Inline(a) => a.set(i, x),
+            Storage::Heap(a) => a.set(i, x),
+        }
+    }
+
+    fn add(&mut self, i: usize, x: T) {
+        // check before spilling, so that a panic leaves the list unchanged
+        let n = self.size();
+        if i > n {
+            panic!(
+                "index must be positive and not greater than the size of list. i: {}, n: {}",
+                i, n
+            )
+        }
+        if let Storage::Inline(a) = &self.storage {
+            if a.is_full() {
+                self.spill();
+            }
+        }
+        match &mut self.storage {
+            Storage::Inline(a) => a.add(i, x),
+            Storage::Heap(a) => a.add(i, x),
+        }
+    }
+
+    fn remove(&mut self, i: usize) -> Option<T> {
+        match &mut self.storage {
+            Storage::Inline(a) => a.remove(i),
+            Storage::Heap(a) => a.remove(i),
+        }
+    }
+}
+
+impl<T, const N: usize> Stack<T> for SmallArrayStack<T, N>
+where
+    T: Clone + Debug,
+{
+    fn push(&mut self, x: T) {
+        self.add(self.size(), x);
+    }
+    fn pop(&mut self) -> Option<T> {
+        if self.size() == 0 {
+            return None;
+        }
+        self.remove(self.size() - 1)
+    }
+}
+
+impl<T, const N: usize> HeapSize for SmallArrayStack<T, N>
+where
+    T: Clone + Debug,
+{
+    fn heap_bytes(&self) -> usize {
+        match &self.storage {
+            Storage::Inline(a) => a.heap_bytes(),
+            Storage::Heap(a) => a.heap_bytes(),
+        }
+    }
+}
+
+impl<T, const N: usize> Debug for SmallArrayStack<T, N>
+where
+    T: Clone + Debug,
+{
+    // Whether it has spilled is shown instead of the storage it delegates to.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let values = (0..self.size()).filter_map(move |i| self.get(i));
+        f.debug_struct("SmallArrayStack")
+            .field("elements", &debug_entries(values))
+            .field("size", &self.size())
+            .field("capacity", &self.capacity())
+            .field("spilled", &self.spilled())
+            .finish()
+    }
+}
+
+impl<T, const N: usize> Display for SmallArrayStack<T, N>
+where
+    T: Clone + Debug + Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let values = (0..self.size()).filter_map(move |i| self.get(i));
+        f.write_str("[")?;
+        Display::fmt(&format_with(values, ", "), f)?;
+        f.write_str("]")
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::SmallArrayStack;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::queue::Stack;
+    use std::fmt::Debug;
+
+    // Decode operations, each of which pushes a value and pops one if op % 4 == 0.
+    pub(super) fn ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Values are pushed and sometimes popped, so that it may have spilled to heap while holding a few elements.
+    impl<'a, T, const N: usize> Arbitrary<'a> for SmallArrayStack<T, N>
+    where
+        T: Clone + Debug + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut list = SmallArrayStack::new();
+            for (op, x) in ops(u)? {
+                list.push(x);
+                if op % 4 == 0 {
+                    list.pop();
+                }
+            }
+            Ok(list)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SmallArrayStack;
+    use interface::list::List;
+    use interface::queue::Stack;
+
+    #[test]
+    fn list_conformance_test() {
+        let mut list: SmallArrayStack<i32, 4> = SmallArrayStack::new();
+        test_support::conformance::check_list(&mut list);
+    }
+
+    #[test]
+    fn stack_conformance_test() {
+        let mut stack: SmallArrayStack<i32, 4> = SmallArrayStack::new();
+        test_support::conformance::check_stack(&mut stack);
+    }
+
+    #[test]
+    fn spill_test() {
+        let mut list: SmallArrayStack<i32, 3> = SmallArrayStack::new();
+        list.add(0, 1);
+        list.add(1, 3);
+        list.add(1, 2);
+        assert!(!list.spilled());
+        assert_eq!(list.size(), 3);
+
+        list.add(0, 0);
+        assert!(list.spilled());
+        assert_eq!(list.size(), 4);
+        assert_eq!(list.capacity(), 6);
+        for i in 0..4 {
+            assert_eq!(list.get(i), Some(&(i as i32)));
+        }
+
+        assert_eq!(list.set(3, 5), Some(3));
+        assert_eq!(list.remove(0), Some(0));
+        assert_eq!(list.get(2), Some(&5));
+        assert_eq!(list.size(), 3);
+    }
+
+    #[test]
+    fn fmt_test() {
+        let mut list: SmallArrayStack<i32, 2> = SmallArrayStack::new();
+        list.push(1);
+        assert_eq!(
+            format!("{:?}", list),
+            "SmallArrayStack { elements: [1], size: 1, capacity: 2, spilled: false }"
+        );
+        list.push(2);
+        list.push(3);
+        assert_eq!(list.to_string(), "[1, 2, 3]");
+        assert_eq!(
+            format!("{:?}", list),
+            format!(
+                "SmallArrayStack {{ elements: [1, 2, 3], size: 3, capacity: {}, spilled: true }}",
+                list.capacity()
+            )
+        );
+    }
+
+    #[test]
+    fn add_out_of_range_test() {
+        let mut list: SmallArrayStack<i32, 2> = SmallArrayStack::new();
+        list.push(0);
+        list.push(1);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| list.add(3, 2)));
+        assert!(result.is_err());
+        // a full inline buffer doesn't spill for an index out of range
+        assert!(!list.spilled());
+        assert_eq!(list.size(), 2);
+        assert_eq!(list.get(1), Some(&1));
+    }
+
+    #[test]
+    fn stack_test() {
+        let mut stack: SmallArrayStack<char, 2> = SmallArrayStack::new();
+        assert_eq!(stack.pop(), None);
+        stack.push('a');
+        stack.push('b');
+        stack.push('c');
+        assert!(stack.spilled());
+        assert_eq!(stack.pop(), Some('c'));
+        assert_eq!(stack.pop(), Some('b'));
+        assert_eq!(stack.pop(), Some('a'));
+        assert_eq!(stack.size(), 0);
+    }
+
+    #[test]
+    fn heap_size_test() {
+        use interface::heap_size::HeapSize;
+
+        let mut list: SmallArrayStack<u64, 2> = SmallArrayStack::new();
+        list.push(1);
+        list.push(2);
+        assert_eq!(list.heap_bytes(), 0);
+        list.push(3);
+        assert_eq!(list.heap_bytes(), 4 * std::mem::size_of::<u64>());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        test_support::replay::check_arbitrary(|list: SmallArrayStack<u16, 4>, u| {
+            // replay the same operations on Vec, recording whether it has ever grown beyond N
+            let mut expected = Vec::new();
+            let mut spilled = false;
+            for (op, x) in super::arb::ops::<u16>(u).unwrap() {
+                expected.push(x);
+                spilled |= expected.len() > 4;
+                if op % 4 == 0 {
+                    expected.pop();
+                }
+            }
+            assert!((0..list.size())
+                .map(|i| list.get(i))
+                .eq(expected.iter().map(Some)));
+            assert_eq!(list.spilled(), spilled);
+        });
+    }
+}
@@ -0,0 +1,111 @@
+use interface::set_ops::sorted;
+
+// Static set stored in an implicit layout of a complete binary search tree, which is rebuilt from sorted values
+// on every update. Implementors provide the conversion from and into sorted values, and updates are shared.
+pub(crate) trait SortedLayout<T>: Default
+where
+    T: Ord,
+{
+    // Build the layout from values sorted in strictly ascending order.
+    fn from_sorted(values: Vec<T>) -> Self;
+
+    // Move values out in ascending order.
+    fn into_sorted_vec(self) -> Vec<T>;
+
+    fn from_unsorted<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut values = iter.into_iter().collect::<Vec<_>>();
+        values.sort();
+        values.dedup();
+        Self::from_sorted(values)
+    }
+
+    // Rebuild the layout after f updates sorted values, and return the result of f.
+    fn rebuild<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Vec<T>) -> R,
+    {
+        let mut values = std::mem::take(self).into_sorted_vec();
+        let r = f(&mut values);
+        *self = Self::from_sorted(values);
+        r
+    }
+
+    fn add_sorted(&mut self, x: T) -> bool {
+        self.rebuild(|values| match values.binary_search(&x) {
+            Ok(_) => false,
+            Err(i) => {
+                values.insert(i, x);
+                true
+            }
+        })
+    }
+
+    fn remove_sorted(&mut self, x: T) -> Option<T> {
+        self.rebuild(|values| values.binary_search(&x).ok().map(|i| values.remove(i)))
+    }
+
+    // Merge sorted values of both, keeping values of self for ones contained in both, and build the layout once.
+    fn merge_sorted(self, other: Self) -> Self
+    where
+        Self: Sized,
+    {
+        let values = sorted::union(self.into_sorted_vec(), other.into_sorted_vec());
+        Self::from_sorted(values.collect())
+    }
+}
+
+// Panic unless values are sorted in strictly ascending order.
+pub(crate) fn check_sorted<T>(values: &[T])
+where
+    T: Ord,
+{
+    if values.windows(2).any(|w| w[0] >= w[1]) {
+        panic!("values must be sorted in strictly ascending order")
+    }
+}
+
+// Return BFS indices (1-indexed) of n nodes of a complete tree in in-order, starting from the leftmost node
+// and stepping to successors.
+pub(crate) fn in_order(n: usize) -> impl Iterator<Item = usize> {
+    let mut k = if n == 0 { 0 } else { 1 << n.ilog2() };
+    std::iter::from_fn(move || {
+        if k == 0 {
+            return None;
+        }
+        let cur = k;
+        if 2 * k < n {
+            k = 2 * k + 1;
+            while 2 * k <= n {
+                k *= 2;
+            }
+        } else {
+            k >>= k.trailing_ones() + 1;
+        }
+        Some(cur)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::in_order;
+
+    #[test]
+    fn in_order_test() {
+        for n in 0..64 {
+            let keys = in_order(n).collect::<Vec<_>>();
+            assert_eq!(keys.len(), n);
+            let mut sorted = keys.clone();
+            sorted.sort_unstable();
+            assert!(sorted.iter().copied().eq(1..=n));
+            // the left subtree precedes the node, and the right one follows it
+            for (i, &k) in keys.iter().enumerate() {
+                let pos = |c: usize| keys.iter().position(|&j| j == c);
+                assert!(pos(2 * k).is_none_or(|p| p < i));
+                assert!(pos(2 * k + 1).is_none_or(|p| p > i));
+            }
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use crate::array_stack::ArrayStack;
+use interface::fmt::{debug_entries, format_with};
+use interface::heap_size::HeapSize;
+use interface::list::List;
+use interface::queue::{Queue, Stack};
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// FIFO queue implementation built from two ArrayStacks.
+/// Values are pushed to the back stack, and moved to the front stack in reverse order when it runs out,
+/// so that each value is moved at most once.
+/// O(1): add(x)
+/// O(1) amortized: remove()
+pub struct TwoStackQueue<T>
+where
+    T: Clone + Debug,
+{
+    front: ArrayStack<T>,
+    back: ArrayStack<T>,
+}
+
+#[cfg(test)]
+thread_local! {
+    // Number of values moved from the back stack to the front one on this thread, which tests read to check
+    // the amortized bound of remove.
+    static TRANSFERS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+#[cfg(test)]
+fn count_transfer() {
+    TRANSFERS.with(|t| t.set(t.get() + 1));
+}
+
+#[cfg(not(test))]
+fn count_transfer() {}
+
+impl<T> Default for TwoStackQueue<T>
+where
+    T: Clone + Debug,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TwoStackQueue<T>
+where
+    T: Clone + Debug,
+{
+    /// Generate empty TwoStackQueue.
+    pub fn new() -> Self {
+        TwoStackQueue {
+            front: ArrayStack::new(),
+            back: ArrayStack::new(),
+        }
+    }
+
+    /// Return the number of elements.
+    pub fn size(&self) -> usize {
+        self.front.size() + self.back.size()
+    }
+
+    /// Return the number of elements which can be held without reallocation of either stack.
+    pub fn capacity(&self) -> usize {
+        self.front.capacity() + self.back.capacity()
+    }
+
+    // Return the elements from the head to the tail, where the front stack holds the head at its top.
+    fn values(&self) -> impl Iterator<Item = &T> + Clone {
+        self.front.iter().rev().chain(self.back.iter())
+    }
+}
+
+impl<T> Queue<T> for TwoStackQueue<T>
+where
+    T: Clone + Debug,
+{
+    fn add(&mut self, x: T) -> bool {
+        self.back.push(x);
+        true
+    }
+
+    fn remove(&mut self) -> Option<T> {
+        if self.front.size() == 0 {
+            while self.back.size() > 0 {
+                let x = self.back.pop().expect("element within size must exist");
+                self.front.push(x);
+                count_transfer();
+            }
+        }
+        if self.front.size() == 0 {
+            return None;
+        }
+        self.front.pop()
+    }
+}
+
+impl<T> HeapSize for TwoStackQueue<T>
+where
+    T: Clone + Debug,
+{
+    fn heap_bytes(&self) -> usize {
+        self.front.heap_bytes() + self.back.heap_bytes()
+    }
+}
+
+impl<T> Debug for TwoStackQueue<T>
+where
+    T: Clone + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TwoStackQueue")
+            .field("elements", &debug_entries(self.values()))
+            .field("size", &self.size())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl<T> Display for TwoStackQueue<T>
+where
+    T: Clone + Debug + Display,
+{
+    // Elements are written from the head to the tail.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        Display::fmt(&format_with(self.values(), ", "), f)?;
+        f.write_str("]")
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::TwoStackQueue;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::queue::Queue;
+    use std::fmt::Debug;
+
+    // Decode operations, each of which adds a value and removes one if op % 3 == 0.
+    pub(super) fn ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Values are added and sometimes removed, so that both stacks can hold values.
+    impl<'a, T> Arbitrary<'a> for TwoStackQueue<T>
+    where
+        T: Clone + Debug + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut queue = TwoStackQueue::new();
+            for (op, x) in ops(u)? {
+                queue.add(x);
+                if op % 3 == 0 {
+                    queue.remove();
+                }
+            }
+            Ok(queue)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TwoStackQueue;
+    use interface::list::List;
+    use interface::queue::Queue;
+
+    #[test]
+    fn queue_test() {
+        let mut queue: TwoStackQueue<i32> = TwoStackQueue::new();
+        assert_eq!(queue.remove(), None);
+
+        assert!(queue.add(1));
+        assert!(queue.add(2));
+        assert_eq!(queue.remove(), Some(1));
+        assert!(queue.add(3));
+        assert!(queue.add(4));
+        assert_eq!(queue.size(), 3);
+        assert_eq!(queue.remove(), Some(2));
+        assert_eq!(queue.remove(), Some(3));
+        assert!(queue.add(5));
+        assert_eq!(queue.remove(), Some(4));
+        assert_eq!(queue.remove(), Some(5));
+        assert_eq!(queue.remove(), None);
+        assert_eq!(queue.size(), 0);
+    }
+
+    #[test]
+    fn fmt_test() {
+        let mut queue: TwoStackQueue<i32> = TwoStackQueue::new();
+        for x in 1..=3 {
+            queue.add(x);
+        }
+        assert_eq!(queue.remove(), Some(1));
+        queue.add(4);
+        assert_eq!(queue.to_string(), "[2, 3, 4]");
+        assert_eq!(
+            format!("{:?}", queue),
+            format!(
+                "TwoStackQueue {{ elements: [2, 3, 4], size: 3, capacity: {} }}",
+                queue.capacity()
+            )
+        );
+    }
+
+    #[test]
+    fn amortized_test() {
+        let mut queue = TwoStackQueue::new();
+        let mut expected = std::collections::VecDeque::new();
+        let (transfers, moves) = (super::TRANSFERS.with(|t| t.get()), crate::raw::moves());
+        let mut ops = 0;
+        for i in 0..1000 {
+            for x in 0..(i % 7) {
+                assert!(queue.add(x));
+                expected.push_back(x);
+                ops += 1;
+            }
+            for _ in 0..(i % 5) {
+                // the whole back stack is moved only when the front stack runs out
+                let (front, back) = (queue.front.size(), queue.back.size());
+                assert_eq!(queue.remove(), expected.pop_front());
+                assert_eq!(queue.back.size(), if front == 0 { 0 } else { back });
+                ops += 1;
+            }
+        }
+        // values moved between stacks, and slots moved when either stack is resized, are O(1) per operation
+        let transfers = super::TRANSFERS.with(|t| t.get()) - transfers;
+        let moves = crate::raw::moves() - moves;
+        assert!(transfers > 0);
+        assert!(
+            transfers + moves <= 4 * ops,
+            "{} + {} > 4 * {}",
+            transfers,
+            moves,
+            ops
+        );
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use std::collections::VecDeque;
+
+        test_support::replay::check_arbitrary(|queue: TwoStackQueue<u16>, u| {
+            // replay the same operations on VecDeque
+            let mut expected = VecDeque::new();
+            for (op, x) in super::arb::ops::<u16>(u).unwrap() {
+                expected.push_back(x);
+                if op % 3 == 0 {
+                    expected.pop_front();
+                }
+            }
+            // the front stack holds the oldest values in reverse order, followed by the back stack
+            let front = queue.front.iter().rev();
+            let back = queue.back.iter();
+            assert!(front.chain(back).eq(expected.iter()));
+        });
+    }
+}
@@ -0,0 +1,387 @@
+use crate::sorted_layout::{check_sorted, in_order, SortedLayout};
+use interface::heap_size::HeapSize;
+use interface::set::{SSet, SSetExt};
+use std::borrow::Borrow;
+
+/// Static SSet implementation with backing array in van Emde Boas layout of an implicit complete binary search tree.
+/// A tree of height h is stored as its top tree of height h/2 followed by its bottom trees, each laid out recursively,
+/// so that a search touches O(log_B n) blocks for any block size B (cache-oblivious).
+/// O(log n): find(x)
+/// O(n): add(x), remove(x) since the layout is rebuilt
+#[derive(Debug)]
+pub struct VebLayoutSet<T>
+where
+    T: Ord,
+{
+    // nodes of the perfect tree of height h, where absent nodes of the complete tree are None
+    a: Box<[Option<T>]>,
+    n: usize,
+    h: usize,
+    // for the depth d, splits[d] is the split whose bottom trees are rooted at d
+    splits: Box<[Split]>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Split {
+    // the size of the top tree
+    top: usize,
+    // the size of each bottom tree
+    bottom: usize,
+    // the depth of the root of the top tree
+    root_depth: usize,
+}
+
+impl<T> VebLayoutSet<T>
+where
+    T: Ord,
+{
+    /// Generate empty VebLayoutSet
+    pub fn new() -> Self {
+        Self::from_sorted(Vec::new())
+    }
+
+    /// Generate VebLayoutSet from values sorted in strictly ascending order.
+    pub fn from_sorted(values: Vec<T>) -> Self {
+        SortedLayout::from_sorted(values)
+    }
+
+    // Record the split of the subtree of height h rooted at the depth d into the top and bottom trees.
+    fn split(&mut self, d: usize, h: usize) {
+        if h <= 1 {
+            return;
+        }
+        let top_h = h / 2;
+        let bottom_h = h - top_h;
+        self.splits[d + top_h] = Split {
+            top: (1 << top_h) - 1,
+            bottom: (1 << bottom_h) - 1,
+            root_depth: d,
+        };
+        self.split(d, top_h);
+        self.split(d + top_h, bottom_h);
+    }
+
+    // Return the position in the backing array of the node whose BFS index (1-indexed) is k.
+    fn position(&self, k: usize) -> usize {
+        let depth = k.ilog2() as usize;
+        let mut pos = [0; usize::BITS as usize];
+        for d in 1..=depth {
+            pos[d] = self.child_position(&pos, d, k >> (depth - d));
+        }
+        pos[depth]
+    }
+
+    // The bottom tree containing the node k at the depth d is placed after the top tree and
+    // preceding bottom trees, where its order among them is the lower bits of k.
+    fn child_position(&self, pos: &[usize], d: usize, k: usize) -> usize {
+        let s = self.splits[d];
+        pos[s.root_depth] + s.top + (k & s.top) * s.bottom
+    }
+
+    /// Return an iterator which visits elements in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        in_order(self.n).map(move |k| {
+            self.a[self.position(k)]
+                .as_ref()
+                .expect("node within size must exist")
+        })
+    }
+
+    // Return the position of the smallest value y which satisfies y >= x.
+    fn lower_bound<Q>(&self, x: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.partition(|y| y.borrow() < x).1
+    }
+
+    // Return the positions of the largest value which satisfies below and the smallest one which doesn't,
+    // where below holds for a prefix of values in ascending order.
+    fn partition<F>(&self, below: F) -> (Option<usize>, Option<usize>)
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut pos = [0; usize::BITS as usize];
+        let (mut last, mut first) = (None, None);
+        let mut k = 1;
+        let mut d = 0;
+        while k <= self.n {
+            if d > 0 {
+                pos[d] = self.child_position(&pos, d, k);
+            }
+            let y = self.a[pos[d]]
+                .as_ref()
+                .expect("node within size must exist");
+            if below(y) {
+                last = Some(pos[d]);
+                k = 2 * k + 1;
+            } else {
+                first = Some(pos[d]);
+                k *= 2;
+            }
+            d += 1;
+        }
+        (last, first)
+    }
+}
+
+impl<T> SortedLayout<T> for VebLayoutSet<T>
+where
+    T: Ord,
+{
+    fn from_sorted(values: Vec<T>) -> Self {
+        check_sorted(&values);
+        let n = values.len();
+        let h = (usize::BITS - n.leading_zeros()) as usize;
+        let mut set = VebLayoutSet {
+            a: Vec::new().into_boxed_slice(),
+            n,
+            h,
+            splits: vec![Split::default(); h].into_boxed_slice(),
+        };
+        set.split(0, h);
+
+        let mut a = std::iter::repeat_with(|| None)
+            .take((1 << h) - 1)
+            .collect::<Vec<_>>();
+        for (k, x) in in_order(n).zip(values) {
+            a[set.position(k)] = Some(x);
+        }
+        set.a = a.into_boxed_slice();
+        set
+    }
+
+    fn into_sorted_vec(mut self) -> Vec<T> {
+        let positions = in_order(self.n)
+            .map(|k| self.position(k))
+            .collect::<Vec<_>>();
+        positions
+            .into_iter()
+            .map(|p| self.a[p].take().expect("node within size must exist"))
+            .collect()
+    }
+}
+
+impl<T> Default for VebLayoutSet<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::iter::FromIterator<T> for VebLayoutSet<T>
+where
+    T: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_unsorted(iter)
+    }
+}
+
+impl<T> SSet<T> for VebLayoutSet<T>
+where
+    T: Ord + Clone,
+{
+    fn size(&self) -> usize {
+        self.n
+    }
+
+    fn add(&mut self, x: T) -> bool {
+        self.add_sorted(x)
+    }
+
+    fn remove(&mut self, x: T) -> Option<T> {
+        self.remove_sorted(x)
+    }
+
+    fn find(&self, x: T) -> Option<T> {
+        self.find_by(&x).cloned()
+    }
+
+    fn find_ref(&self, x: &T) -> Option<&T> {
+        self.find_by(x)
+    }
+
+    fn successor(&self, x: &T) -> Option<&T> {
+        self.partition(|y| y <= x)
+            .1
+            .and_then(|p| self.a[p].as_ref())
+    }
+
+    fn predecessor(&self, x: &T) -> Option<&T> {
+        self.partition(|y| y < x).0.and_then(|p| self.a[p].as_ref())
+    }
+}
+
+impl<T> SSetExt<T> for VebLayoutSet<T>
+where
+    T: Ord + Clone,
+{
+    fn find_by<Q>(&self, x: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.lower_bound(x).and_then(|p| self.a[p].as_ref())
+    }
+}
+
+impl<T> VebLayoutSet<T>
+where
+    T: Ord,
+{
+    /// Remove all elements and return them in ascending order, leaving an empty set.
+    pub fn drain(&mut self) -> std::vec::IntoIter<T> {
+        std::mem::take(self).into_iter()
+    }
+
+    /// Merge two sets into one, keeping elements of self for values contained in both.
+    /// Sorted elements of both are merged and the layout is built once, instead of adding them one by one.
+    /// O(n + m)
+    pub fn merge(self, other: Self) -> Self {
+        self.merge_sorted(other)
+    }
+}
+
+impl<T> IntoIterator for VebLayoutSet<T>
+where
+    T: Ord,
+{
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    // Elements are moved out in ascending order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_sorted_vec().into_iter()
+    }
+}
+
+impl<T> HeapSize for VebLayoutSet<T>
+where
+    T: Ord,
+{
+    fn heap_bytes(&self) -> usize {
+        self.a.len() * std::mem::size_of::<Option<T>>() + self.h * std::mem::size_of::<Split>()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::VebLayoutSet;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // The layout is determined by the set of values, so they're simply collected.
+    impl<'a, T> Arbitrary<'a> for VebLayoutSet<T>
+    where
+        T: Ord + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_iter::<T>()?.collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VebLayoutSet;
+    use interface::set::SSet;
+
+    #[test]
+    fn sset_conformance_test() {
+        let mut set: VebLayoutSet<usize> = VebLayoutSet::new();
+        test_support::conformance::check_sset(&mut set);
+    }
+
+    #[test]
+    fn sset_test() {
+        let mut set: VebLayoutSet<i32> = VebLayoutSet::new();
+        assert_eq!(set.size(), 0);
+        assert_eq!(set.find(0), None);
+
+        set = vec![9, 1, 5, 3, 7, 5].into_iter().collect();
+        assert_eq!(set.size(), 5);
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![1, 3, 5, 7, 9]);
+        assert_eq!(set.find(0), Some(1));
+        assert_eq!(set.find(4), Some(5));
+        assert_eq!(set.find(10), None);
+
+        assert!(set.add(4));
+        assert!(!set.add(4));
+        assert_eq!(set.remove(1), Some(1));
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![3, 4, 5, 7, 9]);
+    }
+
+    #[test]
+    fn layout_test() {
+        // the perfect tree of height 4 is split into the top tree of height 2 and 4 bottom trees of height 2
+        let set = VebLayoutSet::from_sorted((0..15).collect());
+        let bfs = [1, 2, 3, 4, 8, 9, 5, 10, 11, 6, 12, 13, 7, 14, 15];
+        for (p, &k) in bfs.iter().enumerate() {
+            assert_eq!(set.position(k), p);
+        }
+        assert_eq!(set.a[0], Some(7));
+    }
+
+    #[test]
+    fn find_test() {
+        for n in 0..70 {
+            let set = VebLayoutSet::from_sorted((0..n).map(|x| 2 * x).collect());
+            assert!(set.iter().copied().eq((0..n).map(|x| 2 * x)));
+            for x in 0..2 * n + 2 {
+                let expected = if x + 1 < 2 * n { Some(x + x % 2) } else { None };
+                assert_eq!(set.find(x), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn drain_test() {
+        let mut set: VebLayoutSet<String> = ["b", "c", "a"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(set.drain().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(set.size(), 0);
+        assert_eq!(set.drain().next(), None);
+
+        set.add("x".to_string());
+        set.add("w".to_string());
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec!["w", "x"]);
+    }
+
+    #[test]
+    fn merge_test() {
+        for n in [0, 1, 30, 100].iter().copied() {
+            let a: VebLayoutSet<i32> = (0..n).filter(|x| x % 2 == 0).collect();
+            let b: VebLayoutSet<i32> = (0..n).filter(|x| x % 3 == 0).collect();
+            let merged = a.merge(b);
+            assert!(merged
+                .iter()
+                .copied()
+                .eq((0..n).filter(|x| x % 2 == 0 || x % 3 == 0)));
+            assert_eq!(merged.find(7), (8..n).find(|x| x % 2 == 0 || x % 3 == 0));
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use std::collections::BTreeSet;
+
+        test_support::replay::check_arbitrary(|set: VebLayoutSet<u8>, u| {
+            let expected = u
+                .arbitrary_iter::<u8>()
+                .unwrap()
+                .collect::<Result<BTreeSet<_>, _>>()
+                .unwrap();
+            assert!(set.iter().eq(expected.iter()));
+            // exactly the nodes of the complete tree are present in the perfect tree
+            assert_eq!(set.a.len(), (1 << set.h) - 1);
+            assert_eq!(set.a.iter().filter(|x| x.is_some()).count(), set.size());
+            assert!((1..=set.size()).all(|k| set.a[set.position(k)].is_some()));
+            for x in 0..=u8::MAX {
+                assert_eq!(set.find(x), expected.range(x..).next().copied());
+            }
+        });
+    }
+}
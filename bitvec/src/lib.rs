@@ -0,0 +1,7 @@
+#![warn(missing_docs)]
+//! bitvec implements compact arrays which store values in bits packed into machine words.
+
+/// Implementation for array of fixed-width integers packed into words.
+pub mod packed;
+
+pub use packed::PackedIntVec;
@@ -0,0 +1,294 @@
+use interface::heap_size::HeapSize;
+
+/// Array of n integers of w bits each, stored contiguously in 64-bit words.
+/// A value may straddle the boundary between two words.
+/// O(1): get(i), set(i, x), push(x), pop()
+/// O(1 + n - i): add(i, x), remove(i)
+#[derive(Debug, Clone)]
+pub struct PackedIntVec {
+    words: Vec<u64>,
+    w: usize,
+    n: usize,
+}
+
+impl PackedIntVec {
+    /// Generate empty PackedIntVec whose values have w bits.
+    pub fn new(w: usize) -> Self {
+        if w == 0 || w > 64 {
+            panic!("width must be within [1, 64]. w: {}", w)
+        }
+        PackedIntVec {
+            words: Vec::new(),
+            w,
+            n: 0,
+        }
+    }
+
+    /// Generate empty PackedIntVec whose values have w bits, with room for n values.
+    pub fn with_capacity(w: usize, n: usize) -> Self {
+        let mut v = Self::new(w);
+        v.words.reserve_exact((n * w).div_ceil(64));
+        v
+    }
+
+    /// Return the number of bits of a value.
+    pub fn width(&self) -> usize {
+        self.w
+    }
+
+    /// Return the number of values.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Return the largest value which fits in the width.
+    pub fn max_value(&self) -> u64 {
+        u64::MAX >> (64 - self.w)
+    }
+
+    fn within_bound(&self, i: usize) -> bool {
+        i < self.n
+    }
+
+    // Read the i-th slot, which may lie across two words.
+    fn read(&self, i: usize) -> u64 {
+        let bit = i * self.w;
+        let (k, off) = (bit / 64, bit % 64);
+        let mut x = self.words[k] >> off;
+        if off + self.w > 64 {
+            x |= self.words[k + 1] << (64 - off);
+        }
+        x & self.max_value()
+    }
+
+    // Overwrite the i-th slot, which may lie across two words.
+    fn write(&mut self, i: usize, x: u64) {
+        let mask = self.max_value();
+        let bit = i * self.w;
+        let (k, off) = (bit / 64, bit % 64);
+        self.words[k] = (self.words[k] & !(mask << off)) | (x << off);
+        if off + self.w > 64 {
+            let rest = 64 - off;
+            self.words[k + 1] = (self.words[k + 1] & !(mask >> rest)) | (x >> rest);
+        }
+    }
+
+    fn check_value(&self, x: u64) {
+        if x > self.max_value() {
+            panic!("value must fit in the width. x: {}, w: {}", x, self.w)
+        }
+    }
+
+    /// Return the value at the position i.
+    pub fn get(&self, i: usize) -> Option<u64> {
+        if !self.within_bound(i) {
+            None
+        } else {
+            Some(self.read(i))
+        }
+    }
+
+    /// Set the value at the position i, and return the previous one.
+    pub fn set(&mut self, i: usize, x: u64) -> Option<u64> {
+        if !self.within_bound(i) {
+            panic!(
+                "index must be positive and less than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
+        self.check_value(x);
+        let y = self.read(i);
+        self.write(i, x);
+        Some(y)
+    }
+
+    /// Add a value at the end.
+    pub fn push(&mut self, x: u64) {
+        self.check_value(x);
+        while self.words.len() * 64 < (self.n + 1) * self.w {
+            self.words.push(0);
+        }
+        self.n += 1;
+        self.write(self.n - 1, x);
+    }
+
+    /// Remove the value at the end and return it.
+    pub fn pop(&mut self) -> Option<u64> {
+        if self.n == 0 {
+            return None;
+        }
+        let x = self.read(self.n - 1);
+        self.write(self.n - 1, 0);
+        self.n -= 1;
+        Some(x)
+    }
+
+    /// Add a value at the position i, and shift following values to backward.
+    pub fn add(&mut self, i: usize, x: u64) {
+        if i > self.n {
+            panic!(
+                "index must be positive and less than or equal to the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
+        self.push(x);
+        for k in (i + 1..self.n).rev() {
+            let y = self.read(k - 1);
+            self.write(k, y);
+        }
+        self.write(i, x);
+    }
+
+    /// Remove a value at the position i, and shift following values to forward.
+    pub fn remove(&mut self, i: usize) -> Option<u64> {
+        if !self.within_bound(i) {
+            return None;
+        }
+        let x = self.read(i);
+        for k in i..self.n - 1 {
+            let y = self.read(k + 1);
+            self.write(k, y);
+        }
+        self.pop();
+        Some(x)
+    }
+
+    /// Return an iterator from the first value to the last one.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.n).map(move |i| self.read(i))
+    }
+}
+
+impl HeapSize for PackedIntVec {
+    fn heap_bytes(&self) -> usize {
+        self.words.capacity() * std::mem::size_of::<u64>()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::PackedIntVec;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // Decode the width and operations, each of which adds a value at i and removes one at op / 4
+    // if op % 4 == 0.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn ops(u: &mut Unstructured<'_>) -> Result<(usize, Vec<(u8, usize, u64)>)> {
+        let w = u.int_in_range(1..=64)?;
+        let ops = (0..u.arbitrary_len::<(u8, usize, u64)>()?)
+            .map(|_| u.arbitrary())
+            .collect::<Result<_>>()?;
+        Ok((w, ops))
+    }
+
+    // The width is chosen first, and values truncated to it are added at arbitrary positions and sometimes removed,
+    // so that values straddle word boundaries.
+    impl<'a> Arbitrary<'a> for PackedIntVec {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let (w, ops) = ops(u)?;
+            let mut v = PackedIntVec::new(w);
+            for (op, i, x) in ops {
+                v.add(i % (v.size() + 1), x & v.max_value());
+                if op % 4 == 0 {
+                    v.remove(op as usize / 4 % v.size());
+                }
+            }
+            Ok(v)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PackedIntVec;
+
+    #[test]
+    fn list_test() {
+        let mut v = PackedIntVec::new(5);
+        assert_eq!(v.size(), 0);
+        assert_eq!(v.get(0), None);
+        assert_eq!(v.remove(0), None);
+        assert_eq!(v.pop(), None);
+
+        for x in 0..20 {
+            v.push(x);
+        }
+        assert_eq!(v.max_value(), 31);
+        assert_eq!(v.set(12, 31), Some(12));
+        assert_eq!(v.get(12), Some(31));
+        // the 13th value lies across the first and second words
+        assert_eq!(v.get(13), Some(13));
+
+        v.add(0, 7);
+        assert_eq!(v.size(), 21);
+        assert_eq!(v.get(0), Some(7));
+        assert_eq!(v.get(20), Some(19));
+        assert_eq!(v.remove(1), Some(0));
+        assert_eq!(v.remove(0), Some(7));
+        assert_eq!(v.pop(), Some(19));
+        let expected = (1..19).map(|x| if x == 12 { 31 } else { x });
+        assert!(v.iter().eq(expected));
+    }
+
+    #[test]
+    fn widths_test() {
+        for w in [1, 3, 7, 13, 32, 63, 64] {
+            let mut v = PackedIntVec::new(w);
+            let max = v.max_value();
+            for i in 0..200u64 {
+                v.push(i.wrapping_mul(0x9e37_79b9_7f4a_7c15) & max);
+            }
+            for i in 0..200u64 {
+                assert_eq!(
+                    v.get(i as usize),
+                    Some(i.wrapping_mul(0x9e37_79b9_7f4a_7c15) & max)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn heap_size_test() {
+        use interface::heap_size::HeapSize;
+
+        let mut v = PackedIntVec::with_capacity(4, 1000);
+        for x in 0..1000 {
+            v.push(x % 16);
+        }
+        // 4 bits per value instead of a word per value
+        assert_eq!(v.heap_bytes(), (1000 * 4_usize).div_ceil(64) * 8);
+    }
+
+    #[test]
+    #[should_panic]
+    fn overflow_test() {
+        let mut v = PackedIntVec::new(3);
+        v.push(8);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        test_support::replay::check_arbitrary(|v: PackedIntVec, u| {
+            // replay the same operations on Vec
+            let (w, ops) = super::arb::ops(u).unwrap();
+            let mask = u64::MAX >> (64 - w);
+            let mut expected = Vec::new();
+            for (op, i, x) in ops {
+                expected.insert(i % (expected.len() + 1), x & mask);
+                if op % 4 == 0 {
+                    expected.remove(op as usize / 4 % expected.len());
+                }
+            }
+            assert_eq!(v.width(), w);
+            assert!(v.iter().eq(expected.iter().copied()));
+            // bits after the last value are cleared
+            let bits = v.size() * w;
+            assert!(v.words.len() * 64 >= bits);
+            if !bits.is_multiple_of(64) {
+                assert_eq!(v.words[bits / 64] >> (bits % 64), 0);
+            }
+            assert!(v.words[bits.div_ceil(64)..].iter().all(|&word| word == 0));
+        });
+    }
+}
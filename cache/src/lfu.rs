@@ -0,0 +1,472 @@
+use interface::cache::Cache;
+use interface::heap_size::HeapSize;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Cache implementation which evicts the least frequently used entry.
+/// Ties are broken by evicting the least recently used one among them.
+/// Entries are kept in frequency buckets: a doubly-linked list of buckets ordered by frequency,
+/// each of which holds a doubly-linked list of entries ordered by recency.
+/// O(1): get(k), put(k, v), remove(k)
+#[derive(Debug)]
+pub struct LfuCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    map: HashMap<K, usize>,
+    entries: Vec<Option<Entry<K, V>>>,
+    free_entries: Vec<usize>,
+    buckets: Vec<Option<Bucket>>,
+    free_buckets: Vec<usize>,
+    // bucket with the minimum frequency
+    head: Option<usize>,
+    capacity: usize,
+}
+
+#[derive(Debug)]
+struct Entry<K, V> {
+    k: K,
+    v: V,
+    bucket: usize,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    freq: u64,
+    // most recently used entry
+    first: Option<usize>,
+    // least recently used entry
+    last: Option<usize>,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+impl<K, V> LfuCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Generate empty LfuCache which holds at most capacity entries.
+    pub fn new(capacity: usize) -> Self {
+        LfuCache {
+            map: HashMap::with_capacity(capacity),
+            entries: Vec::with_capacity(capacity),
+            free_entries: Vec::new(),
+            buckets: Vec::new(),
+            free_buckets: Vec::new(),
+            head: None,
+            capacity,
+        }
+    }
+
+    /// Return the number of accesses recorded for a key.
+    pub fn frequency(&self, k: &K) -> Option<u64> {
+        let e = *self.map.get(k)?;
+        Some(self.bucket(self.entry(e).bucket).freq)
+    }
+
+    fn entry(&self, e: usize) -> &Entry<K, V> {
+        self.entries[e].as_ref().expect("entry must be allocated")
+    }
+
+    fn entry_mut(&mut self, e: usize) -> &mut Entry<K, V> {
+        self.entries[e].as_mut().expect("entry must be allocated")
+    }
+
+    fn bucket(&self, b: usize) -> &Bucket {
+        self.buckets[b].as_ref().expect("bucket must be allocated")
+    }
+
+    fn bucket_mut(&mut self, b: usize) -> &mut Bucket {
+        self.buckets[b].as_mut().expect("bucket must be allocated")
+    }
+
+    // Allocate a bucket for freq and link it right after prev (or at the head if prev is None).
+    fn insert_bucket(&mut self, freq: u64, prev: Option<usize>) -> usize {
+        let next = match prev {
+            Some(p) => self.bucket(p).next,
+            None => self.head,
+        };
+        let bucket = Bucket {
+            freq,
+            first: None,
+            last: None,
+            prev,
+            next,
+        };
+        let b = match self.free_buckets.pop() {
+            Some(b) => {
+                self.buckets[b] = Some(bucket);
+                b
+            }
+            None => {
+                self.buckets.push(Some(bucket));
+                self.buckets.len() - 1
+            }
+        };
+        match prev {
+            Some(p) => self.bucket_mut(p).next = Some(b),
+            None => self.head = Some(b),
+        }
+        if let Some(n) = next {
+            self.bucket_mut(n).prev = Some(b);
+        }
+        b
+    }
+
+    fn remove_bucket(&mut self, b: usize) {
+        let bucket = self.buckets[b].take().expect("bucket must be allocated");
+        match bucket.prev {
+            Some(p) => self.bucket_mut(p).next = bucket.next,
+            None => self.head = bucket.next,
+        }
+        if let Some(n) = bucket.next {
+            self.bucket_mut(n).prev = bucket.prev;
+        }
+        self.free_buckets.push(b);
+    }
+
+    // Link an entry as the most recently used one of a bucket.
+    fn push_entry(&mut self, e: usize, b: usize) {
+        let first = self.bucket(b).first;
+        {
+            let entry = self.entry_mut(e);
+            entry.bucket = b;
+            entry.prev = None;
+            entry.next = first;
+        }
+        match first {
+            Some(f) => self.entry_mut(f).prev = Some(e),
+            None => self.bucket_mut(b).last = Some(e),
+        }
+        self.bucket_mut(b).first = Some(e);
+    }
+
+    // Unlink an entry from its bucket, and release the bucket if it gets empty.
+    // Return the neighbor bucket which precedes the released one.
+    fn unlink_entry(&mut self, e: usize) -> Option<usize> {
+        let (b, prev, next) = {
+            let entry = self.entry(e);
+            (entry.bucket, entry.prev, entry.next)
+        };
+        match prev {
+            Some(p) => self.entry_mut(p).next = next,
+            None => self.bucket_mut(b).first = next,
+        }
+        match next {
+            Some(n) => self.entry_mut(n).prev = prev,
+            None => self.bucket_mut(b).last = prev,
+        }
+        if self.bucket(b).first.is_none() {
+            let prev_bucket = self.bucket(b).prev;
+            self.remove_bucket(b);
+            prev_bucket
+        } else {
+            Some(b)
+        }
+    }
+
+    // Move an entry to the bucket whose frequency is one larger.
+    fn touch(&mut self, e: usize) {
+        let b = self.entry(e).bucket;
+        let freq = self.bucket(b).freq + 1;
+        let target = match self.bucket(b).next {
+            Some(n) if self.bucket(n).freq == freq => n,
+            _ => {
+                // link a new bucket after b before unlinking, as b may be released
+                self.insert_bucket(freq, Some(b))
+            }
+        };
+        self.unlink_entry(e);
+        self.push_entry(e, target);
+    }
+
+    fn release_entry(&mut self, e: usize) -> Entry<K, V> {
+        self.unlink_entry(e);
+        let entry = self.entries[e].take().expect("entry must be allocated");
+        self.free_entries.push(e);
+        self.map.remove(&entry.k);
+        entry
+    }
+
+    fn evict(&mut self) -> Option<(K, V)> {
+        let b = self.head?;
+        let e = self.bucket(b).last?;
+        let entry = self.release_entry(e);
+        Some((entry.k, entry.v))
+    }
+}
+
+impl<K, V> Cache<K, V> for LfuCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    fn size(&self) -> usize {
+        self.map.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn get(&mut self, k: &K) -> Option<&V> {
+        let e = *self.map.get(k)?;
+        self.touch(e);
+        Some(&self.entry(e).v)
+    }
+
+    fn put(&mut self, k: K, v: V) -> Option<V> {
+        if let Some(&e) = self.map.get(&k) {
+            self.touch(e);
+            return Some(std::mem::replace(&mut self.entry_mut(e).v, v));
+        }
+        if self.capacity == 0 {
+            return None;
+        }
+        if self.size() >= self.capacity {
+            self.evict();
+        }
+        let b = match self.head {
+            Some(h) if self.bucket(h).freq == 1 => h,
+            _ => self.insert_bucket(1, None),
+        };
+        let entry = Entry {
+            k: k.clone(),
+            v,
+            bucket: b,
+            prev: None,
+            next: None,
+        };
+        let e = match self.free_entries.pop() {
+            Some(e) => {
+                self.entries[e] = Some(entry);
+                e
+            }
+            None => {
+                self.entries.push(Some(entry));
+                self.entries.len() - 1
+            }
+        };
+        self.push_entry(e, b);
+        self.map.insert(k, e);
+        None
+    }
+
+    fn remove(&mut self, k: &K) -> Option<V> {
+        let e = *self.map.get(k)?;
+        Some(self.release_entry(e).v)
+    }
+}
+
+impl<K, V> HeapSize for LfuCache<K, V>
+where
+    K: Hash + Eq + Clone,
+{
+    fn heap_bytes(&self) -> usize {
+        // hash map is approximated by its key/value slots, excluding control bytes
+        self.map.capacity() * std::mem::size_of::<(K, usize)>()
+            + self.entries.capacity() * std::mem::size_of::<Option<Entry<K, V>>>()
+            + self.free_entries.capacity() * std::mem::size_of::<usize>()
+            + self.buckets.capacity() * std::mem::size_of::<Option<Bucket>>()
+            + self.free_buckets.capacity() * std::mem::size_of::<usize>()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::LfuCache;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::cache::Cache;
+    use std::hash::Hash;
+
+    // Entries are put beyond a small capacity, and keys put so far are accessed or removed again,
+    // so that entries are spread over buckets of various frequencies.
+    // Decode the capacity and operations of (op, i, key, value), where op % 4 chooses get or remove of the
+    // i-th key put so far, or put otherwise.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn ops<'a, K, V>(u: &mut Unstructured<'a>) -> Result<(usize, Vec<(u8, usize, K, V)>)>
+    where
+        K: Arbitrary<'a>,
+        V: Arbitrary<'a>,
+    {
+        let capacity = u.int_in_range(0..=16)?;
+        let ops = (0..u.arbitrary_len::<(u8, usize, K, V)>()?)
+            .map(|_| u.arbitrary())
+            .collect::<Result<_>>()?;
+        Ok((capacity, ops))
+    }
+
+    impl<'a, K, V> Arbitrary<'a> for LfuCache<K, V>
+    where
+        K: Hash + Eq + Clone + Arbitrary<'a>,
+        V: Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let (capacity, ops) = ops::<K, V>(u)?;
+            let mut cache = LfuCache::new(capacity);
+            let mut keys: Vec<K> = Vec::new();
+            for (op, i, k, v) in ops {
+                match op % 4 {
+                    0 if !keys.is_empty() => {
+                        cache.get(&keys[i % keys.len()]);
+                    }
+                    1 if !keys.is_empty() => {
+                        cache.remove(&keys[i % keys.len()]);
+                    }
+                    _ => {
+                        keys.push(k.clone());
+                        cache.put(k, v);
+                    }
+                }
+            }
+            Ok(cache)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LfuCache;
+    use interface::cache::Cache;
+
+    #[test]
+    fn cache_test() {
+        let mut cache: LfuCache<i32, &str> = LfuCache::new(2);
+        assert_eq!(cache.capacity(), 2);
+        assert_eq!(cache.size(), 0);
+        assert_eq!(cache.get(&1), None);
+
+        assert_eq!(cache.put(1, "a"), None);
+        assert_eq!(cache.put(2, "b"), None);
+        assert_eq!(cache.size(), 2);
+        assert_eq!(cache.get(&1), Some(&"a"));
+
+        // 2 is the least frequently used
+        assert_eq!(cache.put(3, "c"), None);
+        assert_eq!(cache.size(), 2);
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&3), Some(&"c"));
+
+        // 1 and 3 are used twice, and 1 is the least recently used
+        assert_eq!(cache.put(4, "d"), None);
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&3), Some(&"c"));
+        assert_eq!(cache.get(&4), Some(&"d"));
+
+        assert_eq!(cache.put(4, "e"), Some("d"));
+        assert_eq!(cache.frequency(&3), Some(3));
+        assert_eq!(cache.frequency(&4), Some(3));
+
+        assert_eq!(cache.remove(&3), Some("c"));
+        assert_eq!(cache.remove(&3), None);
+        assert_eq!(cache.size(), 1);
+        assert_eq!(cache.get(&4), Some(&"e"));
+    }
+
+    #[test]
+    fn frequency_test() {
+        let mut cache: LfuCache<char, usize> = LfuCache::new(3);
+        cache.put('a', 0);
+        cache.put('b', 0);
+        cache.put('c', 0);
+        for _ in 0..3 {
+            cache.get(&'a');
+        }
+        cache.get(&'b');
+        cache.get(&'c');
+        cache.get(&'c');
+        assert_eq!(cache.frequency(&'a'), Some(4));
+        assert_eq!(cache.frequency(&'b'), Some(2));
+        assert_eq!(cache.frequency(&'c'), Some(3));
+
+        cache.put('d', 0);
+        assert_eq!(cache.get(&'b'), None);
+        cache.put('e', 0);
+        assert_eq!(cache.get(&'d'), None);
+        assert_eq!(cache.size(), 3);
+        assert_eq!(cache.frequency(&'e'), Some(1));
+    }
+
+    #[test]
+    fn zero_capacity_test() {
+        let mut cache: LfuCache<i32, i32> = LfuCache::new(0);
+        assert_eq!(cache.put(1, 1), None);
+        assert_eq!(cache.size(), 0);
+        assert_eq!(cache.get(&1), None);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        test_support::replay::check_arbitrary(|cache: LfuCache<u8, u16>, u| {
+            // replay the same operations on a list of (key, value, frequency, last access),
+            // which evicts the entry with the minimum frequency and then the minimum last access
+            let (capacity, ops) = super::arb::ops::<u8, u16>(u).unwrap();
+            let mut expected: Vec<(u8, u16, u64, usize)> = Vec::new();
+            let mut keys = Vec::new();
+            for (tick, (op, i, k, v)) in ops.into_iter().enumerate() {
+                match op % 4 {
+                    0 if !keys.is_empty() => {
+                        let k = keys[i % keys.len()];
+                        if let Some(e) = expected.iter_mut().find(|e| e.0 == k) {
+                            e.2 += 1;
+                            e.3 = tick;
+                        }
+                    }
+                    1 if !keys.is_empty() => {
+                        let k = keys[i % keys.len()];
+                        expected.retain(|e| e.0 != k);
+                    }
+                    _ => {
+                        keys.push(k);
+                        if let Some(e) = expected.iter_mut().find(|e| e.0 == k) {
+                            *e = (k, v, e.2 + 1, tick);
+                        } else if capacity > 0 {
+                            if expected.len() == capacity {
+                                let (victim, _) = expected
+                                    .iter()
+                                    .enumerate()
+                                    .min_by_key(|(_, e)| (e.2, e.3))
+                                    .unwrap();
+                                expected.remove(victim);
+                            }
+                            expected.push((k, v, 1, tick));
+                        }
+                    }
+                }
+            }
+            assert_eq!(cache.capacity(), capacity);
+            assert_eq!(cache.size(), expected.len());
+            for &(k, v, freq, _) in &expected {
+                let e = cache.entry(cache.map[&k]);
+                assert_eq!((e.k, e.v), (k, v));
+                assert_eq!(cache.frequency(&k), Some(freq));
+            }
+
+            // buckets are non-empty and ordered by frequency, and entries by recency within a bucket
+            let mut count = 0;
+            let (mut prev, mut b) = (None, cache.head);
+            while let Some(bi) = b {
+                let bucket = cache.bucket(bi);
+                assert_eq!(bucket.prev, prev);
+                assert!(prev.is_none_or(|p| cache.bucket(p).freq < bucket.freq));
+                assert!(bucket.first.is_some());
+                let (mut prev_e, mut e) = (None, bucket.first);
+                while let Some(ei) = e {
+                    let entry = cache.entry(ei);
+                    assert_eq!((entry.bucket, entry.prev), (bi, prev_e));
+                    let tick = |k| expected.iter().find(|e| e.0 == k).unwrap().3;
+                    assert!(prev_e.is_none_or(|p| tick(cache.entry(p).k) > tick(entry.k)));
+                    count += 1;
+                    prev_e = e;
+                    e = entry.next;
+                }
+                assert_eq!(bucket.last, prev_e);
+                prev = b;
+                b = bucket.next;
+            }
+            assert_eq!(count, expected.len());
+        });
+    }
+}
@@ -0,0 +1,7 @@
+#![warn(missing_docs)]
+//! cache implements Cache interface with various replacement policies.
+
+/// Implementation for Cache which evicts the least frequently used entry.
+pub mod lfu;
+
+pub use lfu::LfuCache;
@@ -0,0 +1,231 @@
+use array_list::array_queue::ArrayQueue;
+use interface::heap_size::HeapSize;
+use interface::list::List;
+use interface::queue::Queue;
+use std::fmt::Debug;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Bounded FIFO queue which is shared among threads, wrapping ArrayQueue with a mutex.
+/// Producers block while it's full, and consumers block while it's empty.
+/// O(1): add(x), remove() except for waiting time
+#[derive(Debug)]
+pub struct BlockingQueue<T: Clone + Debug> {
+    queue: Mutex<ArrayQueue<T>>,
+    capacity: usize,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+impl<T> BlockingQueue<T>
+where
+    T: Clone + Debug,
+{
+    /// Generate empty BlockingQueue which holds at most capacity values.
+    pub fn new(capacity: usize) -> Self {
+        if capacity == 0 {
+            panic!("capacity must be positive. capacity: {}", capacity)
+        }
+        BlockingQueue {
+            queue: Mutex::new(ArrayQueue::new()),
+            capacity,
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    /// Return the maximum number of values.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Return the number of values at the moment.
+    pub fn size(&self) -> usize {
+        self.lock().size()
+    }
+
+    /// Add a value to the tail of a queue, waiting until it has a room.
+    pub fn add(&self, x: T) {
+        let mut queue = self.lock();
+        while queue.size() >= self.capacity {
+            queue = self.not_full.wait(queue).unwrap();
+        }
+        self.push(queue, x);
+    }
+
+    /// Add a value to the tail of a queue if it has a room.
+    /// Return the given value as an error if it's full.
+    pub fn try_add(&self, x: T) -> Result<(), T> {
+        let queue = self.lock();
+        if queue.size() >= self.capacity {
+            return Err(x);
+        }
+        self.push(queue, x);
+        Ok(())
+    }
+
+    /// Add a value to the tail of a queue, waiting at most timeout until it has a room.
+    /// Return the given value as an error if it's still full.
+    /// A timeout too long to represent, such as Duration::MAX, waits as long as add does.
+    pub fn add_timeout(&self, x: T, timeout: Duration) -> Result<(), T> {
+        let deadline = match Instant::now().checked_add(timeout) {
+            Some(deadline) => deadline,
+            None => {
+                self.add(x);
+                return Ok(());
+            }
+        };
+        let mut queue = self.lock();
+        while queue.size() >= self.capacity {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(x);
+            }
+            queue = self.not_full.wait_timeout(queue, deadline - now).unwrap().0;
+        }
+        self.push(queue, x);
+        Ok(())
+    }
+
+    /// Remove the oldest value, waiting until a value is added.
+    pub fn remove(&self) -> T {
+        let mut queue = self.lock();
+        loop {
+            if let Some(x) = Queue::remove(&mut *queue) {
+                drop(queue);
+                self.not_full.notify_one();
+                return x;
+            }
+            queue = self.not_empty.wait(queue).unwrap();
+        }
+    }
+
+    /// Remove the oldest value if exists.
+    pub fn try_remove(&self) -> Option<T> {
+        let x = Queue::remove(&mut *self.lock())?;
+        self.not_full.notify_one();
+        Some(x)
+    }
+
+    /// Remove the oldest value, waiting at most timeout until a value is added.
+    /// Return null(Empty) if it's still empty.
+    /// A timeout too long to represent, such as Duration::MAX, waits as long as remove does.
+    pub fn remove_timeout(&self, timeout: Duration) -> Option<T> {
+        let deadline = match Instant::now().checked_add(timeout) {
+            Some(deadline) => deadline,
+            None => return Some(self.remove()),
+        };
+        let mut queue = self.lock();
+        loop {
+            if let Some(x) = Queue::remove(&mut *queue) {
+                drop(queue);
+                self.not_full.notify_one();
+                return Some(x);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            queue = self
+                .not_empty
+                .wait_timeout(queue, deadline - now)
+                .unwrap()
+                .0;
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ArrayQueue<T>> {
+        self.queue.lock().unwrap()
+    }
+
+    fn push(&self, mut queue: MutexGuard<'_, ArrayQueue<T>>, x: T) {
+        Queue::add(&mut *queue, x);
+        drop(queue);
+        self.not_empty.notify_one();
+    }
+}
+
+impl<T> HeapSize for BlockingQueue<T>
+where
+    T: Clone + Debug,
+{
+    fn heap_bytes(&self) -> usize {
+        self.lock().heap_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockingQueue;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn queue_test() {
+        let queue: BlockingQueue<i32> = BlockingQueue::new(2);
+        assert_eq!(queue.capacity(), 2);
+        assert_eq!(queue.try_remove(), None);
+        queue.add(1);
+        assert_eq!(queue.try_add(2), Ok(()));
+        assert_eq!(queue.try_add(3), Err(3));
+        assert_eq!(queue.add_timeout(3, Duration::from_millis(10)), Err(3));
+        assert_eq!(queue.size(), 2);
+
+        assert_eq!(queue.remove(), 1);
+        assert_eq!(queue.try_remove(), Some(2));
+        assert_eq!(queue.remove_timeout(Duration::from_millis(10)), None);
+        assert_eq!(queue.size(), 0);
+    }
+
+    #[test]
+    fn blocking_test() {
+        const N: usize = 1000;
+        let queue = Arc::new(BlockingQueue::new(4));
+        let producer = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                for x in 0..N {
+                    queue.add(x);
+                }
+            })
+        };
+        for x in 0..N {
+            assert_eq!(queue.remove(), x);
+            assert!(queue.size() <= queue.capacity());
+        }
+        producer.join().unwrap();
+        assert_eq!(queue.try_remove(), None);
+    }
+
+    #[test]
+    fn timeout_test() {
+        let queue = Arc::new(BlockingQueue::new(1));
+        let producer = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                queue.add('a');
+            })
+        };
+        assert_eq!(queue.remove_timeout(Duration::from_secs(10)), Some('a'));
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn max_timeout_test() {
+        // Duration::MAX can't be added to the current time, and waits without a deadline
+        let queue = Arc::new(BlockingQueue::new(1));
+        assert_eq!(queue.add_timeout('a', Duration::MAX), Ok(()));
+        let consumer = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                queue.remove()
+            })
+        };
+        assert_eq!(queue.add_timeout('b', Duration::MAX), Ok(()));
+        assert_eq!(consumer.join().unwrap(), 'a');
+        assert_eq!(queue.remove_timeout(Duration::MAX), Some('b'));
+    }
+}
@@ -0,0 +1,434 @@
+use crate::sync::{fence, AtomicIsize, AtomicPtr, Ordering, UnsafeCell};
+use interface::heap_size::HeapSize;
+use interface::queue::Stack;
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::sync::{Arc, Mutex};
+
+/// Generate empty work-stealing deque, and return its owner and thief handles.
+/// Owner adds and removes values at the bottom as a stack,
+/// and thieves remove values from the top concurrently.
+/// O(1): push(x), pop(), steal()
+pub fn deque<T>() -> (Worker<T>, Stealer<T>) {
+    let inner = Arc::new(Inner::new());
+    (
+        Worker {
+            inner: inner.clone(),
+            _not_sync: PhantomData,
+        },
+        Stealer { inner },
+    )
+}
+
+/// Owner side of a work-stealing deque. It can be moved to another thread, but not shared.
+#[derive(Debug)]
+pub struct Worker<T> {
+    inner: Arc<Inner<T>>,
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+/// Thief side of a work-stealing deque. It can be cloned and shared among threads.
+#[derive(Debug)]
+pub struct Stealer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+// Circular buffer whose capacity is a power of two.
+// Indices grow monotonically and are wrapped around by masking, as ArrayDeque does with modulo.
+#[derive(Debug)]
+struct Buffer<T> {
+    a: Box<[UnsafeCell<MaybeUninit<T>>]>,
+}
+
+impl<T> Buffer<T> {
+    fn alloc(capacity: usize) -> *mut Buffer<T> {
+        let a = std::iter::repeat_with(|| UnsafeCell::new(MaybeUninit::uninit()))
+            .take(capacity)
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        Box::into_raw(Box::new(Buffer { a }))
+    }
+
+    fn capacity(&self) -> usize {
+        self.a.len()
+    }
+
+    fn slot(&self, i: isize) -> &UnsafeCell<MaybeUninit<T>> {
+        &self.a[(i as usize) & (self.capacity() - 1)]
+    }
+
+    unsafe fn write(&self, i: isize, x: T) {
+        self.slot(i)
+            .with_mut(|p| std::ptr::write_volatile(p, MaybeUninit::new(x)))
+    }
+
+    // Read a value bitwise. A caller must forget it unless it wins the right to own it.
+    unsafe fn read(&self, i: isize) -> MaybeUninit<T> {
+        self.slot(i).with(|p| std::ptr::read_volatile(p))
+    }
+
+    // Copy the value at i from other bitwise, whose ownership stays with the deque.
+    unsafe fn copy_from(&self, other: &Buffer<T>, i: isize) {
+        self.slot(i).with_mut(|dst| {
+            other
+                .slot(i)
+                .with(|src| std::ptr::copy_nonoverlapping(src, dst, 1))
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Inner<T> {
+    top: AtomicIsize,
+    bottom: AtomicIsize,
+    buffer: AtomicPtr<Buffer<T>>,
+    // Buffers replaced by resize. Thieves may still read them, so they are released on drop.
+    retired: Mutex<Vec<*mut Buffer<T>>>,
+}
+
+impl<T> Inner<T> {
+    // Loom models start from the smallest buffer, so that a few pushes exercise resize and retirement.
+    const MIN_CAPACITY: usize = if cfg!(loom) { 2 } else { 16 };
+
+    fn new() -> Self {
+        Inner {
+            top: AtomicIsize::new(0),
+            bottom: AtomicIsize::new(0),
+            buffer: AtomicPtr::new(Buffer::alloc(Self::MIN_CAPACITY)),
+            retired: Mutex::new(Vec::new()),
+        }
+    }
+
+    // Count the current buffer and retired ones, which are kept until drop.
+    fn heap_bytes(&self) -> usize {
+        let buffer_bytes = |buffer: *mut Buffer<T>| unsafe {
+            std::mem::size_of::<Buffer<T>>() + (*buffer).capacity() * std::mem::size_of::<T>()
+        };
+        let retired = self.retired.lock().unwrap();
+        std::mem::size_of::<Inner<T>>()
+            + buffer_bytes(self.buffer.load(Ordering::Acquire))
+            + retired.iter().map(|&b| buffer_bytes(b)).sum::<usize>()
+            + retired.capacity() * std::mem::size_of::<*mut Buffer<T>>()
+    }
+
+    fn size(&self) -> usize {
+        let b = self.bottom.load(Ordering::Relaxed);
+        let t = self.top.load(Ordering::Relaxed);
+        std::cmp::max(b - t, 0) as usize
+    }
+}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let t = self.top.load(Ordering::Relaxed);
+        let b = self.bottom.load(Ordering::Relaxed);
+        let buffer = self.buffer.load(Ordering::Relaxed);
+        unsafe {
+            for i in t..b {
+                (*buffer).read(i).assume_init_drop();
+            }
+            drop(Box::from_raw(buffer));
+            for retired in self.retired.get_mut().unwrap().drain(..) {
+                // values in a retired buffer have been moved into the current one
+                drop(Box::from_raw(retired));
+            }
+        }
+    }
+}
+
+unsafe impl<T: Send> Send for Worker<T> {}
+unsafe impl<T: Send> Send for Stealer<T> {}
+unsafe impl<T: Send> Sync for Stealer<T> {}
+
+impl<T> Worker<T> {
+    /// Return the number of values at the moment.
+    pub fn size(&self) -> usize {
+        self.inner.size()
+    }
+
+    /// Return a new thief handle of this deque.
+    pub fn stealer(&self) -> Stealer<T> {
+        Stealer {
+            inner: self.inner.clone(),
+        }
+    }
+
+    // Double the capacity of the buffer, and retire the old one.
+    fn resize(&self, t: isize, b: isize) -> *mut Buffer<T> {
+        let old = self.inner.buffer.load(Ordering::Relaxed);
+        unsafe {
+            let new = Buffer::alloc((*old).capacity() * 2);
+            for i in t..b {
+                (*new).copy_from(&*old, i);
+            }
+            self.inner.buffer.store(new, Ordering::Release);
+            self.inner.retired.lock().unwrap().push(old);
+            new
+        }
+    }
+}
+
+impl<T> Stack<T> for Worker<T> {
+    fn push(&mut self, x: T) {
+        let b = self.inner.bottom.load(Ordering::Relaxed);
+        let t = self.inner.top.load(Ordering::Acquire);
+        let mut buffer = self.inner.buffer.load(Ordering::Relaxed);
+        unsafe {
+            if b - t >= (*buffer).capacity() as isize {
+                buffer = self.resize(t, b);
+            }
+            (*buffer).write(b, x);
+        }
+        fence(Ordering::Release);
+        self.inner.bottom.store(b + 1, Ordering::Relaxed);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let b = self.inner.bottom.load(Ordering::Relaxed) - 1;
+        let buffer = self.inner.buffer.load(Ordering::Relaxed);
+        self.inner.bottom.store(b, Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        let t = self.inner.top.load(Ordering::Relaxed);
+
+        if t > b {
+            // empty
+            self.inner.bottom.store(b + 1, Ordering::Relaxed);
+            return None;
+        }
+        let x = unsafe { (*buffer).read(b) };
+        if t == b {
+            // the last value: race against thieves
+            let won = self
+                .inner
+                .top
+                .compare_exchange(t, t + 1, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok();
+            self.inner.bottom.store(b + 1, Ordering::Relaxed);
+            if !won {
+                return None;
+            }
+        }
+        Some(unsafe { x.assume_init() })
+    }
+}
+
+impl<T> Stealer<T> {
+    /// Remove the oldest value from the top of a deque.
+    /// Return null(Empty) if it's empty.
+    pub fn steal(&self) -> Option<T> {
+        loop {
+            let t = self.inner.top.load(Ordering::Acquire);
+            fence(Ordering::SeqCst);
+            let b = self.inner.bottom.load(Ordering::Acquire);
+            if t >= b {
+                return None;
+            }
+            let buffer = self.inner.buffer.load(Ordering::Acquire);
+            let x = unsafe { (*buffer).read(t) };
+            if self
+                .inner
+                .top
+                .compare_exchange(t, t + 1, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok()
+            {
+                return Some(unsafe { x.assume_init() });
+            }
+            // another thread took it first, so the copy must not be dropped
+        }
+    }
+
+    /// Return the number of values at the moment.
+    pub fn size(&self) -> usize {
+        self.inner.size()
+    }
+}
+
+impl<T> Clone for Stealer<T> {
+    fn clone(&self) -> Self {
+        Stealer {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> HeapSize for Worker<T> {
+    fn heap_bytes(&self) -> usize {
+        self.inner.heap_bytes()
+    }
+}
+
+impl<T> HeapSize for Stealer<T> {
+    fn heap_bytes(&self) -> usize {
+        self.inner.heap_bytes()
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::deque;
+    use interface::queue::Stack;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn stack_conformance_test() {
+        let (mut stack, _) = deque::<i32>();
+        test_support::conformance::check_stack(&mut stack);
+    }
+
+    #[test]
+    fn deque_test() {
+        let (mut worker, stealer) = deque();
+        assert_eq!(worker.pop(), None);
+        assert_eq!(stealer.steal(), None);
+
+        for x in 0..100 {
+            worker.push(x);
+        }
+        assert_eq!(worker.size(), 100);
+        assert_eq!(worker.pop(), Some(99));
+        assert_eq!(stealer.steal(), Some(0));
+        assert_eq!(stealer.steal(), Some(1));
+        assert_eq!(worker.pop(), Some(98));
+        assert_eq!(stealer.size(), 96);
+
+        for x in 2..98 {
+            assert_eq!(stealer.steal(), Some(x));
+        }
+        assert_eq!(worker.pop(), None);
+        assert_eq!(stealer.steal(), None);
+    }
+
+    #[test]
+    fn drop_test() {
+        let counter = Arc::new(());
+        {
+            let (mut worker, _stealer) = deque();
+            for _ in 0..50 {
+                worker.push(counter.clone());
+            }
+            worker.pop();
+            assert_eq!(Arc::strong_count(&counter), 50);
+        }
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn concurrent_steal_test() {
+        const N: usize = if cfg!(miri) { 200 } else { 10000 };
+        const THIEVES: usize = 4;
+        let (mut worker, stealer) = deque();
+        let stolen = Arc::new(AtomicUsize::new(0));
+        let sum = Arc::new(AtomicUsize::new(0));
+
+        let handles = (0..THIEVES)
+            .map(|_| {
+                let stealer = stealer.clone();
+                let stolen = stolen.clone();
+                let sum = sum.clone();
+                thread::spawn(move || {
+                    while stolen.load(Ordering::SeqCst) < N {
+                        if let Some(x) = stealer.steal() {
+                            sum.fetch_add(x, Ordering::SeqCst);
+                            stolen.fetch_add(1, Ordering::SeqCst);
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for x in 1..=N {
+            worker.push(x);
+            if x % 3 == 0 {
+                if let Some(y) = worker.pop() {
+                    sum.fetch_add(y, Ordering::SeqCst);
+                    stolen.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }
+        while let Some(y) = worker.pop() {
+            sum.fetch_add(y, Ordering::SeqCst);
+            stolen.fetch_add(1, Ordering::SeqCst);
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(stolen.load(Ordering::SeqCst), N);
+        assert_eq!(sum.load(Ordering::SeqCst), N * (N + 1) / 2);
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::deque;
+    use interface::queue::Stack;
+    use loom::thread;
+
+    #[test]
+    fn push_pop_steal() {
+        // the owner pops the last value while a thief steals it, so exactly one of them must win
+        loom::model(|| {
+            let (mut worker, stealer) = deque();
+            worker.push(1);
+            worker.push(2);
+            let thief = thread::spawn(move || stealer.steal());
+            let mut got = std::iter::from_fn(|| worker.pop()).collect::<Vec<_>>();
+            got.extend(thief.join().unwrap());
+            got.sort_unstable();
+            assert_eq!(got, vec![1, 2]);
+        });
+    }
+
+    #[test]
+    fn resize_steal() {
+        // pushes beyond the capacity replace the buffer while a thief may be reading the old one
+        let mut builder = loom::model::Builder::new();
+        builder.preemption_bound = Some(3);
+        builder.check(|| {
+            let (mut worker, stealer) = deque();
+            worker.push(1);
+            let thief = thread::spawn(move || {
+                let first = stealer.steal();
+                (first, stealer.steal())
+            });
+            worker.push(2);
+            worker.push(3);
+            let (first, second) = thief.join().unwrap();
+            // a thief takes values from the top in the order they were pushed
+            if let (Some(x), Some(y)) = (first, second) {
+                assert!(x < y);
+            }
+            let mut got = first.into_iter().chain(second).collect::<Vec<_>>();
+            got.extend(std::iter::from_fn(|| worker.pop()));
+            got.sort_unstable();
+            assert_eq!(got, vec![1, 2, 3]);
+        });
+    }
+
+    #[test]
+    fn concurrent_steal() {
+        // two thieves race for the top while the owner pops from the bottom
+        let mut builder = loom::model::Builder::new();
+        builder.preemption_bound = Some(3);
+        builder.check(|| {
+            let (mut worker, stealer) = deque();
+            worker.push(1);
+            worker.push(2);
+            let thieves = (0..2)
+                .map(|_| {
+                    let stealer = stealer.clone();
+                    thread::spawn(move || stealer.steal())
+                })
+                .collect::<Vec<_>>();
+            let mut got = worker.pop().into_iter().collect::<Vec<_>>();
+            for thief in thieves {
+                got.extend(thief.join().unwrap());
+            }
+            got.extend(std::iter::from_fn(|| worker.pop()));
+            got.sort_unstable();
+            assert_eq!(got, vec![1, 2]);
+        });
+    }
+}
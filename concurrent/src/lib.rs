@@ -0,0 +1,21 @@
+#![warn(missing_docs)]
+//! concurrent implements thread-safe counterparts of stack and queue interfaces.
+
+/// Blocking bounded queue guarded by mutex and condition variables.
+pub mod blocking_queue;
+/// Chase-Lev work-stealing deque.
+pub mod chase_lev;
+/// Michael-Scott lock-free FIFO queue.
+pub mod ms_queue;
+/// Single-producer single-consumer lock-free ring buffer.
+pub mod spsc_ring;
+/// Treiber lock-free stack.
+pub mod treiber;
+
+mod sync;
+
+pub use blocking_queue::BlockingQueue;
+pub use chase_lev::{Stealer, Worker};
+pub use ms_queue::MsQueue;
+pub use spsc_ring::SpscRing;
+pub use treiber::TreiberStack;
@@ -0,0 +1,354 @@
+use crate::sync::{AtomicPtr, AtomicUsize, Ordering};
+use interface::queue::Queue;
+use std::mem::MaybeUninit;
+use std::ptr;
+
+/// Lock-free FIFO queue by Michael and Scott, linking nodes from a dummy head to a tail.
+/// It supports multiple producers and multiple consumers.
+/// Dequeued nodes are released only when no other thread is operating on a queue,
+/// otherwise they are kept in a pending list and released later by the last operating thread.
+/// O(1): add(x), remove() in the absence of contention
+#[derive(Debug)]
+pub struct MsQueue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    threads_in_op: AtomicUsize,
+    to_be_deleted: AtomicPtr<Node<T>>,
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    // uninitialized for the dummy head, and moved out when a node becomes the dummy head
+    x: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+    // link in the pending list, kept apart from next which stale readers may still follow
+    next_deleted: *mut Node<T>,
+}
+
+impl<T> Node<T> {
+    fn alloc(x: MaybeUninit<T>) -> *mut Node<T> {
+        Box::into_raw(Box::new(Node {
+            x,
+            next: AtomicPtr::new(ptr::null_mut()),
+            next_deleted: ptr::null_mut(),
+        }))
+    }
+}
+
+unsafe impl<T: Send> Send for MsQueue<T> {}
+unsafe impl<T: Send> Sync for MsQueue<T> {}
+
+impl<T> Default for MsQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MsQueue<T> {
+    /// Generate empty MsQueue.
+    pub fn new() -> Self {
+        let dummy = Node::alloc(MaybeUninit::uninit());
+        MsQueue {
+            head: AtomicPtr::new(dummy),
+            tail: AtomicPtr::new(dummy),
+            threads_in_op: AtomicUsize::new(0),
+            to_be_deleted: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Return true if it has no value at the moment.
+    pub fn is_empty(&self) -> bool {
+        self.threads_in_op.fetch_add(1, Ordering::SeqCst);
+        let head = self.head.load(Ordering::SeqCst);
+        let empty = unsafe { (*head).next.load(Ordering::SeqCst).is_null() };
+        self.try_reclaim(ptr::null_mut());
+        empty
+    }
+
+    /// Add a value to the tail of a queue.
+    pub fn add(&self, x: T) {
+        let node = Node::alloc(MaybeUninit::new(x));
+        self.threads_in_op.fetch_add(1, Ordering::SeqCst);
+        loop {
+            let tail = self.tail.load(Ordering::SeqCst);
+            let next = unsafe { (*tail).next.load(Ordering::SeqCst) };
+            if tail != self.tail.load(Ordering::SeqCst) {
+                continue;
+            }
+            if next.is_null() {
+                let linked = unsafe {
+                    (*tail)
+                        .next
+                        .compare_exchange(next, node, Ordering::SeqCst, Ordering::SeqCst)
+                        .is_ok()
+                };
+                if linked {
+                    let _ =
+                        self.tail
+                            .compare_exchange(tail, node, Ordering::SeqCst, Ordering::SeqCst);
+                    break;
+                }
+            } else {
+                // help a lagging tail to catch up
+                let _ = self
+                    .tail
+                    .compare_exchange(tail, next, Ordering::SeqCst, Ordering::SeqCst);
+            }
+        }
+        self.try_reclaim(ptr::null_mut());
+    }
+
+    /// Remove the oldest value from the head of a queue.
+    pub fn remove(&self) -> Option<T> {
+        self.threads_in_op.fetch_add(1, Ordering::SeqCst);
+        let (x, old) = loop {
+            let head = self.head.load(Ordering::SeqCst);
+            let tail = self.tail.load(Ordering::SeqCst);
+            let next = unsafe { (*head).next.load(Ordering::SeqCst) };
+            if head != self.head.load(Ordering::SeqCst) {
+                continue;
+            }
+            if next.is_null() {
+                break (None, ptr::null_mut());
+            }
+            if head == tail {
+                let _ = self
+                    .tail
+                    .compare_exchange(tail, next, Ordering::SeqCst, Ordering::SeqCst);
+                continue;
+            }
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+            {
+                // next becomes the dummy head, and only the winner moves its value out
+                let x = unsafe { ptr::read(&(*next).x).assume_init() };
+                break (Some(x), head);
+            }
+        };
+        self.try_reclaim(old);
+        x
+    }
+
+    fn try_reclaim(&self, old: *mut Node<T>) {
+        if self.threads_in_op.load(Ordering::SeqCst) == 1 {
+            // the only thread in operation: nodes pending so far are unreachable from others
+            let nodes = self.to_be_deleted.swap(ptr::null_mut(), Ordering::SeqCst);
+            if self.threads_in_op.fetch_sub(1, Ordering::SeqCst) == 1 {
+                unsafe { delete_nodes(nodes) };
+            } else if !nodes.is_null() {
+                self.chain_pending_nodes(nodes);
+            }
+            if !old.is_null() {
+                drop(unsafe { Box::from_raw(old) });
+            }
+        } else {
+            if !old.is_null() {
+                self.chain_pending_nodes(old);
+            }
+            self.threads_in_op.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    // Prepend a chain of nodes linked by next_deleted to the pending list.
+    fn chain_pending_nodes(&self, first: *mut Node<T>) {
+        let mut last = first;
+        unsafe {
+            while !(*last).next_deleted.is_null() {
+                last = (*last).next_deleted;
+            }
+            let mut pending = self.to_be_deleted.load(Ordering::SeqCst);
+            loop {
+                (*last).next_deleted = pending;
+                match self.to_be_deleted.compare_exchange(
+                    pending,
+                    first,
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                ) {
+                    Ok(_) => return,
+                    Err(p) => pending = p,
+                }
+            }
+        }
+    }
+}
+
+// Release a chain of nodes linked by next_deleted, whose values have been moved out.
+unsafe fn delete_nodes<T>(mut node: *mut Node<T>) {
+    while !node.is_null() {
+        let next = (*node).next_deleted;
+        drop(Box::from_raw(node));
+        node = next;
+    }
+}
+
+impl<T> Drop for MsQueue<T> {
+    fn drop(&mut self) {
+        while self.remove().is_some() {}
+        unsafe {
+            drop(Box::from_raw(self.head.load(Ordering::Relaxed)));
+            delete_nodes(self.to_be_deleted.load(Ordering::Relaxed));
+        }
+    }
+}
+
+impl<T> Queue<T> for MsQueue<T> {
+    fn add(&mut self, x: T) -> bool {
+        MsQueue::add(self, x);
+        true
+    }
+
+    fn remove(&mut self) -> Option<T> {
+        MsQueue::remove(self)
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::MsQueue;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn queue_test() {
+        use interface::queue::Queue;
+
+        let mut queue: MsQueue<i32> = MsQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(Queue::remove(&mut queue), None);
+        assert!(Queue::add(&mut queue, 1));
+        assert!(Queue::add(&mut queue, 2));
+        assert!(!queue.is_empty());
+        assert_eq!(Queue::remove(&mut queue), Some(1));
+        assert!(Queue::add(&mut queue, 3));
+        assert_eq!(Queue::remove(&mut queue), Some(2));
+        assert_eq!(Queue::remove(&mut queue), Some(3));
+        assert_eq!(Queue::remove(&mut queue), None);
+    }
+
+    #[test]
+    fn drop_test() {
+        let counter = Arc::new(());
+        {
+            let queue = MsQueue::new();
+            for _ in 0..10 {
+                queue.add(counter.clone());
+            }
+            queue.remove();
+            assert_eq!(Arc::strong_count(&counter), 10);
+        }
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn concurrent_test() {
+        const N: usize = if cfg!(miri) { 50 } else { 10000 };
+        const PRODUCERS: usize = 2;
+        const CONSUMERS: usize = 2;
+        let queue = Arc::new(MsQueue::new());
+        let received = Arc::new(AtomicUsize::new(0));
+        let sum = Arc::new(AtomicUsize::new(0));
+
+        let consumers = (0..CONSUMERS)
+            .map(|_| {
+                let queue = queue.clone();
+                let received = received.clone();
+                let sum = sum.clone();
+                thread::spawn(move || {
+                    // values from each producer must come out in order
+                    let mut last = [0; PRODUCERS];
+                    while received.load(Ordering::SeqCst) < PRODUCERS * N {
+                        if let Some((p, x)) = queue.remove() {
+                            assert!(last[p] < x);
+                            last[p] = x;
+                            sum.fetch_add(x, Ordering::SeqCst);
+                            received.fetch_add(1, Ordering::SeqCst);
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let producers = (0..PRODUCERS)
+            .map(|p| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    for x in 1..=N {
+                        queue.add((p, x));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in producers.into_iter().chain(consumers) {
+            handle.join().unwrap();
+        }
+        assert!(queue.is_empty());
+        assert_eq!(sum.load(Ordering::SeqCst), PRODUCERS * N * (N + 1) / 2);
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::MsQueue;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    #[test]
+    fn concurrent_add_remove() {
+        loom::model(|| {
+            let queue = Arc::new(MsQueue::new());
+            let producer = {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    queue.add(1);
+                    queue.add(2);
+                })
+            };
+            let consumer = {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    let mut got = Vec::new();
+                    for _ in 0..2 {
+                        if let Some(x) = queue.remove() {
+                            got.push(x);
+                        }
+                    }
+                    got
+                })
+            };
+            producer.join().unwrap();
+            let mut got = consumer.join().unwrap();
+            // values are received in FIFO order, and the rest remain in a queue
+            assert!(got.windows(2).all(|w| w[0] < w[1]));
+            while let Some(x) = queue.remove() {
+                got.push(x);
+            }
+            assert_eq!(got, vec![1, 2]);
+        });
+    }
+
+    #[test]
+    fn concurrent_remove() {
+        loom::model(|| {
+            let queue = Arc::new(MsQueue::new());
+            queue.add(1);
+            queue.add(2);
+            let handles = (0..2)
+                .map(|_| {
+                    let queue = queue.clone();
+                    thread::spawn(move || queue.remove())
+                })
+                .collect::<Vec<_>>();
+            let mut got = handles
+                .into_iter()
+                .filter_map(|h| h.join().unwrap())
+                .collect::<Vec<_>>();
+            got.sort_unstable();
+            assert_eq!(got, vec![1, 2]);
+            assert!(queue.is_empty());
+        });
+    }
+}
@@ -0,0 +1,355 @@
+use crate::sync::{AtomicUsize, Ordering, UnsafeCell};
+use interface::heap_size::HeapSize;
+use std::mem::MaybeUninit;
+use std::sync::Arc;
+
+/// Lock-free bounded FIFO queue for a single producer and a single consumer.
+/// Values are stored in a circular buffer as ArrayQueue does, and head/tail indices are atomic.
+/// Indices run over twice the capacity, so that a full ring is told from an empty one for any capacity.
+/// O(1): push(x), pop()
+#[derive(Debug)]
+pub struct SpscRing<T> {
+    a: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    // index of the next value to pop within [0, 2 * capacity), which only consumer advances
+    head: CachePadded<AtomicUsize>,
+    // index of the next slot to push within [0, 2 * capacity), which only producer advances
+    tail: CachePadded<AtomicUsize>,
+}
+
+/// Producer side of SpscRing.
+#[derive(Debug)]
+pub struct Producer<T> {
+    ring: Arc<SpscRing<T>>,
+}
+
+/// Consumer side of SpscRing.
+#[derive(Debug)]
+pub struct Consumer<T> {
+    ring: Arc<SpscRing<T>>,
+}
+
+// Align a value to a cache line, so head and tail updated by different threads don't share one.
+#[derive(Debug)]
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+unsafe impl<T: Send> Send for SpscRing<T> {}
+unsafe impl<T: Send> Sync for SpscRing<T> {}
+
+impl<T> SpscRing<T> {
+    /// Generate empty SpscRing which holds at most capacity values,
+    /// and return its producer and consumer.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(capacity: usize) -> (Producer<T>, Consumer<T>) {
+        if capacity == 0 || capacity > usize::MAX / 2 {
+            panic!(
+                "capacity must be positive and at most half of usize::MAX. capacity: {}",
+                capacity
+            )
+        }
+        let ring = Arc::new(SpscRing {
+            a: std::iter::repeat_with(|| UnsafeCell::new(MaybeUninit::uninit()))
+                .take(capacity)
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+            head: CachePadded(AtomicUsize::new(0)),
+            tail: CachePadded(AtomicUsize::new(0)),
+        });
+        (Producer { ring: ring.clone() }, Consumer { ring })
+    }
+
+    fn capacity(&self) -> usize {
+        self.a.len()
+    }
+
+    // Return the number of values, loading the index of the calling side before the other one.
+    // The calling side's index can't move during the call, and the other side keeps its index within
+    // the capacity from it. The result is clamped to the capacity as well, so that a stale pair of indices
+    // never reads as a size beyond it.
+    fn size(&self, from_producer: bool) -> usize {
+        let (head, tail) = if from_producer {
+            let tail = self.tail.0.load(Ordering::Acquire);
+            (self.head.0.load(Ordering::Acquire), tail)
+        } else {
+            let head = self.head.0.load(Ordering::Acquire);
+            (head, self.tail.0.load(Ordering::Acquire))
+        };
+        self.distance(head, tail).min(self.capacity())
+    }
+
+    // Return the number of values between head and tail indices.
+    fn distance(&self, head: usize, tail: usize) -> usize {
+        if head <= tail {
+            tail - head
+        } else {
+            tail + 2 * self.capacity() - head
+        }
+    }
+
+    // Return the index next to i, which wraps around at twice the capacity.
+    fn next(&self, i: usize) -> usize {
+        if i + 1 == 2 * self.capacity() {
+            0
+        } else {
+            i + 1
+        }
+    }
+
+    fn slot(&self, i: usize) -> &UnsafeCell<MaybeUninit<T>> {
+        let capacity = self.capacity();
+        &self.a[if i < capacity { i } else { i - capacity }]
+    }
+}
+
+impl<T> Drop for SpscRing<T> {
+    fn drop(&mut self) {
+        let head = self.head.0.load(Ordering::Relaxed);
+        let tail = self.tail.0.load(Ordering::Relaxed);
+        let mut i = head;
+        while i != tail {
+            self.slot(i)
+                .with_mut(|p| unsafe { (*p).assume_init_drop() });
+            i = self.next(i);
+        }
+    }
+}
+
+impl<T> Producer<T> {
+    /// Add a value to the tail if it's not full.
+    /// Return the given value as an error if it's full.
+    pub fn push(&mut self, x: T) -> Result<(), T> {
+        let tail = self.ring.tail.0.load(Ordering::Relaxed);
+        let head = self.ring.head.0.load(Ordering::Acquire);
+        if self.ring.distance(head, tail) == self.ring.capacity() {
+            return Err(x);
+        }
+        self.ring.slot(tail).with_mut(|p| unsafe { (*p).write(x) });
+        self.ring
+            .tail
+            .0
+            .store(self.ring.next(tail), Ordering::Release);
+        Ok(())
+    }
+
+    /// Return the maximum number of values.
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    /// Return a snapshot of the number of values, which may already be smaller when it's returned
+    /// because the consumer can remove values concurrently.
+    pub fn size(&self) -> usize {
+        self.ring.size(true)
+    }
+}
+
+impl<T> Consumer<T> {
+    /// Remove the oldest value if exists.
+    pub fn pop(&mut self) -> Option<T> {
+        let head = self.ring.head.0.load(Ordering::Relaxed);
+        let tail = self.ring.tail.0.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        let x = self
+            .ring
+            .slot(head)
+            .with(|p| unsafe { (*p).assume_init_read() });
+        self.ring
+            .head
+            .0
+            .store(self.ring.next(head), Ordering::Release);
+        Some(x)
+    }
+
+    /// Return the maximum number of values.
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+
+    /// Return a snapshot of the number of values, which may already be larger when it's returned
+    /// because the producer can add values concurrently.
+    pub fn size(&self) -> usize {
+        self.ring.size(false)
+    }
+}
+
+impl<T> HeapSize for Producer<T> {
+    fn heap_bytes(&self) -> usize {
+        std::mem::size_of::<SpscRing<T>>() + self.ring.capacity() * std::mem::size_of::<T>()
+    }
+}
+
+// The shared ring is reported by Producer, so that the sum of both sides counts it once.
+impl<T> HeapSize for Consumer<T> {
+    fn heap_bytes(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::SpscRing;
+    use interface::heap_size::HeapSize;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn ring_test() {
+        let (mut producer, mut consumer) = SpscRing::new(3);
+        assert_eq!(producer.capacity(), 3);
+        assert_eq!(consumer.pop(), None);
+        assert_eq!(producer.push(1), Ok(()));
+        assert_eq!(producer.push(2), Ok(()));
+        assert_eq!(producer.push(3), Ok(()));
+        assert_eq!(producer.push(4), Err(4));
+        assert_eq!(consumer.size(), 3);
+
+        assert_eq!(consumer.pop(), Some(1));
+        assert_eq!(producer.push(4), Ok(()));
+        assert_eq!(consumer.pop(), Some(2));
+        assert_eq!(consumer.pop(), Some(3));
+        assert_eq!(consumer.pop(), Some(4));
+        assert_eq!(consumer.pop(), None);
+        assert_eq!(producer.size(), 0);
+    }
+
+    #[test]
+    fn wrap_test() {
+        // indices wrap around many times with a capacity which is not a power of two
+        let (mut producer, mut consumer) = SpscRing::new(3);
+        for x in 0..100 {
+            assert_eq!(producer.push(2 * x), Ok(()));
+            assert_eq!(producer.push(2 * x + 1), Ok(()));
+            assert_eq!(consumer.size(), 2);
+            assert_eq!(consumer.pop(), Some(2 * x));
+            assert_eq!(consumer.pop(), Some(2 * x + 1));
+            assert_eq!(consumer.pop(), None);
+        }
+        for x in 0..3 {
+            assert_eq!(producer.push(x), Ok(()));
+        }
+        assert_eq!(producer.push(3), Err(3));
+        assert_eq!(producer.size(), 3);
+    }
+
+    #[test]
+    fn heap_size_test() {
+        let (producer, consumer) = SpscRing::<u64>::new(5);
+        let ring = std::mem::size_of::<SpscRing<u64>>() + 5 * std::mem::size_of::<u64>();
+        assert_eq!(producer.heap_bytes() + consumer.heap_bytes(), ring);
+    }
+
+    #[test]
+    fn drop_test() {
+        let counter = Arc::new(());
+        {
+            let (mut producer, mut consumer) = SpscRing::new(4);
+            for _ in 0..4 {
+                producer.push(counter.clone()).unwrap();
+            }
+            consumer.pop();
+            producer.push(counter.clone()).unwrap();
+            assert_eq!(Arc::strong_count(&counter), 5);
+        }
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn concurrent_test() {
+        const N: usize = if cfg!(miri) { 100 } else { 10000 };
+        let (mut producer, mut consumer) = SpscRing::new(16);
+        let handle = thread::spawn(move || {
+            for x in 0..N {
+                let mut x = x;
+                while let Err(y) = producer.push(x) {
+                    x = y;
+                    thread::yield_now();
+                }
+            }
+        });
+        let mut expected = 0;
+        while expected < N {
+            match consumer.pop() {
+                Some(x) => {
+                    assert_eq!(x, expected);
+                    expected += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+        handle.join().unwrap();
+        assert_eq!(consumer.pop(), None);
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::SpscRing;
+    use loom::thread;
+
+    #[test]
+    fn push_pop() {
+        // a slot is reused after wrapping around, so that a value is read only after it's written
+        // and overwritten only after it's read
+        loom::model(|| {
+            let (mut producer, mut consumer) = SpscRing::new(1);
+            let handle = thread::spawn(move || {
+                for x in 0..3 {
+                    let mut x = x;
+                    while let Err(y) = producer.push(x) {
+                        x = y;
+                        thread::yield_now();
+                    }
+                }
+            });
+            for expected in 0..3 {
+                loop {
+                    match consumer.pop() {
+                        Some(x) => {
+                            assert_eq!(x, expected);
+                            break;
+                        }
+                        None => thread::yield_now(),
+                    }
+                }
+            }
+            handle.join().unwrap();
+            assert_eq!(consumer.pop(), None);
+        });
+    }
+
+    #[test]
+    fn size_within_capacity() {
+        // a size seen while the other side moves doesn't exceed the capacity
+        loom::model(|| {
+            let (mut producer, mut consumer) = SpscRing::new(1);
+            let handle = thread::spawn(move || {
+                for x in 0..2 {
+                    let _ = producer.push(x);
+                    assert!(producer.size() <= 1);
+                }
+            });
+            for _ in 0..2 {
+                consumer.pop();
+                assert!(consumer.size() <= 1);
+            }
+            handle.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn drop_unconsumed() {
+        // values left in the ring are dropped by whichever side is dropped last
+        loom::model(|| {
+            let (mut producer, mut consumer) = SpscRing::new(2);
+            let handle = thread::spawn(move || {
+                producer.push(String::from("a")).unwrap();
+                producer.push(String::from("b")).unwrap();
+            });
+            let got = consumer.pop();
+            handle.join().unwrap();
+            assert!(got.is_none() || got.as_deref() == Some("a"));
+        });
+    }
+}
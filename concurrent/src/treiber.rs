@@ -0,0 +1,347 @@
+use crate::sync::{AtomicPtr, AtomicUsize, Ordering};
+use interface::queue::Stack;
+use std::mem::ManuallyDrop;
+use std::ptr;
+
+/// Lock-free stack which replaces the head pointer by compare-and-swap.
+/// Popped nodes are released only when no other thread is pushing or popping,
+/// otherwise they are kept in a pending list and released later by the last thread leaving.
+/// Pushing threads are counted as well, so the head a pusher compares against is never released
+/// and its address is never reused by another node until the pusher leaves.
+/// O(1): push(x), pop() in the absence of contention
+#[derive(Debug)]
+pub struct TreiberStack<T> {
+    head: AtomicPtr<Node<T>>,
+    active_threads: AtomicUsize,
+    to_be_deleted: AtomicPtr<Node<T>>,
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    // moved out by pop, so a node never drops it
+    x: ManuallyDrop<T>,
+    // atomic since a popping thread may read it while another one relinks the node to the pending list
+    next: AtomicPtr<Node<T>>,
+}
+
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TreiberStack<T> {
+    /// Generate empty TreiberStack.
+    pub fn new() -> Self {
+        TreiberStack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            active_threads: AtomicUsize::new(0),
+            to_be_deleted: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Return true if it has no value at the moment.
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+
+    /// Add a value to the top of a stack.
+    pub fn push(&self, x: T) {
+        let node = Box::into_raw(Box::new(Node {
+            x: ManuallyDrop::new(x),
+            next: AtomicPtr::new(ptr::null_mut()),
+        }));
+        // a loaded head cannot be released, so its address is not reused while this thread is counted
+        self.active_threads.fetch_add(1, Ordering::SeqCst);
+        let mut head = self.head.load(Ordering::SeqCst);
+        loop {
+            unsafe { (*node).next.store(head, Ordering::Relaxed) };
+            match self
+                .head
+                .compare_exchange_weak(head, node, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => break,
+                Err(h) => head = h,
+            }
+        }
+        self.try_reclaim(ptr::null_mut());
+    }
+
+    /// Remove the last-added value.
+    pub fn pop(&self) -> Option<T> {
+        // SeqCst orders the counter against head, so a thread which observes itself alone
+        // never releases a node another thread has loaded
+        self.active_threads.fetch_add(1, Ordering::SeqCst);
+        let mut head = self.head.load(Ordering::SeqCst);
+        while !head.is_null() {
+            // head cannot be released while this thread is counted in active_threads
+            let next = unsafe { (*head).next.load(Ordering::Relaxed) };
+            match self
+                .head
+                .compare_exchange_weak(head, next, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => break,
+                Err(h) => head = h,
+            }
+        }
+        let x = if head.is_null() {
+            None
+        } else {
+            Some(unsafe { ManuallyDrop::take(&mut (*head).x) })
+        };
+        self.try_reclaim(head);
+        x
+    }
+
+    // Leave the section entered by push or pop, releasing old popped by this thread if possible.
+    fn try_reclaim(&self, old: *mut Node<T>) {
+        if self.active_threads.load(Ordering::SeqCst) == 1 {
+            // the only active thread: nodes pending so far are unreachable from others
+            let nodes = self.to_be_deleted.swap(ptr::null_mut(), Ordering::SeqCst);
+            if self.active_threads.fetch_sub(1, Ordering::SeqCst) == 1 {
+                unsafe { delete_nodes(nodes) };
+            } else if !nodes.is_null() {
+                self.chain_pending_nodes(nodes);
+            }
+            if !old.is_null() {
+                drop(unsafe { Box::from_raw(old) });
+            }
+        } else {
+            if !old.is_null() {
+                // old.next still points to a node in a stack, so it's chained alone
+                self.chain_pending_range(old, old);
+            }
+            self.active_threads.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    // Prepend a chain of nodes starting from first to the pending list.
+    fn chain_pending_nodes(&self, first: *mut Node<T>) {
+        let mut last = first;
+        unsafe {
+            while !(*last).next.load(Ordering::Relaxed).is_null() {
+                last = (*last).next.load(Ordering::Relaxed);
+            }
+        }
+        self.chain_pending_range(first, last);
+    }
+
+    // Prepend nodes from first to last to the pending list.
+    fn chain_pending_range(&self, first: *mut Node<T>, last: *mut Node<T>) {
+        unsafe {
+            let mut pending = self.to_be_deleted.load(Ordering::SeqCst);
+            loop {
+                (*last).next.store(pending, Ordering::Relaxed);
+                match self.to_be_deleted.compare_exchange_weak(
+                    pending,
+                    first,
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                ) {
+                    Ok(_) => return,
+                    Err(p) => pending = p,
+                }
+            }
+        }
+    }
+}
+
+// Release a chain of nodes whose values have been moved out.
+unsafe fn delete_nodes<T>(mut node: *mut Node<T>) {
+    while !node.is_null() {
+        let next = (*node).next.load(Ordering::Relaxed);
+        drop(Box::from_raw(node));
+        node = next;
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+        unsafe { delete_nodes(self.to_be_deleted.load(Ordering::Relaxed)) };
+    }
+}
+
+impl<T> Stack<T> for TreiberStack<T> {
+    fn push(&mut self, x: T) {
+        TreiberStack::push(self, x)
+    }
+    fn pop(&mut self) -> Option<T> {
+        TreiberStack::pop(self)
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::TreiberStack;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn stack_conformance_test() {
+        let mut stack: TreiberStack<i32> = TreiberStack::new();
+        test_support::conformance::check_stack(&mut stack);
+    }
+
+    #[test]
+    fn stack_test() {
+        use interface::queue::Stack;
+
+        let mut stack: TreiberStack<i32> = TreiberStack::new();
+        assert!(stack.is_empty());
+        assert_eq!(Stack::pop(&mut stack), None);
+        Stack::push(&mut stack, 1);
+        Stack::push(&mut stack, 2);
+        assert!(!stack.is_empty());
+        assert_eq!(Stack::pop(&mut stack), Some(2));
+        Stack::push(&mut stack, 3);
+        assert_eq!(Stack::pop(&mut stack), Some(3));
+        assert_eq!(Stack::pop(&mut stack), Some(1));
+        assert_eq!(Stack::pop(&mut stack), None);
+    }
+
+    #[test]
+    fn drop_test() {
+        let counter = Arc::new(());
+        {
+            let stack = TreiberStack::new();
+            for _ in 0..10 {
+                stack.push(counter.clone());
+            }
+            stack.pop();
+            assert_eq!(Arc::strong_count(&counter), 10);
+        }
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn concurrent_test() {
+        const N: usize = if cfg!(miri) { 50 } else { 10000 };
+        const THREADS: usize = 4;
+        let stack = Arc::new(TreiberStack::new());
+        let sum = Arc::new(AtomicUsize::new(0));
+
+        let handles = (0..THREADS)
+            .map(|t| {
+                let stack = stack.clone();
+                let sum = sum.clone();
+                thread::spawn(move || {
+                    for x in 1..=N {
+                        stack.push(t * N + x);
+                        if let Some(y) = stack.pop() {
+                            sum.fetch_add(y, Ordering::SeqCst);
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        while let Some(y) = stack.pop() {
+            sum.fetch_add(y, Ordering::SeqCst);
+        }
+        let total = THREADS * N;
+        assert_eq!(sum.load(Ordering::SeqCst), total * (total + 1) / 2);
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::TreiberStack;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    #[test]
+    fn concurrent_push_pop() {
+        loom::model(|| {
+            let stack = Arc::new(TreiberStack::new());
+            let pusher = {
+                let stack = stack.clone();
+                thread::spawn(move || {
+                    stack.push(1);
+                    stack.push(2);
+                })
+            };
+            let popper = {
+                let stack = stack.clone();
+                thread::spawn(move || stack.pop())
+            };
+            pusher.join().unwrap();
+            let mut got = popper.join().unwrap().into_iter().collect::<Vec<_>>();
+            // the rest are popped in LIFO order, and no value is lost or duplicated
+            let rest = std::iter::from_fn(|| stack.pop()).collect::<Vec<_>>();
+            assert!(rest.windows(2).all(|w| w[0] > w[1]));
+            got.extend(rest);
+            got.sort_unstable();
+            assert_eq!(got, vec![1, 2]);
+        });
+    }
+
+    #[test]
+    fn concurrent_pop() {
+        // nodes popped while another thread is popping are reclaimed without use after free
+        loom::model(|| {
+            let stack = Arc::new(TreiberStack::new());
+            stack.push(1);
+            stack.push(2);
+            let handles = (0..2)
+                .map(|_| {
+                    let stack = stack.clone();
+                    thread::spawn(move || stack.pop())
+                })
+                .collect::<Vec<_>>();
+            let mut got = handles
+                .into_iter()
+                .filter_map(|h| h.join().unwrap())
+                .collect::<Vec<_>>();
+            got.sort_unstable();
+            assert_eq!(got, vec![1, 2]);
+            assert!(stack.is_empty());
+        });
+    }
+
+    #[test]
+    fn concurrent_push_pop_reclaim() {
+        // pushes race with pops which release nodes, so a pusher must hold off reclamation
+        // of the head it compares against
+        let mut builder = loom::model::Builder::new();
+        // three threads are intractable without bounding preemptions
+        builder.preemption_bound = Some(3);
+        builder.check(|| {
+            let stack = Arc::new(TreiberStack::new());
+            stack.push(1);
+            let handles = vec![
+                {
+                    let stack = stack.clone();
+                    thread::spawn(move || {
+                        stack.push(2);
+                        stack.pop()
+                    })
+                },
+                {
+                    let stack = stack.clone();
+                    thread::spawn(move || stack.pop())
+                },
+                {
+                    let stack = stack.clone();
+                    thread::spawn(move || {
+                        stack.push(3);
+                        None
+                    })
+                },
+            ];
+            let mut got = handles
+                .into_iter()
+                .filter_map(|h| h.join().unwrap())
+                .collect::<Vec<_>>();
+            got.extend(std::iter::from_fn(|| stack.pop()));
+            got.sort_unstable();
+            assert_eq!(got, vec![1, 2, 3]);
+        });
+    }
+}
@@ -0,0 +1,709 @@
+use interface::heap_size::HeapSize;
+use std::ops::{Bound, RangeBounds};
+
+// Handle which represents the absence of a node.
+const NIL: u32 = u32::MAX;
+
+#[derive(Debug)]
+enum Node<K, V> {
+    // Keys and values sorted by key, with the handle of the next leaf in key order.
+    Leaf {
+        keys: Vec<K>,
+        values: Vec<V>,
+        next: u32,
+    },
+    // Separators where children[i] holds keys within [keys[i - 1], keys[i]).
+    Internal {
+        keys: Vec<K>,
+        children: Vec<u32>,
+    },
+}
+
+impl<K, V> Node<K, V> {
+    fn empty() -> Self {
+        Node::Leaf {
+            keys: Vec::new(),
+            values: Vec::new(),
+            next: NIL,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf { keys, .. } | Node::Internal { keys, .. } => keys.len(),
+        }
+    }
+
+    fn heap_bytes(&self) -> usize {
+        match self {
+            Node::Leaf { keys, values, .. } => {
+                keys.capacity() * std::mem::size_of::<K>()
+                    + values.capacity() * std::mem::size_of::<V>()
+            }
+            Node::Internal { keys, children } => {
+                keys.capacity() * std::mem::size_of::<K>()
+                    + children.capacity() * std::mem::size_of::<u32>()
+            }
+        }
+    }
+}
+
+/// Sorted map realized by B+ tree, where values live only in leaves and leaves are chained in key order.
+/// Every node other than the root holds between b - 1 and 2b - 1 keys, and all leaves have the same depth.
+/// Nodes live in an arena and refer to each other by handles, as blocks do on disk.
+/// O(b log_b n): insert(k, v), remove(k)
+/// O(log b log_b n): get(k)
+/// O(log b log_b n + m): range(r) for m entries within the range
+#[derive(Debug)]
+pub struct BPlusTree<K, V> {
+    nodes: Vec<Node<K, V>>,
+    free: Vec<u32>,
+    root: u32,
+    b: usize,
+    n: usize,
+}
+
+impl<K, V> Default for BPlusTree<K, V>
+where
+    K: Ord + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> BPlusTree<K, V>
+where
+    K: Ord + Clone,
+{
+    /// Generate empty BPlusTree with b = 16.
+    pub fn new() -> Self {
+        Self::with_order(16)
+    }
+
+    /// Generate empty BPlusTree whose nodes hold at most 2b - 1 keys.
+    pub fn with_order(b: usize) -> Self {
+        if b < 2 {
+            panic!("order must be at least 2. b: {}", b)
+        }
+        BPlusTree {
+            nodes: vec![Node::empty()],
+            free: Vec::new(),
+            root: 0,
+            b,
+            n: 0,
+        }
+    }
+
+    /// Return the number of entries.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    fn max_keys(&self) -> usize {
+        2 * self.b - 1
+    }
+
+    fn min_keys(&self) -> usize {
+        self.b - 1
+    }
+
+    fn alloc(&mut self, node: Node<K, V>) -> u32 {
+        match self.free.pop() {
+            Some(u) => {
+                self.nodes[u as usize] = node;
+                u
+            }
+            None => {
+                let u = self.nodes.len();
+                if u >= NIL as usize {
+                    panic!("tree cannot hold more nodes. n: {}", u)
+                }
+                self.nodes.push(node);
+                u as u32
+            }
+        }
+    }
+
+    fn release(&mut self, u: u32) {
+        self.nodes[u as usize] = Node::empty();
+        self.free.push(u);
+    }
+
+    fn node(&self, u: u32) -> &Node<K, V> {
+        &self.nodes[u as usize]
+    }
+
+    fn node_mut(&mut self, u: u32) -> &mut Node<K, V> {
+        &mut self.nodes[u as usize]
+    }
+
+    // Take a node out of the arena to modify it together with its sibling.
+    fn take(&mut self, u: u32) -> Node<K, V> {
+        std::mem::replace(self.node_mut(u), Node::empty())
+    }
+
+    fn put(&mut self, u: u32, node: Node<K, V>) {
+        *self.node_mut(u) = node;
+    }
+
+    /// Return the value of a key.
+    /// Return null(Empty) if the key doesn't exist.
+    pub fn get(&self, k: &K) -> Option<&V> {
+        let mut u = self.root;
+        loop {
+            match self.node(u) {
+                Node::Internal { keys, children } => {
+                    u = children[keys.partition_point(|s| s <= k)];
+                }
+                Node::Leaf { keys, values, .. } => {
+                    return keys.binary_search(k).ok().map(|i| &values[i]);
+                }
+            }
+        }
+    }
+
+    /// Return the mutable value of a key.
+    /// Return null(Empty) if the key doesn't exist.
+    pub fn get_mut(&mut self, k: &K) -> Option<&mut V> {
+        let mut u = self.root;
+        while let Node::Internal { keys, children } = self.node(u) {
+            u = children[keys.partition_point(|s| s <= k)];
+        }
+        match self.node_mut(u) {
+            Node::Leaf { keys, values, .. } => {
+                keys.binary_search(k).ok().map(move |i| &mut values[i])
+            }
+            Node::Internal { .. } => unreachable!("descent must end at a leaf"),
+        }
+    }
+
+    /// Return true if the key exists.
+    pub fn contains(&self, k: &K) -> bool {
+        self.get(k).is_some()
+    }
+
+    /// Insert a value with a key, and return the previous value of the key.
+    /// Return null(Empty) if the key didn't exist.
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        let (old, split) = self.insert_into(self.root, k, v);
+        if let Some((sep, right)) = split {
+            let root = self.alloc(Node::Internal {
+                keys: vec![sep],
+                children: vec![self.root, right],
+            });
+            self.root = root;
+        }
+        if old.is_none() {
+            self.n += 1;
+        }
+        old
+    }
+
+    // Insert into the subtree u, and return the separator and the new right node if u is split.
+    fn insert_into(&mut self, u: u32, k: K, v: V) -> (Option<V>, Option<(K, u32)>) {
+        let max = self.max_keys();
+        let child = match self.node(u) {
+            Node::Internal { keys, children } => {
+                let i = keys.partition_point(|s| s <= &k);
+                Some((i, children[i]))
+            }
+            Node::Leaf { .. } => None,
+        };
+        let old = match child {
+            Some((i, c)) => {
+                let (old, split) = self.insert_into(c, k, v);
+                if let Some((sep, right)) = split {
+                    if let Node::Internal { keys, children } = self.node_mut(u) {
+                        keys.insert(i, sep);
+                        children.insert(i + 1, right);
+                    }
+                }
+                old
+            }
+            None => match self.node_mut(u) {
+                Node::Leaf { keys, values, .. } => match keys.binary_search(&k) {
+                    Ok(i) => Some(std::mem::replace(&mut values[i], v)),
+                    Err(i) => {
+                        keys.insert(i, k);
+                        values.insert(i, v);
+                        None
+                    }
+                },
+                Node::Internal { .. } => unreachable!("node without child must be a leaf"),
+            },
+        };
+        if self.node(u).len() > max {
+            (old, Some(self.split(u)))
+        } else {
+            (old, None)
+        }
+    }
+
+    // Move the upper half of an overflowing node to a new node, and return the separator and the new node.
+    fn split(&mut self, u: u32) -> (K, u32) {
+        match self.take(u) {
+            Node::Leaf {
+                mut keys,
+                mut values,
+                next,
+            } => {
+                let mid = keys.len() / 2;
+                let right_keys = keys.split_off(mid);
+                let right_values = values.split_off(mid);
+                let sep = right_keys[0].clone();
+                let right = self.alloc(Node::Leaf {
+                    keys: right_keys,
+                    values: right_values,
+                    next,
+                });
+                self.put(
+                    u,
+                    Node::Leaf {
+                        keys,
+                        values,
+                        next: right,
+                    },
+                );
+                (sep, right)
+            }
+            Node::Internal {
+                mut keys,
+                mut children,
+            } => {
+                let mid = keys.len() / 2;
+                let right_keys = keys.split_off(mid + 1);
+                let sep = keys.pop().expect("middle key must exist");
+                let right_children = children.split_off(mid + 1);
+                let right = self.alloc(Node::Internal {
+                    keys: right_keys,
+                    children: right_children,
+                });
+                self.put(u, Node::Internal { keys, children });
+                (sep, right)
+            }
+        }
+    }
+
+    /// Remove a key, and return its value.
+    /// Return null(Empty) if the key doesn't exist.
+    pub fn remove(&mut self, k: &K) -> Option<V> {
+        let v = self.remove_from(self.root, k)?;
+        self.n -= 1;
+        // the root with a single child is replaced by the child
+        if let Node::Internal { keys, children } = self.node(self.root) {
+            if keys.is_empty() {
+                let child = children[0];
+                self.release(self.root);
+                self.root = child;
+            }
+        }
+        Some(v)
+    }
+
+    fn remove_from(&mut self, u: u32, k: &K) -> Option<V> {
+        let (i, c) = match self.node_mut(u) {
+            Node::Leaf { keys, values, .. } => {
+                let i = keys.binary_search(k).ok()?;
+                keys.remove(i);
+                return Some(values.remove(i));
+            }
+            Node::Internal { keys, children } => {
+                let i = keys.partition_point(|s| s <= k);
+                (i, children[i])
+            }
+        };
+        let v = self.remove_from(c, k)?;
+        if self.node(c).len() < self.min_keys() {
+            self.rebalance(u, i);
+        }
+        Some(v)
+    }
+
+    // Fix the underflowing i-th child of u by borrowing a key from a sibling, or merging with it.
+    fn rebalance(&mut self, u: u32, i: usize) {
+        let (left, right) = match self.node(u) {
+            Node::Internal { children, .. } => (
+                i.checked_sub(1).map(|j| children[j]),
+                children.get(i + 1).copied(),
+            ),
+            Node::Leaf { .. } => unreachable!("parent must be internal"),
+        };
+        let min = self.min_keys();
+        match (left, right) {
+            (Some(l), _) if self.node(l).len() > min => self.rotate(u, i - 1, true),
+            (_, Some(r)) if self.node(r).len() > min => self.rotate(u, i, false),
+            (Some(_), _) => self.merge(u, i - 1),
+            (None, Some(_)) => self.merge(u, i),
+            (None, None) => unreachable!("non-root node must have a sibling"),
+        }
+    }
+
+    // Move a key between the j-th and the (j + 1)-th children of u, to the right if to_right is true.
+    fn rotate(&mut self, u: u32, j: usize, to_right: bool) {
+        let mut parent = self.take(u);
+        let (sep, l, r) = match &mut parent {
+            Node::Internal { keys, children } => (&mut keys[j], children[j], children[j + 1]),
+            Node::Leaf { .. } => unreachable!("parent must be internal"),
+        };
+        let (mut left, mut right) = (self.take(l), self.take(r));
+        match (&mut left, &mut right) {
+            (
+                Node::Leaf {
+                    keys: lk,
+                    values: lv,
+                    ..
+                },
+                Node::Leaf {
+                    keys: rk,
+                    values: rv,
+                    ..
+                },
+            ) => {
+                if to_right {
+                    rk.insert(0, lk.pop().expect("left leaf must have a spare key"));
+                    rv.insert(0, lv.pop().expect("left leaf must have a spare value"));
+                } else {
+                    lk.push(rk.remove(0));
+                    lv.push(rv.remove(0));
+                }
+                *sep = rk[0].clone();
+            }
+            (
+                Node::Internal {
+                    keys: lk,
+                    children: lc,
+                },
+                Node::Internal {
+                    keys: rk,
+                    children: rc,
+                },
+            ) => {
+                // the separator moves down, and the key from the sibling moves up
+                if to_right {
+                    let k = lk.pop().expect("left node must have a spare key");
+                    rk.insert(0, std::mem::replace(sep, k));
+                    rc.insert(0, lc.pop().expect("left node must have a spare child"));
+                } else {
+                    let k = rk.remove(0);
+                    lk.push(std::mem::replace(sep, k));
+                    lc.push(rc.remove(0));
+                }
+            }
+            _ => unreachable!("siblings must have the same depth"),
+        }
+        self.put(l, left);
+        self.put(r, right);
+        self.put(u, parent);
+    }
+
+    // Merge the (j + 1)-th child of u into the j-th child.
+    fn merge(&mut self, u: u32, j: usize) {
+        let (sep, l, r) = match self.node_mut(u) {
+            Node::Internal { keys, children } => {
+                (keys.remove(j), children[j], children.remove(j + 1))
+            }
+            Node::Leaf { .. } => unreachable!("parent must be internal"),
+        };
+        let right = self.take(r);
+        match (self.node_mut(l), right) {
+            (
+                Node::Leaf {
+                    keys: lk,
+                    values: lv,
+                    next,
+                },
+                Node::Leaf {
+                    keys: mut rk,
+                    values: mut rv,
+                    next: right_next,
+                },
+            ) => {
+                lk.append(&mut rk);
+                lv.append(&mut rv);
+                *next = right_next;
+            }
+            (
+                Node::Internal {
+                    keys: lk,
+                    children: lc,
+                },
+                Node::Internal {
+                    keys: mut rk,
+                    children: mut rc,
+                },
+            ) => {
+                lk.push(sep);
+                lk.append(&mut rk);
+                lc.append(&mut rc);
+            }
+            _ => unreachable!("siblings must have the same depth"),
+        }
+        self.release(r);
+    }
+
+    /// Return an iterator over entries within a range of keys in ascending order.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        let start = range.start_bound();
+        let mut u = self.root;
+        while let Node::Internal { keys, children } = self.node(u) {
+            let i = match start {
+                Bound::Included(k) | Bound::Excluded(k) => keys.partition_point(|s| s <= k),
+                Bound::Unbounded => 0,
+            };
+            u = children[i];
+        }
+        let i = match (self.node(u), start) {
+            (Node::Leaf { keys, .. }, Bound::Included(k)) => keys.partition_point(|x| x < k),
+            (Node::Leaf { keys, .. }, Bound::Excluded(k)) => keys.partition_point(|x| x <= k),
+            _ => 0,
+        };
+        Range {
+            tree: self,
+            leaf: u,
+            i,
+            end: range.end_bound().cloned(),
+        }
+    }
+
+    /// Return an iterator over all entries in ascending order of keys.
+    pub fn iter(&self) -> Range<'_, K, V> {
+        self.range(..)
+    }
+}
+
+impl<K, V> HeapSize for BPlusTree<K, V> {
+    fn heap_bytes(&self) -> usize {
+        self.nodes.capacity() * std::mem::size_of::<Node<K, V>>()
+            + self.nodes.iter().map(|u| u.heap_bytes()).sum::<usize>()
+            + self.free.capacity() * std::mem::size_of::<u32>()
+    }
+}
+
+/// Iterator over entries of BPlusTree, which follows the chain of leaves.
+#[derive(Debug)]
+pub struct Range<'a, K, V> {
+    tree: &'a BPlusTree<K, V>,
+    leaf: u32,
+    i: usize,
+    end: Bound<K>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V>
+where
+    K: Ord,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        while self.leaf != NIL {
+            let (keys, values, next) = match &self.tree.nodes[self.leaf as usize] {
+                Node::Leaf { keys, values, next } => (keys, values, *next),
+                Node::Internal { .. } => unreachable!("leaves must be chained to leaves"),
+            };
+            if self.i == keys.len() {
+                self.leaf = next;
+                self.i = 0;
+                continue;
+            }
+            let k = &keys[self.i];
+            let within = match &self.end {
+                Bound::Included(end) => k <= end,
+                Bound::Excluded(end) => k < end,
+                Bound::Unbounded => true,
+            };
+            if !within {
+                self.leaf = NIL;
+                return None;
+            }
+            self.i += 1;
+            return Some((k, &values[self.i - 1]));
+        }
+        None
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::BPlusTree;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // Decode the order and operations of (op, i, key, value), where op % 4 == 0 removes the i-th key
+    // inserted so far.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn ops<'a, K, V>(u: &mut Unstructured<'a>) -> Result<(usize, Vec<(u8, usize, K, V)>)>
+    where
+        K: Arbitrary<'a>,
+        V: Arbitrary<'a>,
+    {
+        let b = u.int_in_range(2..=8)?;
+        let ops = (0..u.arbitrary_len::<(u8, usize, K, V)>()?)
+            .map(|_| u.arbitrary())
+            .collect::<Result<_>>()?;
+        Ok((b, ops))
+    }
+
+    // A small order is chosen so that nodes split, and keys inserted so far are sometimes removed again
+    // so that nodes are merged and their handles are freed.
+    impl<'a, K, V> Arbitrary<'a> for BPlusTree<K, V>
+    where
+        K: Ord + Clone + Arbitrary<'a>,
+        V: Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let (b, ops) = ops::<K, V>(u)?;
+            let mut tree = BPlusTree::with_order(b);
+            let mut keys: Vec<K> = Vec::new();
+            for (op, i, k, v) in ops {
+                if op % 4 == 0 && !keys.is_empty() {
+                    tree.remove(&keys[i % keys.len()]);
+                } else {
+                    keys.push(k.clone());
+                    tree.insert(k, v);
+                }
+            }
+            Ok(tree)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BPlusTree, Node, NIL};
+    use std::collections::BTreeMap;
+
+    // Validate the size of nodes, the depth of leaves and the chain of leaves, and return the depth.
+    fn check<K: Ord + Clone + std::fmt::Debug, V>(tree: &BPlusTree<K, V>) -> usize {
+        fn depth<K: Ord + Clone, V>(tree: &BPlusTree<K, V>, u: u32, root: bool) -> usize {
+            let node = tree.node(u);
+            assert!(node.len() <= tree.max_keys());
+            assert!(root || node.len() >= tree.min_keys());
+            match node {
+                Node::Leaf { keys, .. } => {
+                    assert!(keys.windows(2).all(|w| w[0] < w[1]));
+                    1
+                }
+                Node::Internal { keys, children } => {
+                    assert_eq!(children.len(), keys.len() + 1);
+                    let ds = children
+                        .iter()
+                        .map(|&c| depth(tree, c, false))
+                        .collect::<Vec<_>>();
+                    assert!(ds.iter().all(|&d| d == ds[0]));
+                    ds[0] + 1
+                }
+            }
+        }
+        let d = depth(tree, tree.root, true);
+        let keys = tree.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>();
+        assert_eq!(keys.len(), tree.size());
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        d
+    }
+
+    #[test]
+    fn map_test() {
+        let mut tree: BPlusTree<i32, char> = BPlusTree::with_order(2);
+        assert_eq!(tree.get(&1), None);
+        assert_eq!(tree.remove(&1), None);
+        for (k, v) in [(5, 'e'), (1, 'a'), (3, 'c'), (4, 'd'), (2, 'b'), (6, 'f')].iter() {
+            assert_eq!(tree.insert(*k, *v), None);
+        }
+        assert_eq!(tree.insert(3, 'C'), Some('c'));
+        assert_eq!(tree.size(), 6);
+        assert!(check(&tree) > 1);
+        assert_eq!(tree.get(&3), Some(&'C'));
+        *tree.get_mut(&4).unwrap() = 'D';
+        assert_eq!(tree.iter().map(|(_, v)| *v).collect::<String>(), "abCDef");
+        assert_eq!(
+            tree.range(2..5).map(|(k, _)| *k).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+        assert_eq!(tree.range(7..).next(), None);
+
+        for k in 1..=6 {
+            assert!(tree.remove(&k).is_some());
+            check(&tree);
+        }
+        assert_eq!(tree.size(), 0);
+        assert_eq!(tree.iter().next(), None);
+    }
+
+    #[test]
+    fn random_test() {
+        use std::ops::Bound::{Excluded, Included};
+
+        for b in [2, 3, 16].iter() {
+            let mut tree = BPlusTree::with_order(*b);
+            let mut expected = BTreeMap::new();
+            // pseudo random operations by linear congruential generator
+            let mut x: u64 = 17;
+            let steps = if cfg!(miri) { 1000 } else { 20000 };
+            for step in 0..steps {
+                x = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let k = (x >> 33) % 2000;
+                match (x >> 20) % 3 {
+                    0 => assert_eq!(tree.remove(&k), expected.remove(&k)),
+                    _ => assert_eq!(tree.insert(k, step), expected.insert(k, step)),
+                }
+                if step % 2000 == 0 {
+                    check(&tree);
+                }
+            }
+            check(&tree);
+            assert_eq!(tree.size(), expected.len());
+            assert!(tree.iter().eq(expected.iter()));
+            assert!(tree
+                .range((Excluded(500), Included(1500)))
+                .eq(expected.range((Excluded(500), Included(1500)))));
+            for k in 0..2000 {
+                assert_eq!(tree.get(&k), expected.get(&k));
+            }
+            for k in 0..2000 {
+                assert_eq!(tree.remove(&k), expected.remove(&k));
+            }
+            assert_eq!(check(&tree), 1);
+            assert_eq!(tree.nodes.len() - tree.free.len(), 1);
+            assert!(matches!(tree.node(tree.root), Node::Leaf { next: NIL, .. }));
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        // Return the number of nodes reachable from u.
+        fn reachable<K, V>(tree: &BPlusTree<K, V>, u: u32) -> usize {
+            match &tree.nodes[u as usize] {
+                Node::Leaf { .. } => 1,
+                Node::Internal { children, .. } => {
+                    1 + children.iter().map(|&c| reachable(tree, c)).sum::<usize>()
+                }
+            }
+        }
+
+        test_support::replay::check_arbitrary(|tree: BPlusTree<u8, u16>, u| {
+            // replay the same operations on BTreeMap
+            let (b, ops) = super::arb::ops::<u8, u16>(u).unwrap();
+            let mut expected = BTreeMap::new();
+            let mut keys = Vec::new();
+            for (op, i, k, v) in ops {
+                if op % 4 == 0 && !keys.is_empty() {
+                    expected.remove(&keys[i % keys.len()]);
+                } else {
+                    keys.push(k);
+                    expected.insert(k, v);
+                }
+            }
+            assert_eq!(tree.b, b);
+            check(&tree);
+            assert!(tree.iter().eq(expected.iter()));
+            // every node is either reachable from the root or freed
+            assert_eq!(
+                reachable(&tree, tree.root) + tree.free.len(),
+                tree.nodes.len()
+            );
+        });
+    }
+}
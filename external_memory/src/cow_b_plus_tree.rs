@@ -0,0 +1,725 @@
+use interface::heap_size::HeapSize;
+use std::ops::{Bound, RangeBounds};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+enum Node<K, V> {
+    Leaf {
+        keys: Vec<K>,
+        values: Vec<V>,
+    },
+    // Separators where children[i] holds keys within [keys[i - 1], keys[i]).
+    Internal {
+        keys: Vec<K>,
+        children: Vec<Arc<Node<K, V>>>,
+    },
+}
+
+// The separator and the new right node of a split node.
+type Split<K, V> = Option<(K, Arc<Node<K, V>>)>;
+
+impl<K, V> Node<K, V> {
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf { keys, .. } | Node::Internal { keys, .. } => keys.len(),
+        }
+    }
+}
+
+impl<K, V> Node<K, V>
+where
+    K: Ord,
+{
+    fn get(&self, k: &K) -> Option<&V> {
+        let mut u = self;
+        loop {
+            match u {
+                Node::Internal { keys, children } => {
+                    u = &children[keys.partition_point(|s| s <= k)];
+                }
+                Node::Leaf { keys, values } => {
+                    return keys.binary_search(k).ok().map(|i| &values[i]);
+                }
+            }
+        }
+    }
+
+    fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Clone,
+    {
+        let start = range.start_bound();
+        let mut stack = Vec::new();
+        let mut u = self;
+        while let Node::Internal { keys, children } = u {
+            let i = match start {
+                Bound::Included(k) | Bound::Excluded(k) => keys.partition_point(|s| s <= k),
+                Bound::Unbounded => 0,
+            };
+            stack.push((u, i + 1));
+            u = &children[i];
+        }
+        let i = match (u, start) {
+            (Node::Leaf { keys, .. }, Bound::Included(k)) => keys.partition_point(|x| x < k),
+            (Node::Leaf { keys, .. }, Bound::Excluded(k)) => keys.partition_point(|x| x <= k),
+            _ => 0,
+        };
+        Range {
+            stack,
+            leaf: Some(u),
+            i,
+            end: range.end_bound().cloned(),
+        }
+    }
+}
+
+impl<K, V> Node<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    // Insert into this node, and return the separator and the new right node if it's split.
+    // Nodes on the path are copied if a snapshot shares them.
+    fn insert(&mut self, k: K, v: V, max: usize) -> (Option<V>, Split<K, V>) {
+        let old = match self {
+            Node::Leaf { keys, values } => match keys.binary_search(&k) {
+                Ok(i) => return (Some(std::mem::replace(&mut values[i], v)), None),
+                Err(i) => {
+                    keys.insert(i, k);
+                    values.insert(i, v);
+                    None
+                }
+            },
+            Node::Internal { keys, children } => {
+                let i = keys.partition_point(|s| s <= &k);
+                let (old, split) = Arc::make_mut(&mut children[i]).insert(k, v, max);
+                if let Some((sep, right)) = split {
+                    keys.insert(i, sep);
+                    children.insert(i + 1, right);
+                }
+                old
+            }
+        };
+        if self.len() > max {
+            (old, Some(self.split()))
+        } else {
+            (old, None)
+        }
+    }
+
+    fn split(&mut self) -> (K, Arc<Node<K, V>>) {
+        match self {
+            Node::Leaf { keys, values } => {
+                let mid = keys.len() / 2;
+                let right_keys = keys.split_off(mid);
+                let right_values = values.split_off(mid);
+                let sep = right_keys[0].clone();
+                let right = Node::Leaf {
+                    keys: right_keys,
+                    values: right_values,
+                };
+                (sep, Arc::new(right))
+            }
+            Node::Internal { keys, children } => {
+                let mid = keys.len() / 2;
+                let right_keys = keys.split_off(mid + 1);
+                let sep = keys.pop().expect("middle key must exist");
+                let right = Node::Internal {
+                    keys: right_keys,
+                    children: children.split_off(mid + 1),
+                };
+                (sep, Arc::new(right))
+            }
+        }
+    }
+
+    // Remove an existing key from this node, and fix children which underflow.
+    fn remove(&mut self, k: &K, min: usize) -> V {
+        match self {
+            Node::Leaf { keys, values } => {
+                let i = keys.binary_search(k).expect("key to remove must exist");
+                keys.remove(i);
+                values.remove(i)
+            }
+            Node::Internal { keys, children } => {
+                let i = keys.partition_point(|s| s <= k);
+                let v = Arc::make_mut(&mut children[i]).remove(k, min);
+                if children[i].len() < min {
+                    rebalance(keys, children, i, min);
+                }
+                v
+            }
+        }
+    }
+}
+
+// Fix the underflowing i-th child by borrowing a key from a sibling, or merging with it.
+fn rebalance<K, V>(keys: &mut Vec<K>, children: &mut Vec<Arc<Node<K, V>>>, i: usize, min: usize)
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    if i > 0 && children[i - 1].len() > min {
+        rotate(keys, children, i - 1, true);
+    } else if i + 1 < children.len() && children[i + 1].len() > min {
+        rotate(keys, children, i, false);
+    } else if i > 0 {
+        merge(keys, children, i - 1);
+    } else {
+        merge(keys, children, i);
+    }
+}
+
+// Move a key between the j-th and the (j + 1)-th children, to the right if to_right is true.
+fn rotate<K, V>(keys: &mut [K], children: &mut [Arc<Node<K, V>>], j: usize, to_right: bool)
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    let sep = &mut keys[j];
+    let (l, r) = children.split_at_mut(j + 1);
+    match (Arc::make_mut(&mut l[j]), Arc::make_mut(&mut r[0])) {
+        (
+            Node::Leaf {
+                keys: lk,
+                values: lv,
+            },
+            Node::Leaf {
+                keys: rk,
+                values: rv,
+            },
+        ) => {
+            if to_right {
+                rk.insert(0, lk.pop().expect("left leaf must have a spare key"));
+                rv.insert(0, lv.pop().expect("left leaf must have a spare value"));
+            } else {
+                lk.push(rk.remove(0));
+                lv.push(rv.remove(0));
+            }
+            *sep = rk[0].clone();
+        }
+        (
+            Node::Internal {
+                keys: lk,
+                children: lc,
+            },
+            Node::Internal {
+                keys: rk,
+                children: rc,
+            },
+        ) => {
+            // the separator moves down, and the key from the sibling moves up
+            if to_right {
+                let k = lk.pop().expect("left node must have a spare key");
+                rk.insert(0, std::mem::replace(sep, k));
+                rc.insert(0, lc.pop().expect("left node must have a spare child"));
+            } else {
+                let k = rk.remove(0);
+                lk.push(std::mem::replace(sep, k));
+                lc.push(rc.remove(0));
+            }
+        }
+        _ => unreachable!("siblings must have the same depth"),
+    }
+}
+
+// Merge the (j + 1)-th child into the j-th child.
+fn merge<K, V>(keys: &mut Vec<K>, children: &mut Vec<Arc<Node<K, V>>>, j: usize)
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    let sep = keys.remove(j);
+    let right = children.remove(j + 1);
+    // the right node is copied only if a snapshot still refers to it
+    let right = Arc::try_unwrap(right).unwrap_or_else(|shared| (*shared).clone());
+    match (Arc::make_mut(&mut children[j]), right) {
+        (
+            Node::Leaf {
+                keys: lk,
+                values: lv,
+            },
+            Node::Leaf {
+                keys: mut rk,
+                values: mut rv,
+            },
+        ) => {
+            lk.append(&mut rk);
+            lv.append(&mut rv);
+        }
+        (
+            Node::Internal {
+                keys: lk,
+                children: lc,
+            },
+            Node::Internal {
+                keys: mut rk,
+                children: mut rc,
+            },
+        ) => {
+            lk.push(sep);
+            lk.append(&mut rk);
+            lc.append(&mut rc);
+        }
+        _ => unreachable!("siblings must have the same depth"),
+    }
+}
+
+/// Sorted map realized by B+ tree with copy-on-write nodes, which takes snapshots in O(1).
+/// Nodes are shared with snapshots, and a modification copies only the nodes on its path which are shared.
+/// Leaves aren't chained, since a copied leaf would have to relink its shared predecessor,
+/// so that range scans keep the path from the root instead.
+/// O(1): snapshot()
+/// O(b log_b n): insert(k, v), remove(k)
+/// O(log b log_b n): get(k)
+/// O(log b log_b n + m): range(r) for m entries within the range
+#[derive(Debug)]
+pub struct CowBPlusTree<K, V> {
+    root: Arc<Node<K, V>>,
+    b: usize,
+    n: usize,
+}
+
+/// Immutable version of CowBPlusTree at the time snapshot() was called.
+/// It shares unmodified nodes with the tree, and is cheap to clone and to send to other threads.
+#[derive(Debug)]
+pub struct Snapshot<K, V> {
+    root: Arc<Node<K, V>>,
+    n: usize,
+}
+
+impl<K, V> Clone for Snapshot<K, V> {
+    fn clone(&self) -> Self {
+        Snapshot {
+            root: self.root.clone(),
+            n: self.n,
+        }
+    }
+}
+
+impl<K, V> Default for CowBPlusTree<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> CowBPlusTree<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    /// Generate empty CowBPlusTree with b = 16.
+    pub fn new() -> Self {
+        Self::with_order(16)
+    }
+
+    /// Generate empty CowBPlusTree whose nodes hold at most 2b - 1 keys.
+    pub fn with_order(b: usize) -> Self {
+        if b < 2 {
+            panic!("order must be at least 2. b: {}", b)
+        }
+        CowBPlusTree {
+            root: Arc::new(Node::Leaf {
+                keys: Vec::new(),
+                values: Vec::new(),
+            }),
+            b,
+            n: 0,
+        }
+    }
+
+    /// Return the number of entries.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Return the current version, which isn't affected by later modifications.
+    pub fn snapshot(&self) -> Snapshot<K, V> {
+        Snapshot {
+            root: self.root.clone(),
+            n: self.n,
+        }
+    }
+
+    /// Return the value of a key.
+    /// Return null(Empty) if the key doesn't exist.
+    pub fn get(&self, k: &K) -> Option<&V> {
+        self.root.get(k)
+    }
+
+    /// Return true if the key exists.
+    pub fn contains(&self, k: &K) -> bool {
+        self.get(k).is_some()
+    }
+
+    /// Insert a value with a key, and return the previous value of the key.
+    /// Return null(Empty) if the key didn't exist.
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        let max = 2 * self.b - 1;
+        let (old, split) = Arc::make_mut(&mut self.root).insert(k, v, max);
+        if let Some((sep, right)) = split {
+            let left = self.root.clone();
+            self.root = Arc::new(Node::Internal {
+                keys: vec![sep],
+                children: vec![left, right],
+            });
+        }
+        if old.is_none() {
+            self.n += 1;
+        }
+        old
+    }
+
+    /// Remove a key, and return its value.
+    /// Return null(Empty) if the key doesn't exist.
+    pub fn remove(&mut self, k: &K) -> Option<V> {
+        // nodes aren't copied for a key which doesn't exist
+        if !self.contains(k) {
+            return None;
+        }
+        let v = Arc::make_mut(&mut self.root).remove(k, self.b - 1);
+        self.n -= 1;
+        // the root with a single child is replaced by the child
+        let child = match self.root.as_ref() {
+            Node::Internal { keys, children } if keys.is_empty() => Some(children[0].clone()),
+            _ => None,
+        };
+        if let Some(child) = child {
+            self.root = child;
+        }
+        Some(v)
+    }
+
+    /// Return an iterator over entries within a range of keys in ascending order.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        self.root.range(range)
+    }
+
+    /// Return an iterator over all entries in ascending order of keys.
+    pub fn iter(&self) -> Range<'_, K, V> {
+        self.range(..)
+    }
+}
+
+impl<K, V> Snapshot<K, V>
+where
+    K: Ord + Clone,
+{
+    /// Return the number of entries.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Return the value of a key.
+    /// Return null(Empty) if the key doesn't exist.
+    pub fn get(&self, k: &K) -> Option<&V> {
+        self.root.get(k)
+    }
+
+    /// Return true if the key exists.
+    pub fn contains(&self, k: &K) -> bool {
+        self.get(k).is_some()
+    }
+
+    /// Return an iterator over entries within a range of keys in ascending order.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        self.root.range(range)
+    }
+
+    /// Return an iterator over all entries in ascending order of keys.
+    pub fn iter(&self) -> Range<'_, K, V> {
+        self.range(..)
+    }
+}
+
+impl<K, V> HeapSize for CowBPlusTree<K, V> {
+    // Nodes shared with snapshots are counted as well.
+    fn heap_bytes(&self) -> usize {
+        fn node_bytes<K, V>(u: &Node<K, V>) -> usize {
+            std::mem::size_of::<Node<K, V>>()
+                + match u {
+                    Node::Leaf { keys, values } => {
+                        keys.capacity() * std::mem::size_of::<K>()
+                            + values.capacity() * std::mem::size_of::<V>()
+                    }
+                    Node::Internal { keys, children } => {
+                        keys.capacity() * std::mem::size_of::<K>()
+                            + children.capacity() * std::mem::size_of::<Arc<Node<K, V>>>()
+                            + children.iter().map(|c| node_bytes(c)).sum::<usize>()
+                    }
+                }
+        }
+        node_bytes(&self.root)
+    }
+}
+
+/// Iterator over entries of CowBPlusTree or Snapshot, which keeps the path from the root to the current leaf.
+#[derive(Debug)]
+pub struct Range<'a, K, V> {
+    // internal nodes on the path, and the index of the child to visit next
+    stack: Vec<(&'a Node<K, V>, usize)>,
+    leaf: Option<&'a Node<K, V>>,
+    i: usize,
+    end: Bound<K>,
+}
+
+impl<'a, K, V> Range<'a, K, V> {
+    // Move to the leftmost leaf of the next subtree on the path.
+    fn advance(&mut self) {
+        self.leaf = None;
+        while let Some((u, ci)) = self.stack.last_mut() {
+            let children = match u {
+                Node::Internal { children, .. } => children,
+                Node::Leaf { .. } => unreachable!("path must consist of internal nodes"),
+            };
+            if *ci == children.len() {
+                self.stack.pop();
+                continue;
+            }
+            let mut c: &'a Node<K, V> = &children[*ci];
+            *ci += 1;
+            while let Node::Internal { children, .. } = c {
+                self.stack.push((c, 1));
+                c = &children[0];
+            }
+            self.leaf = Some(c);
+            self.i = 0;
+            return;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V>
+where
+    K: Ord,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        while let Some(leaf) = self.leaf {
+            let (keys, values) = match leaf {
+                Node::Leaf { keys, values } => (keys, values),
+                Node::Internal { .. } => unreachable!("leaf must be a leaf node"),
+            };
+            if self.i == keys.len() {
+                self.advance();
+                continue;
+            }
+            let k = &keys[self.i];
+            let within = match &self.end {
+                Bound::Included(end) => k <= end,
+                Bound::Excluded(end) => k < end,
+                Bound::Unbounded => true,
+            };
+            if !within {
+                self.leaf = None;
+                self.stack.clear();
+                return None;
+            }
+            self.i += 1;
+            return Some((k, &values[self.i - 1]));
+        }
+        None
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::CowBPlusTree;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // Decode the order and operations of (op, i, key, value), where op % 4 == 0 removes the i-th key
+    // inserted so far.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn ops<'a, K, V>(u: &mut Unstructured<'a>) -> Result<(usize, Vec<(u8, usize, K, V)>)>
+    where
+        K: Arbitrary<'a>,
+        V: Arbitrary<'a>,
+    {
+        let b = u.int_in_range(2..=8)?;
+        let ops = (0..u.arbitrary_len::<(u8, usize, K, V)>()?)
+            .map(|_| u.arbitrary())
+            .collect::<Result<_>>()?;
+        Ok((b, ops))
+    }
+
+    // A small order is chosen so that nodes split, and keys inserted so far are sometimes removed again
+    // so that nodes are merged.
+    impl<'a, K, V> Arbitrary<'a> for CowBPlusTree<K, V>
+    where
+        K: Ord + Clone + Arbitrary<'a>,
+        V: Clone + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let (b, ops) = ops::<K, V>(u)?;
+            let mut tree = CowBPlusTree::with_order(b);
+            let mut keys: Vec<K> = Vec::new();
+            for (op, i, k, v) in ops {
+                if op % 4 == 0 && !keys.is_empty() {
+                    tree.remove(&keys[i % keys.len()]);
+                } else {
+                    keys.push(k.clone());
+                    tree.insert(k, v);
+                }
+            }
+            Ok(tree)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CowBPlusTree, Node};
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn snapshot_test() {
+        let mut tree: CowBPlusTree<u32, u32> = CowBPlusTree::with_order(4);
+        for k in 0..1000 {
+            tree.insert(k, k);
+        }
+        let before = tree.snapshot();
+        tree.insert(500, 0);
+        tree.insert(1000, 1000);
+        for k in (0..1000).step_by(2) {
+            tree.remove(&k);
+        }
+        assert_eq!(tree.size(), 501);
+        assert_eq!(tree.get(&500), None);
+        assert_eq!(tree.get(&501), Some(&501));
+
+        // the snapshot still sees the version at the time it was taken
+        assert_eq!(before.size(), 1000);
+        assert_eq!(before.get(&500), Some(&500));
+        assert_eq!(before.get(&1000), None);
+        assert!(before
+            .iter()
+            .map(|(k, v)| (*k, *v))
+            .eq((0..1000).map(|k| (k, k))));
+        assert!(before.range(10..20).map(|(k, _)| *k).eq(10..20));
+        assert!(tree.range(10..20).map(|(k, _)| *k).eq((11..20).step_by(2)));
+
+        let clone = before.clone();
+        drop(before);
+        assert_eq!(clone.get(&998), Some(&998));
+    }
+
+    #[test]
+    fn sharing_test() {
+        let mut tree: CowBPlusTree<u32, ()> = CowBPlusTree::with_order(4);
+        for k in 0..1000 {
+            tree.insert(k, ());
+        }
+        let snapshot = tree.snapshot();
+        assert!(Arc::ptr_eq(&tree.root, &snapshot.root));
+        tree.insert(1000, ());
+        // only the path to the last leaf is copied, and the rest is shared
+        let (root, old_root) = (tree.root.as_ref(), snapshot.root.as_ref());
+        match (root, old_root) {
+            (Node::Internal { children: a, .. }, Node::Internal { children: b, .. }) => {
+                assert!(!Arc::ptr_eq(a.last().unwrap(), b.last().unwrap()));
+                let shared = a.iter().zip(b.iter()).filter(|(x, y)| Arc::ptr_eq(x, y));
+                assert_eq!(shared.count(), b.len() - 1);
+            }
+            _ => panic!("root must be internal"),
+        }
+        // a tree without snapshots is modified in place
+        drop(snapshot);
+        let root = Arc::as_ptr(&tree.root);
+        tree.insert(1001, ());
+        assert_eq!(Arc::as_ptr(&tree.root), root);
+    }
+
+    #[test]
+    fn random_test() {
+        let mut tree = CowBPlusTree::with_order(3);
+        let mut expected = BTreeMap::new();
+        let mut snapshots = Vec::new();
+        // pseudo random operations by linear congruential generator
+        let mut x: u64 = 19;
+        let steps: u64 = if cfg!(miri) { 2000 } else { 10000 };
+        for step in 0..steps {
+            x = x
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let k = (x >> 33) % 1000;
+            match (x >> 20) % 3 {
+                0 => assert_eq!(tree.remove(&k), expected.remove(&k)),
+                _ => assert_eq!(tree.insert(k, step), expected.insert(k, step)),
+            }
+            if step % 1000 == 999 {
+                snapshots.push((tree.snapshot(), expected.clone()));
+            }
+        }
+        assert!(tree.iter().eq(expected.iter()));
+        for (snapshot, expected) in snapshots.iter() {
+            assert_eq!(snapshot.size(), expected.len());
+            assert!(snapshot.iter().eq(expected.iter()));
+            assert!(snapshot.range(200..=700).eq(expected.range(200..=700)));
+        }
+    }
+
+    #[test]
+    fn thread_test() {
+        let mut tree = CowBPlusTree::new();
+        for k in 0..100 {
+            tree.insert(k, k.to_string());
+        }
+        let snapshot = tree.snapshot();
+        let reader = std::thread::spawn(move || snapshot.iter().map(|(k, _)| *k).sum::<i32>());
+        for k in 100..200 {
+            tree.insert(k, k.to_string());
+        }
+        assert_eq!(reader.join().unwrap(), 4950);
+        assert_eq!(tree.size(), 200);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        // Validate the size of nodes and the depth of leaves, and return the depth.
+        fn depth(u: &Node<u8, u16>, b: usize, root: bool) -> usize {
+            assert!(u.len() < 2 * b);
+            assert!(root || u.len() >= b - 1);
+            match u {
+                Node::Leaf { keys, values } => {
+                    assert_eq!(keys.len(), values.len());
+                    1
+                }
+                Node::Internal { keys, children } => {
+                    assert_eq!(children.len(), keys.len() + 1);
+                    let ds = children
+                        .iter()
+                        .map(|c| depth(c, b, false))
+                        .collect::<Vec<_>>();
+                    assert!(ds.iter().all(|&d| d == ds[0]));
+                    ds[0] + 1
+                }
+            }
+        }
+
+        test_support::replay::check_arbitrary(|tree: CowBPlusTree<u8, u16>, u| {
+            // replay the same operations on BTreeMap
+            let (b, ops) = super::arb::ops::<u8, u16>(u).unwrap();
+            let mut expected = BTreeMap::new();
+            let mut keys = Vec::new();
+            for (op, i, k, v) in ops {
+                if op % 4 == 0 && !keys.is_empty() {
+                    expected.remove(&keys[i % keys.len()]);
+                } else {
+                    keys.push(k);
+                    expected.insert(k, v);
+                }
+            }
+            assert_eq!(tree.b, b);
+            depth(&tree.root, b, true);
+            assert_eq!(tree.size(), expected.len());
+            assert!(tree.iter().eq(expected.iter()));
+        });
+    }
+}
@@ -0,0 +1,217 @@
+use crate::temp_file::TempFile;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+// The maximum number of runs merged at once, which bounds open files and read buffers.
+const FAN_IN: usize = 64;
+
+// Memory charged for each line in addition to its bytes.
+const LINE_OVERHEAD: usize = std::mem::size_of::<Vec<u8>>();
+
+// Sorted lines spilled to a temporary file.
+#[derive(Debug)]
+struct Run {
+    file: TempFile,
+}
+
+impl Run {
+    fn create(dir: &Path) -> io::Result<(Self, BufWriter<File>)> {
+        let (file, writer) = TempFile::create(dir, "external_sort")?;
+        Ok((Run { file }, writer))
+    }
+
+    fn write(dir: &Path, lines: &[Vec<u8>]) -> io::Result<Self> {
+        let (run, mut writer) = Run::create(dir)?;
+        write_lines(&mut writer, lines.iter())?;
+        writer.flush()?;
+        Ok(run)
+    }
+
+    fn open(&self) -> io::Result<Lines<BufReader<File>>> {
+        Ok(Lines(self.file.open()?))
+    }
+}
+
+// Lines of a reader without the trailing '\n'.
+struct Lines<R>(R);
+
+impl<R: BufRead> Lines<R> {
+    fn next_line(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut line = Vec::new();
+        if self.0.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        Ok(Some(line))
+    }
+}
+
+fn write_lines<'a, W: Write>(
+    writer: &mut W,
+    lines: impl Iterator<Item = &'a Vec<u8>>,
+) -> io::Result<()> {
+    for line in lines {
+        writer.write_all(line)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Sort lines of reader by bytes, and write them to writer with '\n' after each line.
+/// Lines are sorted in memory as long as they fit in memory_budget bytes, and spilled to temporary files as
+/// sorted runs. Runs are merged by a heap, at most 64 at once.
+/// O(N log N) time, O(N) disk and O(memory_budget) memory for N bytes of input
+pub fn external_sort<R, W>(reader: R, writer: W, memory_budget: usize) -> io::Result<()>
+where
+    R: BufRead,
+    W: Write,
+{
+    external_sort_in(&std::env::temp_dir(), reader, writer, memory_budget)
+}
+
+/// Sort lines of reader like external_sort, spilling runs to files in dir instead of the temporary directory.
+pub fn external_sort_in<R, W>(
+    dir: &Path,
+    reader: R,
+    mut writer: W,
+    memory_budget: usize,
+) -> io::Result<()>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut lines = Lines(reader);
+    let mut runs = Vec::new();
+    let mut buffer = Vec::new();
+    let mut used = 0;
+    while let Some(line) = lines.next_line()? {
+        used += line.len() + LINE_OVERHEAD;
+        buffer.push(line);
+        if used > memory_budget {
+            buffer.sort_unstable();
+            runs.push(Run::write(dir, &buffer)?);
+            buffer.clear();
+            used = 0;
+        }
+    }
+    buffer.sort_unstable();
+    if runs.is_empty() {
+        // everything fit in memory
+        write_lines(&mut writer, buffer.iter())?;
+        return writer.flush();
+    }
+    if !buffer.is_empty() {
+        runs.push(Run::write(dir, &buffer)?);
+    }
+    drop(buffer);
+
+    while runs.len() > FAN_IN {
+        let mut merged = Vec::new();
+        for group in runs.chunks(FAN_IN) {
+            let (run, mut w) = Run::create(dir)?;
+            merge(group, &mut w)?;
+            w.flush()?;
+            merged.push(run);
+        }
+        runs = merged;
+    }
+    merge(&runs, &mut writer)?;
+    writer.flush()
+}
+
+// Merge sorted runs into writer, taking the smallest head line from a heap.
+fn merge<W: Write>(runs: &[Run], writer: &mut W) -> io::Result<()> {
+    let mut readers = runs.iter().map(Run::open).collect::<io::Result<Vec<_>>>()?;
+    let mut heap = BinaryHeap::with_capacity(readers.len());
+    for (i, r) in readers.iter_mut().enumerate() {
+        if let Some(line) = r.next_line()? {
+            heap.push(Reverse((line, i)));
+        }
+    }
+    while let Some(Reverse((line, i))) = heap.pop() {
+        writer.write_all(&line)?;
+        writer.write_all(b"\n")?;
+        if let Some(next) = readers[i].next_line()? {
+            heap.push(Reverse((next, i)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{external_sort, external_sort_in};
+
+    fn input(n: usize) -> Vec<String> {
+        // pseudo random lines by linear congruential generator
+        let mut x: u64 = 13;
+        (0..n)
+            .map(|_| {
+                x = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                format!("{:x}", x >> (x % 48))
+            })
+            .collect()
+    }
+
+    fn sort(lines: &[String], memory_budget: usize) -> Vec<String> {
+        let text = lines.join("\n");
+        let mut out = Vec::new();
+        external_sort(text.as_bytes(), &mut out, memory_budget).unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn in_memory_test() {
+        let lines = input(1000);
+        let mut expected = lines.clone();
+        expected.sort_unstable();
+        assert_eq!(sort(&lines, 1 << 20), expected);
+
+        let mut out = Vec::new();
+        external_sort(&b""[..], &mut out, 0).unwrap();
+        assert!(out.is_empty());
+        external_sort(&b"b\n\na\n"[..], &mut out, 1 << 20).unwrap();
+        assert_eq!(out, b"\na\nb\n");
+    }
+
+    #[test]
+    // Miri can't access files in isolation
+    #[cfg_attr(miri, ignore)]
+    fn spill_test() {
+        let lines = input(5000);
+        let mut expected = lines.clone();
+        expected.sort_unstable();
+        // about 60 lines fit in a run, so that runs are merged in 2 passes
+        assert_eq!(sort(&lines, 2048), expected);
+        // a line per run
+        let mut expected = lines[..300].to_vec();
+        expected.sort_unstable();
+        assert_eq!(sort(&lines[..300], 0), expected);
+    }
+
+    #[test]
+    // Miri can't access files in isolation
+    #[cfg_attr(miri, ignore)]
+    fn cleanup_test() {
+        let dir = std::env::temp_dir().join(format!("external_sort_test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let text = input(500).join("\n");
+        let mut out = Vec::new();
+        external_sort_in(&dir, text.as_bytes(), &mut out, 256).unwrap();
+        assert_eq!(out.iter().filter(|&&b| b == b'\n').count(), 500);
+        // runs are removed once merged
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
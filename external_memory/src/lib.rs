@@ -0,0 +1,17 @@
+#![warn(missing_docs)]
+//! external_memory implements algorithms and structures for data larger than main memory.
+
+/// Implementation for B+ tree whose leaves are chained for range scans.
+pub mod b_plus_tree;
+/// Implementation for B+ tree with copy-on-write nodes and snapshots.
+pub mod cow_b_plus_tree;
+/// Implementation for external merge sort over lines.
+pub mod external_sort;
+/// Implementation for FIFO queue which spills its middle part to disk.
+pub mod spill_queue;
+mod temp_file;
+
+pub use b_plus_tree::BPlusTree;
+pub use cow_b_plus_tree::{CowBPlusTree, Snapshot};
+pub use external_sort::{external_sort, external_sort_in};
+pub use spill_queue::SpillQueue;
@@ -0,0 +1,205 @@
+use crate::temp_file::TempFile;
+use array_list::array_queue::ArrayQueue;
+use interface::heap_size::HeapSize;
+use interface::list::List;
+use interface::queue::Queue;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// Values spilled to a temporary file in order.
+#[derive(Debug)]
+struct Segment {
+    file: TempFile,
+    n: usize,
+}
+
+/// FIFO queue which holds at most memory_budget values in memory, and spills the rest to disk.
+/// Values near the head and the tail stay in two ArrayQueues, and full tail segments in between are written to
+/// temporary files, which are read back when the head runs out.
+/// Disk errors panic, since Queue has no way to report them.
+/// O(1) amortized: add(x), remove() with O(1) disk access per memory_budget / 2 values
+#[derive(Debug)]
+pub struct SpillQueue<T>
+where
+    T: Clone + Debug + Serialize + DeserializeOwned,
+{
+    head: ArrayQueue<T>,
+    segments: VecDeque<Segment>,
+    tail: ArrayQueue<T>,
+    // the number of values in each of head, tail and segments at most
+    segment_len: usize,
+    dir: PathBuf,
+    n: usize,
+}
+
+impl<T> SpillQueue<T>
+where
+    T: Clone + Debug + Serialize + DeserializeOwned,
+{
+    /// Generate empty SpillQueue which spills to the temporary directory.
+    pub fn new(memory_budget: usize) -> Self {
+        Self::new_in(&std::env::temp_dir(), memory_budget)
+    }
+
+    /// Generate empty SpillQueue which spills to files in dir.
+    pub fn new_in(dir: &Path, memory_budget: usize) -> Self {
+        if memory_budget < 2 {
+            panic!(
+                "memory budget must hold at least 2 values. memory_budget: {}",
+                memory_budget
+            )
+        }
+        SpillQueue {
+            head: ArrayQueue::new(),
+            segments: VecDeque::new(),
+            tail: ArrayQueue::new(),
+            segment_len: memory_budget / 2,
+            dir: dir.to_path_buf(),
+            n: 0,
+        }
+    }
+
+    /// Return the number of values.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Return the number of values spilled to disk.
+    pub fn spilled(&self) -> usize {
+        self.segments.iter().map(|s| s.n).sum()
+    }
+
+    // Write all values in the tail to a new segment.
+    fn spill(&mut self) -> io::Result<()> {
+        let (file, mut writer) = TempFile::create(&self.dir, "spill_queue")?;
+        let mut n = 0;
+        while let Some(x) = Queue::remove(&mut self.tail) {
+            bincode::serialize_into(&mut writer, &x).map_err(io::Error::other)?;
+            n += 1;
+        }
+        writer.flush()?;
+        self.segments.push_back(Segment { file, n });
+        Ok(())
+    }
+
+    // Fill the empty head with the oldest segment, or the tail if nothing is spilled.
+    fn refill(&mut self) -> io::Result<()> {
+        match self.segments.pop_front() {
+            Some(segment) => {
+                let mut reader = segment.file.open()?;
+                for _ in 0..segment.n {
+                    let x = bincode::deserialize_from(&mut reader).map_err(io::Error::other)?;
+                    Queue::add(&mut self.head, x);
+                }
+            }
+            None => std::mem::swap(&mut self.head, &mut self.tail),
+        }
+        Ok(())
+    }
+}
+
+impl<T> Queue<T> for SpillQueue<T>
+where
+    T: Clone + Debug + Serialize + DeserializeOwned,
+{
+    fn add(&mut self, x: T) -> bool {
+        if self.segments.is_empty() && self.tail.size() == 0 && self.head.size() < self.segment_len
+        {
+            Queue::add(&mut self.head, x);
+        } else {
+            Queue::add(&mut self.tail, x);
+            if self.tail.size() >= self.segment_len {
+                if let Err(e) = self.spill() {
+                    panic!("failed to spill values to {:?}. error: {}", self.dir, e)
+                }
+            }
+        }
+        self.n += 1;
+        true
+    }
+
+    fn remove(&mut self) -> Option<T> {
+        if self.head.size() == 0 {
+            if let Err(e) = self.refill() {
+                panic!(
+                    "failed to read spilled values from {:?}. error: {}",
+                    self.dir, e
+                )
+            }
+        }
+        let x = Queue::remove(&mut self.head)?;
+        self.n -= 1;
+        Some(x)
+    }
+}
+
+impl<T> HeapSize for SpillQueue<T>
+where
+    T: Clone + Debug + Serialize + DeserializeOwned,
+{
+    fn heap_bytes(&self) -> usize {
+        self.head.heap_bytes()
+            + self.tail.heap_bytes()
+            + self.segments.capacity() * std::mem::size_of::<Segment>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpillQueue;
+    use interface::queue::Queue;
+
+    #[test]
+    // Miri can't access files in isolation
+    #[cfg_attr(miri, ignore)]
+    fn queue_test() {
+        let mut queue: SpillQueue<String> = SpillQueue::new(4);
+        assert_eq!(queue.remove(), None);
+        for x in 0..3 {
+            assert!(queue.add(x.to_string()));
+        }
+        // 2 values fit in the head, and the tail isn't full yet
+        assert_eq!(queue.spilled(), 0);
+        assert!(queue.add(3.to_string()));
+        assert_eq!(queue.spilled(), 2);
+        assert_eq!(queue.size(), 4);
+        for x in 0..4 {
+            assert_eq!(queue.remove(), Some(x.to_string()));
+        }
+        assert_eq!(queue.remove(), None);
+        assert_eq!(queue.size(), 0);
+    }
+
+    #[test]
+    // Miri can't access files in isolation
+    #[cfg_attr(miri, ignore)]
+    fn interleaved_test() {
+        let dir = std::env::temp_dir().join(format!("spill_queue_test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        {
+            let mut queue: SpillQueue<(u64, Vec<u8>)> = SpillQueue::new_in(&dir, 16);
+            let mut expected = std::collections::VecDeque::new();
+            for x in 0..2000u64 {
+                let value = (x, vec![x as u8; (x % 7) as usize]);
+                queue.add(value.clone());
+                expected.push_back(value);
+                if x % 3 == 0 {
+                    assert_eq!(queue.remove(), expected.pop_front());
+                }
+                assert_eq!(queue.size(), expected.len());
+            }
+            assert!(queue.spilled() > 1000);
+            assert!(std::fs::read_dir(&dir).unwrap().count() > 0);
+            for _ in 0..500 {
+                assert_eq!(queue.remove(), expected.pop_front());
+            }
+        }
+        // spilled segments are removed with the queue
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
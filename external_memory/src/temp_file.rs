@@ -0,0 +1,31 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static FILE_ID: AtomicUsize = AtomicUsize::new(0);
+
+// File which is removed on drop, named uniquely within the process.
+#[derive(Debug)]
+pub(crate) struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    pub(crate) fn create(dir: &Path, prefix: &str) -> io::Result<(Self, BufWriter<File>)> {
+        let id = FILE_ID.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!("{}-{}-{}.tmp", prefix, std::process::id(), id));
+        let file = File::create(&path)?;
+        Ok((TempFile { path }, BufWriter::new(file)))
+    }
+
+    pub(crate) fn open(&self) -> io::Result<BufReader<File>> {
+        Ok(BufReader::new(File::open(&self.path)?))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
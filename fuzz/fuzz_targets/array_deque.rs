@@ -0,0 +1,85 @@
+#![no_main]
+//! Apply arbitrary List and Deque operations to ArrayDeque and compare it with VecDeque after every operation.
+
+use arbitrary::Arbitrary;
+use array_list::array_deque::ArrayDeque;
+use interface::list::List;
+use libfuzzer_sys::fuzz_target;
+use std::collections::VecDeque;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Add(usize, u8),
+    Remove(usize),
+    Set(usize, u8),
+    PushFront(u8),
+    PopFront,
+    PushBack(u8),
+    PopBack,
+    Swap(usize, usize),
+    RotateLeft(usize),
+    RotateRight(usize),
+    AddAll(usize, Vec<u8>),
+    Truncate(usize),
+    ResizeWith(u8, u8),
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    let mut deque = ArrayDeque::new();
+    let mut model: VecDeque<u8> = VecDeque::new();
+    for op in ops {
+        // positions are reduced into range, since out of range access panics by contract
+        let n = model.len();
+        match op {
+            Op::Add(i, x) => {
+                deque.add(i % (n + 1), x);
+                model.insert(i % (n + 1), x);
+            }
+            Op::Remove(i) if n > 0 => assert_eq!(deque.remove(i % n), model.remove(i % n)),
+            Op::Set(i, x) if n > 0 => {
+                assert_eq!(deque.set(i % n, x), Some(std::mem::replace(&mut model[i % n], x)));
+            }
+            Op::PushFront(x) => {
+                deque.add(0, x);
+                model.push_front(x);
+            }
+            Op::PopFront if n > 0 => assert_eq!(deque.remove(0), model.pop_front()),
+            Op::PushBack(x) => {
+                deque.push_back(x);
+                model.push_back(x);
+            }
+            Op::PopBack if n > 0 => assert_eq!(deque.pop_back(), model.pop_back()),
+            Op::Swap(i, j) if n > 0 => {
+                deque.swap(i % n, j % n);
+                model.swap(i % n, j % n);
+            }
+            Op::RotateLeft(k) if n > 0 => {
+                deque.rotate_left(k % n);
+                model.rotate_left(k % n);
+            }
+            Op::RotateRight(k) if n > 0 => {
+                deque.rotate_right(k % n);
+                model.rotate_right(k % n);
+            }
+            Op::AddAll(i, values) => {
+                let i = i % (n + 1);
+                deque.add_all(i, values.iter().copied());
+                for (k, x) in values.into_iter().enumerate() {
+                    model.insert(i + k, x);
+                }
+            }
+            Op::Truncate(len) => {
+                deque.truncate(len % (n + 1));
+                model.truncate(len % (n + 1));
+            }
+            Op::ResizeWith(len, x) => {
+                deque.resize_with(len as usize, || x);
+                model.resize(len as usize, x);
+            }
+            _ => {}
+        }
+        assert_eq!(deque.size(), model.len());
+        assert!(deque.iter().eq(model.iter()));
+        assert!(deque.iter().rev().eq(model.iter().rev()));
+    }
+});
@@ -0,0 +1,49 @@
+#![no_main]
+//! Apply arbitrary Queue operations to ArrayQueue and compare it with VecDeque after every operation.
+
+use arbitrary::Arbitrary;
+use array_list::array_queue::ArrayQueue;
+use interface::list::List;
+use interface::queue::Queue;
+use libfuzzer_sys::fuzz_target;
+use std::collections::VecDeque;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Add(u8),
+    Remove,
+    AddAll(Vec<u8>),
+    RemoveN(u8),
+    Drain,
+    // drop the drain after taking some values, which must remove the rest as well
+    DrainTake(u8),
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    let mut queue = ArrayQueue::new();
+    let mut model: VecDeque<u8> = VecDeque::new();
+    for op in ops {
+        match op {
+            Op::Add(x) => {
+                assert!(Queue::add(&mut queue, x));
+                model.push_back(x);
+            }
+            Op::Remove => assert_eq!(Queue::remove(&mut queue), model.pop_front()),
+            Op::AddAll(values) => {
+                assert_eq!(Queue::add_all(&mut queue, values.iter().copied()), values.len());
+                model.extend(values);
+            }
+            Op::RemoveN(k) => {
+                let k = (k as usize).min(model.len());
+                assert!(queue.remove_n(k).into_iter().eq(model.drain(..k)));
+            }
+            Op::Drain => assert!(queue.drain().eq(model.drain(..))),
+            Op::DrainTake(k) => {
+                let taken = queue.drain().take(k as usize).collect::<Vec<_>>();
+                assert!(taken.into_iter().eq(model.drain(..).take(k as usize)));
+            }
+        }
+        assert_eq!(queue.size(), model.len());
+        assert!((0..model.len()).all(|i| queue.get(i) == model.get(i)));
+    }
+});
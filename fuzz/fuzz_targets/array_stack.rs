@@ -0,0 +1,72 @@
+#![no_main]
+//! Apply arbitrary List operations to ArrayStack and compare it with Vec after every operation.
+
+use arbitrary::Arbitrary;
+use array_list::array_stack::ArrayStack;
+use interface::list::List;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Add(usize, u8),
+    Remove(usize),
+    Set(usize, u8),
+    PushBack(u8),
+    PopBack,
+    Swap(usize, usize),
+    RotateLeft(usize),
+    AddAll(usize, Vec<u8>),
+    Truncate(usize),
+    ResizeWith(u8, u8),
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    let mut stack = ArrayStack::new();
+    let mut model: Vec<u8> = Vec::new();
+    for op in ops {
+        // positions are reduced into range, since out of range access panics by contract
+        let n = model.len();
+        match op {
+            Op::Add(i, x) => {
+                stack.add(i % (n + 1), x);
+                model.insert(i % (n + 1), x);
+            }
+            Op::Remove(i) if n > 0 => {
+                assert_eq!(stack.remove(i % n), Some(model.remove(i % n)));
+            }
+            Op::Set(i, x) if n > 0 => {
+                assert_eq!(stack.set(i % n, x), Some(std::mem::replace(&mut model[i % n], x)));
+            }
+            Op::PushBack(x) => {
+                stack.push_back(x);
+                model.push(x);
+            }
+            Op::PopBack if n > 0 => assert_eq!(stack.pop_back(), model.pop()),
+            Op::Swap(i, j) if n > 0 => {
+                stack.swap(i % n, j % n);
+                model.swap(i % n, j % n);
+            }
+            Op::RotateLeft(k) if n > 0 => {
+                stack.rotate_left(k % n);
+                model.rotate_left(k % n);
+            }
+            Op::AddAll(i, values) => {
+                stack.add_all(i % (n + 1), values.iter().copied());
+                let i = i % (n + 1);
+                model.splice(i..i, values);
+            }
+            Op::Truncate(len) => {
+                stack.truncate(len % (n + 1));
+                model.truncate(len % (n + 1));
+            }
+            Op::ResizeWith(len, x) => {
+                stack.resize_with(len as usize, || x);
+                model.resize(len as usize, x);
+            }
+            _ => {}
+        }
+        assert_eq!(stack.size(), model.len());
+        assert!(stack.iter().eq(model.iter()));
+        assert!(stack.iter().rev().eq(model.iter().rev()));
+    }
+});
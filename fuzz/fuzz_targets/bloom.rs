@@ -0,0 +1,27 @@
+#![no_main]
+//! Insert arbitrary values to BloomFilter and check that it never reports false negatives,
+//! including after union and intersection.
+
+use hash_table::bloom::BloomFilter;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (u8, u8, Vec<u32>, Vec<u32>)| {
+    let (m, k, xs, ys) = input;
+    let (m, k) = (m as usize + 1, k as usize % 8 + 1);
+    let mut a = BloomFilter::with_params(m, k);
+    let mut b = BloomFilter::with_params(m, k);
+    for x in &xs {
+        a.insert(x);
+        assert!(a.maybe_contains(x));
+    }
+    for y in &ys {
+        b.insert(y);
+    }
+    let union = a.union(&b).expect("filters with the same params must be combined");
+    assert!(xs.iter().chain(ys.iter()).all(|x| union.maybe_contains(x)));
+    let intersection = a.intersection(&b).expect("filters with the same params must be combined");
+    assert!(xs
+        .iter()
+        .filter(|x| ys.contains(x))
+        .all(|x| intersection.maybe_contains(x)));
+});
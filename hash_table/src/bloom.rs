@@ -0,0 +1,174 @@
+use interface::heap_size::HeapSize;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Bloom filter answers approximate membership query with bit array of m bits and k hash functions.
+/// It never returns false negative, and returns false positive with configured rate.
+/// O(k): insert(x), maybe_contains(x)
+#[derive(Debug, Clone)]
+pub struct BloomFilter<T: Hash> {
+    bits: Box<[u64]>,
+    m: usize,
+    k: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> BloomFilter<T>
+where
+    T: Hash,
+{
+    /// Generate BloomFilter sized for n elements with false positive rate fp_rate.
+    /// m = -n ln(fp_rate) / (ln 2)^2, k = (m / n) ln 2
+    pub fn new(n: usize, fp_rate: f64) -> Self {
+        if !(fp_rate > 0.0 && fp_rate < 1.0) {
+            panic!(
+                "false positive rate must be within (0, 1). fp_rate: {}",
+                fp_rate
+            )
+        }
+        let n = std::cmp::max(n, 1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let m = (-n * fp_rate.ln() / (ln2 * ln2)).ceil() as usize;
+        let k = ((m as f64 / n) * ln2).round() as usize;
+        BloomFilter::with_params(m, k)
+    }
+
+    /// Generate BloomFilter with m bits and k hash functions.
+    pub fn with_params(m: usize, k: usize) -> Self {
+        let m = std::cmp::max(m, 1);
+        BloomFilter {
+            bits: vec![0; m.div_ceil(64)].into_boxed_slice(),
+            m,
+            k: std::cmp::max(k, 1),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the number of bits.
+    pub fn bits(&self) -> usize {
+        self.m
+    }
+
+    /// Return the number of hash functions.
+    pub fn hashes(&self) -> usize {
+        self.k
+    }
+
+    /// Add a value to a filter.
+    pub fn insert(&mut self, x: &T) {
+        for pos in self.positions(x) {
+            self.bits[pos / 64] |= 1 << (pos % 64);
+        }
+    }
+
+    /// Return false if a value was never inserted, otherwise return true.
+    /// True may be returned for a value which was never inserted (false positive).
+    pub fn maybe_contains(&self, x: &T) -> bool {
+        self.positions(x)
+            .all(|pos| self.bits[pos / 64] & (1 << (pos % 64)) != 0)
+    }
+
+    /// Return a filter which contains all values inserted to either filter.
+    /// Return null(Empty) if both filters don't have identical parameters.
+    pub fn union(&self, other: &Self) -> Option<Self> {
+        self.combine(other, |a, b| a | b)
+    }
+
+    /// Return a filter which approximates values inserted to both filters.
+    /// Return null(Empty) if both filters don't have identical parameters.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        self.combine(other, |a, b| a & b)
+    }
+
+    fn combine(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Option<Self> {
+        if self.m != other.m || self.k != other.k {
+            return None;
+        }
+        let bits = self
+            .bits
+            .iter()
+            .zip(other.bits.iter())
+            .map(|(&a, &b)| f(a, b))
+            .collect::<Vec<_>>();
+        Some(BloomFilter {
+            bits: bits.into_boxed_slice(),
+            m: self.m,
+            k: self.k,
+            _marker: PhantomData,
+        })
+    }
+
+    // Derive k positions from two hash values: g_i(x) = h1(x) + i * h2(x) mod m
+    fn positions(&self, x: &T) -> impl Iterator<Item = usize> {
+        let mut hasher = DefaultHasher::new();
+        x.hash(&mut hasher);
+        let h1 = hasher.finish();
+        hasher.write_u64(h1);
+        // h2 must be odd not to degenerate into a single position
+        let h2 = hasher.finish() | 1;
+        let m = self.m as u64;
+        (0..self.k as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % m) as usize)
+    }
+}
+
+impl<T> HeapSize for BloomFilter<T>
+where
+    T: Hash,
+{
+    fn heap_bytes(&self) -> usize {
+        self.bits.len() * std::mem::size_of::<u64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BloomFilter;
+
+    #[test]
+    fn params_test() {
+        let filter: BloomFilter<i32> = BloomFilter::new(1000, 0.01);
+        // m = 9586, k = 7 for n = 1000, fp_rate = 0.01
+        assert_eq!(filter.bits(), 9586);
+        assert_eq!(filter.hashes(), 7);
+    }
+
+    #[test]
+    fn membership_test() {
+        let mut filter: BloomFilter<i32> = BloomFilter::new(1000, 0.01);
+        for x in 0..1000 {
+            filter.insert(&x);
+        }
+        for x in 0..1000 {
+            assert!(filter.maybe_contains(&x));
+        }
+        let false_positives = (1000..11000).filter(|x| filter.maybe_contains(x)).count();
+        assert!(
+            false_positives < 300,
+            "false positives: {}",
+            false_positives
+        );
+    }
+
+    #[test]
+    fn combine_test() {
+        let mut a: BloomFilter<&str> = BloomFilter::with_params(256, 3);
+        let mut b: BloomFilter<&str> = BloomFilter::with_params(256, 3);
+        a.insert(&"apple");
+        a.insert(&"banana");
+        b.insert(&"banana");
+        b.insert(&"cherry");
+
+        let union = a.union(&b).unwrap();
+        assert!(union.maybe_contains(&"apple"));
+        assert!(union.maybe_contains(&"banana"));
+        assert!(union.maybe_contains(&"cherry"));
+
+        let intersection = a.intersection(&b).unwrap();
+        assert!(intersection.maybe_contains(&"banana"));
+
+        let c: BloomFilter<&str> = BloomFilter::with_params(128, 3);
+        assert!(a.union(&c).is_none());
+        assert!(a.intersection(&c).is_none());
+    }
+}
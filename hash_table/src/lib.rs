@@ -0,0 +1,5 @@
+#![warn(missing_docs)]
+//! hash_table implements hash-based structures for USet and approximate membership.
+
+/// Bloom filter providing approximate membership query.
+pub mod bloom;
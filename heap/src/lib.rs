@@ -0,0 +1,7 @@
+#![warn(missing_docs)]
+//! heap implements priority queues backed by implicit binary trees.
+
+/// Implementation for bounded heap which keeps the top k values.
+pub mod top_k;
+
+pub use top_k::TopK;
@@ -0,0 +1,269 @@
+use interface::heap_size::HeapSize;
+use std::cmp::Ordering;
+
+/// Bounded binary heap which keeps only the k largest (or smallest) values pushed so far.
+/// The root holds the worst of the kept values, so that a new value only has to beat it.
+/// O(1): peek()
+/// O(log k): push(x)
+/// O(k log k): into_sorted_vec()
+#[derive(Debug, Clone)]
+pub struct TopK<T: Ord> {
+    a: Vec<T>,
+    k: usize,
+    // Greater is kept if true, otherwise Less is kept.
+    largest: bool,
+}
+
+impl<T> TopK<T>
+where
+    T: Ord,
+{
+    /// Generate empty TopK which keeps the k largest values.
+    pub fn largest(k: usize) -> Self {
+        TopK {
+            a: Vec::with_capacity(k),
+            k,
+            largest: true,
+        }
+    }
+
+    /// Generate empty TopK which keeps the k smallest values.
+    pub fn smallest(k: usize) -> Self {
+        TopK {
+            a: Vec::with_capacity(k),
+            k,
+            largest: false,
+        }
+    }
+
+    /// Return the number of values kept.
+    pub fn size(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Return the maximum number of values kept.
+    pub fn capacity(&self) -> usize {
+        self.k
+    }
+
+    /// Return the worst of the kept values, which a new value has to beat once k values are kept.
+    /// Return null(Empty) if no value is kept.
+    pub fn peek(&self) -> Option<&T> {
+        self.a.first()
+    }
+
+    /// Push a value, and return the value which is dropped from the top k, or the given value if it doesn't
+    /// enter the top k.
+    /// Return null(Empty) if fewer than k values have been kept.
+    pub fn push(&mut self, x: T) -> Option<T> {
+        if self.a.len() < self.k {
+            self.a.push(x);
+            self.bubble_up(self.a.len() - 1);
+            return None;
+        }
+        match self.a.first() {
+            Some(root) if self.better(&x, root) => {
+                let dropped = std::mem::replace(&mut self.a[0], x);
+                self.trickle_down(0);
+                Some(dropped)
+            }
+            _ => Some(x),
+        }
+    }
+
+    /// Return an iterator over the kept values in arbitrary order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.a.iter()
+    }
+
+    /// Consume TopK and return the kept values from the best to the worst.
+    /// The largest value comes first for largest(k), and the smallest one comes first for smallest(k).
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        // Moving the root to the tail repeatedly leaves the worst value last and the best one first.
+        let mut n = self.a.len();
+        while n > 1 {
+            n -= 1;
+            self.a.swap(0, n);
+            self.sift(0, n);
+        }
+        self.a
+    }
+
+    // Return true if x should be kept rather than y.
+    fn better(&self, x: &T, y: &T) -> bool {
+        let ord = x.cmp(y);
+        if self.largest {
+            ord == Ordering::Greater
+        } else {
+            ord == Ordering::Less
+        }
+    }
+
+    fn parent(i: usize) -> usize {
+        (i - 1) / 2
+    }
+
+    fn bubble_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let p = Self::parent(i);
+            if !self.better(&self.a[p], &self.a[i]) {
+                break;
+            }
+            self.a.swap(i, p);
+            i = p;
+        }
+    }
+
+    fn trickle_down(&mut self, i: usize) {
+        self.sift(i, self.a.len());
+    }
+
+    // Move the value at i down within a[..n] until no child is worse than it.
+    fn sift(&mut self, mut i: usize, n: usize) {
+        loop {
+            let (l, r) = (2 * i + 1, 2 * i + 2);
+            let mut worst = i;
+            if l < n && self.better(&self.a[worst], &self.a[l]) {
+                worst = l;
+            }
+            if r < n && self.better(&self.a[worst], &self.a[r]) {
+                worst = r;
+            }
+            if worst == i {
+                break;
+            }
+            self.a.swap(i, worst);
+            i = worst;
+        }
+    }
+}
+
+impl<T> Extend<T> for TopK<T>
+where
+    T: Ord,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+impl<T> HeapSize for TopK<T>
+where
+    T: Ord,
+{
+    fn heap_bytes(&self) -> usize {
+        self.a.capacity() * std::mem::size_of::<T>()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::TopK;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // Decode k, whether to keep the largest, and the values to push.
+    pub(super) fn values<'a, T>(u: &mut Unstructured<'a>) -> Result<(usize, bool, Vec<T>)>
+    where
+        T: Arbitrary<'a>,
+    {
+        let k = u.int_in_range(0..=64)?;
+        let largest = bool::arbitrary(u)?;
+        Ok((k, largest, u.arbitrary_iter()?.collect::<Result<_>>()?))
+    }
+
+    // Either direction is chosen with a small k, and values are pushed beyond it.
+    impl<'a, T> Arbitrary<'a> for TopK<T>
+    where
+        T: Ord + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let (k, largest, values) = values::<T>(u)?;
+            let mut top = if largest {
+                TopK::largest(k)
+            } else {
+                TopK::smallest(k)
+            };
+            for x in values {
+                top.push(x);
+            }
+            Ok(top)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TopK;
+
+    #[test]
+    fn largest_test() {
+        let mut top: TopK<i32> = TopK::largest(3);
+        assert_eq!(top.capacity(), 3);
+        assert_eq!(top.peek(), None);
+        assert_eq!(top.push(5), None);
+        assert_eq!(top.push(1), None);
+        assert_eq!(top.push(8), None);
+        assert_eq!(top.size(), 3);
+        assert_eq!(top.peek(), Some(&1));
+        assert_eq!(top.push(0), Some(0));
+        assert_eq!(top.push(7), Some(1));
+        assert_eq!(top.peek(), Some(&5));
+        assert_eq!(top.push(5), Some(5));
+        assert_eq!(top.size(), 3);
+        assert_eq!(top.into_sorted_vec(), vec![8, 7, 5]);
+    }
+
+    #[test]
+    fn smallest_test() {
+        let mut top: TopK<char> = TopK::smallest(2);
+        top.extend("ordinal".chars());
+        assert_eq!(top.peek(), Some(&'d'));
+        assert_eq!(top.into_sorted_vec(), vec!['a', 'd']);
+
+        let mut empty: TopK<i32> = TopK::smallest(0);
+        assert_eq!(empty.push(1), Some(1));
+        assert_eq!(empty.into_sorted_vec(), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn random_test() {
+        // pseudo random sequence by linear congruential generator
+        let mut x: u64 = 42;
+        let mut values = Vec::new();
+        let mut largest = TopK::largest(50);
+        let mut smallest = TopK::smallest(50);
+        for _ in 0..1000 {
+            x = x
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let v = x >> 40;
+            values.push(v);
+            largest.push(v);
+            smallest.push(v);
+        }
+        values.sort_unstable();
+        let expected = values.iter().rev().take(50).copied().collect::<Vec<_>>();
+        assert_eq!(largest.into_sorted_vec(), expected);
+        assert_eq!(smallest.into_sorted_vec(), values[..50].to_vec());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        test_support::replay::check_arbitrary(|top: TopK<u16>, u| {
+            // sort all values pushed, and take the first k
+            let (k, largest, mut expected) = super::arb::values::<u16>(u).unwrap();
+            expected.sort_unstable();
+            if largest {
+                expected.reverse();
+            }
+            expected.truncate(k);
+            assert_eq!((top.capacity(), top.largest), (k, largest));
+            // the root holds the worst value, so no parent is better than its children
+            assert!((1..top.size()).all(|i| !top.better(&top.a[(i - 1) / 2], &top.a[i])));
+            assert_eq!(top.into_sorted_vec(), expected);
+        });
+    }
+}
@@ -0,0 +1,7 @@
+#![warn(missing_docs)]
+//! integer_structures implements SSet for integer keys which exploits their bit representation.
+
+/// Implementation for van Emde Boas tree.
+pub mod veb;
+
+pub use veb::VebTree;
@@ -0,0 +1,622 @@
+use interface::set::{SSet, SSetExt};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+
+/// SSet implementation for integers within [0, 2^w), realized by van Emde Boas tree.
+/// Each node splits keys into high and low halves of bits: a cluster per high half holds the low halves,
+/// and a summary holds which clusters are non-empty. Clusters are created on demand, so it uses O(n) space.
+/// O(log w) = O(log log U): add(x), remove(x), find(x)
+#[derive(Debug)]
+pub struct VebTree {
+    root: Node,
+    w: u32,
+    n: usize,
+}
+
+#[derive(Debug)]
+struct Node {
+    // the number of bits of keys under this node
+    bits: u32,
+    // the smallest key under this node, which all keys are relative to
+    base: usize,
+    // min is not stored in any cluster
+    min: Option<usize>,
+    max: Option<usize>,
+    summary: Option<Box<Node>>,
+    clusters: HashMap<usize, Node>,
+}
+
+impl Node {
+    fn new(bits: u32, base: usize) -> Self {
+        Node {
+            bits,
+            base,
+            min: None,
+            max: None,
+            summary: None,
+            clusters: HashMap::new(),
+        }
+    }
+
+    fn low_bits(&self) -> u32 {
+        self.bits / 2
+    }
+
+    fn high(&self, x: usize) -> usize {
+        (x - self.base) >> self.low_bits()
+    }
+
+    // Return a reference to the key equal to x.
+    fn member(&self, x: usize) -> Option<&usize> {
+        if self.min == Some(x) {
+            return self.min.as_ref();
+        }
+        if self.max == Some(x) {
+            return self.max.as_ref();
+        }
+        if self.bits == 1 {
+            return None;
+        }
+        self.clusters.get(&self.high(x))?.member(x)
+    }
+
+    // Return a reference to the smallest key greater than x.
+    fn successor(&self, x: usize) -> Option<&usize> {
+        if self.bits == 1 {
+            return match self.max {
+                Some(y) if x < y => self.max.as_ref(),
+                _ => None,
+            };
+        }
+        match self.min {
+            None => return None,
+            Some(y) if x < y => return self.min.as_ref(),
+            _ => {}
+        }
+        let h = self.high(x);
+        if let Some(cluster) = self.clusters.get(&h) {
+            if cluster.max.is_some_and(|y| x < y) {
+                return cluster.successor(x);
+            }
+        }
+        let c = *self.summary.as_ref()?.successor(h)?;
+        self.clusters[&c].min.as_ref()
+    }
+
+    // Return a reference to the largest key less than x.
+    fn predecessor(&self, x: usize) -> Option<&usize> {
+        if self.bits == 1 {
+            return match self.min {
+                Some(y) if y < x => self.min.as_ref(),
+                _ => None,
+            };
+        }
+        match self.max {
+            None => return None,
+            Some(y) if y < x => return self.max.as_ref(),
+            _ => {}
+        }
+        let h = self.high(x);
+        if let Some(cluster) = self.clusters.get(&h) {
+            if cluster.min.is_some_and(|y| y < x) {
+                return cluster.predecessor(x);
+            }
+        }
+        // min is not stored in any cluster, so it is the last candidate
+        match self.summary.as_ref().and_then(|s| s.predecessor(h)) {
+            Some(c) => self.clusters[c].max.as_ref(),
+            None => self.min.as_ref().filter(|&&y| y < x),
+        }
+    }
+
+    // Insert x which doesn't exist.
+    fn insert(&mut self, x: usize) {
+        let mut x = x;
+        match self.min {
+            None => {
+                self.min = Some(x);
+                self.max = Some(x);
+                return;
+            }
+            Some(y) if x < y => {
+                // x becomes the new min, and the old one goes down to a cluster
+                self.min = Some(x);
+                x = y;
+            }
+            _ => {}
+        }
+        if self.bits > 1 {
+            let h = self.high(x);
+            let (low, high) = (self.low_bits(), self.bits - self.low_bits());
+            let base = self.base + (h << low);
+            let cluster = self
+                .clusters
+                .entry(h)
+                .or_insert_with(|| Node::new(low, base));
+            if cluster.min.is_none() {
+                self.summary
+                    .get_or_insert_with(|| Box::new(Node::new(high, 0)))
+                    .insert(h);
+            }
+            cluster.insert(x);
+        }
+        if self.max.is_none_or(|y| x > y) {
+            self.max = Some(x);
+        }
+    }
+
+    // Delete x which exists.
+    fn delete(&mut self, x: usize) {
+        if self.min == self.max {
+            self.min = None;
+            self.max = None;
+            return;
+        }
+        if self.bits == 1 {
+            // both keys exist, and the other one remains
+            let y = if x == self.base {
+                self.base + 1
+            } else {
+                self.base
+            };
+            self.min = Some(y);
+            self.max = Some(y);
+            return;
+        }
+        let mut x = x;
+        if self.min == Some(x) {
+            // the smallest key in clusters is pulled up as the new min
+            let c = self
+                .summary
+                .as_ref()
+                .and_then(|s| s.min)
+                .expect("clusters must not be empty");
+            x = self.clusters[&c].min.expect("cluster must not be empty");
+            self.min = Some(x);
+        }
+        let h = self.high(x);
+        let cluster = self.clusters.get_mut(&h).expect("cluster must exist");
+        cluster.delete(x);
+        if cluster.min.is_none() {
+            self.clusters.remove(&h);
+            let summary = self.summary.as_mut().expect("summary must exist");
+            summary.delete(h);
+            if self.max == Some(x) {
+                self.max = match summary.max {
+                    None => self.min,
+                    Some(c) => self.clusters[&c].max,
+                };
+            }
+        } else if self.max == Some(x) {
+            self.max = cluster.max;
+        }
+    }
+}
+
+impl VebTree {
+    /// Generate empty VebTree for integers of w bits.
+    pub fn new(w: u32) -> Self {
+        if w == 0 || w > usize::BITS {
+            panic!("width must be within [1, {}]. w: {}", usize::BITS, w)
+        }
+        VebTree {
+            root: Node::new(w, 0),
+            w,
+            n: 0,
+        }
+    }
+
+    fn within_universe(&self, x: usize) -> bool {
+        self.w == usize::BITS || x >> self.w == 0
+    }
+
+    /// Return the smallest value.
+    pub fn min(&self) -> Option<usize> {
+        self.root.min
+    }
+
+    /// Return the largest value.
+    pub fn max(&self) -> Option<usize> {
+        self.root.max
+    }
+
+    /// Return true if x is contained.
+    pub fn contains(&self, x: usize) -> bool {
+        self.within_universe(x) && self.root.member(x).is_some()
+    }
+
+    /// Return an iterator which visits elements in ascending order by successor search.
+    /// O(log w) per element
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(self.min(), move |&x| {
+            x.checked_add(1)
+                .and_then(|y| self.successor_ref(y))
+                .copied()
+        })
+    }
+
+    /// Remove all elements and return them in ascending order, leaving an empty tree of the same width.
+    pub fn drain(&mut self) -> std::vec::IntoIter<usize> {
+        let values = self.iter().collect::<Vec<_>>();
+        *self = VebTree::new(self.w);
+        values.into_iter()
+    }
+
+    // Return a reference to the smallest value y which satisfies y >= x.
+    fn successor_ref(&self, x: usize) -> Option<&usize> {
+        if !self.within_universe(x) {
+            return None;
+        }
+        self.root.member(x).or_else(|| self.root.successor(x))
+    }
+
+    // Return a reference to the largest value y which satisfies y <= x.
+    fn predecessor_ref(&self, x: usize) -> Option<&usize> {
+        if !self.within_universe(x) {
+            return self.root.max.as_ref();
+        }
+        self.root.member(x).or_else(|| self.root.predecessor(x))
+    }
+}
+
+impl IntoIterator for VebTree {
+    type Item = usize;
+    type IntoIter = std::vec::IntoIter<usize>;
+
+    // Elements are moved out in ascending order.
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain()
+    }
+}
+
+impl SSet<usize> for VebTree {
+    fn size(&self) -> usize {
+        self.n
+    }
+
+    fn add(&mut self, x: usize) -> bool {
+        if !self.within_universe(x) {
+            panic!("value must be less than 2^w. x: {}, w: {}", x, self.w)
+        }
+        if self.root.member(x).is_some() {
+            return false;
+        }
+        self.root.insert(x);
+        self.n += 1;
+        true
+    }
+
+    fn remove(&mut self, x: usize) -> Option<usize> {
+        if !self.contains(x) {
+            return None;
+        }
+        self.root.delete(x);
+        self.n -= 1;
+        Some(x)
+    }
+
+    fn find(&self, x: usize) -> Option<usize> {
+        self.successor_ref(x).copied()
+    }
+
+    fn find_ref(&self, x: &usize) -> Option<&usize> {
+        self.successor_ref(*x)
+    }
+
+    fn successor(&self, x: &usize) -> Option<&usize> {
+        if !self.within_universe(*x) {
+            return None;
+        }
+        self.root.successor(*x)
+    }
+
+    fn predecessor(&self, x: &usize) -> Option<&usize> {
+        if !self.within_universe(*x) {
+            return self.root.max.as_ref();
+        }
+        self.root.predecessor(*x)
+    }
+
+    // Integers are compared by subtraction, and a search which includes x itself
+    // replaces the member check of the default: O(log w).
+    fn closest(&self, x: &usize) -> Option<&usize> {
+        let x = *x;
+        let lo = self.predecessor_ref(x);
+        let hi = self.successor_ref(x);
+        match (lo, hi) {
+            (Some(&l), Some(&h)) if h - x < x - l => hi,
+            (Some(_), _) => lo,
+            (None, _) => hi,
+        }
+    }
+
+    // Walk outward from x by predecessor and successor searches of the tree: O(k log w).
+    fn neighbors(&self, x: &usize, k: usize) -> Vec<&usize> {
+        let x = *x;
+        let mut lo = self.predecessor_ref(x);
+        let mut hi = match lo {
+            Some(&l) if l == x => x.checked_add(1).and_then(|y| self.successor_ref(y)),
+            _ => self.successor_ref(x),
+        };
+        let mut values = Vec::with_capacity(k.min(self.n));
+        while values.len() < k {
+            match (lo, hi) {
+                (Some(l), Some(h)) if h - x < x - l => {
+                    values.push(h);
+                    hi = h.checked_add(1).and_then(|y| self.successor_ref(y));
+                }
+                (Some(l), _) => {
+                    values.push(l);
+                    lo = l.checked_sub(1).and_then(|y| self.predecessor_ref(y));
+                }
+                (None, Some(h)) => {
+                    values.push(h);
+                    hi = h.checked_add(1).and_then(|y| self.successor_ref(y));
+                }
+                (None, None) => break,
+            }
+        }
+        values
+    }
+}
+
+impl SSetExt<usize> for VebTree {
+    // A borrowed form only supports comparison, so the smallest integer not less than x
+    // is found by binary search over the universe before the successor search: O(w).
+    fn find_by<Q>(&self, x: &Q) -> Option<&usize>
+    where
+        usize: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let (mut lo, mut hi) = (0, self.max()?);
+        if hi.borrow() < x {
+            return None;
+        }
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if mid.borrow() < x {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        self.successor_ref(lo)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::VebTree;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::set::SSet;
+
+    // Decode the width and operations, each of which adds a value or removes the one found for it.
+    pub(super) fn ops(u: &mut Unstructured<'_>) -> Result<(u32, Vec<(bool, usize)>)> {
+        let w = u.int_in_range(1..=usize::BITS)?;
+        let ops = (0..u.arbitrary_len::<(bool, usize)>()?)
+            .map(|_| u.arbitrary())
+            .collect::<Result<_>>()?;
+        Ok((w, ops))
+    }
+
+    // The width is chosen first, and values truncated to it are added or the one found for them is removed,
+    // so that clusters and summaries are created and released.
+    impl<'a> Arbitrary<'a> for VebTree {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let (w, ops) = ops(u)?;
+            let mask = usize::MAX >> (usize::BITS - w);
+            let mut set = VebTree::new(w);
+            for (add, x) in ops {
+                if add {
+                    set.add(x & mask);
+                } else if let Some(y) = set.find(x & mask) {
+                    set.remove(y);
+                }
+            }
+            Ok(set)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VebTree;
+    use interface::set::{SSet, SSetExt};
+
+    #[test]
+    fn sset_conformance_test() {
+        let mut set = VebTree::new(8);
+        test_support::conformance::check_sset(&mut set);
+    }
+
+    #[test]
+    fn sset_test() {
+        let mut set = VebTree::new(4);
+        assert_eq!(set.size(), 0);
+        assert_eq!(set.find(0), None);
+        assert_eq!(set.remove(3), None);
+
+        for x in [2, 3, 4, 5, 7, 14, 15] {
+            assert!(set.add(x));
+        }
+        assert!(!set.add(7));
+        assert_eq!(set.size(), 7);
+        assert_eq!(set.min(), Some(2));
+        assert_eq!(set.max(), Some(15));
+        assert_eq!(set.find(0), Some(2));
+        assert_eq!(set.find(6), Some(7));
+        assert_eq!(set.find(8), Some(14));
+        assert_eq!(set.find(15), Some(15));
+        assert_eq!(set.find(16), None);
+        assert_eq!(set.find_by(&8), Some(&14));
+        assert_eq!(set.find_ref(&8), Some(&14));
+        assert_eq!(set.find_ref(&16), None);
+
+        assert_eq!(set.remove(2), Some(2));
+        assert_eq!(set.remove(15), Some(15));
+        assert_eq!(set.remove(15), None);
+        assert_eq!(set.min(), Some(3));
+        assert_eq!(set.max(), Some(14));
+        assert_eq!(set.find(0), Some(3));
+        assert_eq!(set.find(15), None);
+        assert_eq!(set.size(), 5);
+    }
+
+    #[test]
+    fn large_universe_test() {
+        let mut set = VebTree::new(64);
+        let mut expected = std::collections::BTreeSet::new();
+        let n: u64 = if cfg!(miri) { 300 } else { 3000 };
+        for i in 0..n {
+            let x = (i.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> (i % 50)) as usize;
+            if i % 4 == 3 {
+                let y = *expected.iter().nth(i as usize % expected.len()).unwrap();
+                assert_eq!(set.remove(y), expected.take(&y));
+            } else {
+                assert_eq!(set.add(x), expected.insert(x));
+            }
+            assert_eq!(set.size(), expected.len());
+        }
+        assert_eq!(set.min(), expected.iter().next().copied());
+        assert_eq!(set.max(), expected.iter().next_back().copied());
+        for &x in expected.iter().step_by(7) {
+            assert!(set.contains(x));
+            assert_eq!(set.find(x + 1), expected.range(x + 1..).next().copied());
+        }
+        assert_eq!(
+            set.find(usize::MAX),
+            expected.range(usize::MAX..).next().copied()
+        );
+
+        for x in expected.clone() {
+            assert_eq!(set.remove(x), Some(x));
+        }
+        assert_eq!(set.size(), 0);
+        assert_eq!(set.min(), None);
+    }
+
+    #[test]
+    fn iter_test() {
+        let mut set = VebTree::new(64);
+        assert_eq!(set.iter().next(), None);
+        for &x in [5, usize::MAX, 0, 1 << 40, 7].iter() {
+            set.add(x);
+        }
+        assert!(set
+            .iter()
+            .eq([0, 5, 7, 1 << 40, usize::MAX].iter().copied()));
+        assert!(set
+            .drain()
+            .eq([0, 5, 7, 1 << 40, usize::MAX].iter().copied()));
+        assert_eq!(set.size(), 0);
+        assert_eq!(set.min(), None);
+
+        set.add(3);
+        set.add(2);
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn closest_test() {
+        let mut set = VebTree::new(8);
+        assert_eq!(set.closest(&3), None);
+        assert!(set.neighbors(&3, 2).is_empty());
+
+        for &x in [10, 20, 25, 100, 255].iter() {
+            set.add(x);
+        }
+        assert_eq!(set.closest(&0), Some(&10));
+        assert_eq!(set.closest(&15), Some(&10));
+        assert_eq!(set.closest(&16), Some(&20));
+        assert_eq!(set.closest(&25), Some(&25));
+        assert_eq!(set.closest(&200), Some(&255));
+        assert_eq!(set.closest(&usize::MAX), Some(&255));
+        assert_eq!(set.neighbors(&22, 3), [&20, &25, &10]);
+        assert_eq!(set.neighbors(&25, 2), [&25, &20]);
+        assert!(set.neighbors(&0, 0).is_empty());
+        assert_eq!(set.neighbors(&300, 10), [&255, &100, &25, &20, &10]);
+
+        // compare with sorting all values by distance
+        let mut set = VebTree::new(64);
+        let mut values = Vec::new();
+        let mut x: u64 = 1;
+        for _ in 0..200 {
+            x = x
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let y = (x >> (x % 40)) as usize;
+            if set.add(y) {
+                values.push(y);
+            }
+        }
+        for &q in [0, 1 << 20, 1 << 40, usize::MAX, values[0], values[7] + 1].iter() {
+            let mut expected = values.clone();
+            expected.sort_by_key(|&y| (y.abs_diff(q), y));
+            assert_eq!(set.closest(&q), expected.first());
+            assert!(set.neighbors(&q, 20).into_iter().eq(&expected[..20]));
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use super::Node;
+        use std::collections::BTreeSet;
+
+        // Check the layout of clusters and the summary, and return keys under u in ascending order.
+        fn check(u: &Node) -> Vec<usize> {
+            let (min, max) = match (u.min, u.max) {
+                (None, None) => {
+                    assert!(u.clusters.is_empty());
+                    assert!(u.summary.as_ref().is_none_or(|s| s.min.is_none()));
+                    return Vec::new();
+                }
+                (Some(min), Some(max)) => (min, max),
+                _ => panic!("min and max must exist together"),
+            };
+            let mut keys = vec![min];
+            let mut highs = u.clusters.keys().copied().collect::<Vec<_>>();
+            highs.sort_unstable();
+            for h in &highs {
+                let cluster = &u.clusters[h];
+                assert_eq!(cluster.bits, u.low_bits());
+                assert_eq!(cluster.base, u.base + (h << u.low_bits()));
+                assert!(cluster.min.is_some());
+                keys.extend(check(cluster));
+            }
+            if u.bits == 1 {
+                assert!(highs.is_empty());
+                if min != max {
+                    keys.push(max);
+                }
+            } else {
+                let summary = u.summary.as_deref().map_or_else(Vec::new, check);
+                assert_eq!(summary, highs);
+            }
+            assert!(keys.windows(2).all(|w| w[0] < w[1]));
+            assert_eq!(keys.last(), Some(&max));
+            assert!(keys
+                .iter()
+                .all(|&k| u.base <= k && (k - u.base).checked_shr(u.bits).unwrap_or(0) == 0));
+            keys
+        }
+
+        test_support::replay::check_arbitrary(|set: VebTree, u| {
+            // replay the same operations on BTreeSet
+            let (w, ops) = super::arb::ops(u).unwrap();
+            let mask = usize::MAX >> (usize::BITS - w);
+            let mut expected = BTreeSet::new();
+            for (add, x) in ops {
+                if add {
+                    expected.insert(x & mask);
+                } else if let Some(&y) = expected.range(x & mask..).next() {
+                    expected.remove(&y);
+                }
+            }
+            assert_eq!(set.w, w);
+            assert_eq!(set.size(), expected.len());
+            assert!(check(&set.root).iter().eq(expected.iter()));
+            assert!(set.iter().eq(expected.iter().copied()));
+        });
+    }
+}
@@ -0,0 +1,169 @@
+use crate::list::List;
+use crate::queue::Queue;
+
+// Remove all elements from the back, so that the result is in reverse order.
+// It's O(n) for array-based lists which remove the last element in O(1).
+fn drain_reversed<T, L>(l: &mut L) -> Vec<T>
+where
+    L: List<T> + ?Sized,
+{
+    let mut buf = Vec::with_capacity(l.size());
+    while let Some(x) = l.pop_back() {
+        buf.push(x);
+    }
+    buf
+}
+
+/// Reverse the order in which elements are removed from a FIFO queue.
+/// Elements are drained to a temporary buffer and added back.
+/// O(n) queue operations
+pub fn reverse_queue<T, Q>(q: &mut Q)
+where
+    Q: Queue<T> + ?Sized,
+{
+    let mut buf = Vec::new();
+    while let Some(x) = q.remove() {
+        buf.push(x);
+    }
+    while let Some(x) = buf.pop() {
+        q.add(x);
+    }
+}
+
+/// Reverse the order of elements in a list.
+/// O(n) removals and additions at the back
+pub fn reverse_list<T, L>(l: &mut L)
+where
+    L: List<T> + ?Sized,
+{
+    for x in drain_reversed(l) {
+        l.push_back(x);
+    }
+}
+
+/// Add all values of an iterator to the back of a list.
+pub fn extend_list<T, L, I>(l: &mut L, iter: I)
+where
+    L: List<T> + ?Sized,
+    I: IntoIterator<Item = T>,
+{
+    for x in iter {
+        l.push_back(x);
+    }
+}
+
+/// Remove consecutive duplicates, which leaves unique elements if a list is sorted.
+/// Return the number of removed elements.
+/// O(n) removals and additions at the back
+pub fn dedup_sorted<T, L>(l: &mut L) -> usize
+where
+    T: PartialEq,
+    L: List<T> + ?Sized,
+{
+    let mut buf = drain_reversed(l);
+    let n = buf.len();
+    while let Some(x) = buf.pop() {
+        if l.last() != Some(&x) {
+            l.push_back(x);
+        }
+    }
+    n - l.size()
+}
+
+/// Search a sorted list for a value.
+/// Return the position of a matching element, otherwise return the position where it could be inserted as an error.
+/// O(log n) calls of get(i)
+pub fn binary_search<T, L>(l: &L, x: &T) -> Result<usize, usize>
+where
+    T: Ord,
+    L: List<T> + ?Sized,
+{
+    let (mut lo, mut hi) = (0, l.size());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match l.get(mid) {
+            Some(y) if y < x => lo = mid + 1,
+            Some(y) if y == x => return Ok(mid),
+            _ => hi = mid,
+        }
+    }
+    Err(lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{binary_search, dedup_sorted, extend_list, reverse_list, reverse_queue};
+    use crate::list::List;
+    use crate::queue::Queue;
+    use std::collections::VecDeque;
+
+    // Minimal List and FIFO Queue for tests, since no implementation depends on this crate.
+    #[derive(Debug, Default)]
+    struct VecList(VecDeque<i32>);
+
+    impl List<i32> for VecList {
+        fn size(&self) -> usize {
+            self.0.len()
+        }
+        fn get(&self, i: usize) -> Option<&i32> {
+            self.0.get(i)
+        }
+        fn set(&mut self, i: usize, x: i32) -> Option<i32> {
+            Some(std::mem::replace(&mut self.0[i], x))
+        }
+        fn add(&mut self, i: usize, x: i32) {
+            self.0.insert(i, x);
+        }
+        fn remove(&mut self, i: usize) -> Option<i32> {
+            self.0.remove(i)
+        }
+    }
+
+    impl Queue<i32> for VecList {
+        fn add(&mut self, x: i32) -> bool {
+            self.0.push_back(x);
+            true
+        }
+        fn remove(&mut self) -> Option<i32> {
+            self.0.pop_front()
+        }
+    }
+
+    #[test]
+    fn list_test() {
+        let mut list = VecList::default();
+        extend_list(&mut list, vec![1, 1, 2, 3, 3, 3, 5]);
+        assert_eq!(list.size(), 7);
+        assert_eq!(dedup_sorted(&mut list), 3);
+        assert!(list.0.iter().eq([1, 2, 3, 5].iter()));
+        assert_eq!(dedup_sorted(&mut list), 0);
+
+        assert_eq!(binary_search(&list, &3), Ok(2));
+        assert_eq!(binary_search(&list, &0), Err(0));
+        assert_eq!(binary_search(&list, &4), Err(3));
+        assert_eq!(binary_search(&list, &6), Err(4));
+
+        reverse_list(&mut list);
+        assert!(list.0.iter().eq([5, 3, 2, 1].iter()));
+
+        // functions work behind dyn List as well
+        let dyn_list: &mut dyn List<i32> = &mut list;
+        extend_list(dyn_list, 0..2);
+        assert_eq!(dyn_list.size(), 6);
+        assert_eq!(dedup_sorted(&mut VecList::default()), 0);
+    }
+
+    #[test]
+    fn queue_test() {
+        let mut queue = VecList::default();
+        for x in 0..5 {
+            Queue::add(&mut queue, x);
+        }
+        reverse_queue(&mut queue);
+        for x in (0..5).rev() {
+            assert_eq!(Queue::remove(&mut queue), Some(x));
+        }
+        reverse_queue(&mut queue);
+        assert_eq!(Queue::remove(&mut queue), None);
+    }
+}
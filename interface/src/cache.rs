@@ -0,0 +1,15 @@
+/// Cache represents a key-value store with limited capacity.
+/// When it's full, an entry is evicted according to its replacement policy.
+pub trait Cache<K, V> {
+    /// Return the number of entries.
+    fn size(&self) -> usize;
+    /// Return the maximum number of entries.
+    fn capacity(&self) -> usize;
+    /// Return the value for a key, and record the access for replacement policy.
+    fn get(&mut self, k: &K) -> Option<&V>;
+    /// Store a value for a key, and evict an entry if a cache is full.
+    /// Return the previous value if the key exists, otherwise return null(Empty).
+    fn put(&mut self, k: K, v: V) -> Option<V>;
+    /// Remove an entry for a key and return its value.
+    fn remove(&mut self, k: &K) -> Option<V>;
+}
@@ -0,0 +1,91 @@
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// Display adapter which writes values of an iterator separated by a separator.
+/// Format options such as width and precision are applied to each value.
+#[derive(Debug, Clone)]
+pub struct FormatWith<'a, I> {
+    iter: I,
+    sep: &'a str,
+}
+
+/// Return a Display adapter which writes values of iter separated by sep, without collecting them.
+pub fn format_with<I>(iter: I, sep: &str) -> FormatWith<'_, I::IntoIter>
+where
+    I: IntoIterator,
+    I::IntoIter: Clone,
+    I::Item: Display,
+{
+    FormatWith {
+        iter: iter.into_iter(),
+        sep,
+    }
+}
+
+impl<'a, I> Display for FormatWith<'a, I>
+where
+    I: Iterator + Clone,
+    I::Item: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (k, x) in self.iter.clone().enumerate() {
+            if k > 0 {
+                f.write_str(self.sep)?;
+            }
+            x.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Debug adapter which writes values of an iterator as a list, such as `[a, b, c]`.
+/// It lets Debug of a structure show the logical sequence of elements instead of its internal representation.
+#[derive(Clone)]
+pub struct DebugEntries<I> {
+    iter: I,
+}
+
+/// Return a Debug adapter which writes values of iter as a list, without collecting them.
+pub fn debug_entries<I>(iter: I) -> DebugEntries<I::IntoIter>
+where
+    I: IntoIterator,
+    I::IntoIter: Clone,
+    I::Item: Debug,
+{
+    DebugEntries {
+        iter: iter.into_iter(),
+    }
+}
+
+impl<I> Debug for DebugEntries<I>
+where
+    I: Iterator + Clone,
+    I::Item: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{debug_entries, format_with};
+
+    #[test]
+    fn debug_entries_test() {
+        let values = [Some(1), None];
+        assert_eq!(
+            format!("{:?}", debug_entries(values.iter().flatten())),
+            "[1]"
+        );
+        assert_eq!(format!("{:?}", debug_entries(&["a"])), "[\"a\"]");
+    }
+
+    #[test]
+    fn format_with_test() {
+        assert_eq!(format_with(&[1, 2, 3], ", ").to_string(), "1, 2, 3");
+        assert_eq!(format_with(&[1.5, 2.25], " | ").to_string(), "1.5 | 2.25");
+        assert_eq!(format!("{:.1}", format_with(&[1.5, 2.25], " ")), "1.5 2.2");
+        assert_eq!(format!("{:>3}", format_with(&["a", "b"], "")), "  a  b");
+        assert_eq!(format_with(Vec::<i32>::new().iter(), ", ").to_string(), "");
+    }
+}
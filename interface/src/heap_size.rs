@@ -0,0 +1,7 @@
+/// HeapSize reports memory a structure allocates on heap.
+pub trait HeapSize {
+    /// Return the number of bytes allocated on heap by a structure itself,
+    /// such as backing array capacity and nodes.
+    /// Heap owned by elements is not counted.
+    fn heap_bytes(&self) -> usize;
+}
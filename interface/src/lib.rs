@@ -1,9 +1,44 @@
 #![warn(missing_docs)]
 //! interface represents abstract data type which defines whole operations and its semantics for a data structure.
+//! Core traits are dyn-compatible, so that a structure can be selected at runtime behind e.g. `Box<dyn List<T>>`.
+//! Generic helpers live in extension traits such as SSetExt.
 
+/// Provide generic algorithms written against the interfaces
+pub mod algorithms;
+/// Provide cache interface
+pub mod cache;
+/// Provide formatting helpers for collections
+pub mod fmt;
+/// Provide memory usage reporting interface
+pub mod heap_size;
 /// Provide list interface
 pub mod list;
 /// Provide queue related interface
 pub mod queue;
 /// Provide set releated interface
 pub mod set;
+/// Provide set algebra over USet and sorted sequences
+pub mod set_ops;
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::Cache;
+    use crate::heap_size::HeapSize;
+    use crate::list::List;
+    use crate::queue::{BoundedQueue, Deque, Queue, Stack};
+    use crate::set::{SSet, USet};
+
+    // It fails to compile if a core trait loses dyn compatibility.
+    #[test]
+    fn dyn_compatible_test() {
+        let _: Option<Box<dyn List<String>>> = None;
+        let _: Option<Box<dyn Queue<String>>> = None;
+        let _: Option<Box<dyn BoundedQueue<String>>> = None;
+        let _: Option<Box<dyn Stack<String>>> = None;
+        let _: Option<Box<dyn Deque<String>>> = None;
+        let _: Option<Box<dyn USet<String>>> = None;
+        let _: Option<Box<dyn SSet<String>>> = None;
+        let _: Option<Box<dyn Cache<String, String>>> = None;
+        let _: Option<Box<dyn HeapSize>> = None;
+    }
+}
@@ -7,7 +7,141 @@ pub trait List<T> {
     /// Set the value at the position i.
     fn set(&mut self, i: usize, x: T) -> Option<T>;
     /// Add a value at the position i, and shift following elements to backward.
+    /// i must not be greater than the size, where i equal to the size appends a value.
+    /// Implementations panic otherwise, instead of clamping i or leaving a hole.
     fn add(&mut self, i: usize, x: T);
     /// Remove a value at the position i, and shift following elements to forward.
     fn remove(&mut self, i: usize) -> Option<T>;
+    /// Add all values of an iterator from the position i in order, and shift following elements to backward.
+    /// The default adds values one by one, and array-based implementations shift following elements only once.
+    /// It's unavailable behind `dyn List<T>` since it's generic.
+    /// i must not be greater than the size as well as add, even if an iterator is empty.
+    fn add_all<I>(&mut self, i: usize, iter: I)
+    where
+        Self: Sized,
+        I: IntoIterator<Item = T>,
+    {
+        if i > self.size() {
+            panic!(
+                "index must be positive and not greater than the size of list. i: {}, n: {}",
+                i,
+                self.size()
+            )
+        }
+        for (k, x) in iter.into_iter().enumerate() {
+            self.add(i + k, x);
+        }
+    }
+    /// Return the first value, or null(Empty) if a list is empty.
+    fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+    /// Return the last value, or null(Empty) if a list is empty.
+    fn last(&self) -> Option<&T> {
+        self.get_from_end(0)
+    }
+    /// Return the value at the position i counted from the end, where 0 is the last element.
+    /// Return null(Empty) if i is not less than the size, instead of underflowing size() - 1 - i.
+    fn get_from_end(&self, i: usize) -> Option<&T> {
+        let n = self.size();
+        if i >= n {
+            return None;
+        }
+        self.get(n - 1 - i)
+    }
+    /// Set the value at the position i counted from the end, where 0 is the last element.
+    fn set_from_end(&mut self, i: usize, x: T) -> Option<T> {
+        let n = self.size();
+        if i >= n {
+            panic!(
+                "index from the end must be less than the size of list. i: {}, n: {}",
+                i, n
+            )
+        }
+        self.set(n - 1 - i, x)
+    }
+    /// Add a value after the last element.
+    fn push_back(&mut self, x: T) {
+        self.add(self.size(), x)
+    }
+    /// Remove the last value, or return null(Empty) if a list is empty.
+    fn pop_back(&mut self) -> Option<T> {
+        match self.size() {
+            0 => None,
+            n => self.remove(n - 1),
+        }
+    }
+    /// Return true if a value equal to x exists, by scanning from the first element.
+    fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.index_of(x).is_some()
+    }
+    /// Return the first position of a value equal to x.
+    /// Return null(Empty) if it doesn't exist.
+    fn index_of(&self, x: &T) -> Option<usize>
+    where
+        T: PartialEq,
+    {
+        (0..self.size()).find(|&i| self.get(i) == Some(x))
+    }
+    /// Return the last position of a value equal to x, by scanning from the last element.
+    /// Return null(Empty) if it doesn't exist.
+    fn rposition(&self, x: &T) -> Option<usize>
+    where
+        T: PartialEq,
+    {
+        (0..self.size()).rev().find(|&i| self.get(i) == Some(x))
+    }
+    /// Swap values at the positions i and j.
+    /// The default moves values by remove and add, and random-access implementations override it in O(1).
+    fn swap(&mut self, i: usize, j: usize) {
+        let n = self.size();
+        if i >= n || j >= n {
+            panic!(
+                "index must be positive and less than the size of list. i: {}, j: {}, n: {}",
+                i, j, n
+            )
+        }
+        let (i, j) = (std::cmp::min(i, j), std::cmp::max(i, j));
+        if i == j {
+            return;
+        }
+        if let Some(y) = self.remove(j) {
+            if let Some(x) = self.set(i, y) {
+                self.add(j, x);
+            }
+        }
+    }
+    /// Rotate values to the left by k, so that the value at the position k becomes the first.
+    /// The default moves min{k, n - k} values between the front and the back one by one.
+    fn rotate_left(&mut self, k: usize) {
+        let n = self.size();
+        if n == 0 {
+            return;
+        }
+        let k = k % n;
+        if k <= n - k {
+            for _ in 0..k {
+                if let Some(x) = self.remove(0) {
+                    self.push_back(x);
+                }
+            }
+        } else {
+            for _ in 0..(n - k) {
+                if let Some(x) = self.pop_back() {
+                    self.add(0, x);
+                }
+            }
+        }
+    }
+    /// Rotate values to the right by k, so that the value at the position n - k becomes the first.
+    fn rotate_right(&mut self, k: usize) {
+        let n = self.size();
+        if n == 0 {
+            return;
+        }
+        self.rotate_left(n - k % n);
+    }
 }
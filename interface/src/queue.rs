@@ -2,9 +2,52 @@
 /// Provides manipulations for addition, removal with a certain rule.
 pub trait Queue<T> {
     /// Add a value to a queue.
+    /// Return true if it's ingested, otherwise return false (e.g. a bounded queue is full).
     fn add(&mut self, x: T) -> bool;
     /// Remove a next value and return it.
     fn remove(&mut self) -> Option<T>;
+    /// Add all values of an iterator in order.
+    /// Return the number of ingested values.
+    /// It's unavailable behind `dyn Queue<T>` since it's generic.
+    fn add_all<I>(&mut self, iter: I) -> usize
+    where
+        Self: Sized,
+        I: IntoIterator<Item = T>,
+    {
+        let mut ingested = 0;
+        for x in iter {
+            if self.add(x) {
+                ingested += 1;
+            }
+        }
+        ingested
+    }
+    /// Remove at most k next values, and return them in the removed order.
+    fn remove_n(&mut self, k: usize) -> Vec<T> {
+        let mut values = Vec::new();
+        while values.len() < k {
+            match self.remove() {
+                Some(x) => values.push(x),
+                None => break,
+            }
+        }
+        values
+    }
+}
+
+/// BoundedQueue represents a queue which holds at most a fixed number of elements.
+pub trait BoundedQueue<T>: Queue<T> {
+    /// Return the number of elements in a queue.
+    fn size(&self) -> usize;
+    /// Return the maximum number of elements a queue can hold.
+    fn capacity(&self) -> usize;
+    /// Add a value to a queue if it's not full.
+    /// Return the given value as an error if it's full.
+    fn try_add(&mut self, x: T) -> Result<(), T>;
+    /// Return true if no more value can be added without removal.
+    fn is_full(&self) -> bool {
+        self.size() >= self.capacity()
+    }
 }
 
 /// Stack represents LIFO queue.
@@ -1,3 +1,6 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+
 /// USet represents an unordered collection of unique elements.
 pub trait USet<T>
 where
@@ -8,11 +11,32 @@ where
     /// Add a value to a collection if it doesn't exist.
     /// Return true if it's ingested, otherwise return false.
     fn add(&mut self, x: T) -> bool;
-    /// Remove a value from a collection.
+    /// Remove a value equal to a given one from a collection, leaving the probe value to the caller.
     /// Return the element if a given one exists in a collection, otherwise return null(Empty).
-    fn remove(&mut self, x: T) -> Option<T>;
+    fn remove(&mut self, x: &T) -> Option<T>;
     /// Return a value if a given one exists in a collection, otherwise return null(Empty).
     fn find(&self, x: T) -> Option<T>;
+    /// Return a reference to the element equal to a given one, without moving the probe value.
+    fn find_ref(&self, x: &T) -> Option<&T>;
+}
+
+/// USetExt extends USet with queries by a borrowed form of the element.
+/// They are generic methods, so they live outside USet to keep `dyn USet<T>` available.
+pub trait USetExt<T>: USet<T>
+where
+    T: PartialEq + Eq,
+{
+    /// Remove a value equal to a given borrowed form of the element.
+    /// It allows e.g. `USet<String>` to be queried by `&str` without allocating a probe value.
+    fn remove_by<Q>(&mut self, x: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq;
+    /// Return a reference to the element equal to a given borrowed form of the element.
+    fn find_by<Q>(&self, x: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq;
 }
 
 /// SSet represents an ordered collection of unique elements.
@@ -31,4 +55,99 @@ where
     /// Return a minimum value which satisfies condition: y >= x(x: given value).
     /// Called as successor search.
     fn find(&self, x: T) -> Option<T>;
+    /// Successor search without moving the probe value.
+    /// Return a reference to a minimum value which satisfies condition: y >= x.
+    fn find_ref(&self, x: &T) -> Option<&T>;
+    /// Return a reference to a minimum value which satisfies condition: y > x.
+    fn successor(&self, x: &T) -> Option<&T>;
+    /// Return a reference to a maximum value which satisfies condition: y < x.
+    fn predecessor(&self, x: &T) -> Option<&T>;
+    /// Return the value nearest to x, preferring the smaller one on a tie.
+    /// The default compares find_ref(x) with predecessor(x).
+    fn closest(&self, x: &T) -> Option<&T>
+    where
+        T: Distance,
+    {
+        match (self.predecessor(x), self.find_ref(x)) {
+            (Some(l), Some(h)) if h.distance(x) < x.distance(l) => Some(h),
+            (Some(l), _) => Some(l),
+            (None, h) => h,
+        }
+    }
+    /// Return at most k values nearest to x, in order of distance, preferring the smaller one on a tie.
+    /// The default walks outward from x by predecessor and successor searches.
+    fn neighbors(&self, x: &T, k: usize) -> Vec<&T>
+    where
+        T: Distance,
+    {
+        let (mut lo, mut hi) = (self.predecessor(x), self.find_ref(x));
+        let mut values = Vec::with_capacity(std::cmp::min(k, self.size()));
+        while values.len() < k {
+            match (lo, hi) {
+                (Some(l), Some(h)) if h.distance(x) < x.distance(l) => {
+                    values.push(h);
+                    hi = self.successor(h);
+                }
+                (Some(l), _) => {
+                    values.push(l);
+                    lo = self.predecessor(l);
+                }
+                (None, Some(h)) => {
+                    values.push(h);
+                    hi = self.successor(h);
+                }
+                (None, None) => break,
+            }
+        }
+        values
+    }
+}
+
+/// SSetExt extends SSet with successor search by a borrowed form of the element.
+/// It's a generic method, so it lives outside SSet to keep `dyn SSet<T>` available.
+pub trait SSetExt<T>: SSet<T>
+where
+    T: PartialEq + Eq + PartialOrd + Ord,
+{
+    /// Successor search by a borrowed form of the element.
+    /// Return a reference to a minimum value which satisfies condition: y.borrow() >= x.
+    fn find_by<Q>(&self, x: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord;
+}
+
+/// Distance represents how far apart two values of an ordered type are.
+/// SSet uses it to find the nearest values to a given one.
+pub trait Distance {
+    /// Type of distance, which is compared to choose the nearer value.
+    type Output: Ord;
+    /// Return the distance between self and other, which is symmetric.
+    fn distance(&self, other: &Self) -> Self::Output;
+}
+
+// Integers are apart by the absolute difference, which is unsigned so that it never overflows.
+macro_rules! distance_by_abs_diff {
+    ($($t:ty => $d:ty),*) => {
+        $(
+            impl Distance for $t {
+                type Output = $d;
+                fn distance(&self, other: &Self) -> $d {
+                    self.abs_diff(*other)
+                }
+            }
+        )*
+    };
+}
+
+distance_by_abs_diff!(
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize,
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize
+);
+
+impl Distance for std::time::Duration {
+    type Output = std::time::Duration;
+    fn distance(&self, other: &Self) -> std::time::Duration {
+        std::cmp::max(*self, *other) - std::cmp::min(*self, *other)
+    }
 }
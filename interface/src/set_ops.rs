@@ -0,0 +1,232 @@
+use crate::set::USet;
+use std::cmp::Ordering;
+use std::iter::Peekable;
+
+/// Add all values of an iterator to a set.
+/// Return the number of values which were not contained.
+pub fn union_into<T, S, I>(s: &mut S, iter: I) -> usize
+where
+    T: Eq,
+    S: USet<T> + ?Sized,
+    I: IntoIterator<Item = T>,
+{
+    iter.into_iter()
+        .map(|x| s.add(x))
+        .filter(|&added| added)
+        .count()
+}
+
+/// Return values of an iterator which are contained in a set, probing the set for each value.
+/// O(m) calls of find_ref(x) for m values
+pub fn intersection<'a, T, S, I>(a: I, b: &'a S) -> impl Iterator<Item = &'a T> + 'a
+where
+    T: Eq + 'a,
+    S: USet<T> + ?Sized,
+    I: IntoIterator<Item = &'a T>,
+    I::IntoIter: 'a,
+{
+    a.into_iter().filter(move |x| b.find_ref(x).is_some())
+}
+
+/// Return values of an iterator which are not contained in a set, probing the set for each value.
+/// O(m) calls of find_ref(x) for m values
+pub fn difference<'a, T, S, I>(a: I, b: &'a S) -> impl Iterator<Item = &'a T> + 'a
+where
+    T: Eq + 'a,
+    S: USet<T> + ?Sized,
+    I: IntoIterator<Item = &'a T>,
+    I::IntoIter: 'a,
+{
+    a.into_iter().filter(move |x| b.find_ref(x).is_none())
+}
+
+/// Return true if every value of an iterator is contained in a set.
+/// O(m) calls of find_ref(x) for m values
+pub fn is_subset<'a, T, S, I>(a: I, b: &S) -> bool
+where
+    T: Eq + 'a,
+    S: USet<T> + ?Sized,
+    I: IntoIterator<Item = &'a T>,
+{
+    a.into_iter().all(|x| b.find_ref(x).is_some())
+}
+
+/// Set operations over iterators which yield unique values in ascending order, such as iter() of SSet
+/// implementations. Both inputs are walked once by merging them, instead of probing one with the other.
+/// O(n + m)
+pub mod sorted {
+    use super::{Merge, Side};
+
+    /// Return values contained in either a or b in ascending order.
+    pub fn union<T, A, B>(a: A, b: B) -> impl Iterator<Item = T>
+    where
+        T: Ord,
+        A: IntoIterator<Item = T>,
+        B: IntoIterator<Item = T>,
+    {
+        Merge::new(a, b).map(|side| match side {
+            Side::Left(x) | Side::Right(x) | Side::Both(x) => x,
+        })
+    }
+
+    /// Return values contained in both a and b in ascending order. Values are taken from a.
+    pub fn intersection<T, A, B>(a: A, b: B) -> impl Iterator<Item = T>
+    where
+        T: Ord,
+        A: IntoIterator<Item = T>,
+        B: IntoIterator<Item = T>,
+    {
+        Merge::new(a, b).filter_map(|side| match side {
+            Side::Both(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    /// Return values contained in a but not in b in ascending order.
+    pub fn difference<T, A, B>(a: A, b: B) -> impl Iterator<Item = T>
+    where
+        T: Ord,
+        A: IntoIterator<Item = T>,
+        B: IntoIterator<Item = T>,
+    {
+        Merge::new(a, b).filter_map(|side| match side {
+            Side::Left(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    /// Return true if every value of a is contained in b.
+    pub fn is_subset<T, A, B>(a: A, b: B) -> bool
+    where
+        T: Ord,
+        A: IntoIterator<Item = T>,
+        B: IntoIterator<Item = T>,
+    {
+        Merge::new(a, b).all(|side| !matches!(side, Side::Left(_)))
+    }
+}
+
+// The origin of a value yielded by Merge.
+enum Side<T> {
+    Left(T),
+    Right(T),
+    // a value contained in both, taken from the left
+    Both(T),
+}
+
+// Merge two ascending iterators of unique values into an ascending sequence of tagged values.
+struct Merge<A, B>
+where
+    A: Iterator,
+    B: Iterator,
+{
+    a: Peekable<A>,
+    b: Peekable<B>,
+}
+
+impl<T, A, B> Merge<A, B>
+where
+    A: Iterator<Item = T>,
+    B: Iterator<Item = T>,
+{
+    fn new<I, J>(a: I, b: J) -> Self
+    where
+        I: IntoIterator<IntoIter = A>,
+        J: IntoIterator<IntoIter = B>,
+    {
+        Merge {
+            a: a.into_iter().peekable(),
+            b: b.into_iter().peekable(),
+        }
+    }
+}
+
+impl<T, A, B> Iterator for Merge<A, B>
+where
+    T: Ord,
+    A: Iterator<Item = T>,
+    B: Iterator<Item = T>,
+{
+    type Item = Side<T>;
+
+    fn next(&mut self) -> Option<Side<T>> {
+        let order = match (self.a.peek(), self.b.peek()) {
+            (Some(x), Some(y)) => x.cmp(y),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return None,
+        };
+        match order {
+            Ordering::Less => self.a.next().map(Side::Left),
+            Ordering::Greater => self.b.next().map(Side::Right),
+            Ordering::Equal => {
+                self.b.next();
+                self.a.next().map(Side::Both)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{difference, intersection, is_subset, sorted, union_into};
+    use crate::set::USet;
+    use std::collections::HashSet;
+
+    // Minimal USet for tests, since no implementation depends on this crate.
+    #[derive(Debug, Default)]
+    struct HashUSet(HashSet<i32>);
+
+    impl USet<i32> for HashUSet {
+        fn size(&self) -> usize {
+            self.0.len()
+        }
+        fn add(&mut self, x: i32) -> bool {
+            self.0.insert(x)
+        }
+        fn remove(&mut self, x: &i32) -> Option<i32> {
+            self.0.take(x)
+        }
+        fn find(&self, x: i32) -> Option<i32> {
+            self.0.get(&x).copied()
+        }
+        fn find_ref(&self, x: &i32) -> Option<&i32> {
+            self.0.get(x)
+        }
+    }
+
+    #[test]
+    fn probing_test() {
+        let mut set = HashUSet::default();
+        assert_eq!(union_into(&mut set, vec![1, 2, 3, 2]), 3);
+        assert_eq!(union_into(&mut set, 3..6), 2);
+        assert_eq!(set.size(), 5);
+
+        let values = [0, 2, 4, 6];
+        assert_eq!(
+            intersection(&values, &set).collect::<Vec<_>>(),
+            vec![&2, &4]
+        );
+        assert_eq!(difference(&values, &set).collect::<Vec<_>>(), vec![&0, &6]);
+        assert!(is_subset(&values[1..3], &set));
+        assert!(!is_subset(&values, &set));
+
+        // functions work behind dyn USet as well
+        let dyn_set: &mut dyn USet<i32> = &mut set;
+        assert_eq!(union_into(dyn_set, vec![0]), 1);
+        assert!(is_subset(&values[..3], dyn_set));
+    }
+
+    #[test]
+    fn sorted_test() {
+        let (a, b) = (vec![1, 3, 5, 7], vec![2, 3, 4, 7, 8]);
+        assert!(sorted::union(&a, &b).eq([1, 2, 3, 4, 5, 7, 8].iter()));
+        assert!(sorted::intersection(&a, &b).eq([3, 7].iter()));
+        assert!(sorted::difference(&a, &b).eq([1, 5].iter()));
+        assert!(sorted::difference(&b, &a).eq([2, 4, 8].iter()));
+        assert!(!sorted::is_subset(&a, &b));
+        assert!(sorted::is_subset(&[3, 7], &b));
+        assert!(sorted::is_subset(Vec::<i32>::new(), Vec::new()));
+        assert_eq!(sorted::union(a.clone(), Vec::new()).collect::<Vec<_>>(), a);
+    }
+}
@@ -0,0 +1,977 @@
+use interface::fmt::{debug_entries, format_with};
+use interface::heap_size::HeapSize;
+use interface::list::List;
+use interface::queue::{Deque, Queue, Stack};
+use std::fmt::{self, Debug, Display, Formatter};
+
+// Handle which represents the absence of a node.
+const NIL: u32 = u32::MAX;
+
+// Contiguous storage of nodes indexed by u32 handles.
+// Released slots are recycled by later allocations.
+#[derive(Debug)]
+struct Arena<N> {
+    nodes: Vec<N>,
+    free: Vec<u32>,
+}
+
+impl<N> Arena<N> {
+    fn new() -> Self {
+        Arena {
+            nodes: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    fn alloc(&mut self, node: N) -> u32 {
+        match self.free.pop() {
+            Some(h) => {
+                self.nodes[h as usize] = node;
+                h
+            }
+            None => {
+                let h = self.nodes.len();
+                if h >= NIL as usize {
+                    panic!("arena cannot hold more nodes. n: {}", h)
+                }
+                self.nodes.push(node);
+                h as u32
+            }
+        }
+    }
+
+    fn release(&mut self, h: u32) {
+        self.free.push(h);
+    }
+
+    fn capacity(&self) -> usize {
+        self.nodes.capacity()
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.nodes.capacity() * std::mem::size_of::<N>()
+            + self.free.capacity() * std::mem::size_of::<u32>()
+    }
+}
+
+impl<N> std::ops::Index<u32> for Arena<N> {
+    type Output = N;
+
+    fn index(&self, h: u32) -> &N {
+        &self.nodes[h as usize]
+    }
+}
+
+impl<N> std::ops::IndexMut<u32> for Arena<N> {
+    fn index_mut(&mut self, h: u32) -> &mut N {
+        &mut self.nodes[h as usize]
+    }
+}
+
+// Node whose next link can be relinked by sort_chain.
+trait Linked<T> {
+    fn value(&self) -> &T;
+    fn next(&self) -> u32;
+    fn set_next(&mut self, h: u32);
+}
+
+// Sort the chain which starts at head and is terminated by end, by relinking nodes with bottom-up merge sort.
+// Return the first and the last handles of the sorted chain, whose last node links to end.
+fn sort_chain<T, N>(arena: &mut Arena<N>, head: u32, end: u32) -> (u32, u32)
+where
+    T: Ord,
+    N: Linked<T>,
+{
+    let mut list = head;
+    let mut width = 1;
+    loop {
+        let mut p = list;
+        list = end;
+        let mut tail = end;
+        let mut merges = 0;
+        // merge each pair of adjacent runs of width nodes
+        while p != end {
+            merges += 1;
+            let mut q = p;
+            let mut psize = 0;
+            while psize < width && q != end {
+                psize += 1;
+                q = arena[q].next();
+            }
+            let mut qsize = width;
+            while psize > 0 || (qsize > 0 && q != end) {
+                // take from p on ties to keep the order of equal elements
+                let take_p =
+                    qsize == 0 || q == end || (psize > 0 && arena[p].value() <= arena[q].value());
+                let e = if take_p {
+                    let e = p;
+                    p = arena[p].next();
+                    psize -= 1;
+                    e
+                } else {
+                    let e = q;
+                    q = arena[q].next();
+                    qsize -= 1;
+                    e
+                };
+                if tail == end {
+                    list = e;
+                } else {
+                    arena[tail].set_next(e);
+                }
+                tail = e;
+            }
+            p = q;
+        }
+        if tail != end {
+            arena[tail].set_next(end);
+        }
+        if merges <= 1 {
+            return (list, tail);
+        }
+        width *= 2;
+    }
+}
+
+#[derive(Debug)]
+struct SNode<T> {
+    x: Option<T>,
+    next: u32,
+}
+
+impl<T> Linked<T> for SNode<T> {
+    fn value(&self) -> &T {
+        self.x.as_ref().expect("linked node must hold a value")
+    }
+    fn next(&self) -> u32 {
+        self.next
+    }
+    fn set_next(&mut self, h: u32) {
+        self.next = h;
+    }
+}
+
+/// Singly linked list whose nodes live in an arena instead of individual allocations.
+/// It is optimized for implementing stack and fifo queue interface.
+/// O(1): push(x), pop(), add(x), remove()
+pub struct ArenaSLList<T> {
+    arena: Arena<SNode<T>>,
+    head: u32,
+    tail: u32,
+    n: usize,
+}
+
+impl<T> Default for ArenaSLList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::iter::FromIterator<T> for ArenaSLList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        Queue::add_all(&mut list, iter);
+        list
+    }
+}
+
+/// Generate ArenaSLList holding given values in the order they are removed, like `vec![]`.
+#[macro_export]
+macro_rules! sl_list {
+    ($($x:expr),* $(,)?) => {
+        <$crate::arena::ArenaSLList<_> as ::std::iter::FromIterator<_>>::from_iter([$($x),*])
+    };
+}
+
+impl<T> ArenaSLList<T> {
+    /// Generate empty ArenaSLList.
+    pub fn new() -> Self {
+        ArenaSLList {
+            arena: Arena::new(),
+            head: NIL,
+            tail: NIL,
+            n: 0,
+        }
+    }
+
+    /// Return the number of elements.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Return the number of node slots allocated for the arena.
+    pub fn capacity(&self) -> usize {
+        self.arena.capacity()
+    }
+
+    /// Return an iterator which removes elements from the head, in the order of both pop() and remove().
+    /// Elements which aren't consumed are dropped with the iterator, so the list is left empty.
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain { list: self }
+    }
+
+    // Return values from the head to the tail.
+    fn values(&self) -> impl Iterator<Item = &T> + Clone + '_ {
+        let arena = &self.arena;
+        let handles = std::iter::successors(Some(self.head), move |&h| Some(arena[h].next));
+        handles
+            .take(self.n)
+            .filter_map(move |h| arena[h].x.as_ref())
+    }
+
+    // Unlink the head node and return its value.
+    fn remove_head(&mut self) -> Option<T> {
+        if self.n == 0 {
+            return None;
+        }
+        let h = self.head;
+        let x = self.arena[h].x.take();
+        self.head = self.arena[h].next;
+        self.arena.release(h);
+        self.n -= 1;
+        if self.n == 0 {
+            self.tail = NIL;
+        }
+        x
+    }
+
+    /// Sort elements in ascending order by relinking nodes with bottom-up merge sort.
+    /// Equal elements keep their relative order.
+    /// O(n log n) time, O(1) extra space
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        let (head, tail) = sort_chain(&mut self.arena, self.head, NIL);
+        self.head = head;
+        self.tail = tail;
+    }
+}
+
+impl<T> Stack<T> for ArenaSLList<T> {
+    fn push(&mut self, x: T) {
+        let h = self.arena.alloc(SNode {
+            x: Some(x),
+            next: self.head,
+        });
+        self.head = h;
+        if self.n == 0 {
+            self.tail = h;
+        }
+        self.n += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.remove_head()
+    }
+}
+
+impl<T> Queue<T> for ArenaSLList<T> {
+    fn add(&mut self, x: T) -> bool {
+        let h = self.arena.alloc(SNode {
+            x: Some(x),
+            next: NIL,
+        });
+        if self.n == 0 {
+            self.head = h;
+        } else {
+            self.arena[self.tail].next = h;
+        }
+        self.tail = h;
+        self.n += 1;
+        true
+    }
+
+    fn remove(&mut self) -> Option<T> {
+        self.remove_head()
+    }
+}
+
+impl<T> Debug for ArenaSLList<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArenaSLList")
+            .field("elements", &debug_entries(self.values()))
+            .field("size", &self.n)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl<T> Display for ArenaSLList<T>
+where
+    T: Display,
+{
+    // Elements are written from the head, which is the next one to be removed.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        Display::fmt(&format_with(self.values(), ", "), f)?;
+        f.write_str("]")
+    }
+}
+
+/// Iterator which removes elements from ArenaSLList.
+#[derive(Debug)]
+pub struct Drain<'a, T> {
+    list: &'a mut ArenaSLList<T>,
+}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.remove_head()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.n, Some(self.list.n))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> {}
+
+impl<'a, T> Drop for Drain<'a, T> {
+    fn drop(&mut self) {
+        while self.list.remove_head().is_some() {}
+    }
+}
+
+#[derive(Debug)]
+struct DNode<T> {
+    x: Option<T>,
+    prev: u32,
+    next: u32,
+}
+
+impl<T> Linked<T> for DNode<T> {
+    fn value(&self) -> &T {
+        self.x.as_ref().expect("linked node must hold a value")
+    }
+    fn next(&self) -> u32 {
+        self.next
+    }
+    fn set_next(&mut self, h: u32) {
+        self.next = h;
+    }
+}
+
+/// Doubly linked list whose nodes live in an arena instead of individual allocations.
+/// A dummy node at handle 0 links the last node to the first one.
+/// O(1 + min{i, n - i}): get(i), set(i, x), add(i, x), remove(i)
+pub struct ArenaDLList<T> {
+    arena: Arena<DNode<T>>,
+    n: usize,
+}
+
+impl<T> Default for ArenaDLList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::iter::FromIterator<T> for ArenaDLList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        for x in iter {
+            list.add_last(x);
+        }
+        list
+    }
+}
+
+/// Generate ArenaDLList holding given values in order, like `vec![]`.
+#[macro_export]
+macro_rules! dl_list {
+    ($($x:expr),* $(,)?) => {
+        <$crate::arena::ArenaDLList<_> as ::std::iter::FromIterator<_>>::from_iter([$($x),*])
+    };
+}
+
+impl<T> ArenaDLList<T> {
+    // Handle of the dummy node.
+    const DUMMY: u32 = 0;
+
+    /// Generate empty ArenaDLList.
+    pub fn new() -> Self {
+        let mut arena = Arena::new();
+        arena.alloc(DNode {
+            x: None,
+            prev: Self::DUMMY,
+            next: Self::DUMMY,
+        });
+        ArenaDLList { arena, n: 0 }
+    }
+
+    /// Return the number of node slots allocated for the arena.
+    pub fn capacity(&self) -> usize {
+        self.arena.capacity()
+    }
+
+    /// Return an iterator from the first element to the last one, which can be reversed.
+    pub fn iter(&self) -> DLIter<'_, T> {
+        DLIter {
+            arena: &self.arena,
+            front: self.arena[Self::DUMMY].next,
+            back: self.arena[Self::DUMMY].prev,
+            n: self.n,
+        }
+    }
+
+    fn within_bound(&self, i: usize) -> bool {
+        i < self.n
+    }
+
+    // Return the handle of the node at the position i, or the dummy node if i == n.
+    fn get_node(&self, i: usize) -> u32 {
+        if i < self.n / 2 {
+            let mut h = self.arena[Self::DUMMY].next;
+            for _ in 0..i {
+                h = self.arena[h].next;
+            }
+            h
+        } else {
+            let mut h = Self::DUMMY;
+            for _ in i..self.n {
+                h = self.arena[h].prev;
+            }
+            h
+        }
+    }
+
+    fn add_before(&mut self, w: u32, x: T) {
+        let prev = self.arena[w].prev;
+        let u = self.arena.alloc(DNode {
+            x: Some(x),
+            prev,
+            next: w,
+        });
+        self.arena[prev].next = u;
+        self.arena[w].prev = u;
+        self.n += 1;
+    }
+
+    fn unlink(&mut self, w: u32) -> Option<T> {
+        let (prev, next) = (self.arena[w].prev, self.arena[w].next);
+        self.arena[prev].next = next;
+        self.arena[next].prev = prev;
+        let x = self.arena[w].x.take();
+        self.arena.release(w);
+        self.n -= 1;
+        x
+    }
+
+    /// Sort elements in ascending order by relinking nodes with bottom-up merge sort.
+    /// Equal elements keep their relative order.
+    /// O(n log n) time, O(1) extra space
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        let head = self.arena[Self::DUMMY].next;
+        let (head, _) = sort_chain(&mut self.arena, head, Self::DUMMY);
+        // sort_chain only relinks next, so prev links are rebuilt along the sorted chain
+        self.arena[Self::DUMMY].next = head;
+        let (mut prev, mut h) = (Self::DUMMY, head);
+        while h != Self::DUMMY {
+            self.arena[h].prev = prev;
+            prev = h;
+            h = self.arena[h].next;
+        }
+        self.arena[Self::DUMMY].prev = prev;
+    }
+}
+
+impl<T> List<T> for ArenaDLList<T> {
+    fn size(&self) -> usize {
+        self.n
+    }
+
+    fn get(&self, i: usize) -> Option<&T> {
+        if !self.within_bound(i) {
+            None
+        } else {
+            self.arena[self.get_node(i)].x.as_ref()
+        }
+    }
+
+    fn set(&mut self, i: usize, x: T) -> Option<T> {
+        if !self.within_bound(i) {
+            panic!(
+                "index must be positive and less than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
+        let h = self.get_node(i);
+        self.arena[h].x.replace(x)
+    }
+
+    fn add(&mut self, i: usize, x: T) {
+        if i > self.n {
+            panic!(
+                "index must be positive and not greater than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
+        let w = self.get_node(i);
+        self.add_before(w, x);
+    }
+
+    fn remove(&mut self, i: usize) -> Option<T> {
+        if !self.within_bound(i) {
+            return None;
+        }
+        let w = self.get_node(i);
+        self.unlink(w)
+    }
+}
+
+impl<T> Deque<T> for ArenaDLList<T> {
+    fn add_first(&mut self, x: T) {
+        self.add(0, x);
+    }
+    fn remove_first(&mut self) -> Option<T> {
+        self.remove(0)
+    }
+    fn add_last(&mut self, x: T) {
+        self.add(self.size(), x)
+    }
+    fn remove_last(&mut self) -> Option<T> {
+        if self.n == 0 {
+            return None;
+        }
+        self.remove(self.n - 1)
+    }
+}
+
+/// Iterator over elements of ArenaDLList.
+#[derive(Debug)]
+pub struct DLIter<'a, T> {
+    arena: &'a Arena<DNode<T>>,
+    front: u32,
+    back: u32,
+    // the number of remaining elements, which stops both ends when they meet
+    n: usize,
+}
+
+impl<'a, T> Iterator for DLIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.n == 0 {
+            return None;
+        }
+        let node = &self.arena[self.front];
+        self.front = node.next;
+        self.n -= 1;
+        node.x.as_ref()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.n, Some(self.n))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for DLIter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.n == 0 {
+            return None;
+        }
+        let node = &self.arena[self.back];
+        self.back = node.prev;
+        self.n -= 1;
+        node.x.as_ref()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for DLIter<'a, T> {}
+
+// Implemented by hand, since derive would require T: Clone.
+impl<'a, T> Clone for DLIter<'a, T> {
+    fn clone(&self) -> Self {
+        DLIter { ..*self }
+    }
+}
+
+impl<T> Debug for ArenaDLList<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArenaDLList")
+            .field("elements", &debug_entries(self.iter()))
+            .field("size", &self.n)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl<T> Display for ArenaDLList<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        Display::fmt(&format_with(self.iter(), ", "), f)?;
+        f.write_str("]")
+    }
+}
+
+impl<T> HeapSize for ArenaSLList<T> {
+    fn heap_bytes(&self) -> usize {
+        self.arena.heap_bytes()
+    }
+}
+
+impl<T> HeapSize for ArenaDLList<T> {
+    fn heap_bytes(&self) -> usize {
+        self.arena.heap_bytes()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::{ArenaDLList, ArenaSLList};
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::list::List;
+    use interface::queue::{Queue, Stack};
+
+    // Decode operations, where op % 4 chooses pushing, adding, or adding and popping.
+    pub(super) fn sl_ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Decode operations, each of which adds a value at i and removes one there if op % 4 == 0.
+    pub(super) fn dl_ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, usize, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, usize, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Values are added to either end and sometimes removed, so that the arena can have recycled slots.
+    impl<'a, T> Arbitrary<'a> for ArenaSLList<T>
+    where
+        T: Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut list = ArenaSLList::new();
+            for (op, x) in sl_ops(u)? {
+                match op % 4 {
+                    0 => list.push(x),
+                    1 | 2 => {
+                        list.add(x);
+                    }
+                    _ => {
+                        list.add(x);
+                        list.pop();
+                    }
+                }
+            }
+            Ok(list)
+        }
+    }
+
+    // Values are added at arbitrary positions and sometimes removed, so that the arena can have recycled slots.
+    impl<'a, T> Arbitrary<'a> for ArenaDLList<T>
+    where
+        T: Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut list = ArenaDLList::new();
+            for (op, i, x) in dl_ops(u)? {
+                list.add(i % (list.size() + 1), x);
+                if op % 4 == 0 {
+                    list.remove(i % list.size());
+                }
+            }
+            Ok(list)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArenaDLList, ArenaSLList};
+
+    #[test]
+    fn list_conformance_test() {
+        let mut list: ArenaDLList<i32> = ArenaDLList::new();
+        test_support::conformance::check_list(&mut list);
+    }
+
+    #[test]
+    fn stack_conformance_test() {
+        let mut stack: ArenaSLList<i32> = ArenaSLList::new();
+        test_support::conformance::check_stack(&mut stack);
+    }
+
+    #[test]
+    fn sl_list_test() {
+        use interface::queue::{Queue, Stack};
+
+        let mut list: ArenaSLList<i32> = ArenaSLList::new();
+        assert_eq!(list.pop(), None);
+        assert_eq!(list.remove(), None);
+
+        list.push(2);
+        list.push(1);
+        assert!(list.add(3));
+        assert_eq!(list.size(), 3);
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.remove(), Some(2));
+        assert_eq!(list.remove(), Some(3));
+        assert_eq!(list.size(), 0);
+
+        // released slots are reused
+        let capacity = list.capacity();
+        assert!(list.add(4));
+        list.push(5);
+        assert_eq!(list.capacity(), capacity);
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), None);
+    }
+
+    #[test]
+    fn dl_list_test() {
+        use interface::list::List;
+
+        let mut list: ArenaDLList<char> = ArenaDLList::new();
+        assert_eq!(list.size(), 0);
+        assert_eq!(list.get(0), None);
+
+        list.add(0, 'b');
+        list.add(1, 'd');
+        list.add(1, 'r');
+        list.add(2, 'e');
+        list.add(0, 'a');
+        assert_eq!(list.size(), 5);
+        let expected = ['a', 'b', 'r', 'e', 'd'];
+        for (i, x) in expected.iter().enumerate() {
+            assert_eq!(list.get(i), Some(x));
+        }
+
+        assert_eq!(list.remove(0), Some('a'));
+        assert_eq!(list.remove(3), Some('d'));
+        assert_eq!(list.remove(3), None);
+        assert_eq!(list.set(1, 'i'), Some('r'));
+        assert_eq!(list.get(0), Some(&'b'));
+        assert_eq!(list.get(1), Some(&'i'));
+        assert_eq!(list.get(2), Some(&'e'));
+        assert_eq!(list.size(), 3);
+    }
+
+    #[test]
+    fn deque_test() {
+        use interface::queue::Deque;
+
+        let mut deque: ArenaDLList<i32> = ArenaDLList::new();
+        deque.add_first(1);
+        deque.add_last(2);
+        deque.add_first(0);
+        deque.add_last(5);
+
+        assert_eq!(deque.remove_first(), Some(0));
+        assert_eq!(deque.remove_last(), Some(5));
+        assert_eq!(deque.remove_last(), Some(2));
+        assert_eq!(deque.remove_first(), Some(1));
+        assert_eq!(deque.remove_first(), None);
+        assert_eq!(deque.remove_last(), None);
+    }
+
+    #[test]
+    fn macro_test() {
+        let mut sl: ArenaSLList<char> = crate::sl_list!['a', 'b'];
+        assert_eq!(sl.size(), 2);
+        assert!(sl.drain().eq(['a', 'b']));
+        let dl: ArenaDLList<i32> = crate::dl_list![3, 1, 2];
+        assert!(dl.iter().rev().eq([2, 1, 3].iter()));
+    }
+
+    #[test]
+    fn drain_test() {
+        use interface::queue::{Queue, Stack};
+
+        let mut list: ArenaSLList<i32> = ArenaSLList::new();
+        for x in 0..4 {
+            list.add(x);
+        }
+        list.push(-1);
+        assert_eq!(list.to_string(), "[-1, 0, 1, 2, 3]");
+        assert_eq!(
+            format!("{:?}", list),
+            format!(
+                "ArenaSLList {{ elements: [-1, 0, 1, 2, 3], size: 5, capacity: {} }}",
+                list.capacity()
+            )
+        );
+        let mut sum = 0;
+        for x in list.drain() {
+            sum += x;
+        }
+        assert_eq!(sum, 5);
+        assert_eq!(list.size(), 0);
+
+        for x in 0..4 {
+            list.add(x);
+        }
+        assert!(list.drain().take(2).eq(0..2));
+        assert_eq!(list.size(), 0);
+        assert_eq!(list.pop(), None);
+        list.push(7);
+        assert_eq!(list.remove(), Some(7));
+    }
+
+    #[test]
+    fn iter_test() {
+        use interface::list::List;
+        use interface::queue::Deque;
+
+        let mut list: ArenaDLList<i32> = ArenaDLList::new();
+        assert_eq!(list.iter().next(), None);
+        for x in 0..6 {
+            list.add_last(x);
+        }
+        list.remove(2);
+        list.add_first(-1);
+        let expected = [-1, 0, 1, 3, 4, 5];
+        assert!(list.iter().eq(expected.iter()));
+        assert_eq!(format!("{:+}", list), "[-1, +0, +1, +3, +4, +5]");
+        assert!(format!("{:?}", list)
+            .starts_with("ArenaDLList { elements: [-1, 0, 1, 3, 4, 5], size: 6"));
+        assert!(list.iter().rev().eq(expected.iter().rev()));
+        let mut iter = list.iter();
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.next(), Some(&-1));
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next_back(), Some(&4));
+        assert!(iter.eq([0, 1, 3].iter()));
+    }
+
+    #[test]
+    fn sort_test() {
+        use interface::list::List;
+        use interface::queue::{Deque, Queue};
+
+        let mut sl: ArenaSLList<(u32, usize)> = ArenaSLList::new();
+        sl.sort();
+        assert_eq!(sl.remove(), None);
+        let mut dl: ArenaDLList<(u32, usize)> = ArenaDLList::new();
+        dl.sort();
+        assert_eq!(dl.size(), 0);
+
+        // pseudo random keys by linear congruential generator
+        let mut x: u64 = 9;
+        let mut values = Vec::new();
+        for i in 0..1000 {
+            x = x
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            values.push(((x >> 58) as u32, i));
+        }
+        for v in values.iter() {
+            sl.add(*v);
+            dl.add_last(*v);
+        }
+        let capacity = sl.capacity();
+        sl.sort();
+        dl.sort();
+        // nodes are relinked without allocation
+        assert_eq!(sl.capacity(), capacity);
+        values.sort_by_key(|v| v.0);
+
+        // the tail is updated, so that adding to the end keeps the order
+        assert!(sl.add((64, 1000)));
+        dl.add_last((64, 1000));
+        values.push((64, 1000));
+        for (i, v) in values.iter().enumerate() {
+            assert_eq!(dl.get(i), Some(v));
+        }
+        for v in values.iter().rev() {
+            assert_eq!(dl.remove_last(), Some(*v));
+        }
+        for v in values.iter() {
+            assert_eq!(sl.remove(), Some(*v));
+        }
+        assert_eq!(sl.remove(), None);
+    }
+
+    #[test]
+    fn heap_size_test() {
+        use interface::heap_size::HeapSize;
+        use interface::list::List;
+
+        let mut list: ArenaDLList<u32> = ArenaDLList::new();
+        for x in 0..10 {
+            list.add(0, x);
+        }
+        list.remove(0);
+        list.add(0, 9);
+        let bytes = list.heap_bytes();
+        assert!(bytes >= 11 * std::mem::size_of::<super::DNode<u32>>());
+        // a released slot is reused without growing the arena
+        list.remove(0);
+        list.add(0, 10);
+        assert_eq!(list.heap_bytes(), bytes);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use interface::list::List;
+        use std::collections::VecDeque;
+
+        test_support::replay::check_arbitrary(
+            |(mut sl, dl): (ArenaSLList<u16>, ArenaDLList<u16>), u| {
+                // replay the same operations on VecDeque and Vec
+                let mut expected_sl = VecDeque::new();
+                for (op, x) in super::arb::sl_ops::<u16>(u).unwrap() {
+                    match op % 4 {
+                        0 => expected_sl.push_front(x),
+                        1 | 2 => expected_sl.push_back(x),
+                        _ => {
+                            expected_sl.push_back(x);
+                            expected_sl.pop_front();
+                        }
+                    }
+                }
+                let mut expected_dl = Vec::new();
+                for (op, i, x) in super::arb::dl_ops::<u16>(u).unwrap() {
+                    expected_dl.insert(i % (expected_dl.len() + 1), x);
+                    if op % 4 == 0 {
+                        expected_dl.remove(i % expected_dl.len());
+                    }
+                }
+
+                // every slot of the arena holds a linked node or is free, and free slots are distinct
+                let mut free = sl.arena.free.clone();
+                free.sort_unstable();
+                free.dedup();
+                assert_eq!(free.len(), sl.arena.free.len());
+                assert_eq!(sl.arena.nodes.len(), sl.size() + free.len());
+                let mut free = dl.arena.free.clone();
+                free.sort_unstable();
+                free.dedup();
+                assert_eq!(free.len(), dl.arena.free.len());
+                // the dummy node occupies a slot of ArenaDLList
+                assert_eq!(dl.arena.nodes.len(), dl.size() + free.len() + 1);
+
+                assert!(sl.drain().eq(expected_sl.into_iter()));
+                assert!(dl.iter().eq(expected_dl.iter()));
+                assert!(dl.iter().rev().eq(expected_dl.iter().rev()));
+                assert!((0..dl.size())
+                    .map(|i| dl.get(i))
+                    .eq(expected_dl.iter().map(Some)));
+            },
+        );
+    }
+}
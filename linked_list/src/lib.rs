@@ -0,0 +1,7 @@
+#![warn(missing_docs)]
+//! linked_list implements List and Queue interfaces with nodes linked by references.
+
+/// Implementation for singly and doubly linked lists whose nodes live in a contiguous arena.
+pub mod arena;
+/// Implementation for doubly linked list which stores XOR of neighbor addresses in each node.
+pub mod xor_list;
@@ -0,0 +1,433 @@
+use interface::fmt::{debug_entries, format_with};
+use interface::heap_size::HeapSize;
+use interface::queue::Deque;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::ptr;
+
+/// Doubly linked list which stores prev ⊕ next in a single link of each node.
+/// Traversal from either end recovers the neighbor by XOR-ing the link with the node it came from.
+/// O(1): add_first(x), remove_first(), add_last(x), remove_last()
+pub struct XorList<T> {
+    head: *mut Node<T>,
+    tail: *mut Node<T>,
+    n: usize,
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    x: T,
+    // address of prev XOR address of next, where null is 0
+    link: usize,
+}
+
+unsafe impl<T: Send> Send for XorList<T> {}
+unsafe impl<T: Sync> Sync for XorList<T> {}
+
+// Addresses are exposed so that a pointer can be recovered from XOR-ed links.
+fn addr<T>(p: *mut Node<T>) -> usize {
+    p.expose_provenance()
+}
+
+fn from_addr<T>(a: usize) -> *mut Node<T> {
+    ptr::with_exposed_provenance_mut(a)
+}
+
+impl<T> Default for XorList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> XorList<T> {
+    /// Generate empty XorList.
+    pub fn new() -> Self {
+        XorList {
+            head: ptr::null_mut(),
+            tail: ptr::null_mut(),
+            n: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the number of elements.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Return an iterator from the first element to the last one, which can be reversed.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front_prev: ptr::null_mut(),
+            front: self.head,
+            back_next: ptr::null_mut(),
+            back: self.tail,
+            n: self.n,
+            _marker: PhantomData,
+        }
+    }
+
+    // Link a new node outside of end, and make it the new end.
+    fn push_end(end: &mut *mut Node<T>, other_end: &mut *mut Node<T>, x: T) {
+        let node = Box::into_raw(Box::new(Node {
+            x,
+            link: addr(*end),
+        }));
+        if end.is_null() {
+            *other_end = node;
+        } else {
+            // end was linked to null outside, now to node
+            unsafe { (**end).link ^= addr(node) };
+        }
+        *end = node;
+    }
+
+    // Unlink the node at end, and make its neighbor the new end.
+    fn pop_end(end: &mut *mut Node<T>, other_end: &mut *mut Node<T>) -> Option<T> {
+        if end.is_null() {
+            return None;
+        }
+        let node = unsafe { Box::from_raw(*end) };
+        let next: *mut Node<T> = from_addr(node.link);
+        if next.is_null() {
+            *other_end = ptr::null_mut();
+        } else {
+            unsafe { (*next).link ^= addr(*end) };
+        }
+        *end = next;
+        Some(node.x)
+    }
+}
+
+impl<T> Drop for XorList<T> {
+    fn drop(&mut self) {
+        while self.remove_first().is_some() {}
+    }
+}
+
+impl<T> Deque<T> for XorList<T> {
+    fn add_first(&mut self, x: T) {
+        Self::push_end(&mut self.head, &mut self.tail, x);
+        self.n += 1;
+    }
+    fn remove_first(&mut self) -> Option<T> {
+        let x = Self::pop_end(&mut self.head, &mut self.tail)?;
+        self.n -= 1;
+        Some(x)
+    }
+    fn add_last(&mut self, x: T) {
+        Self::push_end(&mut self.tail, &mut self.head, x);
+        self.n += 1;
+    }
+    fn remove_last(&mut self) -> Option<T> {
+        let x = Self::pop_end(&mut self.tail, &mut self.head)?;
+        self.n -= 1;
+        Some(x)
+    }
+}
+
+impl<T> HeapSize for XorList<T> {
+    fn heap_bytes(&self) -> usize {
+        self.n * std::mem::size_of::<Node<T>>()
+    }
+}
+
+impl<T> Debug for XorList<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XorList")
+            .field("elements", &debug_entries(self.iter()))
+            .field("size", &self.n)
+            .finish()
+    }
+}
+
+impl<T> Display for XorList<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        Display::fmt(&format_with(self.iter(), ", "), f)?;
+        f.write_str("]")
+    }
+}
+
+/// Iterator over elements of XorList.
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    front_prev: *mut Node<T>,
+    front: *mut Node<T>,
+    back_next: *mut Node<T>,
+    back: *mut Node<T>,
+    // the number of remaining elements, which stops both ends when they meet
+    n: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    // Return the node at cur, and step away from came_from in the same direction.
+    fn step(came_from: &mut *mut Node<T>, cur: &mut *mut Node<T>) -> &'a T {
+        let node = unsafe { &**cur };
+        let next = from_addr(node.link ^ addr(*came_from));
+        *came_from = *cur;
+        *cur = next;
+        &node.x
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.n == 0 {
+            return None;
+        }
+        self.n -= 1;
+        Some(Self::step(&mut self.front_prev, &mut self.front))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.n, Some(self.n))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.n == 0 {
+            return None;
+        }
+        self.n -= 1;
+        Some(Self::step(&mut self.back_next, &mut self.back))
+    }
+}
+
+// Implemented by hand, since derive would require T: Clone.
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::XorList;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+    use interface::queue::Deque;
+
+    // Decode operations, where op % 4 chooses adding to either end,
+    // or adding to one end and removing from the other.
+    pub(super) fn ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Values are added to either end and sometimes removed, so that both ends are relinked.
+    impl<'a, T> Arbitrary<'a> for XorList<T>
+    where
+        T: Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut deque = XorList::new();
+            for (op, x) in ops(u)? {
+                match op % 4 {
+                    0 => deque.add_first(x),
+                    1 => deque.add_last(x),
+                    2 => {
+                        deque.add_last(x);
+                        deque.remove_first();
+                    }
+                    _ => {
+                        deque.add_first(x);
+                        deque.remove_last();
+                    }
+                }
+            }
+            Ok(deque)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XorList;
+    use interface::queue::Deque;
+
+    #[test]
+    fn deque_test() {
+        let mut deque: XorList<i32> = XorList::new();
+        assert_eq!(deque.remove_first(), None);
+        assert_eq!(deque.remove_last(), None);
+
+        deque.add_first(1);
+        deque.add_last(2);
+        deque.add_first(0);
+        deque.add_last(5);
+        assert_eq!(deque.size(), 4);
+        assert_eq!(deque.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2, 5]);
+        assert_eq!(deque.to_string(), "[0, 1, 2, 5]");
+        assert_eq!(
+            format!("{:?}", deque),
+            "XorList { elements: [0, 1, 2, 5], size: 4 }"
+        );
+
+        assert_eq!(deque.remove_first(), Some(0));
+        assert_eq!(deque.remove_last(), Some(5));
+        assert_eq!(deque.remove_last(), Some(2));
+        assert_eq!(deque.remove_first(), Some(1));
+        assert_eq!(deque.remove_first(), None);
+        assert_eq!(deque.remove_last(), None);
+        assert_eq!(deque.size(), 0);
+
+        deque.add_last(3);
+        assert_eq!(deque.remove_first(), Some(3));
+        deque.add_first(4);
+        assert_eq!(deque.remove_last(), Some(4));
+    }
+
+    #[test]
+    fn interleaved_test() {
+        let mut deque: XorList<usize> = XorList::new();
+        let mut expected = std::collections::VecDeque::new();
+        for x in 0..100 {
+            match x % 5 {
+                0 | 1 => {
+                    deque.add_last(x);
+                    expected.push_back(x);
+                }
+                2 | 3 => {
+                    deque.add_first(x);
+                    expected.push_front(x);
+                }
+                _ => {
+                    assert_eq!(deque.remove_last(), expected.pop_back());
+                    assert_eq!(deque.remove_first(), expected.pop_front());
+                }
+            }
+            assert_eq!(deque.size(), expected.len());
+        }
+        assert!(deque.iter().eq(expected.iter()));
+        assert!(deque.iter().rev().eq(expected.iter().rev()));
+        // both ends stop where they meet
+        let mut iter = deque.iter();
+        let (mut front, mut back): (Vec<&usize>, Vec<&usize>) = (Vec::new(), Vec::new());
+        while let Some(x) = iter.next() {
+            front.push(x);
+            back.extend(iter.next_back());
+        }
+        front.extend(back.into_iter().rev());
+        assert!(front.into_iter().eq(expected.iter()));
+    }
+
+    #[test]
+    fn drop_test() {
+        let counter = std::rc::Rc::new(());
+        {
+            let mut deque = XorList::new();
+            for _ in 0..10 {
+                deque.add_last(counter.clone());
+            }
+            deque.remove_first();
+            assert_eq!(std::rc::Rc::strong_count(&counter), 10);
+        }
+        assert_eq!(std::rc::Rc::strong_count(&counter), 1);
+    }
+
+    // Regression tests for aliasing of raw links, which are meaningful under Miri.
+    #[test]
+    fn aliasing_test() {
+        let mut deque: XorList<String> = XorList::new();
+        for x in 0..8 {
+            deque.add_last(x.to_string());
+        }
+        // shared references from both ends and from cloned iterators coexist
+        let iter = deque.iter();
+        let front = iter.clone().collect::<Vec<_>>();
+        let back = iter.rev().collect::<Vec<_>>();
+        assert!(front
+            .iter()
+            .zip(back.iter().rev())
+            .all(|(a, b)| std::ptr::eq(*a, *b)));
+        let addrs = front
+            .iter()
+            .map(|x| *x as *const String)
+            .collect::<Vec<_>>();
+
+        // nodes stay in place while the list itself moves and its ends are relinked
+        let mut deque = Box::new(deque);
+        deque.add_first("first".to_string());
+        deque.add_last("last".to_string());
+        assert!(deque
+            .iter()
+            .skip(1)
+            .take(8)
+            .map(|x| x as *const String)
+            .eq(addrs.into_iter()));
+        assert_eq!(deque.remove_first().as_deref(), Some("first"));
+        assert_eq!(deque.remove_last().as_deref(), Some("last"));
+
+        // removal from both ends until they meet frees each node once
+        while deque.size() > 1 {
+            deque.remove_first();
+            deque.remove_last();
+        }
+        assert_eq!(deque.iter().count(), 0);
+        deque.add_last("x".to_string());
+        assert_eq!(deque.iter().next_back().map(String::as_str), Some("x"));
+    }
+
+    #[test]
+    fn send_test() {
+        let mut deque = XorList::new();
+        for x in 0..10 {
+            deque.add_last(Box::new(x));
+        }
+        let mut deque = std::thread::spawn(move || {
+            deque.remove_first();
+            deque.add_first(Box::new(-1));
+            deque
+        })
+        .join()
+        .unwrap();
+        assert_eq!(deque.remove_first().map(|x| *x), Some(-1));
+        assert!(deque.iter().map(|x| **x).eq(1..10));
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use std::collections::VecDeque;
+
+        test_support::replay::check_arbitrary(|deque: XorList<u16>, u| {
+            // replay the same operations on VecDeque
+            let mut expected = VecDeque::new();
+            for (op, x) in super::arb::ops::<u16>(u).unwrap() {
+                match op % 4 {
+                    0 => expected.push_front(x),
+                    1 => expected.push_back(x),
+                    2 => {
+                        expected.push_back(x);
+                        expected.pop_front();
+                    }
+                    _ => {
+                        expected.push_front(x);
+                        expected.pop_back();
+                    }
+                }
+            }
+            // links are consistent in both directions
+            assert_eq!(deque.size(), expected.len());
+            assert!(deque.iter().eq(expected.iter()));
+            assert!(deque.iter().rev().eq(expected.iter().rev()));
+        });
+    }
+}
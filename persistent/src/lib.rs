@@ -0,0 +1,13 @@
+#![warn(missing_docs)]
+//! persistent implements immutable structures whose updates return a new version sharing structure with the old one.
+
+/// Implementation for persistent singly linked list.
+pub mod plist;
+/// Implementation for persistent vector.
+pub mod pvector;
+/// Implementation for persistent treap.
+pub mod treap;
+
+pub use plist::PList;
+pub use pvector::PVector;
+pub use treap::PersistentTreap;
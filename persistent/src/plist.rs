@@ -0,0 +1,277 @@
+use interface::fmt::{debug_entries, format_with};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::rc::Rc;
+
+/// Persistent singly linked list (cons list) whose nodes are shared by Rc.
+/// Adding a value returns a new list which shares all nodes of the old one.
+/// O(1): push_front(x), front(), tail(), size(), clone()
+pub struct PList<T> {
+    head: Link<T>,
+}
+
+type Link<T> = Option<Rc<Node<T>>>;
+
+struct Node<T> {
+    x: T,
+    // the number of elements from this node to the end
+    n: usize,
+    next: Link<T>,
+}
+
+impl<T> Default for PList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for PList<T> {
+    fn clone(&self) -> Self {
+        PList {
+            head: self.head.clone(),
+        }
+    }
+}
+
+impl<T> PList<T> {
+    /// Generate empty PList.
+    pub fn new() -> Self {
+        PList { head: None }
+    }
+
+    /// Return the number of elements.
+    pub fn size(&self) -> usize {
+        self.head.as_ref().map_or(0, |node| node.n)
+    }
+
+    /// Return a new list which has a value at the head followed by this list.
+    pub fn push_front(&self, x: T) -> Self {
+        PList {
+            head: Some(Rc::new(Node {
+                x,
+                n: self.size() + 1,
+                next: self.head.clone(),
+            })),
+        }
+    }
+
+    /// Return the value at the head.
+    pub fn front(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.x)
+    }
+
+    /// Return a list without the head, which shares the rest of this list.
+    /// Return empty list if this list is empty.
+    pub fn tail(&self) -> Self {
+        PList {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+        }
+    }
+
+    /// Return the value at the position i.
+    pub fn get(&self, i: usize) -> Option<&T> {
+        self.iter().nth(i)
+    }
+
+    /// Return true if both lists share the same head node.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Return an iterator from the head to the end.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<T> Drop for PList<T> {
+    // Release nodes iteratively while they are not shared, to avoid deep recursion on long lists.
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+        while let Some(node) = head {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => head = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl<T> Debug for PList<T>
+where
+    T: Debug,
+{
+    // Elements are written by iteration instead of nesting nodes, which would recurse once per node.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PList")
+            .field("elements", &debug_entries(self.iter()))
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+impl<T> Display for PList<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        Display::fmt(&format_with(self.iter(), ", "), f)?;
+        f.write_str("]")
+    }
+}
+
+impl<T> std::iter::FromIterator<T> for PList<T> {
+    // The first value of an iterator becomes the head.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values = iter.into_iter().collect::<Vec<_>>();
+        values
+            .into_iter()
+            .rev()
+            .fold(PList::new(), |list, x| list.push_front(x))
+    }
+}
+
+/// Iterator over elements of PList.
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter { next: self.next }
+    }
+}
+
+impl<'a, T> Debug for Iter<'a, T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Iter")
+            .field(&debug_entries(self.clone()))
+            .finish()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.next?;
+        self.next = node.next.as_deref();
+        Some(&node.x)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::PList;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // Decode the values, the first of which becomes the head.
+    pub(super) fn values<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<T>>
+    where
+        T: Arbitrary<'a>,
+    {
+        u.arbitrary_iter()?.collect()
+    }
+
+    impl<'a, T> Arbitrary<'a> for PList<T>
+    where
+        T: Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(values(u)?.into_iter().collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PList;
+
+    #[test]
+    fn plist_test() {
+        let empty: PList<i32> = PList::new();
+        assert_eq!(empty.size(), 0);
+        assert_eq!(empty.front(), None);
+        assert_eq!(empty.tail().size(), 0);
+
+        let a = empty.push_front(3).push_front(2);
+        let b = a.push_front(1);
+        let c = a.push_front(0);
+        assert_eq!(b.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(c.iter().copied().collect::<Vec<_>>(), vec![0, 2, 3]);
+        assert_eq!(a.size(), 2);
+        assert_eq!(b.size(), 3);
+        assert_eq!(b.front(), Some(&1));
+        assert_eq!(b.get(2), Some(&3));
+        assert_eq!(b.get(3), None);
+
+        // both versions share the common suffix
+        assert!(b.tail().ptr_eq(&a));
+        assert!(c.tail().ptr_eq(&a));
+        assert!(b.clone().ptr_eq(&b));
+        assert!(!b.ptr_eq(&c));
+    }
+
+    #[test]
+    fn from_iter_test() {
+        let list = (0..5).collect::<PList<_>>();
+        assert_eq!(list.size(), 5);
+        assert_eq!((&list).into_iter().sum::<i32>(), 10);
+        assert_eq!(list.front(), Some(&0));
+    }
+
+    #[test]
+    fn fmt_test() {
+        let list = (0..3).collect::<PList<_>>();
+        assert_eq!(list.to_string(), "[0, 1, 2]");
+        assert_eq!(
+            format!("{:?}", list),
+            "PList { elements: [0, 1, 2], size: 3 }"
+        );
+        assert_eq!(format!("{:?}", list.tail().iter()), "Iter([1, 2])");
+        assert_eq!(PList::<i32>::new().to_string(), "[]");
+    }
+
+    #[test]
+    fn long_list_drop_test() {
+        let n = if cfg!(miri) { 10_000 } else { 1_000_000 };
+        let list = (0..n).collect::<PList<u32>>();
+        let shared = list.tail().tail();
+        // Debug doesn't recurse along the list either
+        assert!(format!("{:?}", list).ends_with(&format!("size: {} }}", n)));
+        drop(list);
+        assert_eq!(shared.size(), n as usize - 2);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        test_support::replay::check_arbitrary(|list: PList<u8>, u| {
+            // replay the same values on Vec, whose first value becomes the head
+            let expected = super::arb::values::<u8>(u).unwrap();
+            assert_eq!(list.size(), expected.len());
+            assert!(list.iter().eq(expected.iter()));
+            assert!((0..list.size())
+                .map(|i| list.get(i))
+                .eq(expected.iter().map(Some)));
+        });
+    }
+}
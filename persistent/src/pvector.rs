@@ -0,0 +1,505 @@
+use interface::fmt::{debug_entries, format_with};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::rc::Rc;
+
+const BITS: u32 = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+/// Persistent vector realized by a 32-way bit-partitioned trie with a tail buffer.
+/// The last (up to 32) elements are kept in the tail, so that push and pop rarely touch the trie.
+/// O(log_32 n): get(i), set(i, x)
+/// O(1) amortized: push(x), pop()
+/// O(1): size(), clone()
+pub struct PVector<T> {
+    n: usize,
+    // the level of the root, which is a multiple of BITS
+    shift: u32,
+    root: Rc<Node<T>>,
+    tail: Rc<Node<T>>,
+}
+
+enum Node<T> {
+    Branch(Vec<Rc<Node<T>>>),
+    Leaf(Vec<T>),
+}
+
+impl<T> Node<T> {
+    fn children(&self) -> &Vec<Rc<Node<T>>> {
+        match self {
+            Node::Branch(children) => children,
+            Node::Leaf(_) => unreachable!("leaf has no children"),
+        }
+    }
+
+    fn values(&self) -> &Vec<T> {
+        match self {
+            Node::Leaf(values) => values,
+            Node::Branch(_) => unreachable!("branch has no values"),
+        }
+    }
+}
+
+impl<T> Default for PVector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for PVector<T> {
+    fn clone(&self) -> Self {
+        PVector {
+            n: self.n,
+            shift: self.shift,
+            root: self.root.clone(),
+            tail: self.tail.clone(),
+        }
+    }
+}
+
+impl<T> PVector<T> {
+    /// Generate empty PVector.
+    pub fn new() -> Self {
+        PVector {
+            n: 0,
+            shift: BITS,
+            root: Rc::new(Node::Branch(Vec::new())),
+            tail: Rc::new(Node::Leaf(Vec::new())),
+        }
+    }
+
+    /// Return the number of elements.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    // Return the index of the first element in the tail.
+    fn tail_offset(&self) -> usize {
+        if self.n < WIDTH {
+            0
+        } else {
+            ((self.n - 1) >> BITS) << BITS
+        }
+    }
+
+    // Return the leaf which holds the element at the position i.
+    fn leaf_for(&self, i: usize) -> &Vec<T> {
+        if i >= self.tail_offset() {
+            return self.tail.values();
+        }
+        let mut u = &self.root;
+        let mut level = self.shift;
+        while level > 0 {
+            u = &u.children()[(i >> level) & MASK];
+            level -= BITS;
+        }
+        u.values()
+    }
+
+    /// Return the value at the position i.
+    pub fn get(&self, i: usize) -> Option<&T> {
+        if i >= self.n {
+            None
+        } else {
+            Some(&self.leaf_for(i)[i & MASK])
+        }
+    }
+
+    /// Return the last value.
+    pub fn last(&self) -> Option<&T> {
+        self.n.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// Return an iterator from the first element to the last one.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            vector: self,
+            i: 0,
+            leaf: &[],
+        }
+    }
+
+    // Build a chain of branches down to the level 0 which ends with the leaf.
+    fn new_path(level: u32, leaf: Rc<Node<T>>) -> Rc<Node<T>> {
+        if level == 0 {
+            leaf
+        } else {
+            Rc::new(Node::Branch(vec![Self::new_path(level - BITS, leaf)]))
+        }
+    }
+
+    // Return a copy of the path to the position of the full tail, with the tail attached.
+    fn push_tail(&self, level: u32, parent: &Node<T>, leaf: Rc<Node<T>>) -> Rc<Node<T>> {
+        let i = ((self.n - 1) >> level) & MASK;
+        let mut children = parent.children().clone();
+        let child = if level == BITS {
+            leaf
+        } else {
+            match children.get(i) {
+                Some(child) => self.push_tail(level - BITS, child, leaf),
+                None => Self::new_path(level - BITS, leaf),
+            }
+        };
+        if i < children.len() {
+            children[i] = child;
+        } else {
+            children.push(child);
+        }
+        Rc::new(Node::Branch(children))
+    }
+
+    // Return a copy of the path to the last leaf without it, or None if the node becomes empty.
+    fn pop_tail(&self, level: u32, u: &Node<T>) -> Option<Rc<Node<T>>> {
+        let i = ((self.n - 2) >> level) & MASK;
+        let mut children = u.children().clone();
+        if level > BITS {
+            match self.pop_tail(level - BITS, &children[i]) {
+                Some(child) => {
+                    children[i] = child;
+                    return Some(Rc::new(Node::Branch(children)));
+                }
+                None if i == 0 => return None,
+                None => {}
+            }
+        } else if i == 0 {
+            return None;
+        }
+        children.truncate(i);
+        Some(Rc::new(Node::Branch(children)))
+    }
+}
+
+impl<T> PVector<T>
+where
+    T: Clone,
+{
+    /// Return a new version which has x at the end.
+    pub fn push(&self, x: T) -> Self {
+        if self.n - self.tail_offset() < WIDTH {
+            let mut tail = self.tail.values().clone();
+            tail.push(x);
+            return PVector {
+                n: self.n + 1,
+                shift: self.shift,
+                root: self.root.clone(),
+                tail: Rc::new(Node::Leaf(tail)),
+            };
+        }
+        // the tail is full, so it moves into the trie
+        let leaf = self.tail.clone();
+        let (root, shift) = if (self.n >> BITS) > (1 << self.shift) {
+            let path = Self::new_path(self.shift, leaf);
+            let root = Node::Branch(vec![self.root.clone(), path]);
+            (Rc::new(root), self.shift + BITS)
+        } else {
+            (self.push_tail(self.shift, &self.root, leaf), self.shift)
+        };
+        PVector {
+            n: self.n + 1,
+            shift,
+            root,
+            tail: Rc::new(Node::Leaf(vec![x])),
+        }
+    }
+
+    /// Return a new version without the last element.
+    /// Return empty vector if this vector is empty.
+    pub fn pop(&self) -> Self {
+        if self.n <= 1 {
+            return PVector::new();
+        }
+        if self.n - self.tail_offset() > 1 {
+            let mut tail = self.tail.values().clone();
+            tail.pop();
+            return PVector {
+                n: self.n - 1,
+                shift: self.shift,
+                root: self.root.clone(),
+                tail: Rc::new(Node::Leaf(tail)),
+            };
+        }
+        // the tail becomes empty, so the last leaf of the trie becomes the new tail
+        let tail = Rc::new(Node::Leaf(self.leaf_for(self.n - 2).clone()));
+        let mut root = self
+            .pop_tail(self.shift, &self.root)
+            .unwrap_or_else(|| Rc::new(Node::Branch(Vec::new())));
+        let mut shift = self.shift;
+        if shift > BITS && root.children().len() == 1 {
+            root = root.children()[0].clone();
+            shift -= BITS;
+        }
+        PVector {
+            n: self.n - 1,
+            shift,
+            root,
+            tail,
+        }
+    }
+
+    /// Return a new version whose value at the position i is replaced with x.
+    pub fn set(&self, i: usize, x: T) -> Self {
+        if i >= self.n {
+            panic!(
+                "index must be positive and less than the size of list. i: {}, n: {}",
+                i, self.n
+            )
+        }
+        let mut v = self.clone();
+        if i >= self.tail_offset() {
+            let mut tail = self.tail.values().clone();
+            tail[i & MASK] = x;
+            v.tail = Rc::new(Node::Leaf(tail));
+        } else {
+            v.root = Self::assoc(self.shift, &self.root, i, x);
+        }
+        v
+    }
+
+    // Return a copy of the path to the position i, with the value replaced.
+    fn assoc(level: u32, u: &Node<T>, i: usize, x: T) -> Rc<Node<T>> {
+        if level == 0 {
+            let mut values = u.values().clone();
+            values[i & MASK] = x;
+            Rc::new(Node::Leaf(values))
+        } else {
+            let mut children = u.children().clone();
+            let j = (i >> level) & MASK;
+            children[j] = Self::assoc(level - BITS, &children[j], i, x);
+            Rc::new(Node::Branch(children))
+        }
+    }
+}
+
+impl<T> Debug for PVector<T>
+where
+    T: Debug,
+{
+    // Elements are written in index order instead of the trie of shared nodes.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PVector")
+            .field("elements", &debug_entries(self.iter()))
+            .field("size", &self.n)
+            .finish()
+    }
+}
+
+impl<T> Display for PVector<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        Display::fmt(&format_with(self.iter(), ", "), f)?;
+        f.write_str("]")
+    }
+}
+
+impl<T> std::iter::FromIterator<T> for PVector<T>
+where
+    T: Clone,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(PVector::new(), |vector, x| vector.push(x))
+    }
+}
+
+/// Iterator over elements of PVector.
+pub struct Iter<'a, T> {
+    vector: &'a PVector<T>,
+    i: usize,
+    // the rest of the current leaf
+    leaf: &'a [T],
+}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            vector: self.vector,
+            i: self.i,
+            leaf: self.leaf,
+        }
+    }
+}
+
+impl<'a, T> Debug for Iter<'a, T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Iter")
+            .field(&debug_entries(self.clone()))
+            .finish()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.leaf.is_empty() {
+            if self.i >= self.vector.n {
+                return None;
+            }
+            self.leaf = &self.vector.leaf_for(self.i)[self.i & MASK..];
+        }
+        let (x, rest) = self.leaf.split_first()?;
+        self.leaf = rest;
+        self.i += 1;
+        Some(x)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PVector<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::PVector;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // Decode operations, each of which pushes a value and pops one if op % 4 == 0.
+    pub(super) fn ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Values are pushed and sometimes popped, so that the tail and the trie are both exercised.
+    impl<'a, T> Arbitrary<'a> for PVector<T>
+    where
+        T: Clone + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut v = PVector::new();
+            for (op, x) in ops(u)? {
+                v = v.push(x);
+                if op % 4 == 0 {
+                    v = v.pop();
+                }
+            }
+            Ok(v)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PVector;
+
+    #[test]
+    fn pvector_test() {
+        let v0: PVector<i32> = PVector::new();
+        assert_eq!(v0.size(), 0);
+        assert_eq!(v0.get(0), None);
+        assert_eq!(v0.last(), None);
+        assert_eq!(v0.pop().size(), 0);
+
+        let v1 = v0.push(1).push(2).push(3);
+        let v2 = v1.set(1, 5).push(4);
+        let v3 = v2.pop().pop();
+        assert_eq!(v1.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(v2.iter().copied().collect::<Vec<_>>(), vec![1, 5, 3, 4]);
+        assert_eq!(v3.iter().copied().collect::<Vec<_>>(), vec![1, 5]);
+        assert_eq!(v2.get(3), Some(&4));
+        assert_eq!(v2.get(4), None);
+        assert_eq!(v3.last(), Some(&5));
+    }
+
+    #[test]
+    fn fmt_test() {
+        let v = (0..40).collect::<PVector<_>>();
+        assert_eq!(
+            v.to_string(),
+            format!(
+                "[{}]",
+                (0..40)
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        );
+        // neither the trie nor Rc is exposed
+        let small = v.pop().pop();
+        let debug = format!("{:?}", (0..3).collect::<PVector<_>>());
+        assert_eq!(debug, "PVector { elements: [0, 1, 2], size: 3 }");
+        assert!(format!("{:?}", small).ends_with("37], size: 38 }"));
+        let mut iter = small.iter();
+        iter.nth(35);
+        assert_eq!(format!("{:?}", iter), "Iter([36, 37])");
+    }
+
+    #[test]
+    fn deep_trie_test() {
+        // more than 32 * 32 + 32 elements makes the trie three levels deep
+        let n = if cfg!(miri) { 3_000 } else { 40_000 };
+        let mut v = PVector::new();
+        let mut versions = Vec::new();
+        for x in 0..n {
+            v = v.push(x);
+            if x % 1000 == 999 {
+                versions.push(v.clone());
+            }
+        }
+        assert_eq!(v.size(), n);
+        assert!(v.iter().copied().eq(0..n));
+        // snapshots are not affected by later pushes
+        for (k, old) in versions.iter().enumerate() {
+            assert_eq!(old.size(), (k + 1) * 1000);
+            assert!(old.iter().copied().eq(0..old.size()));
+        }
+
+        let w = v.set(5, 0).set(n - 1, 0).set(33 * 32, 0);
+        assert_eq!(w.get(5), Some(&0));
+        assert_eq!(w.get(33 * 32), Some(&0));
+        assert_eq!(w.last(), Some(&0));
+        assert_eq!(v.get(5), Some(&5));
+        assert_eq!(v.get(33 * 32), Some(&(33 * 32)));
+
+        let mut u = v.clone();
+        for k in (0..n).rev() {
+            assert_eq!(u.last(), Some(&k));
+            u = u.pop();
+            assert_eq!(u.size(), k);
+        }
+        assert_eq!(v.size(), n);
+        assert!(v.iter().copied().eq(0..n));
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use super::WIDTH;
+
+        let mut deep = false;
+        test_support::replay::check_arbitrary(|v: PVector<u8>, u| {
+            // replay the same operations on Vec
+            let mut expected = Vec::new();
+            for (op, x) in super::arb::ops::<u8>(u).unwrap() {
+                expected.push(x);
+                if op % 4 == 0 {
+                    expected.pop();
+                }
+            }
+            // the tail holds the last elements which don't fill the trie, and is never empty unless v is
+            let tail = v.tail.values().len();
+            assert_eq!(tail, v.size() - v.tail_offset());
+            assert!(tail <= WIDTH && (tail > 0 || v.size() == 0));
+            deep |= v.size() > WIDTH;
+            assert!(v.iter().eq(expected.iter()));
+            assert!((0..v.size())
+                .map(|i| v.get(i))
+                .eq(expected.iter().map(Some)));
+        });
+        assert!(deep);
+    }
+}
@@ -0,0 +1,862 @@
+use interface::fmt::{debug_entries, format_with};
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::ops::{Bound, RangeBounds};
+use std::rc::Rc;
+
+/// Persistent sorted set realized by a treap whose updates copy only the search path.
+/// Each version shares all untouched subtrees with the version it was derived from.
+/// O(log n) expected: add(x), remove(x), find(x), contains(x)
+/// O(1): size(), clone()
+pub struct PersistentTreap<T> {
+    root: Link<T>,
+}
+
+type Link<T> = Option<Rc<Node<T>>>;
+
+struct Node<T> {
+    x: T,
+    p: u64,
+    // the number of nodes in the subtree rooted at this node
+    n: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+thread_local! {
+    static SEED: Cell<u64> = const { Cell::new(0x2545_f491_4f6c_dd1d) };
+}
+
+// Generate a priority by xorshift, which is enough to keep treaps balanced.
+fn priority() -> u64 {
+    SEED.with(|seed| {
+        let mut s = seed.get();
+        s ^= s << 13;
+        s ^= s >> 7;
+        s ^= s << 17;
+        seed.set(s);
+        s
+    })
+}
+
+fn size<T>(t: &Link<T>) -> usize {
+    t.as_ref().map_or(0, |u| u.n)
+}
+
+fn node<T>(x: T, p: u64, left: Link<T>, right: Link<T>) -> Link<T> {
+    Some(Rc::new(Node {
+        n: size(&left) + size(&right) + 1,
+        x,
+        p,
+        left,
+        right,
+    }))
+}
+
+// Split t into the nodes which satisfy f and the rest. f must hold for a prefix of the sorted order.
+// The search path is copied bottom-up from a stack, since a Cartesian tree can be as deep as its size.
+fn split<T, F>(t: &Link<T>, f: &F) -> (Link<T>, Link<T>)
+where
+    T: Clone,
+    F: Fn(&T) -> bool,
+{
+    let mut path = Vec::new();
+    let mut u = t.as_ref();
+    while let Some(w) = u {
+        let satisfied = f(&w.x);
+        path.push((w, satisfied));
+        u = if satisfied {
+            w.right.as_ref()
+        } else {
+            w.left.as_ref()
+        };
+    }
+    let (mut l, mut r) = (None, None);
+    for (w, satisfied) in path.into_iter().rev() {
+        if satisfied {
+            l = node(w.x.clone(), w.p, w.left.clone(), l);
+        } else {
+            r = node(w.x.clone(), w.p, r, w.right.clone());
+        }
+    }
+    (l, r)
+}
+
+// Merge a and b where every value in a is less than any value in b.
+// The merged path is copied bottom-up from a stack like split.
+fn merge<T>(a: &Link<T>, b: &Link<T>) -> Link<T>
+where
+    T: Clone,
+{
+    let (mut a, mut b) = (a.as_ref(), b.as_ref());
+    // each node on the path with true if it comes from a
+    let mut path = Vec::new();
+    let mut t = loop {
+        match (a, b) {
+            (None, _) => break b.cloned(),
+            (_, None) => break a.cloned(),
+            (Some(u), Some(w)) => {
+                if u.p > w.p {
+                    path.push((u, true));
+                    a = u.right.as_ref();
+                } else {
+                    path.push((w, false));
+                    b = w.left.as_ref();
+                }
+            }
+        }
+    };
+    for (u, from_a) in path.into_iter().rev() {
+        t = if from_a {
+            node(u.x.clone(), u.p, u.left.clone(), t)
+        } else {
+            node(u.x.clone(), u.p, t, u.right.clone())
+        };
+    }
+    t
+}
+
+// Link items given in in-order into a Cartesian tree in O(n), where below(a, b) is true if a must be placed
+// under b. Nodes are built from the deepest ones, and into_node receives the depth of each item.
+fn cartesian<I, T, F, G>(items: Vec<I>, below: F, into_node: G) -> Link<T>
+where
+    F: Fn(&I, &I) -> bool,
+    G: Fn(I, usize) -> (T, u64),
+{
+    let n = items.len();
+    let (mut left, mut right) = (vec![None; n], vec![None; n]);
+    // the right spine of the tree built so far
+    let mut stack: Vec<usize> = Vec::new();
+    for i in 0..n {
+        let mut last = None;
+        while let Some(&t) = stack.last() {
+            if !below(&items[t], &items[i]) {
+                break;
+            }
+            last = stack.pop();
+        }
+        left[i] = last;
+        if let Some(&t) = stack.last() {
+            right[t] = Some(i);
+        }
+        stack.push(i);
+    }
+    let root = *stack.first()?;
+
+    // parents precede their children in breadth-first order
+    let mut order = vec![root];
+    let mut depth = vec![0; n];
+    let mut k = 0;
+    while k < order.len() {
+        let u = order[k];
+        for c in left[u].into_iter().chain(right[u]) {
+            depth[c] = depth[u] + 1;
+            order.push(c);
+        }
+        k += 1;
+    }
+    let mut items = items.into_iter().map(Some).collect::<Vec<_>>();
+    let mut links: Vec<Link<T>> = vec![None; n];
+    for &u in order.iter().rev() {
+        let item = items[u].take().expect("every item must be visited once");
+        let (x, p) = into_node(item, depth[u]);
+        let l = left[u].and_then(|c| links[c].take());
+        let r = right[u].and_then(|c| links[c].take());
+        links[u] = node(x, p, l, r);
+    }
+    links[root].take()
+}
+
+impl<T> Default for PersistentTreap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for PersistentTreap<T> {
+    fn clone(&self) -> Self {
+        PersistentTreap {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Node<T> {
+    // Release descendants iteratively while they are not shared, since a Cartesian tree can be as deep as its
+    // size. Paths copied by split and merge are released in the same way when they are discarded.
+    fn drop(&mut self) {
+        let mut stack = self.left.take().into_iter().collect::<Vec<_>>();
+        stack.extend(self.right.take());
+        while let Some(u) = stack.pop() {
+            if let Ok(mut u) = Rc::try_unwrap(u) {
+                stack.extend(u.left.take());
+                stack.extend(u.right.take());
+            }
+        }
+    }
+}
+
+impl<T> PersistentTreap<T> {
+    /// Generate empty PersistentTreap.
+    pub fn new() -> Self {
+        PersistentTreap { root: None }
+    }
+
+    /// Return the number of elements.
+    pub fn size(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Return true if both versions share the same root node.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Return the i-th smallest element, counting from 0.
+    /// The subtree sizes tell which side holds it.
+    /// O(log n) expected
+    pub fn get(&self, i: usize) -> Option<&T> {
+        let mut u = self.root.as_deref();
+        let mut i = i;
+        while let Some(w) = u {
+            let l = size(&w.left);
+            match i.cmp(&l) {
+                Ordering::Less => u = w.left.as_deref(),
+                Ordering::Equal => return Some(&w.x),
+                Ordering::Greater => {
+                    i -= l + 1;
+                    u = w.right.as_deref();
+                }
+            }
+        }
+        None
+    }
+
+    /// Return an element chosen uniformly at random, where rng generates uniformly random u64 values.
+    /// O(log n) expected
+    pub fn choose<R>(&self, rng: &mut R) -> Option<&T>
+    where
+        R: FnMut() -> u64,
+    {
+        match self.size() {
+            0 => None,
+            // the high half of the product maps u64 into [0, n) without the bias of modulo
+            n => self.get(((rng() as u128 * n as u128) >> 64) as usize),
+        }
+    }
+
+    /// Return an iterator which visits elements in ascending order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+}
+
+impl<T> PersistentTreap<T>
+where
+    T: Ord + Clone,
+{
+    /// Return a new version which contains x in addition to the elements of this one.
+    /// Return a clone of this version if x is already contained.
+    pub fn add(&self, x: T) -> Self {
+        if self.contains(&x) {
+            return self.clone();
+        }
+        let (l, r) = split(&self.root, &|y: &T| *y < x);
+        let single = node(x, priority(), None, None);
+        PersistentTreap {
+            root: merge(&merge(&l, &single), &r),
+        }
+    }
+
+    /// Return a new version which contains x with the priority p instead of a random one.
+    /// Elements with higher priorities are placed closer to the root.
+    /// Return a clone of this version if x is already contained.
+    pub fn add_with_priority(&self, x: T, p: u64) -> Self {
+        if self.contains(&x) {
+            return self.clone();
+        }
+        let (l, r) = split(&self.root, &|y: &T| *y < x);
+        let single = node(x, p, None, None);
+        PersistentTreap {
+            root: merge(&merge(&l, &single), &r),
+        }
+    }
+
+    /// Generate PersistentTreap from (value, priority) pairs sorted by value in strictly ascending order.
+    /// The shape is the Cartesian tree of priorities, which is built without rotations.
+    /// O(n)
+    pub fn from_sorted_with_priorities(pairs: Vec<(T, u64)>) -> Self {
+        if pairs.windows(2).any(|w| w[0].0 >= w[1].0) {
+            panic!("values must be sorted in strictly ascending order")
+        }
+        PersistentTreap {
+            root: cartesian(pairs, |a, b| a.1 < b.1, |pair, _| pair),
+        }
+    }
+
+    /// Return the element with the highest priority among lo <= x <= hi, otherwise return null(Empty).
+    /// For a treap built by build_cartesian, it's the minimum value in the range of positions.
+    /// O(depth)
+    pub fn highest_in(&self, lo: &T, hi: &T) -> Option<&T> {
+        let mut u = self.root.as_deref();
+        while let Some(w) = u {
+            if w.x < *lo {
+                u = w.right.as_deref();
+            } else if w.x > *hi {
+                u = w.left.as_deref();
+            } else {
+                // the first node within the range is the common ancestor of all the others
+                return Some(&w.x);
+            }
+        }
+        None
+    }
+
+    /// Return a new version which doesn't contain x.
+    /// Return a clone of this version if x is not contained.
+    pub fn remove(&self, x: &T) -> Self {
+        if !self.contains(x) {
+            return self.clone();
+        }
+        let (l, r) = split(&self.root, &|y: &T| y < x);
+        let (_, r) = split(&r, &|y: &T| y == x);
+        PersistentTreap {
+            root: merge(&l, &r),
+        }
+    }
+
+    /// Return a new version without elements within r.
+    /// The range is cut out by two splits and the rest is joined by one merge, instead of removing elements one
+    /// by one. The number of removed elements is the difference of sizes.
+    /// O(log n) expected
+    pub fn remove_range<R>(&self, r: R) -> Self
+    where
+        R: RangeBounds<T>,
+    {
+        let (l, rest) = split(&self.root, &|y: &T| match r.start_bound() {
+            Bound::Included(x) => y < x,
+            Bound::Excluded(x) => y <= x,
+            Bound::Unbounded => false,
+        });
+        let (_, r) = split(&rest, &|y: &T| match r.end_bound() {
+            Bound::Included(x) => y <= x,
+            Bound::Excluded(x) => y < x,
+            Bound::Unbounded => true,
+        });
+        PersistentTreap {
+            root: merge(&l, &r),
+        }
+    }
+
+    /// Return a new version which contains elements of both versions, keeping elements of self for values
+    /// contained in both.
+    /// The root with the higher priority splits the other treap by its value, and both sides are merged in the
+    /// same way, so that subtrees which don't interleave are shared instead of added one by one.
+    /// O(m log(n / m + 1)) expected, where m <= n are the sizes of both
+    pub fn merge(&self, other: &Self) -> Self {
+        enum Frame<T> {
+            // merge two treaps, where the flag is true if the first one comes from other
+            Merge(Link<T>, Link<T>, bool),
+            // link the top two results under a node
+            Join(T, u64),
+        }
+
+        let mut frames = vec![Frame::Merge(self.root.clone(), other.root.clone(), false)];
+        let mut results: Vec<Link<T>> = Vec::new();
+        while let Some(frame) = frames.pop() {
+            match frame {
+                Frame::Merge(a, b, swapped) => {
+                    let (top, below, top_from_self) = match (a, b) {
+                        (None, t) | (t, None) => {
+                            results.push(t);
+                            continue;
+                        }
+                        (Some(a), Some(b)) if b.p > a.p => (b, a, swapped),
+                        (Some(a), Some(b)) => (a, b, !swapped),
+                    };
+                    // the root with the higher priority stays at the top, splitting the other by its value
+                    let (l, rest) = split(&Some(below), &|y: &T| *y < top.x);
+                    let (equal, r) = split(&rest, &|y: &T| *y == top.x);
+                    let x = match equal {
+                        Some(e) if !top_from_self => e.x.clone(),
+                        _ => top.x.clone(),
+                    };
+                    frames.push(Frame::Join(x, top.p));
+                    frames.push(Frame::Merge(r, top.right.clone(), top_from_self));
+                    frames.push(Frame::Merge(l, top.left.clone(), top_from_self));
+                }
+                Frame::Join(x, p) => {
+                    let r = results.pop().expect("right side must be merged");
+                    let l = results.pop().expect("left side must be merged");
+                    results.push(node(x, p, l, r));
+                }
+            }
+        }
+        PersistentTreap {
+            root: results.pop().expect("the whole must be merged"),
+        }
+    }
+
+    /// Return true if x is contained.
+    pub fn contains(&self, x: &T) -> bool {
+        let mut u = self.root.as_deref();
+        while let Some(w) = u {
+            match x.cmp(&w.x) {
+                Ordering::Less => u = w.left.as_deref(),
+                Ordering::Greater => u = w.right.as_deref(),
+                Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+
+    /// Return the smallest value y that satisfies y >= x, otherwise return null(Empty).
+    pub fn find(&self, x: &T) -> Option<&T> {
+        let mut u = self.root.as_deref();
+        let mut z = None;
+        while let Some(w) = u {
+            match x.cmp(&w.x) {
+                Ordering::Less => {
+                    z = Some(&w.x);
+                    u = w.left.as_deref();
+                }
+                Ordering::Greater => u = w.right.as_deref(),
+                Ordering::Equal => return Some(&w.x),
+            }
+        }
+        z
+    }
+}
+
+impl<V> PersistentTreap<(usize, V)>
+where
+    V: Ord + Clone,
+{
+    /// Generate the Cartesian tree of values, which is keyed by (position, value) and keeps the minimum value
+    /// at the root. The leftmost one is placed higher among equal values.
+    /// highest_in(&(i, min), &(j, max)) answers the range minimum query of positions i..=j.
+    /// The tree is as deep as the longest monotone run of values, which is not balanced like random priorities.
+    /// O(n)
+    pub fn build_cartesian(values: &[V]) -> Self {
+        let items = values.iter().cloned().enumerate().collect::<Vec<_>>();
+        PersistentTreap {
+            // deeper nodes get lower priorities, which keeps the heap order for later additions
+            root: cartesian(
+                items,
+                |a, b| a.1 > b.1,
+                |item, depth| (item, u64::MAX - depth as u64),
+            ),
+        }
+    }
+}
+
+impl<T> Debug for PersistentTreap<T>
+where
+    T: Debug,
+{
+    // Elements are written in ascending order by iteration, since the tree can be as deep as its size.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistentTreap")
+            .field("elements", &debug_entries(self.iter()))
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+impl<T> Display for PersistentTreap<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("{")?;
+        Display::fmt(&format_with(self.iter(), ", "), f)?;
+        f.write_str("}")
+    }
+}
+
+impl<T> std::iter::FromIterator<T> for PersistentTreap<T>
+where
+    T: Ord + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(PersistentTreap::new(), |treap, x| treap.add(x))
+    }
+}
+
+/// Iterator over elements of PersistentTreap in ascending order.
+pub struct Iter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            stack: self.stack.clone(),
+        }
+    }
+}
+
+impl<'a, T> Debug for Iter<'a, T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Iter")
+            .field(&debug_entries(self.clone()))
+            .finish()
+    }
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left(&mut self, mut u: Option<&'a Node<T>>) {
+        while let Some(w) = u {
+            self.stack.push(w);
+            u = w.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let u = self.stack.pop()?;
+        self.push_left(u.right.as_deref());
+        Some(&u.x)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentTreap<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::PersistentTreap;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // Decode the values to add.
+    pub(super) fn values<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<T>>
+    where
+        T: Arbitrary<'a>,
+    {
+        u.arbitrary_iter()?.collect()
+    }
+
+    impl<'a, T> Arbitrary<'a> for PersistentTreap<T>
+    where
+        T: Ord + Clone + Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(values(u)?.into_iter().collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PersistentTreap;
+    use std::ops::Bound;
+
+    #[test]
+    fn treap_test() {
+        let v0: PersistentTreap<i32> = PersistentTreap::new();
+        assert_eq!(v0.size(), 0);
+        assert_eq!(v0.find(&0), None);
+
+        let v1 = v0.add(5).add(1).add(9).add(3);
+        let v2 = v1.add(7).remove(&1);
+        let v3 = v2.add(7);
+        assert!(v3.ptr_eq(&v2));
+        assert!(v2.remove(&4).ptr_eq(&v2));
+
+        assert_eq!(v0.size(), 0);
+        assert_eq!(v1.iter().copied().collect::<Vec<_>>(), vec![1, 3, 5, 9]);
+        assert_eq!(v2.iter().copied().collect::<Vec<_>>(), vec![3, 5, 7, 9]);
+        assert!(v1.contains(&1));
+        assert!(!v2.contains(&1));
+
+        assert_eq!(v1.find(&6), Some(&9));
+        assert_eq!(v2.find(&6), Some(&7));
+        assert_eq!(v2.find(&5), Some(&5));
+        assert_eq!(v2.find(&10), None);
+    }
+
+    #[test]
+    fn fmt_test() {
+        let treap = [3, 1, 2].iter().copied().collect::<PersistentTreap<_>>();
+        assert_eq!(treap.to_string(), "{1, 2, 3}");
+        assert_eq!(
+            format!("{:?}", treap),
+            "PersistentTreap { elements: [1, 2, 3], size: 3 }"
+        );
+        let mut iter = treap.iter();
+        iter.next();
+        assert_eq!(format!("{:?}", iter), "Iter([2, 3])");
+        // a chain-shaped tree is written without recursion
+        let n = if cfg!(miri) { 1_000 } else { 100_000 };
+        let chain = PersistentTreap::build_cartesian(&(0..n).collect::<Vec<_>>());
+        assert!(format!("{:?}", chain).ends_with(&format!("size: {} }}", n)));
+    }
+
+    #[test]
+    fn priority_test() {
+        let pairs = vec![(1, 10), (2, 40), (3, 20), (4, 30), (5, 50)];
+        let treap = PersistentTreap::from_sorted_with_priorities(pairs);
+        assert_eq!(treap.size(), 5);
+        assert!(treap.iter().copied().eq(1..=5));
+        assert_eq!(treap.highest_in(&0, &9), Some(&5));
+        assert_eq!(treap.highest_in(&1, &4), Some(&2));
+        assert_eq!(treap.highest_in(&3, &4), Some(&4));
+        assert_eq!(treap.highest_in(&6, &9), None);
+
+        // a priority higher than any other puts a value at the root
+        let added = treap.add_with_priority(0, 60).add_with_priority(6, 0);
+        assert_eq!(added.highest_in(&0, &9), Some(&0));
+        assert!(added.iter().copied().eq(0..=6));
+        assert!(
+            PersistentTreap::<i32>::from_sorted_with_priorities(Vec::new())
+                .iter()
+                .next()
+                .is_none()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn unsorted_priority_test() {
+        PersistentTreap::from_sorted_with_priorities(vec![(2, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn cartesian_test() {
+        // pseudo random values by linear congruential generator
+        let mut x: u64 = 11;
+        let values = (0..200)
+            .map(|_| {
+                x = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (x >> 33) % 50
+            })
+            .collect::<Vec<_>>();
+        let treap = PersistentTreap::build_cartesian(&values);
+        assert!(treap.iter().map(|&(_, v)| v).eq(values.iter().copied()));
+        for i in (0..values.len()).step_by(7) {
+            for j in (i..values.len()).step_by(13) {
+                let (k, min) = *treap.highest_in(&(i, 0), &(j, u64::MAX)).unwrap();
+                assert_eq!(min, *values[i..=j].iter().min().unwrap());
+                // the leftmost minimum is found
+                assert_eq!(values[i..=j].iter().position(|&v| v == min), Some(k - i));
+            }
+        }
+        // later versions keep the shape of the Cartesian tree
+        let next = treap.remove(&(0, values[0])).add((values.len(), 0));
+        assert_eq!(next.size(), values.len());
+        assert_eq!(
+            next.highest_in(&(1, 0), &(9, u64::MAX)),
+            treap.highest_in(&(1, 0), &(9, u64::MAX))
+        );
+        // a long sorted input doesn't overflow the stack
+        let n = if cfg!(miri) { 1_000 } else { 100_000 };
+        let sorted = (0..n).collect::<Vec<_>>();
+        let chain = PersistentTreap::build_cartesian(&sorted);
+        assert_eq!(chain.highest_in(&(500, 0), &(900, 0)), Some(&(500, 500)));
+        // and neither do updates along the chain
+        let last = (n - 1, n - 1);
+        let updated = chain
+            .remove(&last)
+            .add((n, 0))
+            .add_with_priority((n + 1, 0), 0)
+            .remove_range((10, 0)..(n - 10, 0));
+        assert_eq!(updated.size(), 21);
+        assert!(!updated.contains(&last));
+        assert!(updated
+            .iter()
+            .map(|&(i, _)| i)
+            .eq((0..10).chain(n - 10..n - 1).chain(n..n + 2)));
+        assert_eq!(chain.size(), n);
+    }
+
+    #[test]
+    fn choose_test() {
+        let empty: PersistentTreap<u32> = PersistentTreap::new();
+        let mut x: u64 = 1;
+        let mut rng = || {
+            x = x
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            x
+        };
+        assert_eq!(empty.get(0), None);
+        assert_eq!(empty.choose(&mut rng), None);
+
+        let treap = (0..10u32).map(|x| x * 3).collect::<PersistentTreap<_>>();
+        for i in 0..10 {
+            assert_eq!(treap.get(i), Some(&(i as u32 * 3)));
+        }
+        assert_eq!(treap.get(10), None);
+
+        // every element is chosen about equally often
+        let mut count = [0; 10];
+        for _ in 0..10000 {
+            let x = *treap.choose(&mut rng).unwrap();
+            count[x as usize / 3] += 1;
+        }
+        assert!(count.iter().all(|&c| 800 < c && c < 1200), "{:?}", count);
+    }
+
+    #[test]
+    fn remove_range_test() {
+        let v0 = (0..100).collect::<PersistentTreap<i32>>();
+        let v1 = v0.remove_range(10..20);
+        assert_eq!(v1.size(), 90);
+        assert!(v1.iter().copied().eq((0..10).chain(20..100)));
+        let v2 = v1.remove_range(..=5).remove_range(95..);
+        assert!(v2.iter().copied().eq((6..10).chain(20..95)));
+        assert_eq!(v2.remove_range(..).size(), 0);
+        assert_eq!(v2.remove_range(50..50).size(), v2.size());
+        assert!(v2
+            .remove_range((Bound::Excluded(6), Bound::Included(20)))
+            .iter()
+            .copied()
+            .eq(std::iter::once(6).chain(21..95)));
+        // older versions are not affected
+        assert_eq!(v0.size(), 100);
+        assert_eq!(v1.size(), 90);
+    }
+
+    #[test]
+    fn merge_test() {
+        // elements are compared by the first value only, to tell which version an element comes from
+        #[derive(Debug, Clone)]
+        struct Tagged(u32, char);
+        impl PartialEq for Tagged {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Tagged {}
+        impl PartialOrd for Tagged {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Tagged {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
+
+        for (n, m) in [(0, 0), (0, 10), (1, 30), (100, 100), (1000, 10)]
+            .iter()
+            .copied()
+        {
+            let a = (0..n)
+                .map(|x| Tagged(x * 2, 'a'))
+                .collect::<PersistentTreap<_>>();
+            let b = (0..m)
+                .map(|x| Tagged(x * 3, 'b'))
+                .collect::<PersistentTreap<_>>();
+            let merged = a.merge(&b);
+            let expected = (0..n)
+                .map(|x| Tagged(x * 2, 'a'))
+                .chain((0..m).map(|x| Tagged(x * 3, 'b')))
+                .collect::<std::collections::BTreeSet<_>>();
+            assert_eq!(merged.size(), expected.len());
+            assert!(merged
+                .iter()
+                .map(|t| (t.0, t.1))
+                .eq(expected.iter().map(|t| (
+                    t.0,
+                    if t.0 % 2 == 0 && t.0 < 2 * n {
+                        'a'
+                    } else {
+                        'b'
+                    }
+                ))));
+            // both versions are intact
+            assert_eq!(a.size(), n as usize);
+            assert_eq!(b.size(), m as usize);
+        }
+
+        // subtrees which don't interleave are shared
+        let low = (0..100).collect::<PersistentTreap<i32>>();
+        let high = (100..200).collect::<PersistentTreap<i32>>();
+        let merged = low.merge(&high);
+        assert!(merged.iter().copied().eq(0..200));
+        assert!(merged.merge(&PersistentTreap::new()).ptr_eq(&merged));
+        // a chain-shaped tree is merged without recursion
+        let n = if cfg!(miri) { 1_000 } else { 100_000 };
+        let chain = PersistentTreap::build_cartesian(&(0..n).collect::<Vec<_>>());
+        let other = [(n, 0), (n / 2, n / 2)].iter().copied().collect();
+        assert_eq!(chain.merge(&other).size(), n + 1);
+    }
+
+    #[test]
+    fn versions_test() {
+        let mut versions = vec![PersistentTreap::new()];
+        let mut expected = vec![std::collections::BTreeSet::new()];
+        let n: u32 = if cfg!(miri) { 100 } else { 500 };
+        for i in 0..n {
+            let x = i.wrapping_mul(2_654_435_761) % 200;
+            let (last, mut set) = (versions.last().unwrap(), expected.last().unwrap().clone());
+            let next = if i % 3 == 0 {
+                set.remove(&x);
+                last.remove(&x)
+            } else {
+                set.insert(x);
+                last.add(x)
+            };
+            versions.push(next);
+            expected.push(set);
+        }
+        // every old version is still intact
+        for (treap, set) in versions.iter().zip(expected.iter()) {
+            assert_eq!(treap.size(), set.len());
+            assert!(treap.iter().eq(set.iter()));
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use super::Link;
+
+        // check the heap order of priorities and subtree sizes, and return the size
+        fn check<T>(t: &Link<T>) -> usize {
+            match t {
+                None => 0,
+                Some(u) => {
+                    let below = |c: &Link<T>| c.iter().all(|c| c.p <= u.p);
+                    assert!(below(&u.left) && below(&u.right));
+                    assert_eq!(u.n, check(&u.left) + check(&u.right) + 1);
+                    u.n
+                }
+            }
+        }
+
+        test_support::replay::check_arbitrary(|treap: PersistentTreap<u8>, u| {
+            // replay the same values on BTreeSet
+            let expected = super::arb::values::<u8>(u)
+                .unwrap()
+                .into_iter()
+                .collect::<std::collections::BTreeSet<_>>();
+            assert_eq!(check(&treap.root), treap.size());
+            assert!(treap.iter().eq(expected.iter()));
+        });
+    }
+}
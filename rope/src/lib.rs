@@ -0,0 +1,7 @@
+#![warn(missing_docs)]
+//! rope implements a text structure for editing large strings at character offsets.
+
+/// Implementation for rope over an implicit treap of text chunks.
+pub mod rope;
+
+pub use crate::rope::Rope;
@@ -0,0 +1,567 @@
+use std::cell::Cell;
+use std::fmt;
+
+// The maximum number of bytes in a chunk built from a long string.
+const CHUNK: usize = 512;
+
+/// Text realized by an implicit treap whose nodes hold chunks of the text in order.
+/// Every node keeps the number of chars and line breaks in its subtree, so that a position is found by descending.
+/// O(log n) expected: insert(i, s), remove(i, j), split_off(i), append(other), line_to_char(l)
+/// O(n): chars(), lines()
+pub struct Rope {
+    root: Link,
+}
+
+type Link = Option<Box<Node>>;
+
+struct Node {
+    text: String,
+    p: u64,
+    // the number of chars in the subtree
+    chars: usize,
+    // the number of '\n' in the subtree
+    breaks: usize,
+    left: Link,
+    right: Link,
+}
+
+thread_local! {
+    static SEED: Cell<u64> = const { Cell::new(0x2545_f491_4f6c_dd1d) };
+}
+
+// Generate a priority by xorshift, which is enough to keep treaps balanced.
+// The generator is shared by all ropes, so that appended ropes are independent treaps.
+fn priority() -> u64 {
+    SEED.with(|seed| {
+        let mut s = seed.get();
+        s ^= s << 13;
+        s ^= s >> 7;
+        s ^= s << 17;
+        seed.set(s);
+        s
+    })
+}
+
+fn chars(t: &Link) -> usize {
+    t.as_ref().map_or(0, |u| u.chars)
+}
+
+fn breaks(t: &Link) -> usize {
+    t.as_ref().map_or(0, |u| u.breaks)
+}
+
+impl Node {
+    fn update(&mut self) {
+        self.chars = chars(&self.left) + self.text.chars().count() + chars(&self.right);
+        self.breaks = breaks(&self.left) + self.text.matches('\n').count() + breaks(&self.right);
+    }
+}
+
+// Return the byte offset of the i-th char of s.
+fn byte_offset(s: &str, i: usize) -> usize {
+    s.char_indices().nth(i).map_or(s.len(), |(b, _)| b)
+}
+
+// Split t into the first k chars and the rest.
+fn split(t: Link, k: usize) -> (Link, Link) {
+    let mut u = match t {
+        None => return (None, None),
+        Some(u) => u,
+    };
+    let left = chars(&u.left);
+    let own = u.chars - left - chars(&u.right);
+    if k <= left {
+        let (l, r) = split(u.left.take(), k);
+        u.left = r;
+        u.update();
+        (l, Some(u))
+    } else if k >= left + own {
+        let (l, r) = split(u.right.take(), k - left - own);
+        u.right = l;
+        u.update();
+        (Some(u), r)
+    } else {
+        // the chunk itself is divided and each part joins a side
+        let rest = u.text.split_off(byte_offset(&u.text, k - left));
+        let mut w = Box::new(Node {
+            text: rest,
+            p: u.p,
+            chars: 0,
+            breaks: 0,
+            left: None,
+            right: u.right.take(),
+        });
+        w.update();
+        u.update();
+        (Some(u), Some(w))
+    }
+}
+
+// Merge a and b where a precedes b.
+fn merge(a: Link, b: Link) -> Link {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut u), Some(mut w)) => {
+            if u.p > w.p {
+                u.right = merge(u.right.take(), Some(w));
+                u.update();
+                Some(u)
+            } else {
+                w.left = merge(Some(u), w.left.take());
+                w.update();
+                Some(w)
+            }
+        }
+    }
+}
+
+impl Default for Rope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rope {
+    /// Generate empty Rope.
+    pub fn new() -> Self {
+        Rope { root: None }
+    }
+
+    /// Return the number of chars.
+    pub fn len_chars(&self) -> usize {
+        chars(&self.root)
+    }
+
+    /// Return the number of lines, which is the number of '\n' plus one.
+    pub fn len_lines(&self) -> usize {
+        breaks(&self.root) + 1
+    }
+
+    // Build a treap of chunks from s.
+    fn build(s: &str) -> Link {
+        let mut t = None;
+        let mut rest = s;
+        while !rest.is_empty() {
+            let mut end = std::cmp::min(CHUNK, rest.len());
+            while !rest.is_char_boundary(end) {
+                end += 1;
+            }
+            let (chunk, tail) = rest.split_at(end);
+            let mut u = Box::new(Node {
+                text: chunk.to_string(),
+                p: priority(),
+                chars: 0,
+                breaks: 0,
+                left: None,
+                right: None,
+            });
+            u.update();
+            t = merge(t, Some(u));
+            rest = tail;
+        }
+        t
+    }
+
+    /// Insert s so that its first char is placed at the position i.
+    pub fn insert(&mut self, i: usize, s: &str) {
+        let n = self.len_chars();
+        if i > n {
+            panic!(
+                "index must be positive and less than or equal to the size of rope. i: {}, n: {}",
+                i, n
+            )
+        }
+        let mid = Self::build(s);
+        let (l, r) = split(self.root.take(), i);
+        self.root = merge(merge(l, mid), r);
+    }
+
+    /// Remove chars in the range from i to j (exclusive) and return them.
+    pub fn remove(&mut self, i: usize, j: usize) -> String {
+        let n = self.len_chars();
+        if i > j || j > n {
+            panic!(
+                "range must satisfy i <= j <= the size of rope. i: {}, j: {}, n: {}",
+                i, j, n
+            )
+        }
+        let (l, r) = split(self.root.take(), j);
+        let (l, mid) = split(l, i);
+        self.root = merge(l, r);
+        Rope { root: mid }.to_string()
+    }
+
+    /// Split the rope at the position i, and return the chars after it as another rope.
+    pub fn split_off(&mut self, i: usize) -> Rope {
+        let n = self.len_chars();
+        if i > n {
+            panic!(
+                "index must be positive and less than or equal to the size of rope. i: {}, n: {}",
+                i, n
+            )
+        }
+        let (l, r) = split(self.root.take(), i);
+        self.root = l;
+        Rope { root: r }
+    }
+
+    /// Concatenate other at the end of this rope.
+    pub fn append(&mut self, mut other: Rope) {
+        self.root = merge(self.root.take(), other.root.take());
+    }
+
+    /// Return the char at the position i.
+    pub fn char_at(&self, i: usize) -> Option<char> {
+        let mut u = self.root.as_deref()?;
+        let mut i = i;
+        loop {
+            let left = chars(&u.left);
+            let own = u.chars - left - chars(&u.right);
+            if i < left {
+                u = u.left.as_deref()?;
+            } else if i < left + own {
+                return u.text.chars().nth(i - left);
+            } else {
+                i -= left + own;
+                u = u.right.as_deref()?;
+            }
+        }
+    }
+
+    /// Return the position of the first char of the line l, otherwise return null(Empty).
+    pub fn line_to_char(&self, l: usize) -> Option<usize> {
+        if l >= self.len_lines() {
+            return None;
+        }
+        // find the position just after the l-th '\n'
+        let mut u = self.root.as_deref();
+        let (mut l, mut i) = (l, 0);
+        while l > 0 {
+            let w = u?;
+            let left = breaks(&w.left);
+            let own = w.breaks - left - breaks(&w.right);
+            if l <= left {
+                u = w.left.as_deref();
+            } else if l <= left + own {
+                let (b, _) = w.text.match_indices('\n').nth(l - left - 1)?;
+                return Some(i + chars(&w.left) + w.text[..b].chars().count() + 1);
+            } else {
+                l -= left + own;
+                i += w.chars - chars(&w.right);
+                u = w.right.as_deref();
+            }
+        }
+        Some(i)
+    }
+
+    /// Return an iterator over chars.
+    pub fn chars(&self) -> Chars<'_> {
+        let mut iter = Chunks { stack: Vec::new() };
+        iter.push_left(self.root.as_deref());
+        Chars {
+            chunks: iter,
+            cur: "".chars(),
+        }
+    }
+
+    /// Return an iterator over lines without trailing '\n'.
+    pub fn lines(&self) -> Lines<'_> {
+        Lines {
+            chars: self.chars(),
+            done: false,
+        }
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(s: &str) -> Self {
+        Rope {
+            root: Self::build(s),
+        }
+    }
+}
+
+impl Drop for Rope {
+    // Release nodes iteratively, so that an unbalanced treap doesn't overflow the stack.
+    fn drop(&mut self) {
+        let mut stack = self.root.take().into_iter().collect::<Vec<_>>();
+        while let Some(mut u) = stack.pop() {
+            stack.extend(u.left.take());
+            stack.extend(u.right.take());
+        }
+    }
+}
+
+impl fmt::Debug for Rope {
+    // The text is written as one string instead of the treap of chunks.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rope")
+            .field("text", &self.to_string())
+            .field("chars", &self.len_chars())
+            .field("lines", &self.len_lines())
+            .finish()
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chunks = Chunks { stack: Vec::new() };
+        chunks.push_left(self.root.as_deref());
+        for chunk in chunks {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+// Iterator over chunks in order.
+struct Chunks<'a> {
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Chunks<'a> {
+    fn push_left(&mut self, mut u: Option<&'a Node>) {
+        while let Some(w) = u {
+            self.stack.push(w);
+            u = w.left.as_deref();
+        }
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let u = self.stack.pop()?;
+        self.push_left(u.right.as_deref());
+        Some(&u.text)
+    }
+}
+
+/// Iterator over chars of Rope.
+pub struct Chars<'a> {
+    chunks: Chunks<'a>,
+    cur: std::str::Chars<'a>,
+}
+
+impl<'a> Iterator for Chars<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        loop {
+            if let Some(c) = self.cur.next() {
+                return Some(c);
+            }
+            self.cur = self.chunks.next()?.chars();
+        }
+    }
+}
+
+/// Iterator over lines of Rope.
+pub struct Lines<'a> {
+    chars: Chars<'a>,
+    done: bool,
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.done {
+            return None;
+        }
+        let mut line = String::new();
+        for c in self.chars.by_ref() {
+            if c == '\n' {
+                return Some(line);
+            }
+            line.push(c);
+        }
+        self.done = true;
+        Some(line)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::Rope;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // Decode operations of (op, i, j, s), each of which inserts s and removes a range
+    // if op % 4 == 0.
+    pub(super) fn ops<'a>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, usize, usize, &'a str)>> {
+        (0..u.arbitrary_len::<(u8, usize, usize, &str)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Strings are inserted at arbitrary positions and ranges are sometimes removed,
+    // so that chunks are split and merged at various offsets.
+    impl<'a> Arbitrary<'a> for Rope {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut rope = Rope::new();
+            for (op, i, j, s) in ops(u)? {
+                let n = rope.len_chars();
+                rope.insert(i % (n + 1), s);
+                if op % 4 == 0 {
+                    let n = rope.len_chars();
+                    let i = i % (n + 1);
+                    rope.remove(i, i + j % (n - i + 1));
+                }
+            }
+            Ok(rope)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rope;
+
+    #[test]
+    fn rope_test() {
+        let mut rope = Rope::new();
+        assert_eq!(rope.len_chars(), 0);
+        assert_eq!(rope.to_string(), "");
+
+        rope.insert(0, "world");
+        rope.insert(0, "hello ");
+        rope.insert(11, "!");
+        rope.insert(5, ",");
+        assert_eq!(rope.to_string(), "hello, world!");
+        assert_eq!(rope.len_chars(), 13);
+        assert_eq!(rope.char_at(7), Some('w'));
+        assert_eq!(rope.char_at(13), None);
+
+        assert_eq!(rope.remove(5, 7), ", ");
+        assert_eq!(rope.to_string(), "helloworld!");
+
+        let tail = rope.split_off(5);
+        assert_eq!(rope.to_string(), "hello");
+        assert_eq!(tail.to_string(), "world!");
+        rope.insert(5, " ");
+        rope.append(tail);
+        assert_eq!(rope.to_string(), "hello world!");
+        assert!(rope.chars().eq("hello world!".chars()));
+    }
+
+    #[test]
+    fn multibyte_test() {
+        let mut rope = Rope::from("日本語テキスト");
+        assert_eq!(rope.len_chars(), 7);
+        rope.insert(3, "の");
+        assert_eq!(rope.remove(4, 8), "テキスト");
+        assert_eq!(rope.to_string(), "日本語の");
+        assert_eq!(rope.char_at(1), Some('本'));
+    }
+
+    #[test]
+    fn lines_test() {
+        let rope = Rope::from("first\nsecond\n\nlast");
+        assert_eq!(rope.len_lines(), 4);
+        assert_eq!(
+            rope.lines().collect::<Vec<_>>(),
+            vec!["first", "second", "", "last"]
+        );
+        assert_eq!(rope.line_to_char(0), Some(0));
+        assert_eq!(rope.line_to_char(1), Some(6));
+        assert_eq!(rope.line_to_char(2), Some(13));
+        assert_eq!(rope.line_to_char(3), Some(14));
+        assert_eq!(rope.line_to_char(4), None);
+    }
+
+    #[test]
+    fn fmt_test() {
+        let mut rope = Rope::from("one\n");
+        rope.append(Rope::from("two"));
+        assert_eq!(rope.to_string(), "one\ntwo");
+        assert_eq!(
+            format!("{:?}", rope),
+            "Rope { text: \"one\\ntwo\", chars: 7, lines: 2 }"
+        );
+    }
+
+    #[test]
+    fn large_text_test() {
+        let line = "0123456789abcdefghijklmnopqrstuvwxyz\n";
+        let n = if cfg!(miri) { 100 } else { 1000 };
+        let text = line.repeat(n);
+        let mut rope = Rope::from(text.as_str());
+        let mut expected = text.clone();
+        assert_eq!(rope.len_lines(), n + 1);
+        for k in 0..n / 5 {
+            let i = (k * 7919) % expected.len();
+            rope.insert(i, "+-");
+            expected.insert_str(i, "+-");
+            let j = (k * 104_729) % (expected.len() - 3);
+            let removed = rope.remove(j, j + 3);
+            assert_eq!(removed, &expected[j..j + 3]);
+            expected.replace_range(j..j + 3, "");
+        }
+        assert_eq!(rope.len_chars(), expected.len());
+        assert_eq!(rope.to_string(), expected);
+        let lines = expected.split('\n').count();
+        assert_eq!(rope.len_lines(), lines);
+        for l in [0, 1, lines / 2, lines - 1] {
+            let i = rope.line_to_char(l).unwrap();
+            let expected_i = expected.split('\n').take(l).map(|s| s.len() + 1).sum();
+            assert_eq!(i, expected_i);
+        }
+    }
+
+    #[test]
+    fn append_test() {
+        // every small rope gets its own priority, so that appending them keeps the treap balanced
+        let n = if cfg!(miri) { 1_000 } else { 200_000 };
+        let mut rope = Rope::new();
+        for _ in 0..n {
+            rope.append(Rope::from("line\n"));
+        }
+        assert_eq!(rope.len_lines(), n + 1);
+        assert_eq!(rope.char_at(5 * n - 1), Some('\n'));
+        rope.insert(0, "x");
+        assert_eq!(rope.remove(0, 2), "xl");
+        assert_eq!(rope.line_to_char(n / 2), Some(5 * (n / 2) - 1));
+        assert_eq!(rope.len_chars(), 5 * n - 1);
+        let tail = rope.split_off(5 * (n / 2));
+        assert_eq!(tail.len_lines(), n - n / 2 + 1);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use super::{Link, Node};
+
+        // Check the aggregates and the heap order of priorities, and return the text of the subtree.
+        fn check(t: &Link) -> String {
+            let u: &Node = match t {
+                None => return String::new(),
+                Some(u) => u,
+            };
+            assert!(!u.text.is_empty());
+            let below = |c: &Link| c.iter().all(|c| c.p <= u.p);
+            assert!(below(&u.left) && below(&u.right));
+            let s = check(&u.left) + &u.text + &check(&u.right);
+            assert_eq!(u.chars, s.chars().count());
+            assert_eq!(u.breaks, s.matches('\n').count());
+            s
+        }
+
+        test_support::replay::check_arbitrary(|rope: Rope, u| {
+            // replay the same operations on Vec<char>
+            let mut expected: Vec<char> = Vec::new();
+            for (op, i, j, s) in super::arb::ops(u).unwrap() {
+                let k = i % (expected.len() + 1);
+                expected.splice(k..k, s.chars());
+                if op % 4 == 0 {
+                    let n = expected.len();
+                    let i = i % (n + 1);
+                    expected.drain(i..i + j % (n - i + 1));
+                }
+            }
+            assert_eq!(check(&rope.root), expected.iter().collect::<String>());
+            assert!(rope.chars().eq(expected.iter().copied()));
+        });
+    }
+}
@@ -0,0 +1,7 @@
+#![warn(missing_docs)]
+//! sampling implements structures which keep random samples of streams in bounded space.
+
+/// Implementation for reservoir sampling.
+pub mod reservoir;
+
+pub use reservoir::Reservoir;
@@ -0,0 +1,209 @@
+use interface::heap_size::HeapSize;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// Uniform random sample of at most k values from a stream of unknown length (reservoir sampling).
+/// The i-th value (1-indexed) replaces a random slot with probability k / i, so that every value seen so far
+/// is in the sample with the same probability.
+/// O(1): add(x)
+/// O(k): merge(other)
+#[derive(Debug, Clone)]
+pub struct Reservoir<T> {
+    sample: Vec<T>,
+    k: usize,
+    // the number of values seen
+    n: u64,
+    seed: u64,
+}
+
+impl<T> Reservoir<T> {
+    /// Generate empty Reservoir which keeps k values.
+    pub fn new(k: usize) -> Self {
+        let seed = RandomState::new().build_hasher().finish();
+        Self::with_seed(k, seed)
+    }
+
+    /// Generate empty Reservoir which keeps k values, with a seed for reproducible sampling.
+    pub fn with_seed(k: usize, seed: u64) -> Self {
+        Reservoir {
+            sample: Vec::with_capacity(k),
+            k,
+            n: 0,
+            seed: scramble(seed),
+        }
+    }
+
+    /// Return the number of values the reservoir keeps at most.
+    pub fn capacity(&self) -> usize {
+        self.k
+    }
+
+    /// Return the number of values seen.
+    pub fn seen(&self) -> u64 {
+        self.n
+    }
+
+    /// Return the current sample.
+    pub fn sample(&self) -> &[T] {
+        &self.sample
+    }
+
+    /// Consume the reservoir and return its sample.
+    pub fn into_sample(self) -> Vec<T> {
+        self.sample
+    }
+
+    // Generate a random number by xorshift.
+    fn next_u64(&mut self) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.seed
+    }
+
+    // Generate a random number within [0, m).
+    fn below(&mut self, m: u64) -> u64 {
+        ((self.next_u64() as u128 * m as u128) >> 64) as u64
+    }
+
+    /// Offer a value from the stream.
+    /// Return true if it's taken into the sample, otherwise return false.
+    pub fn add(&mut self, x: T) -> bool {
+        self.n += 1;
+        if self.sample.len() < self.k {
+            self.sample.push(x);
+            return true;
+        }
+        let i = self.below(self.n);
+        if i < self.k as u64 {
+            self.sample[i as usize] = x;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Merge the sample of another stream, so that this reservoir samples the concatenation of both streams.
+    /// The number of values taken from each sample follows the hypergeometric distribution over stream lengths.
+    pub fn merge(&mut self, other: Reservoir<T>) {
+        let k = std::cmp::min(self.k, other.k);
+        let (mut na, mut nb) = (self.n, other.n);
+        let mut a = std::mem::take(&mut self.sample);
+        let mut b = other.sample;
+        self.shuffle(&mut a);
+        self.shuffle(&mut b);
+        let total = std::cmp::min(k as u64, na + nb);
+        let mut merged = Vec::with_capacity(k);
+        for _ in 0..total {
+            let from_a = self.below(na + nb) < na;
+            let x = if from_a {
+                na -= 1;
+                a.pop()
+            } else {
+                nb -= 1;
+                b.pop()
+            };
+            merged.push(x.expect("sample must hold enough values"));
+        }
+        self.sample = merged;
+        self.n += other.n;
+        self.k = k;
+    }
+
+    fn shuffle(&mut self, v: &mut [T]) {
+        for i in (1..v.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            v.swap(i, j);
+        }
+    }
+}
+
+// Spread bits of a seed by splitmix64, since xorshift from small seeds is correlated at first.
+// The result is never 0, where xorshift gets stuck.
+fn scramble(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (z ^ (z >> 31)) | 1
+}
+
+impl<T> HeapSize for Reservoir<T> {
+    fn heap_bytes(&self) -> usize {
+        self.sample.capacity() * std::mem::size_of::<T>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reservoir;
+
+    #[test]
+    fn reservoir_test() {
+        let mut reservoir = Reservoir::new(3);
+        assert_eq!(reservoir.capacity(), 3);
+        assert!(reservoir.add(1));
+        assert!(reservoir.add(2));
+        assert_eq!(reservoir.sample(), &[1, 2]);
+        assert!(reservoir.add(3));
+        for x in 4..100 {
+            reservoir.add(x);
+        }
+        assert_eq!(reservoir.seen(), 99);
+        let mut sample = reservoir.into_sample();
+        sample.sort();
+        sample.dedup();
+        assert_eq!(sample.len(), 3);
+        assert!(sample.iter().all(|x| (1..100).contains(x)));
+    }
+
+    #[test]
+    fn uniform_test() {
+        // each of 100 values is expected to be sampled 200 times over 2000 trials
+        let mut counts = [0; 100];
+        for trial in 0..2000 {
+            let mut reservoir = Reservoir::with_seed(10, trial);
+            for x in 0..100 {
+                reservoir.add(x);
+            }
+            for &x in reservoir.sample() {
+                counts[x] += 1;
+            }
+        }
+        assert!(
+            counts.iter().all(|&c| (120..280).contains(&c)),
+            "{:?}",
+            counts
+        );
+    }
+
+    #[test]
+    fn merge_test() {
+        // the first stream is 3 times longer, so it should provide 3/4 of the merged sample
+        let mut from_first = 0;
+        for trial in 0..500 {
+            let mut a = Reservoir::with_seed(8, 2 * trial);
+            let mut b = Reservoir::with_seed(8, 2 * trial + 1);
+            for x in 0..300 {
+                a.add(x);
+            }
+            for x in 300..400 {
+                b.add(x);
+            }
+            a.merge(b);
+            assert_eq!(a.seen(), 400);
+            assert_eq!(a.sample().len(), 8);
+            from_first += a.sample().iter().filter(|&&x| x < 300).count();
+        }
+        let ratio = from_first as f64 / (500.0 * 8.0);
+        assert!((0.7..0.8).contains(&ratio), "{}", ratio);
+
+        let mut small = Reservoir::with_seed(5, 1);
+        small.add(1);
+        let mut other = Reservoir::with_seed(5, 2);
+        other.add(2);
+        small.merge(other);
+        let mut sample = small.into_sample();
+        sample.sort();
+        assert_eq!(sample, vec![1, 2]);
+    }
+}
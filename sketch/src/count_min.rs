@@ -0,0 +1,163 @@
+use interface::heap_size::HeapSize;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Count-Min sketch estimates frequency of values with depth rows of width counters.
+/// Estimation never underestimates, and overestimates by at most ε * total with probability 1 - δ.
+/// O(depth): add(x), estimate(x)
+#[derive(Debug, Clone)]
+pub struct CountMinSketch<T: Hash> {
+    counts: Box<[u64]>,
+    width: usize,
+    depth: usize,
+    total: u64,
+    _marker: PhantomData<T>,
+}
+
+impl<T> CountMinSketch<T>
+where
+    T: Hash,
+{
+    /// Generate empty CountMinSketch with depth rows of width counters.
+    pub fn new(width: usize, depth: usize) -> Self {
+        let width = std::cmp::max(width, 1);
+        let depth = std::cmp::max(depth, 1);
+        CountMinSketch {
+            counts: vec![0; width * depth].into_boxed_slice(),
+            width,
+            depth,
+            total: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Generate empty CountMinSketch with error bound ε and failure probability δ.
+    /// width = ⌈e / ε⌉, depth = ⌈ln(1 / δ)⌉
+    pub fn with_error(epsilon: f64, delta: f64) -> Self {
+        if !(epsilon > 0.0 && delta > 0.0 && delta < 1.0) {
+            panic!(
+                "epsilon must be positive and delta must be within (0, 1). epsilon: {}, delta: {}",
+                epsilon, delta
+            )
+        }
+        let width = (std::f64::consts::E / epsilon).ceil() as usize;
+        let depth = (1.0 / delta).ln().ceil() as usize;
+        CountMinSketch::new(width, depth)
+    }
+
+    /// Return the number of counters per row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Return the number of rows.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Return the sum of all counts added.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Count an occurrence of a value.
+    pub fn add(&mut self, x: &T) {
+        self.add_count(x, 1);
+    }
+
+    /// Count c occurrences of a value.
+    pub fn add_count(&mut self, x: &T, c: u64) {
+        for row in 0..self.depth {
+            let pos = self.position(row, x);
+            self.counts[pos] = self.counts[pos].saturating_add(c);
+        }
+        self.total = self.total.saturating_add(c);
+    }
+
+    /// Return the estimated number of occurrences of a value.
+    pub fn estimate(&self, x: &T) -> u64 {
+        (0..self.depth)
+            .map(|row| self.counts[self.position(row, x)])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Add all counts of other sketch into this one.
+    /// Return true if it's merged, otherwise return false as both sketches don't have identical dimensions.
+    pub fn merge(&mut self, other: &Self) -> bool {
+        if self.width != other.width || self.depth != other.depth {
+            return false;
+        }
+        for (a, b) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *a = a.saturating_add(*b);
+        }
+        self.total = self.total.saturating_add(other.total);
+        true
+    }
+
+    // Return the index of the counter for a value in the given row.
+    fn position(&self, row: usize, x: &T) -> usize {
+        let mut hasher = DefaultHasher::new();
+        row.hash(&mut hasher);
+        x.hash(&mut hasher);
+        row * self.width + (hasher.finish() % self.width as u64) as usize
+    }
+}
+
+impl<T> HeapSize for CountMinSketch<T>
+where
+    T: Hash,
+{
+    fn heap_bytes(&self) -> usize {
+        self.counts.len() * std::mem::size_of::<u64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CountMinSketch;
+
+    #[test]
+    fn params_test() {
+        let sketch: CountMinSketch<u32> = CountMinSketch::with_error(0.01, 0.01);
+        assert_eq!(sketch.width(), 272);
+        assert_eq!(sketch.depth(), 5);
+        assert_eq!(sketch.total(), 0);
+    }
+
+    #[test]
+    fn estimate_test() {
+        let mut sketch: CountMinSketch<u32> = CountMinSketch::with_error(0.001, 0.01);
+        for x in 0..100 {
+            for _ in 0..x {
+                sketch.add(&x);
+            }
+        }
+        let total = sketch.total();
+        assert_eq!(total, 4950);
+        for x in 0..100 {
+            let estimate = sketch.estimate(&x);
+            assert!(estimate >= x as u64);
+            assert!(estimate <= x as u64 + total / 100);
+        }
+    }
+
+    #[test]
+    fn merge_test() {
+        let mut a: CountMinSketch<&str> = CountMinSketch::new(64, 4);
+        let mut b: CountMinSketch<&str> = CountMinSketch::new(64, 4);
+        a.add_count(&"apple", 3);
+        b.add_count(&"apple", 2);
+        b.add(&"banana");
+
+        assert!(a.merge(&b));
+        assert!(a.estimate(&"apple") >= 5);
+        assert!(a.estimate(&"banana") >= 1);
+        assert_eq!(a.total(), 6);
+
+        let c: CountMinSketch<&str> = CountMinSketch::new(32, 4);
+        assert!(!a.merge(&c));
+        assert_eq!(a.total(), 6);
+    }
+}
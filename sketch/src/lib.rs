@@ -0,0 +1,7 @@
+#![warn(missing_docs)]
+//! sketch implements probabilistic summaries of streams which answer queries approximately in sublinear space.
+
+/// Count-Min sketch providing approximate frequency count.
+pub mod count_min;
+
+pub use count_min::CountMinSketch;
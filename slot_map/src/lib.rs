@@ -0,0 +1,7 @@
+#![warn(missing_docs)]
+//! slot_map implements a container which hands out stable keys to its elements.
+
+/// Implementation for generation-indexed slot map.
+pub mod slot_map;
+
+pub use crate::slot_map::{Key, SlotMap};
@@ -0,0 +1,349 @@
+use interface::heap_size::HeapSize;
+
+/// Key of an element in SlotMap, which consists of a slot index and its generation.
+/// A key stays valid until its element is removed, and never refers to an element inserted later.
+/// A slot is retired instead of reused once its generation is exhausted, so that generations never wrap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Key {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug)]
+struct Slot<T> {
+    x: Option<T>,
+    // incremented each time the slot is vacated
+    generation: u32,
+    // next vacant slot, valid only while the slot is vacant
+    next_free: usize,
+}
+
+/// Container with backing array realized by boxed slice, which returns a stable key for each element.
+/// Removed slots are reused, and their generation is bumped to invalidate old keys.
+/// A slot vacated at the last generation is never reused.
+/// O(1): get(k), remove(k)
+/// O(1) amortized: insert(x)
+#[derive(Debug)]
+pub struct SlotMap<T> {
+    a: Box<[Slot<T>]>,
+    // number of slots which have been used at least once
+    len: usize,
+    free_head: usize,
+    n: usize,
+}
+
+impl<T> Default for SlotMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SlotMap<T> {
+    /// Generate empty SlotMap.
+    pub fn new() -> Self {
+        SlotMap {
+            a: allocate_with(0).into_boxed_slice(),
+            len: 0,
+            free_head: NONE,
+            n: 0,
+        }
+    }
+
+    /// Return the number of elements.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Add an element and return its key.
+    pub fn insert(&mut self, x: T) -> Key {
+        let index = if self.free_head != NONE {
+            let i = self.free_head;
+            self.free_head = self.a[i].next_free;
+            i
+        } else {
+            // check before resizing, so that a panic leaves the map unchanged
+            if self.len > u32::MAX as usize {
+                panic!("slot map cannot hold more elements. n: {}", self.n)
+            }
+            if self.len == self.capacity() {
+                self.resize();
+            }
+            self.len += 1;
+            self.len - 1
+        };
+        let slot = &mut self.a[index];
+        slot.x = Some(x);
+        self.n += 1;
+        Key {
+            index: index as u32,
+            generation: slot.generation,
+        }
+    }
+
+    /// Remove the element for a key and return it.
+    /// Return null(Empty) if the key is stale or unknown.
+    pub fn remove(&mut self, k: Key) -> Option<T> {
+        let i = k.index as usize;
+        let slot = self.a.get_mut(i)?;
+        if slot.generation != k.generation {
+            return None;
+        }
+        let x = slot.x.take()?;
+        self.n -= 1;
+        // a slot whose generation can't be bumped is left out of the vacant chain,
+        // where its last key finds no element
+        if let Some(generation) = slot.generation.checked_add(1) {
+            slot.generation = generation;
+            slot.next_free = self.free_head;
+            self.free_head = i;
+        }
+        Some(x)
+    }
+
+    /// Return the element for a key.
+    pub fn get(&self, k: Key) -> Option<&T> {
+        let slot = self.a.get(k.index as usize)?;
+        if slot.generation != k.generation {
+            return None;
+        }
+        slot.x.as_ref()
+    }
+
+    /// Return the mutable element for a key.
+    pub fn get_mut(&mut self, k: Key) -> Option<&mut T> {
+        let slot = self.a.get_mut(k.index as usize)?;
+        if slot.generation != k.generation {
+            return None;
+        }
+        slot.x.as_mut()
+    }
+
+    /// Return true if a key refers to an element.
+    pub fn contains_key(&self, k: Key) -> bool {
+        self.get(k).is_some()
+    }
+
+    /// Return an iterator over pairs of a key and an element in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (Key, &T)> {
+        self.a[..self.len]
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| {
+                slot.x.as_ref().map(|x| {
+                    (
+                        Key {
+                            index: i as u32,
+                            generation: slot.generation,
+                        },
+                        x,
+                    )
+                })
+            })
+    }
+
+    fn capacity(&self) -> usize {
+        self.a.len()
+    }
+
+    fn resize(&mut self) {
+        let len = std::cmp::max(self.capacity() * 2, 1);
+        let mut new_array = allocate_with(len);
+        for (i, slot) in self.a.iter_mut().enumerate() {
+            std::mem::swap(&mut new_array[i], slot);
+        }
+        self.a = new_array.into_boxed_slice();
+    }
+}
+
+// Marker of the end of the vacant slot chain.
+const NONE: usize = usize::MAX;
+
+fn allocate_with<T>(n: usize) -> Vec<Slot<T>> {
+    std::iter::repeat_with(|| Slot {
+        x: None,
+        generation: 0,
+        next_free: NONE,
+    })
+    .take(n)
+    .collect()
+}
+
+impl<T> HeapSize for SlotMap<T> {
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * std::mem::size_of::<Slot<T>>()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::SlotMap;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // Decode operations, each of which inserts a value and removes the i-th one in slot order if op % 3 == 0.
+    pub(super) fn ops<'a, T>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, usize, T)>>
+    where
+        T: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, usize, T)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Elements are inserted and sometimes removed, so that vacant slots are chained and generations are bumped.
+    impl<'a, T> Arbitrary<'a> for SlotMap<T>
+    where
+        T: Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut map = SlotMap::new();
+            for (op, i, x) in ops(u)? {
+                map.insert(x);
+                if op % 3 == 0 {
+                    // remove the i-th element in slot order
+                    let k = map.iter().nth(i % map.size()).map(|(k, _)| k);
+                    map.remove(k.expect("element within size must exist"));
+                }
+            }
+            Ok(map)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SlotMap;
+
+    #[test]
+    fn slot_map_test() {
+        let mut map: SlotMap<&str> = SlotMap::new();
+        let a = map.insert("a");
+        let b = map.insert("b");
+        let c = map.insert("c");
+        assert_eq!(map.size(), 3);
+        assert_eq!(map.get(a), Some(&"a"));
+        assert_eq!(map.get(b), Some(&"b"));
+        assert_eq!(map.get(c), Some(&"c"));
+
+        assert_eq!(map.remove(b), Some("b"));
+        assert_eq!(map.remove(b), None);
+        assert_eq!(map.get(b), None);
+        assert!(!map.contains_key(b));
+        assert_eq!(map.size(), 2);
+
+        // the slot of b is reused, but the old key doesn't refer to the new element
+        let d = map.insert("d");
+        assert_ne!(b, d);
+        assert_eq!(map.get(b), None);
+        assert_eq!(map.get(d), Some(&"d"));
+
+        *map.get_mut(a).unwrap() = "e";
+        assert_eq!(map.get(a), Some(&"e"));
+        assert_eq!(
+            map.iter().map(|(_, x)| *x).collect::<Vec<_>>(),
+            vec!["e", "d", "c"]
+        );
+        assert_eq!(
+            map.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            vec![a, d, c]
+        );
+    }
+
+    #[test]
+    fn retire_test() {
+        use super::Key;
+
+        let mut map: SlotMap<char> = SlotMap::new();
+        let a = map.insert('a');
+        // pretend the slot has been reused until the last generation
+        map.a[a.index as usize].generation = u32::MAX;
+        let last = Key {
+            index: a.index,
+            generation: u32::MAX,
+        };
+        assert_eq!(map.get(a), None);
+        assert_eq!(map.remove(last), Some('a'));
+        assert_eq!(map.size(), 0);
+
+        // the slot is retired instead of wrapping its generation around to that of older keys
+        let b = map.insert('b');
+        assert_ne!(b.index, a.index);
+        assert_eq!(map.get(last), None);
+        assert_eq!(map.remove(last), None);
+        assert_eq!(map.get(a), None);
+        assert_eq!(map.get(b), Some(&'b'));
+        assert_eq!(map.iter().map(|(k, _)| k).collect::<Vec<_>>(), vec![b]);
+    }
+
+    #[test]
+    fn capacity_test() {
+        use interface::heap_size::HeapSize;
+
+        let mut map: SlotMap<u32> = SlotMap::new();
+        let keys = (0..100).map(|x| map.insert(x)).collect::<Vec<_>>();
+        let bytes = map.heap_bytes();
+        for k in keys.iter().step_by(2) {
+            map.remove(*k);
+        }
+        for x in 0..50 {
+            map.insert(x);
+        }
+        assert_eq!(map.size(), 100);
+        assert_eq!(map.heap_bytes(), bytes);
+        for (i, k) in keys.iter().enumerate() {
+            if i % 2 == 0 {
+                assert_eq!(map.get(*k), None);
+            } else {
+                assert_eq!(map.get(*k), Some(&(i as u32)));
+            }
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use super::NONE;
+
+        test_support::replay::check_arbitrary(|map: SlotMap<u16>, u| {
+            // replay the same operations on a list of (element, generation) with a stack of vacant slots
+            let mut slots: Vec<(Option<u16>, u32)> = Vec::new();
+            let mut free: Vec<usize> = Vec::new();
+            for (op, i, x) in super::arb::ops::<u16>(u).unwrap() {
+                match free.pop() {
+                    Some(j) => slots[j].0 = Some(x),
+                    None => slots.push((Some(x), 0)),
+                }
+                if op % 3 == 0 {
+                    let live = slots.iter().filter(|s| s.0.is_some()).count();
+                    let (j, slot) = slots
+                        .iter_mut()
+                        .enumerate()
+                        .filter(|(_, s)| s.0.is_some())
+                        .nth(i % live)
+                        .unwrap();
+                    *slot = (None, slot.1 + 1);
+                    free.push(j);
+                }
+            }
+            assert_eq!(map.len, slots.len());
+            assert!(map.len <= map.capacity());
+            assert_eq!(map.size(), slots.iter().filter(|s| s.0.is_some()).count());
+            for (i, &(x, generation)) in slots.iter().enumerate() {
+                assert_eq!(map.a[i].x, x);
+                assert_eq!(map.a[i].generation, generation);
+            }
+            assert!(map
+                .iter()
+                .map(|(_, x)| *x)
+                .eq(slots.iter().filter_map(|s| s.0)));
+
+            // the chain of vacant slots pops the most recently vacated one first
+            let mut chain = Vec::new();
+            let mut i = map.free_head;
+            while i != NONE {
+                chain.push(i);
+                i = map.a[i].next_free;
+            }
+            assert!(chain.iter().eq(free.iter().rev()));
+        });
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{ListSwap, Swap};
+use interface::list::List;
+
+// Ranges no longer than this are sorted by insertion sort.
+const CUTOFF: usize = 16;
+
+/// Sort a slice in ascending order by introsort. Equal elements may be reordered.
+/// Quicksort falls back to heapsort once recursion gets deeper than 2 log n, and short ranges are finished
+/// by insertion sort.
+/// O(n log n) time, O(log n) extra space
+pub fn sort_unstable<T>(a: &mut [T])
+where
+    T: Ord,
+{
+    sort(a);
+}
+
+/// Sort a List in place in ascending order by introsort. Equal elements may be reordered.
+/// Elements are swapped through get(i) and set(i, x), so each swap clones an element.
+/// O(n log n) calls of get(i) and set(i, x), O(log n) extra space
+pub fn sort_list_unstable<T>(list: &mut dyn List<T>)
+where
+    T: Ord + Clone,
+{
+    sort(&mut ListSwap(list));
+}
+
+pub(crate) fn sort<S: Swap + ?Sized>(s: &mut S) {
+    let n = s.size();
+    let depth = 2 * (usize::BITS - n.leading_zeros()) as usize;
+    intro_sort(s, 0, n, depth);
+}
+
+fn intro_sort<S: Swap + ?Sized>(s: &mut S, mut lo: usize, mut hi: usize, mut depth: usize) {
+    while hi - lo > CUTOFF {
+        if depth == 0 {
+            heap_sort(s, lo, hi);
+            return;
+        }
+        depth -= 1;
+        let p = partition(s, lo, hi);
+        // recurse into the shorter side to bound the stack by O(log n)
+        if p - lo < hi - p {
+            intro_sort(s, lo, p, depth);
+            lo = p + 1;
+        } else {
+            intro_sort(s, p + 1, hi, depth);
+            hi = p;
+        }
+    }
+    insertion_sort(s, lo, hi);
+}
+
+// Partition s[lo..hi] around the median of three elements, and return the final position of the pivot.
+// Elements before it are smaller, and elements after it are not smaller.
+pub(crate) fn partition<S: Swap + ?Sized>(s: &mut S, lo: usize, hi: usize) -> usize {
+    let (mid, last) = (lo + (hi - lo) / 2, hi - 1);
+    if s.less(mid, lo) {
+        s.swap(mid, lo);
+    }
+    if s.less(last, mid) {
+        s.swap(last, mid);
+    }
+    if s.less(mid, lo) {
+        s.swap(mid, lo);
+    }
+    s.swap(mid, last);
+    partition_at(s, lo, hi)
+}
+
+// Partition s[lo..hi] around the pivot at s[hi - 1].
+pub(crate) fn partition_at<S: Swap + ?Sized>(s: &mut S, lo: usize, hi: usize) -> usize {
+    let last = hi - 1;
+    let mut i = lo;
+    for j in lo..last {
+        if s.less(j, last) {
+            s.swap(i, j);
+            i += 1;
+        }
+    }
+    s.swap(i, last);
+    i
+}
+
+pub(crate) fn heap_sort<S: Swap + ?Sized>(s: &mut S, lo: usize, hi: usize) {
+    let n = hi - lo;
+    for i in (0..n / 2).rev() {
+        sift_down(s, lo, i, n);
+    }
+    for end in (1..n).rev() {
+        s.swap(lo, lo + end);
+        sift_down(s, lo, 0, end);
+    }
+}
+
+// Move s[lo + i] down the max-heap s[lo..lo + n] until no child is larger.
+fn sift_down<S: Swap + ?Sized>(s: &mut S, lo: usize, mut i: usize, n: usize) {
+    loop {
+        let (l, r) = (2 * i + 1, 2 * i + 2);
+        let mut largest = i;
+        if l < n && s.less(lo + largest, lo + l) {
+            largest = l;
+        }
+        if r < n && s.less(lo + largest, lo + r) {
+            largest = r;
+        }
+        if largest == i {
+            return;
+        }
+        s.swap(lo + i, lo + largest);
+        i = largest;
+    }
+}
+
+pub(crate) fn insertion_sort<S: Swap + ?Sized>(s: &mut S, lo: usize, hi: usize) {
+    for i in lo + 1..hi {
+        let mut j = i;
+        while j > lo && s.less(j, j - 1) {
+            s.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sort_list_unstable, sort_unstable};
+
+    fn values(n: usize, m: u64) -> Vec<u64> {
+        // pseudo random values by linear congruential generator
+        let mut x: u64 = 5;
+        (0..n)
+            .map(|_| {
+                x = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (x >> 33) % m
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sort_test() {
+        let large = if cfg!(miri) { 500 } else { 5000 };
+        for n in [0, 1, 2, 16, 17, 100, large].iter() {
+            for m in [1, 3, u64::MAX].iter() {
+                let mut a = values(*n, *m);
+                let mut expected = a.clone();
+                expected.sort_unstable();
+                sort_unstable(&mut a);
+                assert_eq!(a, expected);
+            }
+        }
+        // inputs which are adversarial for naive pivot choices
+        let mut sorted = (0..large).collect::<Vec<_>>();
+        sort_unstable(&mut sorted);
+        assert!(sorted.windows(2).all(|w| w[0] <= w[1]));
+        let mut reversed = (0..large).rev().collect::<Vec<_>>();
+        sort_unstable(&mut reversed);
+        assert_eq!(reversed, sorted);
+    }
+
+    #[test]
+    fn heap_sort_test() {
+        let mut a = values(1000, 100);
+        let mut expected = a.clone();
+        expected[100..900].sort_unstable();
+        super::heap_sort(&mut a[..], 100, 900);
+        assert_eq!(a, expected);
+    }
+
+    #[test]
+    fn list_test() {
+        use array_list::array_deque::ArrayDeque;
+        use interface::list::List;
+        use interface::queue::Deque;
+
+        let mut list: ArrayDeque<u64> = ArrayDeque::new();
+        let a = values(500, 50);
+        for (i, x) in a.iter().enumerate() {
+            if i % 2 == 0 {
+                list.add_first(*x);
+            } else {
+                list.add_last(*x);
+            }
+        }
+        sort_list_unstable(&mut list);
+        let mut expected = a;
+        expected.sort_unstable();
+        for (i, x) in expected.iter().enumerate() {
+            assert_eq!(list.get(i), Some(x));
+        }
+    }
+}
@@ -0,0 +1,66 @@
+#![warn(missing_docs)]
+//! sorting implements comparison sorts over slices and List.
+
+/// Implementation for unstable introsort.
+pub mod intro_sort;
+/// Implementation for stable merge sort.
+pub mod merge_sort;
+/// Implementation for selection of order statistics.
+pub mod select;
+
+pub use intro_sort::{sort_list_unstable, sort_unstable};
+pub use merge_sort::{sort_list_stable, sort_stable};
+pub use select::{partial_sort, select_nth};
+
+use interface::list::List;
+
+// Random access to elements which can be compared and swapped in place.
+pub(crate) trait Swap {
+    fn size(&self) -> usize;
+    fn less(&self, i: usize, j: usize) -> bool;
+    fn swap(&mut self, i: usize, j: usize);
+}
+
+impl<T> Swap for [T]
+where
+    T: Ord,
+{
+    fn size(&self) -> usize {
+        self.len()
+    }
+    fn less(&self, i: usize, j: usize) -> bool {
+        self[i] < self[j]
+    }
+    fn swap(&mut self, i: usize, j: usize) {
+        <[T]>::swap(self, i, j)
+    }
+}
+
+// List viewed as Swap, which swaps elements by cloning one of them.
+pub(crate) struct ListSwap<'a, T>(pub(crate) &'a mut dyn List<T>);
+
+impl<T> ListSwap<'_, T> {
+    fn at(&self, i: usize) -> &T {
+        self.0.get(i).expect("index within size must exist")
+    }
+}
+
+impl<T> Swap for ListSwap<'_, T>
+where
+    T: Ord + Clone,
+{
+    fn size(&self) -> usize {
+        self.0.size()
+    }
+    fn less(&self, i: usize, j: usize) -> bool {
+        self.at(i) < self.at(j)
+    }
+    fn swap(&mut self, i: usize, j: usize) {
+        if i == j {
+            return;
+        }
+        let x = self.at(i).clone();
+        let y = self.0.set(j, x).expect("index within size must exist");
+        self.0.set(i, y);
+    }
+}
@@ -0,0 +1,147 @@
+use interface::list::List;
+
+// Ranges no longer than this are sorted by insertion sort.
+const CUTOFF: usize = 16;
+
+/// Sort a slice in ascending order by merge sort. Equal elements keep their relative order.
+/// O(n log n) time, O(n) extra space
+pub fn sort_stable<T>(a: &mut [T])
+where
+    T: Ord + Clone,
+{
+    let mut b = a.to_vec();
+    sort_with(a, &mut b);
+}
+
+/// Sort a List in ascending order by merge sort. Equal elements keep their relative order.
+/// Elements are copied to a buffer, so that get(i) and set(i, x) are called O(n) times.
+/// O(n log n) time, O(n) extra space
+pub fn sort_list_stable<T>(list: &mut dyn List<T>)
+where
+    T: Ord + Clone,
+{
+    let mut a = (0..list.size())
+        .map(|i| list.get(i).expect("index within size must exist").clone())
+        .collect::<Vec<_>>();
+    sort_stable(&mut a);
+    for (i, x) in a.into_iter().enumerate() {
+        list.set(i, x);
+    }
+}
+
+// Sort a, where b holds the same elements as a and is used as a buffer.
+fn sort_with<T>(a: &mut [T], b: &mut [T])
+where
+    T: Ord + Clone,
+{
+    if a.len() <= CUTOFF {
+        insertion_sort(a);
+        return;
+    }
+    let mid = a.len() / 2;
+    // sort both halves of b, using the halves of a as buffers, then merge them back to a
+    let (a0, a1) = a.split_at_mut(mid);
+    let (b0, b1) = b.split_at_mut(mid);
+    sort_with(b0, a0);
+    sort_with(b1, a1);
+    merge(b0, b1, a);
+}
+
+fn merge<T>(a0: &[T], a1: &[T], a: &mut [T])
+where
+    T: Ord + Clone,
+{
+    let (mut i, mut j) = (0, 0);
+    for x in a.iter_mut() {
+        // take from a0 on ties to keep the order of equal elements
+        if j == a1.len() || (i < a0.len() && a0[i] <= a1[j]) {
+            *x = a0[i].clone();
+            i += 1;
+        } else {
+            *x = a1[j].clone();
+            j += 1;
+        }
+    }
+}
+
+fn insertion_sort<T: Ord>(a: &mut [T]) {
+    for i in 1..a.len() {
+        let mut j = i;
+        while j > 0 && a[j] < a[j - 1] {
+            a.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sort_list_stable, sort_stable};
+    use std::cmp::Ordering;
+
+    // Value ordered only by key, to observe stability.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Item {
+        key: u32,
+        id: usize,
+    }
+
+    impl PartialOrd for Item {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Item {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.key.cmp(&other.key)
+        }
+    }
+
+    fn items(n: usize) -> Vec<Item> {
+        // pseudo random keys by linear congruential generator
+        let mut x: u64 = 1;
+        (0..n)
+            .map(|id| {
+                x = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                Item {
+                    key: (x >> 58) as u32,
+                    id,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sort_test() {
+        for n in [0, 1, 2, 15, 16, 17, 100, 1000].iter() {
+            let mut a = items(*n);
+            let mut expected = a.clone();
+            expected.sort_by_key(|item| (item.key, item.id));
+            sort_stable(&mut a);
+            assert_eq!(a, expected);
+        }
+    }
+
+    #[test]
+    fn list_test() {
+        use array_list::array_stack::ArrayStack;
+        use interface::list::List;
+        use interface::queue::Stack;
+
+        let mut list: ArrayStack<Item> = ArrayStack::new();
+        let a = items(300);
+        for x in a.iter() {
+            list.push(x.clone());
+        }
+        sort_list_stable(&mut list);
+        let mut expected = a;
+        expected.sort_by_key(|item| (item.key, item.id));
+        assert_eq!(list.size(), 300);
+        for (i, x) in expected.iter().enumerate() {
+            assert_eq!(list.get(i), Some(x));
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use crate::intro_sort::{insertion_sort, sort};
+use crate::Swap;
+use std::cell::Cell;
+
+// Ranges no longer than this are finished by insertion sort.
+const CUTOFF: usize = 16;
+
+thread_local! {
+    static SEED: Cell<u64> = const { Cell::new(0x9e37_79b9_7f4a_7c15) };
+}
+
+// Generate a random number within [0, m) by xorshift.
+fn below(m: usize) -> usize {
+    SEED.with(|seed| {
+        let mut s = seed.get();
+        s ^= s << 13;
+        s ^= s >> 7;
+        s ^= s << 17;
+        seed.set(s);
+        ((s as u128 * m as u128) >> 64) as usize
+    })
+}
+
+/// Reorder a slice so that a[k] is the element which would be there if it were sorted,
+/// elements before it are not larger, and elements after it are not smaller. Return a[k].
+/// Quickselect with random pivots falls back to median of medians pivots after too many rounds,
+/// which keeps the worst case linear.
+/// O(n) time, O(1) extra space
+pub fn select_nth<T>(a: &mut [T], k: usize) -> &mut T
+where
+    T: Ord,
+{
+    if k >= a.len() {
+        panic!(
+            "index must be positive and less than the size of list. i: {}, n: {}",
+            k,
+            a.len()
+        )
+    }
+    // random pivots shrink the range to 3/4 in 2 rounds on average
+    let rounds = 4 * (usize::BITS - a.len().leading_zeros()) as usize;
+    select(a, 0, a.len(), k, rounds);
+    &mut a[k]
+}
+
+/// Reorder a slice so that a[..k] holds the k smallest elements in ascending order.
+/// The order of the rest is unspecified.
+/// O(n + k log k) time
+pub fn partial_sort<T>(a: &mut [T], k: usize)
+where
+    T: Ord,
+{
+    if k >= a.len() {
+        sort(a);
+        return;
+    }
+    if k == 0 {
+        return;
+    }
+    select_nth(a, k - 1);
+    sort(&mut a[..k - 1]);
+}
+
+// Narrow s[lo..hi] down to k, taking random pivots for the given rounds and median of medians after that.
+fn select<S: Swap + ?Sized>(s: &mut S, mut lo: usize, mut hi: usize, k: usize, mut rounds: usize) {
+    while hi - lo > CUTOFF {
+        let p = if rounds > 0 {
+            rounds -= 1;
+            lo + below(hi - lo)
+        } else {
+            median_of_medians(s, lo, hi)
+        };
+        let (lt, gt) = partition3(s, lo, hi, p);
+        if k < lt {
+            hi = lt;
+        } else if k >= gt {
+            lo = gt;
+        } else {
+            return;
+        }
+    }
+    insertion_sort(s, lo, hi);
+}
+
+// Partition s[lo..hi] into elements smaller than, equal to and larger than s[p].
+// Return the range of equal elements.
+fn partition3<S: Swap + ?Sized>(s: &mut S, lo: usize, hi: usize, p: usize) -> (usize, usize) {
+    s.swap(lo, p);
+    // s[lt] always holds an element equal to the pivot
+    let (mut lt, mut i, mut gt) = (lo, lo + 1, hi);
+    while i < gt {
+        if s.less(i, lt) {
+            s.swap(lt, i);
+            lt += 1;
+            i += 1;
+        } else if s.less(lt, i) {
+            gt -= 1;
+            s.swap(i, gt);
+        } else {
+            i += 1;
+        }
+    }
+    (lt, gt)
+}
+
+// Return the position of an element whose rank in s[lo..hi] is between 3/10 and 7/10.
+// Medians of groups of 5 are gathered at the front, and their median is selected recursively.
+fn median_of_medians<S: Swap + ?Sized>(s: &mut S, lo: usize, hi: usize) -> usize {
+    let mut groups = 0;
+    for start in (lo..hi).step_by(5) {
+        let end = std::cmp::min(start + 5, hi);
+        insertion_sort(s, start, end);
+        s.swap(lo + groups, start + (end - start) / 2);
+        groups += 1;
+    }
+    let mid = lo + groups / 2;
+    select(s, lo, lo + groups, mid, 0);
+    mid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{partial_sort, select_nth};
+
+    fn values(n: usize, m: u64) -> Vec<u64> {
+        // pseudo random values by linear congruential generator
+        let mut x: u64 = 11;
+        (0..n)
+            .map(|_| {
+                x = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (x >> 33) % m
+            })
+            .collect()
+    }
+
+    #[test]
+    fn select_test() {
+        for n in [1, 2, 17, 100, 3000].iter() {
+            for m in [1, 5, u64::MAX].iter() {
+                let a = values(*n, *m);
+                let mut sorted = a.clone();
+                sorted.sort_unstable();
+                for k in [0, n / 3, n / 2, n - 1].iter() {
+                    let mut b = a.clone();
+                    assert_eq!(*select_nth(&mut b, *k), sorted[*k]);
+                    assert!(b[..*k].iter().all(|x| *x <= sorted[*k]));
+                    assert!(b[*k..].iter().all(|x| *x >= sorted[*k]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn median_of_medians_test() {
+        // no random rounds, so that every pivot is a median of medians
+        for m in [1, 7, u64::MAX].iter() {
+            let mut a = values(2000, *m);
+            let mut sorted = a.clone();
+            sorted.sort_unstable();
+            super::select(&mut a[..], 0, 2000, 1234, 0);
+            assert_eq!(a[1234], sorted[1234]);
+            assert!(a[..1234].iter().all(|x| *x <= sorted[1234]));
+            assert!(a[1234..].iter().all(|x| *x >= sorted[1234]));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_out_of_range_test() {
+        select_nth(&mut [1, 2, 3], 3);
+    }
+
+    #[test]
+    fn partial_sort_test() {
+        let a = values(1000, 300);
+        let mut sorted = a.clone();
+        sorted.sort_unstable();
+        for k in [0, 1, 10, 999, 1000, 2000].iter() {
+            let mut b = a.clone();
+            partial_sort(&mut b, *k);
+            let k = std::cmp::min(*k, 1000);
+            assert_eq!(b[..k], sorted[..k]);
+            let mut rest = b[k..].to_vec();
+            rest.sort_unstable();
+            assert_eq!(rest, sorted[k..]);
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use interface::heap_size::HeapSize;
+use std::cmp::Ordering;
+
+/// Map keyed by points in K dimensional space realized by k-d tree.
+/// A node at depth d splits the space by the (d mod K)-th coordinate of its point,
+/// smaller coordinates go to the left subtree and the others go to the right one.
+/// O(log n) expected: insert(p, v) for points in random order
+/// insert doesn't rebalance, so points in sorted order make a path of depth n; build(points) makes a balanced tree.
+/// Queries walk the tree with an explicit stack, so that such a path doesn't overflow the call stack.
+/// O(log n) expected: nearest(q) for points evenly distributed
+/// O(n^(1 - 1/K) + m): range(lo, hi) for m points within the box
+#[derive(Debug)]
+pub struct KdTree<const K: usize, T> {
+    root: Link<K, T>,
+    n: usize,
+}
+
+type Link<const K: usize, T> = Option<Box<Node<K, T>>>;
+
+#[derive(Debug)]
+struct Node<const K: usize, T> {
+    p: [f64; K],
+    value: T,
+    left: Link<K, T>,
+    right: Link<K, T>,
+}
+
+impl<const K: usize, T> Drop for Node<K, T> {
+    // Release descendants iteratively, since a tree built by insert can be as deep as its size.
+    fn drop(&mut self) {
+        let mut stack = vec![self.left.take(), self.right.take()];
+        while let Some(t) = stack.pop() {
+            if let Some(mut u) = t {
+                stack.push(u.left.take());
+                stack.push(u.right.take());
+            }
+        }
+    }
+}
+
+fn validate<const K: usize>(p: &[f64; K]) {
+    if p.iter().any(|c| c.is_nan()) {
+        panic!("coordinates of a point must not be NaN. p: {:?}", p)
+    }
+}
+
+fn distance<const K: usize>(p: &[f64; K], q: &[f64; K]) -> f64 {
+    p.iter().zip(q.iter()).map(|(a, b)| (a - b) * (a - b)).sum()
+}
+
+// Build a balanced subtree by splitting points at the median of the axis.
+fn build<const K: usize, T>(mut points: Vec<([f64; K], T)>, depth: usize) -> Link<K, T> {
+    if points.is_empty() {
+        return None;
+    }
+    let axis = depth % K;
+    let mid = points.len() / 2;
+    points.select_nth_unstable_by(mid, |(p, _), (q, _)| {
+        p[axis].partial_cmp(&q[axis]).unwrap_or(Ordering::Equal)
+    });
+    let right = points.split_off(mid + 1);
+    let (p, value) = points.pop().expect("median must exist");
+    // points equal to the median on the axis may be left of it, which queries tolerate
+    Some(Box::new(Node {
+        p,
+        value,
+        left: build(points, depth + 1),
+        right: build(right, depth + 1),
+    }))
+}
+
+impl<const K: usize, T> Default for KdTree<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const K: usize, T> KdTree<K, T> {
+    /// Generate empty KdTree.
+    pub fn new() -> Self {
+        if K == 0 {
+            panic!("dimension must be positive. K: {}", K)
+        }
+        KdTree { root: None, n: 0 }
+    }
+
+    /// Generate balanced KdTree from points in O(n log n).
+    pub fn build(points: Vec<([f64; K], T)>) -> Self {
+        let mut tree = Self::new();
+        points.iter().for_each(|(p, _)| validate(p));
+        tree.n = points.len();
+        tree.root = build(points, 0);
+        tree
+    }
+
+    /// Return the number of points.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Add a point with a value. The same point can be added more than once.
+    pub fn insert(&mut self, p: [f64; K], value: T) {
+        validate(&p);
+        let mut t = &mut self.root;
+        let mut depth = 0;
+        while let Some(u) = t {
+            let axis = depth % K;
+            t = if p[axis] < u.p[axis] {
+                &mut u.left
+            } else {
+                &mut u.right
+            };
+            depth += 1;
+        }
+        *t = Some(Box::new(Node {
+            p,
+            value,
+            left: None,
+            right: None,
+        }));
+        self.n += 1;
+    }
+
+    /// Return the value of a point which equals to p.
+    /// Return null(Empty) if no such point exists.
+    pub fn get(&self, p: &[f64; K]) -> Option<&T> {
+        let mut stack = vec![(&self.root, 0)];
+        while let Some((t, depth)) = stack.pop() {
+            let u = match t {
+                Some(u) => u,
+                None => continue,
+            };
+            if &u.p == p {
+                return Some(&u.value);
+            }
+            let axis = depth % K;
+            // equal coordinates can be on either side after build, and the left side is searched first
+            if p[axis] >= u.p[axis] {
+                stack.push((&u.right, depth + 1));
+            }
+            if p[axis] <= u.p[axis] {
+                stack.push((&u.left, depth + 1));
+            }
+        }
+        None
+    }
+
+    /// Return the closest point to q by Euclidean distance, and its value.
+    /// Return null(Empty) if the tree is empty.
+    pub fn nearest(&self, q: &[f64; K]) -> Option<(&[f64; K], &T)> {
+        validate(q);
+        let mut best: Option<(&Node<K, T>, f64)> = None;
+        // subtrees with the squared distance to the splitting plane if they're on the far side
+        let mut stack = vec![(&self.root, 0, None)];
+        while let Some((t, depth, plane)) = stack.pop() {
+            let u = match t {
+                Some(u) => u,
+                None => continue,
+            };
+            // the far side can hold a closer point only if the splitting plane is closer than the best,
+            // which is checked after the near side has been searched
+            if plane.is_some_and(|p| !best.is_none_or(|(_, bd)| p < bd)) {
+                continue;
+            }
+            let d = distance(&u.p, q);
+            if best.is_none_or(|(_, bd)| d < bd) {
+                best = Some((u, d));
+            }
+            let axis = depth % K;
+            let diff = q[axis] - u.p[axis];
+            let (near, far) = if diff < 0.0 {
+                (&u.left, &u.right)
+            } else {
+                (&u.right, &u.left)
+            };
+            stack.push((far, depth + 1, Some(diff * diff)));
+            stack.push((near, depth + 1, None));
+        }
+        best.map(|(u, _)| (&u.p, &u.value))
+    }
+
+    /// Return all points within the box lo[i] <= p[i] <= hi[i] for each axis i, and their values.
+    pub fn range(&self, lo: &[f64; K], hi: &[f64; K]) -> Vec<(&[f64; K], &T)> {
+        let mut found = Vec::new();
+        let mut stack = vec![(&self.root, 0)];
+        while let Some((t, depth)) = stack.pop() {
+            let u = match t {
+                Some(u) => u,
+                None => continue,
+            };
+            if (0..K).all(|i| lo[i] <= u.p[i] && u.p[i] <= hi[i]) {
+                found.push((&u.p, &u.value));
+            }
+            let axis = depth % K;
+            // equal coordinates can be on either side after build
+            if u.p[axis] <= hi[axis] {
+                stack.push((&u.right, depth + 1));
+            }
+            if lo[axis] <= u.p[axis] {
+                stack.push((&u.left, depth + 1));
+            }
+        }
+        found
+    }
+}
+
+impl<const K: usize, T> HeapSize for KdTree<K, T> {
+    fn heap_bytes(&self) -> usize {
+        let mut bytes = 0;
+        let mut stack = self.root.as_deref().into_iter().collect::<Vec<_>>();
+        while let Some(u) = stack.pop() {
+            bytes += std::mem::size_of::<Node<K, T>>();
+            stack.extend(u.left.as_deref());
+            stack.extend(u.right.as_deref());
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KdTree;
+    use interface::heap_size::HeapSize;
+
+    fn points(n: usize) -> Vec<[f64; 3]> {
+        // pseudo random points by linear congruential generator
+        let mut x: u64 = 7;
+        let mut next = move || {
+            x = x
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (x >> 11) as f64 / (1u64 << 53) as f64
+        };
+        (0..n).map(|_| [next(), next(), next()]).collect()
+    }
+
+    #[test]
+    fn insert_test() {
+        let mut tree: KdTree<2, &str> = KdTree::new();
+        assert_eq!(tree.nearest(&[0.0, 0.0]), None);
+        tree.insert([2.0, 3.0], "a");
+        tree.insert([5.0, 4.0], "b");
+        tree.insert([9.0, 6.0], "c");
+        tree.insert([4.0, 7.0], "d");
+        tree.insert([8.0, 1.0], "e");
+        tree.insert([7.0, 2.0], "f");
+        assert_eq!(tree.size(), 6);
+        assert_eq!(tree.get(&[4.0, 7.0]), Some(&"d"));
+        assert_eq!(tree.get(&[4.0, 6.0]), None);
+
+        assert_eq!(tree.nearest(&[9.0, 2.0]), Some((&[8.0, 1.0], &"e")));
+        assert_eq!(tree.nearest(&[3.0, 6.0]), Some((&[4.0, 7.0], &"d")));
+
+        let mut found = tree
+            .range(&[3.0, 1.0], &[8.0, 4.0])
+            .into_iter()
+            .map(|(_, v)| *v)
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+        assert_eq!(found, vec!["b", "e", "f"]);
+    }
+
+    #[test]
+    fn build_test() {
+        let n = if cfg!(miri) { 200 } else { 2000 };
+        let ps = points(n);
+        let tree = KdTree::build(ps.iter().copied().zip(0..).collect());
+        let mut inserted = KdTree::new();
+        for (i, p) in ps.iter().enumerate() {
+            inserted.insert(*p, i);
+        }
+        assert_eq!(tree.size(), n);
+
+        for q in points(n / 10).iter().map(|p| [p[2], p[0], p[1]]) {
+            let dist = |p: &[f64; 3]| super::distance(p, &q);
+            let expected = ps.iter().map(dist).fold(f64::INFINITY, f64::min);
+            assert_eq!(dist(tree.nearest(&q).unwrap().0), expected);
+            assert_eq!(dist(inserted.nearest(&q).unwrap().0), expected);
+        }
+
+        let (lo, hi) = ([0.2, 0.1, 0.5], [0.6, 0.4, 0.9]);
+        let within = |p: &[f64; 3]| (0..3).all(|i| lo[i] <= p[i] && p[i] <= hi[i]);
+        let mut expected = (0..n).filter(|&i| within(&ps[i])).collect::<Vec<_>>();
+        expected.sort_unstable();
+        for t in [&tree, &inserted].iter() {
+            let mut found = t
+                .range(&lo, &hi)
+                .into_iter()
+                .map(|(_, &i)| i)
+                .collect::<Vec<_>>();
+            found.sort_unstable();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn sorted_path_test() {
+        // monotone points make a path going right at every node
+        let n = if cfg!(miri) { 1_000 } else { 100_000 };
+        let mut tree = KdTree::new();
+        for i in 0..1000.min(n) {
+            tree.insert([i as f64, i as f64], i);
+        }
+        // the rest of the path is linked directly, since inserting it takes quadratic time
+        let mut path = None;
+        for i in (1000..n).rev() {
+            path = Some(Box::new(super::Node {
+                p: [i as f64, i as f64],
+                value: i,
+                left: None,
+                right: path,
+            }));
+        }
+        let mut t = &mut tree.root;
+        while let Some(u) = t {
+            t = &mut u.right;
+        }
+        *t = path;
+        tree.n = n;
+
+        assert_eq!(tree.get(&[(n - 1) as f64; 2]), Some(&(n - 1)));
+        assert_eq!(tree.get(&[0.5, 0.5]), None);
+        assert_eq!(tree.nearest(&[n as f64; 2]).map(|(_, &i)| i), Some(n - 1));
+        assert_eq!(tree.range(&[0.0; 2], &[n as f64; 2]).len(), n);
+        assert_eq!(
+            tree.heap_bytes(),
+            n * std::mem::size_of::<super::Node<2, usize>>()
+        );
+    }
+
+    #[test]
+    fn duplicate_test() {
+        let tree = KdTree::build(vec![([1.0], 'a'), ([1.0], 'b'), ([1.0], 'c'), ([0.0], 'd')]);
+        assert_eq!(tree.range(&[1.0], &[1.0]).len(), 3);
+        assert_eq!(tree.nearest(&[0.4]).map(|(_, v)| *v), Some('d'));
+        // points sharing the splitting coordinate are split to both sides of the median
+        let ps = [[2.0, 1.0], [2.0, 0.0], [2.0, 2.0], [1.0, 5.0], [3.0, 5.0]];
+        let tree = KdTree::build(ps.iter().map(|&p| (p, ())).collect());
+        assert!(ps.iter().all(|p| tree.get(p).is_some()));
+    }
+}
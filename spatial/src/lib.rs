@@ -0,0 +1,10 @@
+#![warn(missing_docs)]
+//! spatial implements structures which answer geometric queries over points.
+
+/// Implementation for k-d tree.
+pub mod kd_tree;
+/// Implementation for point region quadtree.
+pub mod quad_tree;
+
+pub use kd_tree::KdTree;
+pub use quad_tree::{QuadTree, Rect};
@@ -0,0 +1,349 @@
+use interface::heap_size::HeapSize;
+
+/// Axis aligned rectangle which contains points with min[i] <= p[i] <= max[i] for each axis i.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    /// The corner with the smallest coordinates.
+    pub min: [f64; 2],
+    /// The corner with the largest coordinates.
+    pub max: [f64; 2],
+}
+
+impl Rect {
+    /// Generate Rect from two corners.
+    pub fn new(min: [f64; 2], max: [f64; 2]) -> Self {
+        if !(min[0] <= max[0] && min[1] <= max[1]) {
+            panic!(
+                "min must not exceed max on each axis. min: {:?}, max: {:?}",
+                min, max
+            )
+        }
+        Rect { min, max }
+    }
+
+    /// Return true if the rectangle contains a point.
+    pub fn contains(&self, p: &[f64; 2]) -> bool {
+        (0..2).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+
+    /// Return true if two rectangles share any point.
+    pub fn intersects(&self, other: &Rect) -> bool {
+        (0..2).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    // Return the center, halving each corner first so that bounds near f64::MAX don't overflow.
+    fn mid(&self) -> [f64; 2] {
+        [
+            self.min[0] / 2.0 + self.max[0] / 2.0,
+            self.min[1] / 2.0 + self.max[1] / 2.0,
+        ]
+    }
+
+    // Split into four quadrants. Points on the middle lines belong to the upper quadrants.
+    fn quadrants(&self) -> [Rect; 4] {
+        let mid = self.mid();
+        [
+            Rect::new(self.min, mid),
+            Rect::new([mid[0], self.min[1]], [self.max[0], mid[1]]),
+            Rect::new([self.min[0], mid[1]], [mid[0], self.max[1]]),
+            Rect::new(mid, self.max),
+        ]
+    }
+
+    fn quadrant_of(&self, p: &[f64; 2]) -> usize {
+        let mid = self.mid();
+        let east = p[0] >= mid[0];
+        let north = p[1] >= mid[1];
+        east as usize + 2 * north as usize
+    }
+}
+
+/// Map keyed by 2D points realized by point region quadtree.
+/// Each node covers a rectangle and holds points directly until it has more than node capacity,
+/// then it's split into four quadrants of the same size. Empty enough quadrants are merged back on removal.
+/// O(depth): insert(p, v), remove(p) where depth is bounded by the ratio of the bounds to the closest points
+/// O(depth + m): range(r) for m points within the rectangle, when points are evenly distributed
+#[derive(Debug)]
+pub struct QuadTree<T> {
+    root: Node<T>,
+    capacity: usize,
+    n: usize,
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    bounds: Rect,
+    points: Vec<([f64; 2], T)>,
+    children: Option<Box<[Node<T>; 4]>>,
+    // the number of points in this subtree
+    n: usize,
+}
+
+impl<T> Node<T> {
+    fn new(bounds: Rect) -> Self {
+        Node {
+            bounds,
+            points: Vec::new(),
+            children: None,
+            n: 0,
+        }
+    }
+
+    fn insert(&mut self, p: [f64; 2], value: T, capacity: usize, depth: usize) {
+        self.n += 1;
+        if let Some(children) = self.children.as_mut() {
+            let q = self.bounds.quadrant_of(&p);
+            return children[q].insert(p, value, capacity, depth + 1);
+        }
+        self.points.push((p, value));
+        // identical points can't be separated, so splitting stops at some depth
+        if self.points.len() > capacity && depth < QuadTree::<T>::MAX_DEPTH {
+            let [a, b, c, d] = self.bounds.quadrants();
+            self.children = Some(Box::new([
+                Node::new(a),
+                Node::new(b),
+                Node::new(c),
+                Node::new(d),
+            ]));
+            for (p, value) in std::mem::take(&mut self.points) {
+                self.n -= 1;
+                self.insert(p, value, capacity, depth);
+            }
+        }
+    }
+
+    fn remove(&mut self, p: &[f64; 2], capacity: usize) -> Option<T> {
+        let x = match self.children.as_mut() {
+            Some(children) => {
+                let q = self.bounds.quadrant_of(p);
+                children[q].remove(p, capacity)?
+            }
+            None => {
+                let i = self.points.iter().position(|(q, _)| q == p)?;
+                self.points.swap_remove(i).1
+            }
+        };
+        self.n -= 1;
+        if self.n <= capacity {
+            if let Some(children) = self.children.take() {
+                let [a, b, c, d] = *children;
+                for mut u in [a, b, c, d] {
+                    u.drain_into(&mut self.points);
+                }
+            }
+        }
+        Some(x)
+    }
+
+    fn drain_into(&mut self, points: &mut Vec<([f64; 2], T)>) {
+        points.append(&mut self.points);
+        if let Some(children) = self.children.as_mut() {
+            for u in children.iter_mut() {
+                u.drain_into(points);
+            }
+        }
+    }
+
+    fn range<'a>(&'a self, r: &Rect, found: &mut Vec<(&'a [f64; 2], &'a T)>) {
+        if self.n == 0 || !self.bounds.intersects(r) {
+            return;
+        }
+        found.extend(
+            self.points
+                .iter()
+                .filter(|(p, _)| r.contains(p))
+                .map(|(p, v)| (p, v)),
+        );
+        if let Some(children) = self.children.as_ref() {
+            for u in children.iter() {
+                u.range(r, found);
+            }
+        }
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.points.capacity() * std::mem::size_of::<([f64; 2], T)>()
+            + self.children.as_ref().map_or(0, |children| {
+                std::mem::size_of::<[Node<T>; 4]>()
+                    + children.iter().map(|u| u.heap_bytes()).sum::<usize>()
+            })
+    }
+}
+
+impl<T> QuadTree<T> {
+    // Depth at which nodes are no longer split.
+    const MAX_DEPTH: usize = 32;
+
+    /// Generate empty QuadTree covering bounds, whose nodes hold at most 4 points before splitting.
+    pub fn new(bounds: Rect) -> Self {
+        Self::with_node_capacity(bounds, 4)
+    }
+
+    /// Generate empty QuadTree covering bounds, whose nodes hold at most capacity points before splitting.
+    /// Bounds must be finite, since they're split at the middle.
+    pub fn with_node_capacity(bounds: Rect, capacity: usize) -> Self {
+        if capacity == 0 {
+            panic!("node capacity must be positive. capacity: {}", capacity)
+        }
+        if !bounds
+            .min
+            .iter()
+            .chain(bounds.max.iter())
+            .all(|c| c.is_finite())
+        {
+            panic!("bounds must be finite. bounds: {:?}", bounds)
+        }
+        QuadTree {
+            root: Node::new(bounds),
+            capacity,
+            n: 0,
+        }
+    }
+
+    /// Return the rectangle covered by the tree.
+    pub fn bounds(&self) -> Rect {
+        self.root.bounds
+    }
+
+    /// Return the number of points.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Add a point with a value. The same point can be added more than once.
+    /// Return false without adding it if the point is out of bounds, otherwise return true.
+    pub fn insert(&mut self, p: [f64; 2], value: T) -> bool {
+        if !self.root.bounds.contains(&p) {
+            return false;
+        }
+        self.root.insert(p, value, self.capacity, 0);
+        self.n += 1;
+        true
+    }
+
+    /// Remove one of points which equal to p, and return its value.
+    /// Return null(Empty) if no such point exists.
+    pub fn remove(&mut self, p: &[f64; 2]) -> Option<T> {
+        if !self.root.bounds.contains(p) {
+            return None;
+        }
+        let x = self.root.remove(p, self.capacity)?;
+        self.n -= 1;
+        Some(x)
+    }
+
+    /// Return all points within a rectangle, and their values.
+    pub fn range(&self, r: &Rect) -> Vec<(&[f64; 2], &T)> {
+        let mut found = Vec::new();
+        self.root.range(r, &mut found);
+        found
+    }
+}
+
+impl<T> HeapSize for QuadTree<T> {
+    fn heap_bytes(&self) -> usize {
+        self.root.heap_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QuadTree, Rect};
+
+    #[test]
+    fn quad_tree_test() {
+        let mut tree = QuadTree::with_node_capacity(Rect::new([0.0, 0.0], [8.0, 8.0]), 2);
+        assert!(tree.insert([1.0, 1.0], 'a'));
+        assert!(tree.insert([6.0, 1.0], 'b'));
+        assert!(tree.insert([1.0, 6.0], 'c'));
+        assert!(tree.insert([4.0, 4.0], 'd'));
+        assert!(tree.insert([7.0, 7.0], 'e'));
+        assert!(!tree.insert([9.0, 1.0], 'f'));
+        assert_eq!(tree.size(), 5);
+        assert!(tree.root.children.is_some());
+
+        let mut found = tree
+            .range(&Rect::new([0.0, 0.0], [4.0, 4.0]))
+            .into_iter()
+            .map(|(_, v)| *v)
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+        assert_eq!(found, vec!['a', 'd']);
+
+        assert_eq!(tree.remove(&[4.0, 4.0]), Some('d'));
+        assert_eq!(tree.remove(&[4.0, 4.0]), None);
+        assert_eq!(tree.remove(&[9.0, 1.0]), None);
+        assert_eq!(tree.remove(&[7.0, 7.0]), Some('e'));
+        assert_eq!(tree.remove(&[6.0, 1.0]), Some('b'));
+        // quadrants are merged back once the node has room for all points
+        assert!(tree.root.children.is_none());
+        assert_eq!(tree.size(), 2);
+        assert_eq!(tree.range(&tree.bounds()).len(), 2);
+    }
+
+    #[test]
+    fn duplicate_test() {
+        let mut tree = QuadTree::with_node_capacity(Rect::new([0.0, 0.0], [1.0, 1.0]), 1);
+        for i in 0..100 {
+            assert!(tree.insert([0.5, 0.5], i));
+        }
+        assert_eq!(tree.range(&Rect::new([0.5, 0.5], [0.5, 0.5])).len(), 100);
+        for _ in 0..100 {
+            assert!(tree.remove(&[0.5, 0.5]).is_some());
+        }
+        assert_eq!(tree.size(), 0);
+        assert!(tree.root.children.is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "bounds must be finite")]
+    fn infinite_bounds_test() {
+        QuadTree::<()>::new(Rect::new([f64::NEG_INFINITY; 2], [f64::INFINITY; 2]));
+    }
+
+    #[test]
+    fn max_bounds_test() {
+        // the middle of the widest finite bounds doesn't overflow
+        let mut tree = QuadTree::new(Rect::new([-f64::MAX; 2], [f64::MAX; 2]));
+        for i in 0..20 {
+            assert!(tree.insert([f64::MAX / (i + 1) as f64, -1.0], i));
+        }
+        assert!(tree.insert([f64::MAX; 2], 20));
+        // infinite rectangles can still be queried
+        let everything = Rect::new([f64::NEG_INFINITY; 2], [f64::INFINITY; 2]);
+        assert_eq!(tree.range(&everything).len(), 21);
+        assert_eq!(tree.remove(&[f64::MAX; 2]), Some(20));
+    }
+
+    #[test]
+    fn random_test() {
+        // pseudo random points by linear congruential generator
+        let mut x: u64 = 3;
+        let mut next = move || {
+            x = x
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((x >> 11) as f64 / (1u64 << 53) as f64) * 100.0
+        };
+        let ps = (0..1000).map(|_| [next(), next()]).collect::<Vec<_>>();
+        let mut tree = QuadTree::new(Rect::new([0.0, 0.0], [100.0, 100.0]));
+        for (i, p) in ps.iter().enumerate() {
+            assert!(tree.insert(*p, i));
+        }
+        for i in (0..1000).step_by(3) {
+            assert_eq!(tree.remove(&ps[i]), Some(i));
+        }
+        let r = Rect::new([20.0, 30.0], [70.0, 55.0]);
+        let mut expected = (0..1000)
+            .filter(|i| i % 3 != 0 && r.contains(&ps[*i]))
+            .collect::<Vec<_>>();
+        let mut found = tree
+            .range(&r)
+            .into_iter()
+            .map(|(_, &i)| i)
+            .collect::<Vec<_>>();
+        expected.sort_unstable();
+        found.sort_unstable();
+        assert_eq!(found, expected);
+    }
+}
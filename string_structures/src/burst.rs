@@ -0,0 +1,397 @@
+use array_list::array_stack::ArrayStack;
+use interface::heap_size::HeapSize;
+use interface::list::List;
+use interface::queue::Stack;
+
+// The number of suffixes in a bucket which triggers burst by default.
+const DEFAULT_LIMIT: usize = 32;
+
+/// Set of byte strings realized by burst trie.
+/// Trie nodes branch on a byte, and keys sharing a prefix are kept as suffixes in an unsorted ArrayStack bucket
+/// until the bucket exceeds the limit, when it bursts into a new trie node which redistributes the suffixes.
+/// O(|key| + limit): insert(key), remove(key), contains(key)
+/// O(n log limit): iter() since buckets are sorted when visited
+#[derive(Debug)]
+pub struct BurstTrie {
+    root: TrieNode,
+    limit: usize,
+    n: usize,
+}
+
+#[derive(Debug)]
+struct TrieNode {
+    // children indexed by the next byte
+    children: Box<[Child]>,
+    // true if a key ends at this node
+    end: bool,
+}
+
+#[derive(Debug)]
+enum Child {
+    Empty,
+    Trie(Box<TrieNode>),
+    Bucket(ArrayStack<Vec<u8>>),
+}
+
+impl TrieNode {
+    fn new() -> Self {
+        TrieNode {
+            children: std::iter::repeat_with(|| Child::Empty).take(256).collect(),
+            end: false,
+        }
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.children.len() * std::mem::size_of::<Child>()
+            + self
+                .children
+                .iter()
+                .map(|child| match child {
+                    Child::Empty => 0,
+                    Child::Trie(u) => std::mem::size_of::<TrieNode>() + u.heap_bytes(),
+                    Child::Bucket(bucket) => {
+                        bucket.heap_bytes()
+                            + (0..bucket.size())
+                                .filter_map(|i| bucket.get(i))
+                                .map(Vec::capacity)
+                                .sum::<usize>()
+                    }
+                })
+                .sum::<usize>()
+    }
+}
+
+// Return the position of the suffix in the bucket.
+fn position(bucket: &ArrayStack<Vec<u8>>, suffix: &[u8]) -> Option<usize> {
+    (0..bucket.size()).find(|&i| bucket.get(i).map(Vec::as_slice) == Some(suffix))
+}
+
+impl Default for BurstTrie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BurstTrie {
+    /// Generate empty BurstTrie.
+    pub fn new() -> Self {
+        Self::with_limit(DEFAULT_LIMIT)
+    }
+
+    /// Generate empty BurstTrie whose buckets burst when they hold more than limit suffixes.
+    pub fn with_limit(limit: usize) -> Self {
+        BurstTrie {
+            root: TrieNode::new(),
+            limit: std::cmp::max(limit, 1),
+            n: 0,
+        }
+    }
+
+    /// Return the number of keys.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Add a key if it doesn't exist.
+    /// Return true if it's ingested, otherwise return false.
+    pub fn insert<K: AsRef<[u8]>>(&mut self, key: K) -> bool {
+        let added = Self::insert_at(&mut self.root, key.as_ref(), self.limit);
+        if added {
+            self.n += 1;
+        }
+        added
+    }
+
+    fn insert_at(u: &mut TrieNode, key: &[u8], limit: usize) -> bool {
+        let mut u = u;
+        let mut key = key;
+        loop {
+            let (&c, rest) = match key.split_first() {
+                None => return !std::mem::replace(&mut u.end, true),
+                Some(split) => split,
+            };
+            let child = &mut u.children[c as usize];
+            match child {
+                Child::Empty => {
+                    let mut bucket = ArrayStack::new();
+                    bucket.push(rest.to_vec());
+                    *child = Child::Bucket(bucket);
+                    return true;
+                }
+                Child::Bucket(bucket) => {
+                    if position(bucket, rest).is_some() {
+                        return false;
+                    }
+                    bucket.push(rest.to_vec());
+                    if bucket.size() > limit {
+                        // burst the bucket into a trie node
+                        let mut w = TrieNode::new();
+                        for i in 0..bucket.size() {
+                            let suffix = bucket.get(i).expect("suffix within size must exist");
+                            Self::insert_at(&mut w, suffix, limit);
+                        }
+                        *child = Child::Trie(Box::new(w));
+                    }
+                    return true;
+                }
+                Child::Trie(w) => {
+                    u = w;
+                    key = rest;
+                }
+            }
+        }
+    }
+
+    /// Return true if key exists.
+    pub fn contains<K: AsRef<[u8]>>(&self, key: K) -> bool {
+        let mut u = &self.root;
+        let mut key = key.as_ref();
+        loop {
+            let (&c, rest) = match key.split_first() {
+                None => return u.end,
+                Some(split) => split,
+            };
+            match &u.children[c as usize] {
+                Child::Empty => return false,
+                Child::Bucket(bucket) => return position(bucket, rest).is_some(),
+                Child::Trie(w) => {
+                    u = w;
+                    key = rest;
+                }
+            }
+        }
+    }
+
+    /// Remove key if it exists.
+    /// Return true if it's removed, otherwise return false.
+    pub fn remove<K: AsRef<[u8]>>(&mut self, key: K) -> bool {
+        let mut u = &mut self.root;
+        let mut key = key.as_ref();
+        let removed = loop {
+            let (&c, rest) = match key.split_first() {
+                None => break std::mem::replace(&mut u.end, false),
+                Some(split) => split,
+            };
+            let child = &mut u.children[c as usize];
+            match child {
+                Child::Empty => break false,
+                Child::Bucket(bucket) => match position(bucket, rest) {
+                    Some(i) => {
+                        bucket.remove(i);
+                        if bucket.size() == 0 {
+                            *child = Child::Empty;
+                        }
+                        break true;
+                    }
+                    None => break false,
+                },
+                Child::Trie(w) => {
+                    u = w;
+                    key = rest;
+                }
+            }
+        };
+        if removed {
+            self.n -= 1;
+        }
+        removed
+    }
+
+    /// Return an iterator over keys in lexicographic order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            stack: vec![Frame::Node(&self.root, Vec::new())],
+        }
+    }
+}
+
+impl HeapSize for BurstTrie {
+    fn heap_bytes(&self) -> usize {
+        self.root.heap_bytes()
+    }
+}
+
+#[derive(Debug)]
+enum Frame<'a> {
+    // a trie node with the key before it
+    Node(&'a TrieNode, Vec<u8>),
+    Key(Vec<u8>),
+}
+
+/// Iterator over keys of BurstTrie in lexicographic order.
+#[derive(Debug)]
+pub struct Iter<'a> {
+    stack: Vec<Frame<'a>>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        while let Some(frame) = self.stack.pop() {
+            let (u, prefix) = match frame {
+                Frame::Key(key) => return Some(key),
+                Frame::Node(u, prefix) => (u, prefix),
+            };
+            for (c, child) in u.children.iter().enumerate().rev() {
+                let mut key = prefix.clone();
+                key.push(c as u8);
+                match child {
+                    Child::Empty => {}
+                    Child::Trie(w) => self.stack.push(Frame::Node(w, key)),
+                    Child::Bucket(bucket) => {
+                        let mut suffixes = (0..bucket.size())
+                            .filter_map(|i| bucket.get(i))
+                            .collect::<Vec<_>>();
+                        suffixes.sort_unstable();
+                        for suffix in suffixes.into_iter().rev() {
+                            let mut k = key.clone();
+                            k.extend_from_slice(suffix);
+                            self.stack.push(Frame::Key(k));
+                        }
+                    }
+                }
+            }
+            if u.end {
+                return Some(prefix);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::BurstTrie;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // Decode the limit and operations of (op, i, key), where op % 4 == 0 removes the i-th key
+    // inserted so far.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn ops<'a>(u: &mut Unstructured<'a>) -> Result<(usize, Vec<(u8, usize, &'a [u8])>)> {
+        let limit = u.int_in_range(1..=8)?;
+        let ops = (0..u.arbitrary_len::<(u8, usize, &[u8])>()?)
+            .map(|_| u.arbitrary())
+            .collect::<Result<_>>()?;
+        Ok((limit, ops))
+    }
+
+    // A small limit is chosen so that buckets burst, and keys inserted so far are sometimes removed again.
+    impl<'a> Arbitrary<'a> for BurstTrie {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let (limit, ops) = ops(u)?;
+            let mut trie = BurstTrie::with_limit(limit);
+            let mut keys: Vec<&[u8]> = Vec::new();
+            for (op, i, key) in ops {
+                if op % 4 == 0 && !keys.is_empty() {
+                    trie.remove(keys[i % keys.len()]);
+                } else {
+                    trie.insert(key);
+                    keys.push(key);
+                }
+            }
+            Ok(trie)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BurstTrie;
+
+    #[test]
+    fn set_test() {
+        let mut trie = BurstTrie::with_limit(2);
+        assert_eq!(trie.size(), 0);
+        assert!(!trie.contains("a"));
+
+        for w in ["bat", "ball", "band", "bar", "b", "", "cat"] {
+            assert!(trie.insert(w));
+        }
+        assert!(!trie.insert("ball"));
+        assert_eq!(trie.size(), 7);
+        for w in ["bat", "ball", "band", "bar", "b", "", "cat"] {
+            assert!(trie.contains(w));
+        }
+        assert!(!trie.contains("ba"));
+        assert!(!trie.contains("cats"));
+
+        assert!(trie.remove("ball"));
+        assert!(!trie.remove("ball"));
+        assert!(trie.remove(""));
+        assert!(trie.remove("cat"));
+        assert!(!trie.contains("cat"));
+        assert_eq!(trie.size(), 4);
+        assert_eq!(
+            trie.iter().collect::<Vec<_>>(),
+            vec![
+                b"b".to_vec(),
+                b"band".to_vec(),
+                b"bar".to_vec(),
+                b"bat".to_vec()
+            ]
+        );
+    }
+
+    #[test]
+    fn sorted_test() {
+        let mut trie = BurstTrie::new();
+        let mut expected = std::collections::BTreeSet::new();
+        let n: u64 = if cfg!(miri) { 300 } else { 3000 };
+        for i in 0..n {
+            let key = format!("{:x}", i.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 40);
+            assert_eq!(trie.insert(&key), expected.insert(key.into_bytes()));
+        }
+        assert_eq!(trie.size(), expected.len());
+        assert!(trie.iter().eq(expected.iter().cloned()));
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use super::{Child, TrieNode};
+        use interface::list::List;
+        use std::collections::BTreeSet;
+
+        // Check that buckets are non-empty within the limit and hold distinct suffixes,
+        // and return the number of keys.
+        fn check(u: &TrieNode, limit: usize) -> usize {
+            let below = u
+                .children
+                .iter()
+                .map(|child| match child {
+                    Child::Empty => 0,
+                    Child::Trie(w) => check(w, limit),
+                    Child::Bucket(bucket) => {
+                        let suffixes = (0..bucket.size())
+                            .filter_map(|i| bucket.get(i))
+                            .collect::<BTreeSet<_>>();
+                        assert!(0 < bucket.size() && bucket.size() <= limit);
+                        assert_eq!(suffixes.len(), bucket.size());
+                        bucket.size()
+                    }
+                })
+                .sum::<usize>();
+            below + u.end as usize
+        }
+
+        test_support::replay::check_arbitrary(|trie: BurstTrie, u| {
+            // replay the same operations on BTreeSet
+            let (limit, ops) = super::arb::ops(u).unwrap();
+            let mut expected = BTreeSet::new();
+            let mut keys: Vec<&[u8]> = Vec::new();
+            for (op, i, key) in ops {
+                if op % 4 == 0 && !keys.is_empty() {
+                    expected.remove(keys[i % keys.len()]);
+                } else {
+                    expected.insert(key);
+                    keys.push(key);
+                }
+            }
+            assert_eq!(trie.limit, limit);
+            assert_eq!(trie.size(), expected.len());
+            assert_eq!(check(&trie.root, limit), expected.len());
+            assert!(trie.iter().eq(expected.iter().map(|k| k.to_vec())));
+        });
+    }
+}
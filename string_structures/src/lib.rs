@@ -0,0 +1,16 @@
+#![warn(missing_docs)]
+//! string_structures implements structures which index keys by their string (byte sequence) representation.
+
+/// Implementation for burst trie whose leaves are buckets of suffixes.
+pub mod burst;
+/// Implementation for Patricia trie (compressed radix tree).
+pub mod patricia;
+/// Implementation for suffix array with LCP array.
+pub mod suffix_array;
+/// Implementation for ternary search trie.
+pub mod tst;
+
+pub use burst::BurstTrie;
+pub use patricia::PatriciaTrie;
+pub use suffix_array::SuffixArray;
+pub use tst::TernarySearchTrie;
@@ -0,0 +1,401 @@
+use interface::heap_size::HeapSize;
+
+/// Map keyed by byte strings realized by Patricia trie (compressed radix tree).
+/// Each edge is labeled with a byte string, and a node with a single child and no value is merged into its child.
+/// O(|key|): insert(key, v), remove(key), get(key), longest_prefix(key)
+/// O(|prefix| + k): iter_prefix(prefix) for k keys under prefix
+#[derive(Debug)]
+pub struct PatriciaTrie<V> {
+    root: Node<V>,
+    n: usize,
+}
+
+#[derive(Debug)]
+struct Node<V> {
+    // label of the edge from the parent
+    label: Vec<u8>,
+    value: Option<V>,
+    // sorted by the first byte of labels
+    children: Vec<Node<V>>,
+}
+
+impl<V> Node<V> {
+    fn new(label: Vec<u8>, value: Option<V>) -> Self {
+        Node {
+            label,
+            value,
+            children: Vec::new(),
+        }
+    }
+
+    // Return the index of the child whose label starts with b, otherwise where it should be.
+    fn child(&self, b: u8) -> Result<usize, usize> {
+        self.children.binary_search_by_key(&b, |c| c.label[0])
+    }
+
+    fn insert(&mut self, key: &[u8], v: V) -> Option<V> {
+        if key.is_empty() {
+            return self.value.replace(v);
+        }
+        let i = match self.child(key[0]) {
+            Ok(i) => i,
+            Err(i) => {
+                self.children.insert(i, Node::new(key.to_vec(), Some(v)));
+                return None;
+            }
+        };
+        let child = &mut self.children[i];
+        let c = common_prefix(&child.label, key);
+        if c < child.label.len() {
+            // split the edge at c, and the old child hangs below the new node
+            let rest = child.label.split_off(c);
+            let mut mid = Node::new(std::mem::replace(&mut child.label, rest), None);
+            std::mem::swap(child, &mut mid);
+            child.children.push(mid);
+        }
+        child.insert(&key[c..], v)
+    }
+
+    fn remove(&mut self, key: &[u8]) -> Option<V> {
+        if key.is_empty() {
+            return self.value.take();
+        }
+        let i = self.child(key[0]).ok()?;
+        let child = &mut self.children[i];
+        if !key.starts_with(&child.label) {
+            return None;
+        }
+        let x = child.remove(&key[child.label.len()..])?;
+        if child.value.is_none() {
+            match child.children.len() {
+                0 => {
+                    self.children.remove(i);
+                }
+                1 => {
+                    // merge the only grandchild into the child
+                    let mut grandchild = child.children.pop().expect("child must exist");
+                    let mut label = std::mem::take(&mut child.label);
+                    label.append(&mut grandchild.label);
+                    grandchild.label = label;
+                    *child = grandchild;
+                }
+                _ => {}
+            }
+        }
+        Some(x)
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.label.capacity()
+            + self.children.capacity() * std::mem::size_of::<Node<V>>()
+            + self.children.iter().map(Node::heap_bytes).sum::<usize>()
+    }
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+impl<V> Default for PatriciaTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> PatriciaTrie<V> {
+    /// Generate empty PatriciaTrie.
+    pub fn new() -> Self {
+        PatriciaTrie {
+            root: Node::new(Vec::new(), None),
+            n: 0,
+        }
+    }
+
+    /// Return the number of keys.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Associate v with key, and return the previous value if it exists.
+    pub fn insert<K: AsRef<[u8]>>(&mut self, key: K, v: V) -> Option<V> {
+        let old = self.root.insert(key.as_ref(), v);
+        if old.is_none() {
+            self.n += 1;
+        }
+        old
+    }
+
+    /// Remove key, and return its value if it exists.
+    pub fn remove<K: AsRef<[u8]>>(&mut self, key: K) -> Option<V> {
+        let x = self.root.remove(key.as_ref())?;
+        self.n -= 1;
+        Some(x)
+    }
+
+    /// Return the value of key if it exists, otherwise return null(Empty).
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<&V> {
+        let mut u = &self.root;
+        let mut key = key.as_ref();
+        while !key.is_empty() {
+            u = &u.children[u.child(key[0]).ok()?];
+            key = key.strip_prefix(u.label.as_slice())?;
+        }
+        u.value.as_ref()
+    }
+
+    /// Return the longest key which is a prefix of a given key, with its value.
+    /// e.g. the most specific route for an address.
+    pub fn longest_prefix<'k>(&self, key: &'k [u8]) -> Option<(&'k [u8], &V)> {
+        let mut u = &self.root;
+        let mut depth = 0;
+        let mut best = u.value.as_ref().map(|v| (0, v));
+        while depth < key.len() {
+            let i = match u.child(key[depth]) {
+                Ok(i) => i,
+                Err(_) => break,
+            };
+            u = &u.children[i];
+            if !key[depth..].starts_with(&u.label) {
+                break;
+            }
+            depth += u.label.len();
+            if let Some(v) = u.value.as_ref() {
+                best = Some((depth, v));
+            }
+        }
+        best.map(|(d, v)| (&key[..d], v))
+    }
+
+    /// Return an iterator over keys which start with prefix and their values in lexicographic order.
+    pub fn iter_prefix<K: AsRef<[u8]>>(&self, prefix: K) -> Iter<'_, V> {
+        let mut u = &self.root;
+        let mut path = Vec::new();
+        let mut rest = prefix.as_ref();
+        while !rest.is_empty() {
+            let child = match u.child(rest[0]) {
+                Ok(i) => &u.children[i],
+                Err(_) => return Iter { stack: Vec::new() },
+            };
+            // the prefix may end in the middle of the label
+            let c = common_prefix(&child.label, rest);
+            if c < rest.len() && c < child.label.len() {
+                return Iter { stack: Vec::new() };
+            }
+            path.extend_from_slice(&child.label);
+            rest = &rest[c..];
+            u = child;
+        }
+        Iter {
+            stack: vec![(u, path)],
+        }
+    }
+
+    /// Return an iterator over all keys and their values in lexicographic order.
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.iter_prefix([])
+    }
+}
+
+impl<V> HeapSize for PatriciaTrie<V> {
+    fn heap_bytes(&self) -> usize {
+        self.root.heap_bytes()
+    }
+}
+
+/// Iterator over keys and values of PatriciaTrie.
+#[derive(Debug)]
+pub struct Iter<'a, V> {
+    // nodes to visit with their keys
+    stack: Vec<(&'a Node<V>, Vec<u8>)>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((u, key)) = self.stack.pop() {
+            for child in u.children.iter().rev() {
+                let mut k = key.clone();
+                k.extend_from_slice(&child.label);
+                self.stack.push((child, k));
+            }
+            if let Some(v) = u.value.as_ref() {
+                return Some((key, v));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::PatriciaTrie;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // Decode operations of (op, i, key, value), where op % 4 == 0 removes the i-th key inserted so far.
+    #[allow(clippy::type_complexity)]
+    pub(super) fn ops<'a, V>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, usize, &'a [u8], V)>>
+    where
+        V: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, usize, &[u8], V)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Keys are inserted and keys inserted so far are sometimes removed again, so that edges are split and merged.
+    impl<'a, V> Arbitrary<'a> for PatriciaTrie<V>
+    where
+        V: Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut trie = PatriciaTrie::new();
+            let mut keys: Vec<&[u8]> = Vec::new();
+            for (op, i, key, v) in ops(u)? {
+                if op % 4 == 0 && !keys.is_empty() {
+                    trie.remove(keys[i % keys.len()]);
+                } else {
+                    trie.insert(key, v);
+                    keys.push(key);
+                }
+            }
+            Ok(trie)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PatriciaTrie;
+
+    #[test]
+    fn map_test() {
+        let mut trie = PatriciaTrie::new();
+        assert_eq!(trie.size(), 0);
+        assert_eq!(trie.get("a"), None);
+
+        assert_eq!(trie.insert("romane", 1), None);
+        assert_eq!(trie.insert("romanus", 2), None);
+        assert_eq!(trie.insert("romulus", 3), None);
+        assert_eq!(trie.insert("rubens", 4), None);
+        assert_eq!(trie.insert("rom", 5), None);
+        assert_eq!(trie.insert("rubens", 6), Some(4));
+        assert_eq!(trie.size(), 5);
+        assert_eq!(trie.get("romanus"), Some(&2));
+        assert_eq!(trie.get("rom"), Some(&5));
+        assert_eq!(trie.get("roman"), None);
+        assert_eq!(trie.get("romanes"), None);
+        assert_eq!(trie.get(""), None);
+
+        assert_eq!(trie.remove("roman"), None);
+        assert_eq!(trie.remove("romane"), Some(1));
+        assert_eq!(trie.remove("romane"), None);
+        assert_eq!(trie.get("romanus"), Some(&2));
+        assert_eq!(trie.remove("rom"), Some(5));
+        assert_eq!(trie.get("romulus"), Some(&3));
+        assert_eq!(trie.size(), 3);
+
+        let keys = trie
+            .iter()
+            .map(|(k, v)| (String::from_utf8(k).unwrap(), *v))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            vec![
+                ("romanus".to_string(), 2),
+                ("romulus".to_string(), 3),
+                ("rubens".to_string(), 6)
+            ]
+        );
+    }
+
+    #[test]
+    fn prefix_test() {
+        let mut trie = PatriciaTrie::new();
+        for (i, w) in ["tea", "ted", "ten", "inn", "to", "i", "in"]
+            .iter()
+            .enumerate()
+        {
+            trie.insert(w, i);
+        }
+        let under = |p: &str| {
+            trie.iter_prefix(p)
+                .map(|(k, _)| String::from_utf8(k).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(under("te"), vec!["tea", "ted", "ten"]);
+        assert_eq!(under("t"), vec!["tea", "ted", "ten", "to"]);
+        assert_eq!(under("in"), vec!["in", "inn"]);
+        assert_eq!(under("x"), Vec::<String>::new());
+        assert_eq!(under("tex"), Vec::<String>::new());
+        assert_eq!(under("").len(), 7);
+    }
+
+    #[test]
+    fn longest_prefix_test() {
+        // routes keyed by address bytes
+        let mut table = PatriciaTrie::new();
+        table.insert([10u8], "10/8");
+        table.insert([10u8, 1], "10.1/16");
+        table.insert([10u8, 1, 2], "10.1.2/24");
+        table.insert([192u8, 168], "192.168/16");
+
+        assert_eq!(
+            table.longest_prefix(&[10, 1, 2, 3]),
+            Some((&[10u8, 1, 2][..], &"10.1.2/24"))
+        );
+        assert_eq!(
+            table.longest_prefix(&[10, 1, 9, 9]).map(|(_, v)| *v),
+            Some("10.1/16")
+        );
+        assert_eq!(
+            table.longest_prefix(&[10, 2, 0, 0]).map(|(_, v)| *v),
+            Some("10/8")
+        );
+        assert_eq!(table.longest_prefix(&[192, 169, 0, 1]), None);
+
+        table.insert([], "default");
+        assert_eq!(
+            table.longest_prefix(&[8, 8, 8, 8]).map(|(_, v)| *v),
+            Some("default")
+        );
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use super::Node;
+        use std::collections::BTreeMap;
+
+        // Check labels and branching below u, and return the number of values.
+        fn check(u: &Node<u16>, root: bool) -> usize {
+            if !root {
+                assert!(!u.label.is_empty());
+                // a node without value is merged into its child unless it branches
+                assert!(u.value.is_some() || u.children.len() >= 2);
+            }
+            assert!(u.children.windows(2).all(|w| w[0].label[0] < w[1].label[0]));
+            let below = u.children.iter().map(|c| check(c, false)).sum::<usize>();
+            below + u.value.is_some() as usize
+        }
+
+        test_support::replay::check_arbitrary(|trie: PatriciaTrie<u16>, u| {
+            // replay the same operations on BTreeMap
+            let mut expected = BTreeMap::new();
+            let mut keys: Vec<&[u8]> = Vec::new();
+            for (op, i, key, v) in super::arb::ops::<u16>(u).unwrap() {
+                if op % 4 == 0 && !keys.is_empty() {
+                    expected.remove(keys[i % keys.len()]);
+                } else {
+                    expected.insert(key, v);
+                    keys.push(key);
+                }
+            }
+            assert_eq!(trie.size(), expected.len());
+            assert_eq!(check(&trie.root, true), expected.len());
+            assert!(trie
+                .iter()
+                .eq(expected.iter().map(|(k, v)| (k.to_vec(), v))));
+        });
+    }
+}
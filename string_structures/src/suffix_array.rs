@@ -0,0 +1,211 @@
+use interface::heap_size::HeapSize;
+
+/// Suffix array of a byte string, with the LCP array computed by Kasai's algorithm.
+/// Suffixes are sorted by prefix doubling: in each round they are radix sorted by pairs of ranks of
+/// their first k bytes and the next k bytes, so that k doubles.
+/// O(n log n): build(text)
+/// O(m log n): find(pattern) for a pattern of m bytes
+#[derive(Debug, Clone)]
+pub struct SuffixArray {
+    text: Box<[u8]>,
+    sa: Box<[usize]>,
+    lcp: Box<[usize]>,
+}
+
+// Stable sort of indices by their keys within [0, max].
+fn counting_sort(order: &[usize], key: &[usize], max: usize) -> Vec<usize> {
+    let mut count = vec![0; max + 2];
+    for &i in order {
+        count[key[i] + 1] += 1;
+    }
+    for r in 1..count.len() {
+        count[r] += count[r - 1];
+    }
+    let mut sorted = vec![0; order.len()];
+    for &i in order {
+        sorted[count[key[i]]] = i;
+        count[key[i]] += 1;
+    }
+    sorted
+}
+
+impl SuffixArray {
+    /// Generate SuffixArray of text.
+    pub fn build(text: &[u8]) -> Self {
+        let n = text.len();
+        let bytes = text.iter().map(|&b| b as usize).collect::<Vec<_>>();
+        let mut sa = counting_sort(&(0..n).collect::<Vec<_>>(), &bytes, u8::MAX as usize);
+        let mut rank = Self::classify(&sa, |i| (bytes[i], None));
+        let mut k = 1;
+        // until suffixes are distinguished by their first k bytes
+        while n > 0 && rank[sa[n - 1]] < n - 1 {
+            // radix sort by the second half: suffixes without it come first, then by rank of i + k
+            let mut order = (n.saturating_sub(k)..n).collect::<Vec<_>>();
+            order.extend(sa.iter().filter(|&&i| i >= k).map(|&i| i - k));
+            sa = counting_sort(&order, &rank, rank[sa[n - 1]]);
+            rank = Self::classify(&sa, |i| (rank[i], (i + k < n).then(|| rank[i + k])));
+            k *= 2;
+        }
+        let lcp = Self::kasai(text, &sa, &rank);
+        SuffixArray {
+            text: text.into(),
+            sa: sa.into_boxed_slice(),
+            lcp: lcp.into_boxed_slice(),
+        }
+    }
+
+    // Assign ranks to sorted suffixes, where suffixes with the same key share a rank.
+    fn classify<F>(sa: &[usize], key: F) -> Vec<usize>
+    where
+        F: Fn(usize) -> (usize, Option<usize>),
+    {
+        let mut rank = vec![0; sa.len()];
+        for j in 1..sa.len() {
+            rank[sa[j]] = rank[sa[j - 1]] + (key(sa[j - 1]) != key(sa[j])) as usize;
+        }
+        rank
+    }
+
+    // lcp[i] is the length of the longest common prefix of the suffixes sa[i - 1] and sa[i].
+    // Moving from the suffix i to i + 1 loses at most one byte of the common prefix.
+    fn kasai(text: &[u8], sa: &[usize], rank: &[usize]) -> Vec<usize> {
+        let n = text.len();
+        let mut lcp = vec![0; n];
+        let mut h = 0;
+        for i in 0..n {
+            if rank[i] == 0 {
+                h = 0;
+                continue;
+            }
+            let j = sa[rank[i] - 1];
+            while i + h < n && j + h < n && text[i + h] == text[j + h] {
+                h += 1;
+            }
+            lcp[rank[i]] = h;
+            h = h.saturating_sub(1);
+        }
+        lcp
+    }
+
+    /// Return the length of the text.
+    pub fn size(&self) -> usize {
+        self.text.len()
+    }
+
+    /// Return start positions of suffixes in lexicographic order.
+    pub fn suffixes(&self) -> &[usize] {
+        &self.sa
+    }
+
+    /// Return lengths of the longest common prefix between adjacent suffixes, where the first one is 0.
+    pub fn lcp(&self) -> &[usize] {
+        &self.lcp
+    }
+
+    // Return the first m bytes of the suffix i.
+    fn head(&self, i: usize, m: usize) -> &[u8] {
+        &self.text[i..std::cmp::min(i + m, self.text.len())]
+    }
+
+    /// Return start positions of occurrences of pattern, in lexicographic order of their suffixes.
+    pub fn find(&self, pattern: &[u8]) -> &[usize] {
+        let m = pattern.len();
+        let lo = self.sa.partition_point(|&i| self.head(i, m) < pattern);
+        let hi = self.sa.partition_point(|&i| self.head(i, m) <= pattern);
+        &self.sa[lo..hi]
+    }
+
+    /// Return the longest substring which occurs at least twice.
+    pub fn longest_repeat(&self) -> &[u8] {
+        match (0..self.lcp.len()).max_by_key(|&i| self.lcp[i]) {
+            Some(i) => self.head(self.sa[i], self.lcp[i]),
+            None => &[],
+        }
+    }
+}
+
+impl HeapSize for SuffixArray {
+    fn heap_bytes(&self) -> usize {
+        self.text.len() + 2 * self.sa.len() * std::mem::size_of::<usize>()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::SuffixArray;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // The array is determined by the text, so it's simply built.
+    impl<'a> Arbitrary<'a> for SuffixArray {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(SuffixArray::build(u.arbitrary()?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SuffixArray;
+
+    #[test]
+    fn build_test() {
+        let sa = SuffixArray::build(b"banana");
+        assert_eq!(sa.size(), 6);
+        assert_eq!(sa.suffixes(), &[5, 3, 1, 0, 4, 2]);
+        assert_eq!(sa.lcp(), &[0, 1, 3, 0, 0, 2]);
+        assert_eq!(sa.longest_repeat(), b"ana");
+
+        let mut found = sa.find(b"an").to_vec();
+        found.sort();
+        assert_eq!(found, vec![1, 3]);
+        assert_eq!(sa.find(b"banana"), &[0]);
+        assert_eq!(sa.find(b"nab"), &[] as &[usize]);
+        assert_eq!(sa.find(b"").len(), 6);
+
+        assert_eq!(SuffixArray::build(b"").suffixes(), &[] as &[usize]);
+        assert_eq!(SuffixArray::build(b"a").suffixes(), &[0]);
+        assert_eq!(SuffixArray::build(b"aaaa").suffixes(), &[3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn naive_test() {
+        let n: u64 = if cfg!(miri) { 300 } else { 2000 };
+        let text = (0..n)
+            .map(|i| b"abc"[(i.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 60) as usize % 3])
+            .collect::<Vec<_>>();
+        let sa = SuffixArray::build(&text);
+        let mut expected = (0..text.len()).collect::<Vec<_>>();
+        expected.sort_by_key(|&i| &text[i..]);
+        assert_eq!(sa.suffixes(), expected.as_slice());
+        for i in 1..text.len() {
+            let (a, b) = (&text[expected[i - 1]..], &text[expected[i]..]);
+            let h = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+            assert_eq!(sa.lcp()[i], h);
+        }
+        let pattern = &text[100..106];
+        let mut found = sa.find(pattern).to_vec();
+        found.sort();
+        let naive = (0..text.len())
+            .filter(|&i| text[i..].starts_with(pattern))
+            .collect::<Vec<_>>();
+        assert_eq!(found, naive);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        test_support::replay::check_arbitrary(|sa: SuffixArray, u| {
+            // compare with suffixes sorted naively
+            let text: &[u8] = u.arbitrary().unwrap();
+            let mut expected = (0..text.len()).collect::<Vec<_>>();
+            expected.sort_by_key(|&i| &text[i..]);
+            assert_eq!(&*sa.text, text);
+            assert_eq!(sa.suffixes(), &expected[..]);
+            for (k, w) in expected.windows(2).enumerate() {
+                let (a, b) = (&text[w[0]..], &text[w[1]..]);
+                let common = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+                assert_eq!(sa.lcp()[k + 1], common);
+            }
+        });
+    }
+}
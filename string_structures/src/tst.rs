@@ -0,0 +1,510 @@
+use interface::heap_size::HeapSize;
+use std::cmp::Ordering;
+
+/// Map keyed by strings realized by ternary search trie.
+/// Each node has a char and three children: lo and hi for keys whose char at this depth is smaller or larger,
+/// and eq for keys which continue with this char. Nodes hold a single char instead of an array over the alphabet.
+/// O(|key| · σ): insert(key, v), remove(key), get(key) for σ distinct chars
+/// Siblings are kept in unbalanced BSTs, so chars inserted in sorted order make them chains of length σ.
+/// Keys inserted in random order take O(|key| + log n) expected comparisons for n keys.
+/// O(|prefix| + k): iter_prefix(prefix) for k keys under prefix
+#[derive(Debug)]
+pub struct TernarySearchTrie<V> {
+    root: Link<V>,
+    n: usize,
+}
+
+type Link<V> = Option<Box<Node<V>>>;
+
+#[derive(Debug)]
+struct Node<V> {
+    c: char,
+    // the value of the key which ends at this node
+    value: Option<V>,
+    lo: Link<V>,
+    eq: Link<V>,
+    hi: Link<V>,
+}
+
+impl<V> Node<V> {
+    fn new(c: char) -> Self {
+        Node {
+            c,
+            value: None,
+            lo: None,
+            eq: None,
+            hi: None,
+        }
+    }
+
+    // Return lo, eq or hi for a char which compares with c as o.
+    fn child(&self, o: Ordering) -> &Link<V> {
+        match o {
+            Ordering::Less => &self.lo,
+            Ordering::Equal => &self.eq,
+            Ordering::Greater => &self.hi,
+        }
+    }
+
+    fn child_mut(&mut self, o: Ordering) -> &mut Link<V> {
+        match o {
+            Ordering::Less => &mut self.lo,
+            Ordering::Equal => &mut self.eq,
+            Ordering::Greater => &mut self.hi,
+        }
+    }
+
+    // Return true if the node holds nothing but the child o.
+    fn only_child(&self, o: Ordering) -> bool {
+        self.value.is_none()
+            && [Ordering::Less, Ordering::Equal, Ordering::Greater]
+                .iter()
+                .all(|&p| p == o || self.child(p).is_none())
+    }
+}
+
+impl<V> Drop for Node<V> {
+    // Release descendants iteratively, since sibling BSTs can be as deep as the number of distinct chars.
+    fn drop(&mut self) {
+        let mut stack = vec![self.lo.take(), self.eq.take(), self.hi.take()];
+        while let Some(t) = stack.pop() {
+            if let Some(mut u) = t {
+                stack.extend([u.lo.take(), u.eq.take(), u.hi.take()]);
+            }
+        }
+    }
+}
+
+// Remove the key from the subtree t, and release nodes which become empty.
+fn remove<V>(t: &mut Link<V>, key: &[char]) -> Option<V> {
+    // find the path to the node where key ends, as the direction taken from each node
+    let mut nodes = Vec::new();
+    let mut path = Vec::new();
+    let mut u = t.as_deref()?;
+    let mut i = 0;
+    loop {
+        nodes.push(u);
+        let o = key[i].cmp(&u.c);
+        if o == Ordering::Equal {
+            if i + 1 == key.len() {
+                break;
+            }
+            i += 1;
+        }
+        path.push(o);
+        u = u.child(o).as_deref()?;
+    }
+    u.value.as_ref()?;
+    // the nodes from the depth cut down become empty, if the last node has no children
+    let cut = if u.lo.is_none() && u.eq.is_none() && u.hi.is_none() {
+        let mut cut = nodes.len() - 1;
+        while cut > 0 && nodes[cut - 1].only_child(path[cut - 1]) {
+            cut -= 1;
+        }
+        Some(cut)
+    } else {
+        None
+    };
+
+    let mut s = &mut *t;
+    for &o in &path {
+        s = s.as_mut().unwrap().child_mut(o);
+    }
+    let x = s.as_mut().unwrap().value.take();
+    if let Some(cut) = cut {
+        let mut s = t;
+        for &o in &path[..cut] {
+            s = s.as_mut().unwrap().child_mut(o);
+        }
+        *s = None;
+    }
+    x
+}
+
+impl<V> Default for TernarySearchTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> TernarySearchTrie<V> {
+    /// Generate empty TernarySearchTrie.
+    pub fn new() -> Self {
+        TernarySearchTrie { root: None, n: 0 }
+    }
+
+    /// Return the number of keys.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Associate v with key, and return the previous value if it exists.
+    /// The empty key is not supported.
+    pub fn insert(&mut self, key: &str, v: V) -> Option<V> {
+        if key.is_empty() {
+            panic!("key must not be empty")
+        }
+        let mut chars = key.chars().peekable();
+        let mut t = &mut self.root;
+        while let Some(&c) = chars.peek() {
+            let u = t.get_or_insert_with(|| Box::new(Node::new(c)));
+            if c < u.c {
+                t = &mut u.lo;
+            } else if c > u.c {
+                t = &mut u.hi;
+            } else {
+                chars.next();
+                if chars.peek().is_none() {
+                    let old = u.value.replace(v);
+                    if old.is_none() {
+                        self.n += 1;
+                    }
+                    return old;
+                }
+                t = &mut u.eq;
+            }
+        }
+        unreachable!("key must end at a node")
+    }
+
+    // Return the node where key ends.
+    fn find_node(&self, key: &str) -> Option<&Node<V>> {
+        let mut chars = key.chars().peekable();
+        let mut u = self.root.as_deref()?;
+        loop {
+            let c = *chars.peek()?;
+            if c < u.c {
+                u = u.lo.as_deref()?;
+            } else if c > u.c {
+                u = u.hi.as_deref()?;
+            } else {
+                chars.next();
+                if chars.peek().is_none() {
+                    return Some(u);
+                }
+                u = u.eq.as_deref()?;
+            }
+        }
+    }
+
+    /// Return the value of key if it exists, otherwise return null(Empty).
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.find_node(key)?.value.as_ref()
+    }
+
+    /// Return true if key exists.
+    pub fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove key, and return its value if it exists.
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let key = key.chars().collect::<Vec<_>>();
+        if key.is_empty() {
+            return None;
+        }
+        let x = remove(&mut self.root, &key)?;
+        self.n -= 1;
+        Some(x)
+    }
+
+    /// Return an iterator over keys which start with prefix and their values in lexicographic order.
+    pub fn iter_prefix(&self, prefix: &str) -> Iter<'_, V> {
+        let mut stack = Vec::new();
+        if prefix.is_empty() {
+            stack.push(Frame::Visit(self.root.as_deref(), String::new()));
+        } else if let Some(u) = self.find_node(prefix) {
+            stack.push(Frame::Visit(u.eq.as_deref(), prefix.to_string()));
+            if let Some(v) = u.value.as_ref() {
+                stack.push(Frame::Emit(prefix.to_string(), v));
+            }
+        }
+        Iter { stack }
+    }
+
+    /// Return an iterator over all keys and their values in lexicographic order.
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.iter_prefix("")
+    }
+
+    /// Return keys of the same length as key which differ from it in at most d positions
+    /// (Hamming distance), with their values in lexicographic order.
+    pub fn near_neighbors(&self, key: &str, d: usize) -> Vec<(String, &V)> {
+        let key = key.chars().collect::<Vec<_>>();
+        let mut out = Vec::new();
+        let mut stack = vec![Near::Visit(self.root.as_deref(), 0, d, String::new())];
+        while let Some(frame) = stack.pop() {
+            let (u, i, d, prefix) = match frame {
+                Near::Emit(k, v) => {
+                    out.push((k, v));
+                    continue;
+                }
+                Near::Visit(Some(u), i, d, prefix) if i < key.len() => (u, i, d, prefix),
+                Near::Visit(..) => continue,
+            };
+            // visit lo, the key ending here or eq, and hi in this order;
+            // with mismatches left, both sides may hold keys which differ at this char
+            let c = key[i];
+            if d > 0 || c > u.c {
+                stack.push(Near::Visit(u.hi.as_deref(), i, d, prefix.clone()));
+            }
+            if c == u.c || d > 0 {
+                let d = if c == u.c { d } else { d - 1 };
+                let mut next = prefix.clone();
+                next.push(u.c);
+                if i + 1 < key.len() {
+                    stack.push(Near::Visit(u.eq.as_deref(), i + 1, d, next));
+                } else if let Some(v) = u.value.as_ref() {
+                    stack.push(Near::Emit(next, v));
+                }
+            }
+            if d > 0 || c < u.c {
+                stack.push(Near::Visit(u.lo.as_deref(), i, d, prefix));
+            }
+        }
+        out
+    }
+}
+
+// Frame of near_neighbors: a subtree with the index of the char to match, the mismatches left and the key before it.
+enum Near<'a, V> {
+    Visit(Option<&'a Node<V>>, usize, usize, String),
+    Emit(String, &'a V),
+}
+
+impl<V> HeapSize for TernarySearchTrie<V> {
+    fn heap_bytes(&self) -> usize {
+        let mut bytes = 0;
+        let mut stack = self.root.as_deref().into_iter().collect::<Vec<_>>();
+        while let Some(u) = stack.pop() {
+            bytes += std::mem::size_of::<Node<V>>();
+            stack.extend([&u.lo, &u.eq, &u.hi].iter().filter_map(|t| t.as_deref()));
+        }
+        bytes
+    }
+}
+
+#[derive(Debug)]
+enum Frame<'a, V> {
+    // a subtree with the key before it
+    Visit(Option<&'a Node<V>>, String),
+    Emit(String, &'a V),
+}
+
+/// Iterator over keys and values of TernarySearchTrie.
+#[derive(Debug)]
+pub struct Iter<'a, V> {
+    stack: Vec<Frame<'a, V>>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (String, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(frame) = self.stack.pop() {
+            match frame {
+                Frame::Emit(key, v) => return Some((key, v)),
+                Frame::Visit(None, _) => {}
+                Frame::Visit(Some(u), key) => {
+                    // visit lo, the key ending here, eq, and hi in this order
+                    let mut next = key.clone();
+                    next.push(u.c);
+                    self.stack.push(Frame::Visit(u.hi.as_deref(), key.clone()));
+                    self.stack.push(Frame::Visit(u.eq.as_deref(), next.clone()));
+                    if let Some(v) = u.value.as_ref() {
+                        self.stack.push(Frame::Emit(next, v));
+                    }
+                    self.stack.push(Frame::Visit(u.lo.as_deref(), key));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arb {
+    use super::TernarySearchTrie;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    // Decode operations of (op, i, key, value), where op % 4 == 0 removes the i-th key inserted so far
+    // and empty keys are skipped.
+    pub(super) fn ops<'a, V>(u: &mut Unstructured<'a>) -> Result<Vec<(u8, usize, &'a str, V)>>
+    where
+        V: Arbitrary<'a>,
+    {
+        (0..u.arbitrary_len::<(u8, usize, &str, V)>()?)
+            .map(|_| u.arbitrary())
+            .collect()
+    }
+
+    // Non-empty keys are inserted and keys inserted so far are sometimes removed again,
+    // so that emptied nodes are released.
+    impl<'a, V> Arbitrary<'a> for TernarySearchTrie<V>
+    where
+        V: Arbitrary<'a>,
+    {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut trie = TernarySearchTrie::new();
+            let mut keys: Vec<&str> = Vec::new();
+            for (op, i, key, v) in ops(u)? {
+                if op % 4 == 0 && !keys.is_empty() {
+                    trie.remove(keys[i % keys.len()]);
+                } else if !key.is_empty() {
+                    trie.insert(key, v);
+                    keys.push(key);
+                }
+            }
+            Ok(trie)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TernarySearchTrie;
+    use interface::heap_size::HeapSize;
+
+    #[test]
+    fn map_test() {
+        let mut trie = TernarySearchTrie::new();
+        assert_eq!(trie.size(), 0);
+        assert_eq!(trie.get("a"), None);
+
+        for (i, w) in ["she", "sells", "sea", "shells", "by", "the", "sea", "shore"]
+            .iter()
+            .enumerate()
+        {
+            trie.insert(w, i);
+        }
+        assert_eq!(trie.size(), 7);
+        assert_eq!(trie.get("sea"), Some(&6));
+        assert_eq!(trie.get("shell"), None);
+        assert!(trie.contains("shells"));
+        assert!(!trie.contains("s"));
+
+        assert_eq!(trie.remove("shell"), None);
+        assert_eq!(trie.remove("she"), Some(0));
+        assert_eq!(trie.remove("she"), None);
+        assert_eq!(trie.get("shells"), Some(&3));
+        assert_eq!(trie.remove("by"), Some(4));
+        assert_eq!(trie.size(), 5);
+        assert_eq!(
+            trie.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            vec!["sea", "sells", "shells", "shore", "the"]
+        );
+    }
+
+    #[test]
+    fn prefix_test() {
+        let mut trie = TernarySearchTrie::new();
+        for w in ["東京", "東京都", "東北", "京都", "t"] {
+            trie.insert(w, w.chars().count());
+        }
+        let under = |p: &str| trie.iter_prefix(p).map(|(k, _)| k).collect::<Vec<_>>();
+        assert_eq!(under("東"), vec!["東京", "東京都", "東北"]);
+        assert_eq!(under("東京"), vec!["東京", "東京都"]);
+        assert_eq!(under("西"), Vec::<String>::new());
+        assert_eq!(under("").len(), 5);
+    }
+
+    #[test]
+    fn near_neighbors_test() {
+        let mut trie = TernarySearchTrie::new();
+        for w in ["cat", "cot", "cut", "car", "dog", "cart", "at", "bat"] {
+            trie.insert(w, ());
+        }
+        let near = |k: &str, d: usize| {
+            trie.near_neighbors(k, d)
+                .into_iter()
+                .map(|(k, _)| k)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(near("cat", 0), vec!["cat"]);
+        assert_eq!(near("cat", 1), vec!["bat", "car", "cat", "cot", "cut"]);
+        assert_eq!(near("cbt", 1), vec!["cat", "cot", "cut"]);
+        assert_eq!(near("dot", 1), vec!["cot", "dog"]);
+        assert_eq!(near("xyz", 2), Vec::<String>::new());
+        assert_eq!(near("ct", 1), vec!["at"]);
+    }
+
+    #[test]
+    fn sorted_chain_test() {
+        // single-char keys inserted in sorted order make the root's hi chain as long as the number of keys
+        let n = if cfg!(miri) { 1_000 } else { 100_000 };
+        let keys = (0x4e00..)
+            .filter_map(char::from_u32)
+            .take(n)
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let mut trie = TernarySearchTrie::new();
+        for (i, k) in keys.iter().enumerate().take(1000) {
+            trie.insert(k, i);
+        }
+        // the rest of the chain is linked directly, since inserting it takes quadratic time
+        let mut chain = None;
+        for (i, k) in keys.iter().enumerate().skip(1000).rev() {
+            let mut u = super::Node::new(k.chars().next().unwrap());
+            u.value = Some(i);
+            u.hi = chain;
+            chain = Some(Box::new(u));
+        }
+        let mut t = &mut trie.root;
+        while let Some(u) = t {
+            t = &mut u.hi;
+        }
+        *t = chain;
+        trie.n = n;
+        assert_eq!(trie.remove(&keys[n - 1]), Some(n - 1));
+        assert_eq!(trie.remove(&keys[n - 1]), None);
+        assert_eq!(trie.get(&keys[n - 2]), Some(&(n - 2)));
+        assert_eq!(
+            trie.near_neighbors(&keys[n - 2], 0),
+            vec![(keys[n - 2].clone(), &(n - 2))]
+        );
+        assert_eq!(trie.near_neighbors(&keys[0], 1).len(), n - 1);
+        assert!(trie.heap_bytes() > 0);
+        assert_eq!(trie.size(), n - 1);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_test() {
+        use super::Link;
+        use std::collections::BTreeMap;
+
+        // Check that chars are ordered among lo and hi subtrees and no node is empty,
+        // and return the number of values.
+        fn check(t: &Link<u16>, lo: Option<char>, hi: Option<char>) -> usize {
+            let u = match t {
+                None => return 0,
+                Some(u) => u,
+            };
+            assert!(u.value.is_some() || u.lo.is_some() || u.eq.is_some() || u.hi.is_some());
+            assert!(lo.is_none_or(|c| c < u.c) && hi.is_none_or(|c| u.c < c));
+            check(&u.lo, lo, Some(u.c))
+                + check(&u.eq, None, None)
+                + check(&u.hi, Some(u.c), hi)
+                + u.value.is_some() as usize
+        }
+
+        test_support::replay::check_arbitrary(|trie: TernarySearchTrie<u16>, u| {
+            // replay the same operations on BTreeMap
+            let mut expected = BTreeMap::new();
+            let mut keys: Vec<&str> = Vec::new();
+            for (op, i, key, v) in super::arb::ops::<u16>(u).unwrap() {
+                if op % 4 == 0 && !keys.is_empty() {
+                    expected.remove(keys[i % keys.len()]);
+                } else if !key.is_empty() {
+                    expected.insert(key, v);
+                    keys.push(key);
+                }
+            }
+            assert_eq!(trie.size(), expected.len());
+            assert_eq!(check(&trie.root, None, None), expected.len());
+            for (k, v) in &expected {
+                assert_eq!(trie.get(k), Some(v));
+            }
+        });
+    }
+}
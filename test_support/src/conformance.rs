@@ -0,0 +1,354 @@
+use interface::list::List;
+use interface::queue::Stack;
+use interface::set::SSet;
+use std::collections::BTreeSet;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Check that an empty stack behaves like Vec used as a stack, panicking on the first difference.
+/// Implementations call it from their tests, so that edge cases such as pop on an empty stack agree across them.
+/// At most 64 values are held at once, which fits implementations with fixed capacity.
+pub fn check_stack<S>(s: &mut S)
+where
+    S: Stack<i32> + ?Sized,
+{
+    let mut expected = Vec::new();
+    assert_eq!(s.pop(), None, "pop on an empty stack must return None");
+    assert_eq!(s.pop(), None, "pop on an empty stack must return None");
+
+    // pseudo random operations by linear congruential generator
+    let mut x: u64 = 1;
+    for i in 0..1000 {
+        x = x
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        if expected.len() < 64 && x >> 62 != 0 {
+            s.push(i);
+            expected.push(i);
+        } else {
+            assert_eq!(
+                s.pop(),
+                expected.pop(),
+                "the last pushed value must be popped"
+            );
+        }
+    }
+    while let Some(y) = expected.pop() {
+        assert_eq!(s.pop(), Some(y), "the last pushed value must be popped");
+    }
+    assert_eq!(s.pop(), None, "pop on an emptied stack must return None");
+}
+
+/// Check that an empty list behaves like Vec, panicking on the first difference.
+/// Besides random operations within the bound, add and add_all beyond the size must panic without changing a list,
+/// and remove beyond the size must return None.
+/// At most 63 values are held at once, which fits implementations with fixed capacity.
+pub fn check_list<L>(l: &mut L)
+where
+    L: List<i32>,
+{
+    let mut expected = Vec::new();
+    // pseudo random operations by linear congruential generator
+    let mut x: u64 = 1;
+    for i in 0..1000 {
+        x = x
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let n = expected.len();
+        let k = (x >> 33) as usize;
+        match x >> 62 {
+            0 | 1 if n < 63 => {
+                l.add(k % (n + 1), i);
+                expected.insert(k % (n + 1), i);
+            }
+            2 if n > 0 => {
+                assert_eq!(
+                    l.set(k % n, i),
+                    Some(expected[k % n]),
+                    "set must return the old value"
+                );
+                expected[k % n] = i;
+            }
+            _ if n > 0 => {
+                assert_eq!(
+                    l.remove(k % n),
+                    Some(expected.remove(k % n)),
+                    "remove must return the value"
+                );
+            }
+            _ => {}
+        }
+        assert_eq!(
+            l.size(),
+            expected.len(),
+            "size must be the number of values"
+        );
+    }
+    for (i, y) in expected.iter().enumerate() {
+        assert_eq!(
+            l.get(i),
+            Some(y),
+            "get must return the value at the position"
+        );
+    }
+
+    let n = expected.len();
+    assert_eq!(l.get(n), None, "get beyond the size must return None");
+    assert_eq!(l.remove(n), None, "remove beyond the size must return None");
+    let added = catch_unwind(AssertUnwindSafe(|| l.add(n + 1, 0)));
+    assert!(added.is_err(), "add beyond the size must panic");
+    let added = catch_unwind(AssertUnwindSafe(|| l.add_all(n + 1, vec![0])));
+    assert!(added.is_err(), "add_all beyond the size must panic");
+    assert_eq!(l.size(), n, "a panicking add must not change a list");
+    l.add(n, 1);
+    assert_eq!(l.last(), Some(&1), "add at the size must append a value");
+}
+
+/// Check that an empty set behaves like BTreeSet, panicking on the first difference.
+/// After random additions and removals of values below 200, searches around every value are compared,
+/// including closest and neighbors which may be the defaults built on them.
+pub fn check_sset<S>(s: &mut S)
+where
+    S: SSet<usize> + ?Sized,
+{
+    // fewer operations and searches under Miri, which is too slow for static sets rebuilt on each change
+    let (n, step) = if cfg!(miri) { (200, 7) } else { (1000, 1) };
+    let mut expected = BTreeSet::new();
+    // pseudo random operations by linear congruential generator
+    let mut x: u64 = 1;
+    for _ in 0..n {
+        x = x
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let y = (x >> 33) as usize % 200;
+        if x >> 62 != 0 {
+            assert_eq!(
+                s.add(y),
+                expected.insert(y),
+                "add must return whether the value is new"
+            );
+        } else {
+            assert_eq!(
+                s.remove(y),
+                expected.take(&y),
+                "remove must return the value"
+            );
+        }
+        assert_eq!(
+            s.size(),
+            expected.len(),
+            "size must be the number of values"
+        );
+    }
+
+    for q in (0..=210).step_by(step) {
+        let lo = expected.range(..q).next_back();
+        let hi = expected.range(q..).next();
+        assert_eq!(
+            s.find_ref(&q),
+            hi,
+            "find_ref must return the smallest y >= x"
+        );
+        assert_eq!(
+            s.successor(&q),
+            expected.range(q + 1..).next(),
+            "successor must return the smallest y > x"
+        );
+        assert_eq!(
+            s.predecessor(&q),
+            lo,
+            "predecessor must return the largest y < x"
+        );
+
+        let mut nearest = expected.iter().collect::<Vec<_>>();
+        nearest.sort_by_key(|&&y| (y.abs_diff(q), y));
+        assert_eq!(
+            s.closest(&q),
+            nearest.first().copied(),
+            "closest must return the nearest value, preferring the smaller one"
+        );
+        for k in [0, 1, 5, 300] {
+            let n = std::cmp::min(k, nearest.len());
+            assert_eq!(
+                s.neighbors(&q, k),
+                nearest[..n],
+                "neighbors must return the k nearest values in order of distance"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_list, check_sset, check_stack};
+    use interface::list::List;
+    use interface::queue::Stack;
+    use interface::set::SSet;
+
+    struct VecStack(Vec<i32>);
+
+    impl Stack<i32> for VecStack {
+        fn push(&mut self, x: i32) {
+            self.0.push(x);
+        }
+        fn pop(&mut self) -> Option<i32> {
+            self.0.pop()
+        }
+    }
+
+    #[test]
+    fn check_stack_test() {
+        check_stack(&mut VecStack(Vec::new()));
+        let s: &mut dyn Stack<i32> = &mut VecStack(Vec::new());
+        check_stack(s);
+    }
+
+    struct VecList(Vec<i32>);
+
+    impl List<i32> for VecList {
+        fn size(&self) -> usize {
+            self.0.len()
+        }
+        fn get(&self, i: usize) -> Option<&i32> {
+            self.0.get(i)
+        }
+        fn set(&mut self, i: usize, x: i32) -> Option<i32> {
+            Some(std::mem::replace(&mut self.0[i], x))
+        }
+        fn add(&mut self, i: usize, x: i32) {
+            self.0.insert(i, x);
+        }
+        fn remove(&mut self, i: usize) -> Option<i32> {
+            if i < self.0.len() {
+                Some(self.0.remove(i))
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn check_list_test() {
+        check_list(&mut VecList(Vec::new()));
+    }
+
+    // List which appends values added beyond the size.
+    struct Clamping(VecList);
+
+    impl List<i32> for Clamping {
+        fn size(&self) -> usize {
+            self.0.size()
+        }
+        fn get(&self, i: usize) -> Option<&i32> {
+            self.0.get(i)
+        }
+        fn set(&mut self, i: usize, x: i32) -> Option<i32> {
+            self.0.set(i, x)
+        }
+        fn add(&mut self, i: usize, x: i32) {
+            let i = std::cmp::min(i, self.size());
+            self.0.add(i, x)
+        }
+        fn remove(&mut self, i: usize) -> Option<i32> {
+            self.0.remove(i)
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "add beyond the size must panic")]
+    fn check_list_failure_test() {
+        check_list(&mut Clamping(VecList(Vec::new())));
+    }
+
+    // Stack which loses values beyond the capacity of one.
+    struct Forgetful(Option<i32>);
+
+    impl Stack<i32> for Forgetful {
+        fn push(&mut self, x: i32) {
+            self.0 = Some(x);
+        }
+        fn pop(&mut self) -> Option<i32> {
+            self.0.take()
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn check_stack_failure_test() {
+        check_stack(&mut Forgetful(None));
+    }
+
+    // SSet over a sorted Vec, which relies on the default closest and neighbors.
+    struct VecSet(Vec<usize>);
+
+    impl SSet<usize> for VecSet {
+        fn size(&self) -> usize {
+            self.0.len()
+        }
+        fn add(&mut self, x: usize) -> bool {
+            match self.0.binary_search(&x) {
+                Ok(_) => false,
+                Err(i) => {
+                    self.0.insert(i, x);
+                    true
+                }
+            }
+        }
+        fn remove(&mut self, x: usize) -> Option<usize> {
+            let i = self.0.binary_search(&x).ok()?;
+            Some(self.0.remove(i))
+        }
+        fn find(&self, x: usize) -> Option<usize> {
+            self.find_ref(&x).copied()
+        }
+        fn find_ref(&self, x: &usize) -> Option<&usize> {
+            self.0.get(self.0.partition_point(|y| y < x))
+        }
+        fn successor(&self, x: &usize) -> Option<&usize> {
+            self.0.get(self.0.partition_point(|y| y <= x))
+        }
+        fn predecessor(&self, x: &usize) -> Option<&usize> {
+            let i = self.0.partition_point(|y| y < x);
+            self.0.get(i.checked_sub(1)?)
+        }
+    }
+
+    #[test]
+    fn check_sset_test() {
+        check_sset(&mut VecSet(Vec::new()));
+        let s: &mut dyn SSet<usize> = &mut VecSet(Vec::new());
+        check_sset(s);
+    }
+
+    // Set whose predecessor includes the value itself.
+    struct Inclusive(VecSet);
+
+    impl SSet<usize> for Inclusive {
+        fn size(&self) -> usize {
+            self.0.size()
+        }
+        fn add(&mut self, x: usize) -> bool {
+            self.0.add(x)
+        }
+        fn remove(&mut self, x: usize) -> Option<usize> {
+            self.0.remove(x)
+        }
+        fn find(&self, x: usize) -> Option<usize> {
+            self.0.find(x)
+        }
+        fn find_ref(&self, x: &usize) -> Option<&usize> {
+            self.0.find_ref(x)
+        }
+        fn successor(&self, x: &usize) -> Option<&usize> {
+            self.0.successor(x)
+        }
+        fn predecessor(&self, x: &usize) -> Option<&usize> {
+            self.0.predecessor(&(x + 1))
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "predecessor must return the largest y < x")]
+    fn check_sset_failure_test() {
+        check_sset(&mut Inclusive(VecSet(Vec::new())));
+    }
+}
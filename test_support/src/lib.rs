@@ -0,0 +1,8 @@
+#![warn(missing_docs)]
+//! test_support provides helpers shared by tests of implementations.
+//! It's only a dev-dependency, so that none of it is compiled into the structures themselves.
+
+/// Provide conformance checks against std collections
+pub mod conformance;
+/// Provide replay of Arbitrary implementations against models
+pub mod replay;
@@ -0,0 +1,61 @@
+use arbitrary::{Arbitrary, Unstructured};
+
+/// Return n pseudo random bytes generated from seed by linear congruential generator.
+/// Tests of Arbitrary implementations feed them to arbitrary::Unstructured, so that generated structures are reproducible.
+pub fn random_bytes(seed: u64, n: usize) -> Vec<u8> {
+    let mut x = seed;
+    (0..n)
+        .map(|_| {
+            x = x
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (x >> 56) as u8
+        })
+        .collect()
+}
+
+/// Generate X by its Arbitrary implementation from 16 suffixes of the same pseudo random bytes,
+/// and pass each to check with another Unstructured over the same suffix.
+/// check decodes the operations from it again, replays them on a model, and compares the model with X.
+pub fn check_arbitrary<X, F>(mut check: F)
+where
+    X: for<'a> Arbitrary<'a>,
+    F: FnMut(X, &mut Unstructured<'_>),
+{
+    let bytes = random_bytes(7, 4096);
+    for start in (0..bytes.len()).step_by(256) {
+        let x = X::arbitrary(&mut Unstructured::new(&bytes[start..])).unwrap();
+        check(x, &mut Unstructured::new(&bytes[start..]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_arbitrary, random_bytes};
+
+    #[test]
+    fn random_bytes_test() {
+        let bytes = random_bytes(7, 4096);
+        assert_eq!(bytes.len(), 4096);
+        assert_eq!(bytes, random_bytes(7, 4096));
+        assert_ne!(bytes, random_bytes(8, 4096));
+        // every byte value appears in a long enough sequence
+        let mut seen = [false; 256];
+        bytes.iter().for_each(|&b| seen[b as usize] = true);
+        assert!(seen.iter().all(|&s| s));
+    }
+
+    #[test]
+    fn check_arbitrary_test() {
+        let mut runs = 0;
+        check_arbitrary(|v: Vec<u16>, u| {
+            let expected = u
+                .arbitrary_iter::<u16>()
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>();
+            assert_eq!(Ok(v), expected);
+            runs += 1;
+        });
+        assert_eq!(runs, 16);
+    }
+}